@@ -31,6 +31,12 @@ The `parse-fasta` function reads FASTA data and returns a dictionary mapping seq
 
 #raw(repr(sequences), block: true)
 
+`parse-fasta` uses the whole header line as the identifier and rejects repeated identifiers unless `duplicates` is set to `"rename"` or `"keep-first"`. By default it is forgiving about layout: lines before the first header are ignored, a bare `>` header gives the identifier `""`, and lines starting with `;` are kept as sequence. Pass `strict: true` to reject the first two and skip `;` comment lines instead, which is what `parse-sequences` always does. For FASTQ files, or to separate descriptions from identifiers, use `parse-sequences`. It returns one dictionary per record with `id`, `description`, `sequence` and `quality` keys, where `quality` is the quality string or, with `quality: "phred"`, an array of Phred scores. Both functions accept gzip-compressed bytes and report the line number of malformed input.
+
+```typ
+#let reads = parse-sequences(read("reads.fastq.gz", encoding: none), quality: "phred")
+```
+
 == FASTA rendering
 
 Use `render-fasta` to display sequences in the standard FASTA format.
@@ -96,6 +102,34 @@ The `align-seq-pair` function performs pairwise alignment using either match/mis
 
 The DNA example uses a custom match/mismatch model for a global alignment, while the protein example uses BLOSUM62 for a local alignment. The alignment scores are #dna_pair_alignment.score and #protein_pair_alignment.score.
 
+The full dynamic programming matrix is kept so that it can be drawn (see #link(<dp-matrix>)[Dynamic programming matrix visualization]), which limits it to `max-dp-cells` cells: 4,000,000 by default, or about 2000 × 2000 residues. Larger alignments fail unless `oversize: "alignment-only"` is set, in which case the score and a single optimal alignment are computed in linear memory and no DP matrix is returned. Raising `max-dp-cells` keeps the matrix at the cost of memory and compile time.
+
+```typ
+#let long_alignment = align-seq-pair(
+  read("genome_a.fasta"),
+  read("genome_b.fasta"),
+  match-score: 1,
+  mismatch-score: -1,
+  gap-penalty: -2,
+  oversize: "alignment-only",
+)
+```
+
+When the orientation of a nucleotide sequence is unknown, `strand: "both"` also aligns the reverse complement of `seq-2`, using IUPAC ambiguity codes, and returns the higher-scoring strand. The result's `strand` is then "forward" or "reverse", and `seq-2` holds the sequence that was actually aligned, so it can be passed to the rendering functions unchanged. `seq-2-ranges` maps each alignment back to positions on the input sequence; on the reverse strand the first position is the larger one. With `strand-report: "all"`, the other strand's result is kept as `other-strand`.
+
+```typ
+#let read_alignment = align-seq-pair(
+  "GGCTAGCA",
+  "AATGCTAGCCTT",
+  match-score: 2,
+  mismatch-score: -3,
+  gap-penalty: -5,
+  mode: "local",
+  strand: "both",
+)
+// read_alignment.strand == "reverse"
+```
+
 == Rendering pairwise alignments
 
 Pairwise alignments can be rendered using the `render-pair-alignment` function. The example below uses the local protein alignment from the previous section, showing the unaligned regions using a light gray color.
@@ -123,7 +157,34 @@ Pairwise alignments can be rendered using the `render-pair-alignment` function.
   kind: image,
 )
 
-== Dynamic programming matrix visualization
+Each alignment also carries an EMBOSS-style `similarity` string: `|` marks identical residues, `:` a positive substitution score, `.` a score of zero, and a space a negative score or a gap. Passing it to `render-pair-alignment` shows similar residues in the middle line, not just identities; `similar-char` and `weak-char` change the symbols used.
+
+```typ
+#render-pair-alignment(
+  protein_pair_alignment.seq-1,
+  protein_pair_alignment.seq-2,
+  protein_pair_alignment.traceback-paths.at(0),
+  similarity: protein_pair_alignment.alignments.at(0).similarity,
+  match-char: "|",
+  hide-unaligned: true,
+)
+```
+
+#figure(
+  render-pair-alignment(
+    protein_pair_alignment.seq-1,
+    protein_pair_alignment.seq-2,
+    protein_pair_alignment.traceback-paths.at(0),
+    similarity: protein_pair_alignment.alignments.at(0).similarity,
+    match-char: "|",
+    hide-unaligned: true,
+  ),
+  caption: [Local protein alignment with identities (`|`), similar residues (`:`) and zero-scoring substitutions (`.`).],
+  supplement: none,
+  kind: image,
+)
+
+== Dynamic programming matrix visualization <dp-matrix>
 
 Dynamic programming is the core procedure used by the pairwise alignment algorithm: it fills a matrix of optimal scores for all prefix pairs of the two sequences, where each cell stores the best score achievable at that position and arrows indicate the traceback directions that can lead to an optimal alignment. The `render-dp-matrix` function renders the DP matrix of a given alignment, overlaying the traceback path used to produce the final alignment.
 
@@ -151,6 +212,23 @@ Dynamic programming is the core procedure used by the pairwise alignment algorit
   kind: image,
 )
 
+== Dot plots
+
+The `get-dot-plot` function compares two sequences without computing a DP matrix and returns the matching regions as diagonal segments, which keeps whole-genome comparisons compact. The default "kmer" method reports exact words of `word-size` residues. The "window" method keeps windows of `window` residue pairs that score at least `threshold`, counting identities by default or using `scoring-matrix` or match/mismatch scores. With `strand: "both"`, segments against the reverse complement of `seq-2` are reported too, with `strand: "reverse"` and `y` coordinates that decrease along the segment.
+
+```typ
+#let plot = get-dot-plot(
+  "ACGTTGCAAGGCTAGCA",
+  "TTGCAAGTGCTAGCCTT",
+  word-size: 5,
+  strand: "both",
+)
+// Each segment has strand, x-start, y-start, x-end, y-end and length.
+#for segment in plot.segments [
+  #segment.strand: #segment.x-start–#segment.x-end vs #segment.y-start–#segment.y-end \
+]
+```
+
 == Scoring matrices <scoring-matrices>
 
 Substitution matrices assign scores for aligning residues in pairwise and multiple sequence alignments, rewarding likely substitutions and penalizing unlikely ones. They represent substitution preferences as log-odds scores derived from observed evolutionary changes. In this way, they guide alignment algorithms by quantifying how plausible it is for one residue to replace another over time, helping produce biologically meaningful alignments. `genotypst` provides scoring matrices from the BLOSUM @henikoff_amino_1992 and PAM @dayhoff_model_1979 families for protein sequences, as well as the EDNAFULL matrix for DNA and RNA sequences.
@@ -170,6 +248,13 @@ Use `get-scoring-matrix` to retrieve a matrix data and `get-score-from-matrix` t
   block: true,
 )
 
+Built-in matrices also report their scale and the statistics implied by their scores: `scale-bits` gives the bits per score unit, and `statistics` holds λ, the background and target frequencies, the relative entropy in bits and the expected score. The background is the one implied by the matrix when it exists; otherwise Robinson & Robinson amino acid frequencies (or equal A/C/G/T frequencies) are assumed, as for PAM250, and `background-source` records which was used. Pass `background: "implied"` to fail instead of falling back. Values stated in the matrix file header are available as `reported-entropy` and `reported-expected-score`.
+
+```typ
+#let stats = blosum62.statistics
+BLOSUM62 uses 1/#calc.round(1 / blosum62.scale-bits) bit units and has a relative entropy of #calc.round(stats.relative-entropy, digits: 2) bits (#stats.background-source background).
+```
+
 You can render the entire scoring matrix using `render-scoring-matrix` function. In the example below `scale-limit: 7` set the color scale limits to -7 to 7.
 
 ```typ
@@ -190,11 +275,25 @@ You can render the entire scoring matrix using `render-scoring-matrix` function.
       scale-limit: 7,
     )
   },
-  caption: [BLOSUM62 scoring matrix.],
+  caption: [BLOSUM62 scoring matrix (#blosum62.statistics.background-source background).],
   supplement: none,
   kind: image,
 )
 
+PAM matrices for distances other than the bundled ones can be computed with `extrapolate-pam-matrix`, which raises the Dayhoff PAM1 mutation probability matrix to the requested power, e.g. `render-scoring-matrix(extrapolate-pam-matrix(70))`. Scores use the units of the bundled PAM matrices unless `scale` is given: half bits below PAM180 and third bits from PAM180 on.
+
+You can also derive your own log-odds matrix with `derive-scoring-matrix`, following the BLOSUM construction: residue pairs are counted in each column of a trusted alignment (or taken from a pair-count table), sequences above `cluster-identity` are merged into a single cluster, and scores are `scale` times the log2 odds ratio, rounded to integers. The result can be rendered like any other matrix or passed to `align-seq-pair` as `scoring-matrix`.
+
+```typ
+#let custom = derive-scoring-matrix(
+  sequences: parse-fasta(read("alignment.fasta")),
+  cluster-identity: 62%,
+  pseudocount: 1,
+  name: "MY62",
+)
+#align-seq-pair("HEAGAWGHEE", "PAWHEAE", scoring-matrix: custom, gap-penalty: -8)
+```
+
 == Multiple sequence alignments
 
 The `render-msa` function displays multiple sequence alignments and can optionally color residues, show the consensus sequence, and display residue conservation.
@@ -242,6 +341,63 @@ Residue coloring represents amino acid physicochemical properties. The sequence
 
 The bars above the alignment indicate the degree of residue conservation at each column.
 
+By default the bars show Shannon information content. `conservation-score` selects another measure: `"jensen-shannon"` for the divergence from background frequencies @capra_predicting_2007, `"property-entropy"` for entropy over physicochemical residue classes @mirny_universally_1999, or `"sum-of-pairs"` for the mean substitution score over all sequence pairs, using `conservation-matrix` (BLOSUM62 or EDNAFULL by default). Bars are drawn relative to the highest value each score can reach.
+
+```typ
+#render-msa(protein_msa, start: 100, end: 145, show-conservation: true, conservation-score: "jensen-shannon")
+```
+
+Alignments in other formats are read with `parse-msa`, which accepts Stockholm (as written by HMMER and Rfam), Clustal, sequential or interleaved PHYLIP, aligned FASTA and A2M/A3M, and detects the format from the first line. Its `sequences` entry is the same dictionary `parse-fasta` returns. Annotation tracks come back alongside: Stockholm `#=GC` lines such as `SS_cons` and `RF` and the Clustal conservation line in `column-annotations`, `#=GR` lines in `residue-annotations`, and `#=GF` lines in `file-annotations`. FASTA-style input is read as A3M only when its sequences mix uppercase and lowercase residues, so all-lowercase FASTA stays plain FASTA; pass `format: "a3m"` or `format: "a2m"` to force it. A2M/A3M insertions (lowercase residues) are padded into insert columns, or dropped with `insertions: "remove"`.
+
+```typ
+#let rfam = parse-msa(read("RF00005.stockholm.txt"))
+#render-msa(rfam.sequences)
+#rfam.column-annotations.at("SS_cons")
+```
+
+`minimum-column-occupancy` only hides columns when rendering. To reduce an alignment itself, `trim-msa` removes columns by `max-gap-fraction`, by `min-conservation` (relative to the highest value of `conservation-score`), or with `automated: true`, which picks a gap threshold from the distribution of column gap fractions in the spirit of trimAl's gappyout mode @capella-gutierrez_trimal_2009. It returns the trimmed alignment and `kept-columns`, the original position of each remaining column. `filter-msa` removes redundant sequences like hhfilter @steinegger_hh-suite3_2019: it keeps the first (query) sequence and drops sequences sharing more than `max-identity` with one already kept, or covering less than `min-coverage` of the query.
+
+```typ
+#let trimmed = trim-msa(protein_msa, automated: true)
+#let filtered = filter-msa(trimmed.alignment, max-identity: 90%, min-coverage: 50%)
+#render-msa(filtered.alignment)
+```
+
+`get-identity-matrix` computes the percent identity of every pair of sequences, over the columns where both have a residue (`definition: "aligned"`), over the shorter sequence (`"shorter"`), or over all columns (`"columns"`). Sequences are ordered by average-linkage clustering (UPGMA) on `100% - identity` so that similar sequences sit together; use `cluster` to choose single or complete linkage, or `none` to keep the input order. The result is shaped like a scoring matrix with the sequence identifiers as its alphabet, so `render-scoring-matrix` draws it as a heatmap. Because that color scale is centered on zero, identities run from the neutral midpoint of `diverging-color-map` at 0% to its blue end at 100%.
+
+```typ
+#let identity = get-identity-matrix(protein_msa)
+#render-scoring-matrix(identity, triangle: "full", color-map: diverging-color-map)
+```
+
+For nucleotide alignments of population samples, `get-popgen-stats` reports the number of segregating sites, nucleotide diversity π @nei_mathematical_1979, Watterson's θ @watterson_number_1975 and Tajima's D @tajima_statistical_1989, together with the number of haplotypes and the haplotype diversity. Gaps and ambiguity codes are missing data: by default every column with missing data is dropped (`missing: "complete-deletion"`), while `"pairwise-deletion"` uses each column with the sequences that have a base there. With `window`, the same statistics are also computed in sliding windows of alignment columns, ready for a table or a line plot along the alignment.
+
+```typ
+#let samples = parse-msa(read("samples.afa")).sequences
+#let stats = get-popgen-stats(samples, window: 100, step: 25)
+#table(
+  columns: 4,
+  [S], [π], [θ#sub[W]], [Tajima's D],
+  [#stats.segregating-sites], [#calc.round(stats.pi, digits: 4)],
+  [#calc.round(stats.theta-w, digits: 4)], [#calc.round(stats.tajima-d, digits: 2)],
+)
+#let pi-track = stats.windows.map(w => ((w.start + w.end) / 2, w.pi))
+```
+
+`get-snp-sites` lists the variant columns relative to one sequence of the alignment, the `reference`. Each site has its alignment column, its position on the ungapped reference, the reference and alternative alleles, and each sequence's genotype as an allele index (0 for the reference allele, none for a gap or ambiguous base). With `snp-alignment: true` it also returns the alignment reduced to those columns, which `render-msa` draws as a variant-only panel.
+
+```typ
+#let snps = get-snp-sites(samples, "reference", snp-alignment: true)
+#render-msa(snps.alignment, colors: true)
+#table(
+  columns: 3,
+  [Position], [Ref], [Alt],
+  ..snps.sites.map(site => (
+    [#site.position], [#site.reference], [#site.alternatives.join(",")],
+  )).flatten(),
+)
+```
+
 == Sequence logos
 
 Sequence logos @schneider_sequence_1990 are graphical summaries of residue variation and conservation across positions in a sequence alignment and are commonly used to visualize binding sites, motifs, and functional domains. In a sequence logo, the total height of each stack represents the information content (in bits) at that position, while individual letters are scaled according to their relative frequencies.
@@ -261,6 +417,52 @@ In the example below, we visualize the same region as the MSA of the previous se
 
 Like `render-msa`, `render-sequence-logo` automatically applies the appropriate color palette based on the sequence alphabet.
 
+Closely related sequences can dominate column statistics. Set `weighting` to `"henikoff"` for position-based weights @henikoff_position-based_1994 or to `"identity"` to weight each sequence by the inverse number of sequences sharing at least `identity-threshold` identity with it. Both `render-msa` and `render-sequence-logo` then compute conservation, letter heights and the consensus from weighted counts. `get-sequence-weights` returns the weights and the effective number of sequences.
+
+```typ
+#let weights = get-sequence-weights(protein_msa, method: "identity", identity-threshold: 62%)
+#render-sequence-logo(protein_msa, start: 100, end: 145, weighting: "henikoff")
+```
+
+Classic logos measure information against a uniform background. Setting `background` to `"blosum62"` (the amino acid frequencies implied by BLOSUM62) or to a dictionary of residue frequencies draws relative-entropy logos instead, where stack height is the relative entropy of each column to the background. Sparse alignments can be smoothed with `pseudocounts`: `"dirichlet"` adds `pseudocount-weight` counts spread by background frequency, and `"blosum62"` mixes in substitution-derived counts as in PSI-BLAST @altschul_gapped_1997. With `show-depleted: true`, residues rarer than their background frequency are drawn below the baseline.
+
+```typ
+#render-sequence-logo(
+  protein_msa,
+  start: 100,
+  end: 145,
+  background: "blosum62",
+  pseudocounts: "blosum62",
+  show-depleted: true,
+)
+```
+
+=== Motif scanning
+
+The alignment behind a motif logo can also be turned into a position weight matrix with `build-pwm`: each column's counts are smoothed with a pseudocount spread by `background` frequency @nishida_pseudocounts_2009, and scored as log2 odds against the background. `scan-motif` then scores every window of a sequence on both strands and reports hits with a p-value below `p-value` or a score above `min-score`. P-values are computed exactly from the distribution of scores of background sequences, as in FIMO @grant_fimo_2011. Hits come back as genome-map features, so the logo shows what the motif looks like and the genome map where it occurs.
+
+```typ
+#let sites = (s1: "TTGACA", s2: "TTGACT", s3: "TTTACA", s4: "TTGATA")
+#let pwm = build-pwm(sites, alphabet: "dna")
+#let hits = scan-motif(pwm, locus, p-value: 1e-3, label: "-35 box")
+#render-sequence-logo(sites)
+#render-genome-map(hits, coordinate-axis: true, unit: "bp")
+```
+
+=== Motif databases
+
+Motifs published in databases usually come as matrices rather than as the sites they were built from. `parse-motifs` reads MEME minimal format, JASPAR (`.jaspar` and bare `.pfm`) and TRANSFAC files into per-position residue frequencies, detecting the format from the first line. Passing a motif to `render-sequence-logo` in place of an alignment draws it from its frequencies: with the default uniform background, stack heights are the information content of each position, and `background` (for example the motif's own MEME background) turns them into relative entropy. `show-depleted`, `start`, `end` and `coordinate-axis` work as for alignments.
+
+```typ
+#let motifs = parse-motifs(read("MA0004.1.jaspar"))
+#render-sequence-logo(motifs.first(), coordinate-axis: true)
+
+#let meme = parse-motifs(read("crp.meme"))
+#render-sequence-logo(meme.first(), background: meme.first().background)
+```
+
+The matrix returned by `build-pwm` can be drawn the same way, showing the motif after pseudocounts.
+
 == Residue palettes
 
 `genotypst` uses predefined color palettes to assign colors to sequence residues. These palettes can be used to customize residue colors across different visualizations, as described in #link(<specifying-residue-palettes>)[Specifying residue palettes].
@@ -340,7 +542,7 @@ Genome maps enable visualization of the genes and other genomic elements within
   kind: image,
 )
 
-If your genomic features are stored in a GFF3 file, you can use the `parse-gff` function to parse the file and return an array of dictionaries suitable as input to `render-genome-map`.
+If your genomic features are stored in a GFF3 file, you can use the `parse-gff` function to parse the file and return an array of dictionaries suitable as input to `render-genome-map`. Compressed files (gzip or BGZF) can be passed as bytes, e.g. `parse-gff(read("features.gff3.gz", encoding: none))`.
 
 In the example below:
 
@@ -381,9 +583,31 @@ In the example below:
   unit: "bp",
 )
 
+== Translation and open reading frames
+
+`translate-sequence` translates a nucleotide sequence with any of the NCBI genetic codes (`genetic-code: 1` to `33`), in one of the six reading frames. With `initiator: true`, an alternative start codon at the beginning, such as `GTG` in bacteria (`genetic-code: 11`), is translated as methionine. `stop` keeps stop codons as `*`, truncates the protein at the first one, or removes them. `translate-six-frames` returns all six frames at once.
+
+`find-orfs` reports open reading frames, from the most upstream start codon to the next in-frame stop codon, on both strands. `start-codons` accepts `ATG` only (`"atg"`), the start codons of the genetic code (`"table"`), or any sense codon (`"any"`); `min-length` is counted in nucleotides, including the stop codon. The ORFs come back in the same shape as `parse-gff` features, with their frame and translation as attributes, so they can be drawn directly:
+
+```typ
+#let locus = "ATGAAACGCATTAGCACCACCATTACCACCACCATCACCATTACCACAGGTAACGGTGCGGGCTGAGTTCA"
+#let orfs = find-orfs(locus, genetic-code: 11, start-codons: "table", min-length: 30)
+#render-genome-map(orfs, coordinate-axis: true, unit: "bp")
+#translate-six-frames("ATGGCCTGA")
+```
+
+== Sequence composition tracks
+
+`get-composition-track` slides a window along a nucleotide sequence and returns its GC content, GC skew, cumulative GC skew, linguistic complexity @troyanskaya_sequence_2002 and k-mer entropy. Set `window` and `step` in bases; windows are reported with the same 1-based `start` and `end` as genome-map features, so a plot of the values lines up with `render-genome-map` over the same region.
+
+```typ
+#let track = get-composition-track(locus, window: 20, step: 5)
+#track.map(w => (w.start, w.gc-content))
+```
+
 = Working with phylogenetic trees
 
-`genotypst` includes functions to parse and render phylogenetic trees. Trees can be created by parsing Newick-formatted strings with `parse-newick` or by manually constructing nested dictionary structures.
+`genotypst` includes functions to parse and render phylogenetic trees. Trees can be created by parsing Newick-formatted strings (or gzip-compressed bytes) with `parse-newick` or by manually constructing nested dictionary structures.
 
 ```typst
 #let parsed_newick_tree = parse-newick(
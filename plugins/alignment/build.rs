@@ -34,18 +34,58 @@ fn normalize_residue(token: &str, matrix: &str) -> String {
     }
 }
 
+/// Number following `key` in `text`, e.g. `0.6979` for `Entropy =   0.6979`.
+fn number_after(text: &str, key: &str) -> Option<f64> {
+    let rest = &text[text.find(key)? + key.len()..];
+    let rest = rest.trim_start().strip_prefix('=')?.trim_start();
+    let end = rest
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+        .unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+/// Scale, entropy and expected score stated in a matrix header.
+///
+/// BLOSUM headers give the scale as `1/N Bit Units` and the expected score in
+/// bits; PAM headers give `scale = ln(2)/N` and the expected score in matrix
+/// units. Expected scores are returned in matrix units.
+fn parse_header(header: &str) -> (Option<f64>, Option<f64>, Option<f64>) {
+    let divisor = |after: &str| -> Option<f64> {
+        let rest = &header[header.find(after)? + after.len()..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        rest[..end].parse::<f64>().ok().filter(|n| *n > 0.0)
+    };
+    let scale_bits = divisor("scale = ln(2)/")
+        .or_else(|| divisor("in 1/"))
+        .map(|n| 1.0 / n);
+    let entropy = number_after(header, "Entropy");
+    let expected = match number_after(header, "Expected score") {
+        Some(score) => Some(score),
+        None => number_after(header, "Expected")
+            .zip(scale_bits)
+            .map(|(bits, scale)| bits / scale),
+    };
+    (scale_bits, entropy, expected)
+}
+
 fn main() {
     let out_dir = env::var("OUT_DIR").unwrap();
     let dest_path = Path::new(&out_dir).join("generated_matrices.rs");
     let mut f = File::create(&dest_path).unwrap();
 
     let mut variants = Vec::new();
+    let mut variant_decls = Vec::new();
     let mut from_str_arms = Vec::new();
     let mut name_arms = Vec::new();
     let mut score_dimension_arms = Vec::new();
     let mut scores_arms = Vec::new();
     let mut lookup_map_arms = Vec::new();
     let mut alphabet_arms = Vec::new();
+    let mut scale_bits_arms = Vec::new();
+    let mut entropy_arms = Vec::new();
+    let mut expected_arms = Vec::new();
 
     let entries = fs::read_dir("src/data").expect("Failed to read src/data");
     for entry in entries {
@@ -61,11 +101,16 @@ fn main() {
             let mut residues = Vec::new();
             let mut scores: Vec<i32> = Vec::new();
             let mut row_index = 0usize;
+            let mut header = String::new();
 
             for line in reader.lines() {
                 let line = line.unwrap();
                 let line = match line.split_once('#') {
-                    Some((before, _)) => before.trim(),
+                    Some((before, comment)) => {
+                        header.push_str(comment);
+                        header.push('\n');
+                        before.trim()
+                    }
                     None => line.trim(),
                 };
                 if line.is_empty() {
@@ -182,6 +227,10 @@ fn main() {
             .unwrap();
 
             variants.push(pascal_name.clone());
+            variant_decls.push(format!(
+                "/// The {} matrix.\n    {}",
+                uppercase_name, pascal_name
+            ));
             from_str_arms.push(format!(
                 "\"{}\" => Some(Self::{})",
                 uppercase_name, pascal_name
@@ -197,6 +246,10 @@ fn main() {
                 "Self::{} => &{}_ALPHABET",
                 pascal_name, uppercase_name
             ));
+            let (scale_bits, entropy, expected) = parse_header(&header);
+            scale_bits_arms.push(format!("Self::{} => {:?}", pascal_name, scale_bits));
+            entropy_arms.push(format!("Self::{} => {:?}", pascal_name, entropy));
+            expected_arms.push(format!("Self::{} => {:?}", pascal_name, expected));
         }
     }
 
@@ -220,62 +273,94 @@ fn main() {
     writeln!(
         f,
         "
+/// Substitution matrices bundled with the crate.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
 #[serde(rename_all = \"UPPERCASE\")]
-pub(crate) enum BuiltinMatrix {{
+pub enum BuiltinMatrix {{
     {}
 }}
 
 impl BuiltinMatrix {{
-    pub(crate) fn from_name(s: &str) -> Option<Self> {{
+    /// Looks up a matrix by name, ignoring case.
+    pub fn from_name(s: &str) -> Option<Self> {{
         match s.to_ascii_uppercase().as_str() {{
             {},
             _ => None,
         }}
     }}
 
-    pub(crate) fn name(&self) -> &'static str {{
+    /// Canonical uppercase matrix name.
+    pub fn name(&self) -> &'static str {{
+        match self {{
+            {}
+        }}
+    }}
+
+    /// Number of residues along each matrix axis.
+    pub fn score_dimension(&self) -> usize {{
+        match self {{
+            {}
+        }}
+    }}
+
+    /// Row-major scores; `i32::MIN`/`i32::MAX` encode -inf/+inf.
+    pub fn scores(&self) -> &'static [i32] {{
+        match self {{
+            {}
+        }}
+    }}
+
+    /// Maps a residue byte (either case) to its matrix index.
+    pub fn lookup_map(&self) -> &'static [Option<u8>; 256] {{
         match self {{
             {}
         }}
     }}
 
-    pub(crate) fn score_dimension(&self) -> usize {{
+    /// Residues in matrix order.
+    pub fn alphabet(&self) -> &'static [u8] {{
         match self {{
             {}
         }}
     }}
 
-    pub(crate) fn scores(&self) -> &'static [i32] {{
+    /// Bits per score unit, as stated in the matrix header.
+    pub fn scale_bits(&self) -> Option<f64> {{
         match self {{
             {}
         }}
     }}
 
-    pub(crate) fn lookup_map(&self) -> &'static [Option<u8>; 256] {{
+    /// Relative entropy in bits, as stated in the matrix header.
+    pub fn reported_entropy(&self) -> Option<f64> {{
         match self {{
             {}
         }}
     }}
 
-    pub(crate) fn alphabet(&self) -> &'static [u8] {{
+    /// Expected score in matrix units, as stated in the matrix header.
+    pub fn reported_expected_score(&self) -> Option<f64> {{
         match self {{
             {}
         }}
     }}
 
-    pub(crate) fn all_names() -> &'static [&'static str] {{
+    /// Names of all bundled matrices.
+    pub fn all_names() -> &'static [&'static str] {{
         &[{}]
     }}
 }}
 ",
-        variants.join(",\n    "),
+        variant_decls.join(",\n    "),
         from_str_arms.join(",\n            "),
         name_arms.join(",\n            "),
         score_dimension_arms.join(",\n            "),
         scores_arms.join(",\n            "),
         lookup_map_arms.join(",\n            "),
         alphabet_arms.join(",\n            "),
+        scale_bits_arms.join(",\n            "),
+        entropy_arms.join(",\n            "),
+        expected_arms.join(",\n            "),
         all_names_str
     )
     .unwrap();
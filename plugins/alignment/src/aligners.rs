@@ -1,26 +1,37 @@
 //! Pairwise alignment algorithms.
 
 use crate::alignment::{
-    AlignmentResult, Arrows, Cell, DPMatrix, fill_matrix_linear, traceback_all_paths,
+    AlignmentResult, Arrows, Cell, DEFAULT_MAX_CELLS, DPMatrix, fill_matrix_linear,
+    traceback_all_paths,
 };
+use crate::linear_space;
 use crate::scoring::{AlignmentError, ScoringConfig};
 
 /// Global alignment algorithm.
 #[derive(Debug, Clone)]
-pub(crate) struct GlobalAligner {
+pub struct GlobalAligner {
     scoring: ScoringConfig,
+    max_cells: usize,
 }
 
 impl GlobalAligner {
-    pub(crate) fn new(scoring: ScoringConfig) -> Self {
-        Self { scoring }
+    /// Creates an aligner with the default cell limit, [`DEFAULT_MAX_CELLS`].
+    pub fn new(scoring: ScoringConfig) -> Self {
+        Self {
+            scoring,
+            max_cells: DEFAULT_MAX_CELLS,
+        }
     }
 
-    pub(crate) fn align(
-        &self,
-        seq1: &[u8],
-        seq2: &[u8],
-    ) -> Result<AlignmentResult, AlignmentError> {
+    /// Sets the largest DP matrix, in cells, that `align` may allocate.
+    pub fn with_max_cells(mut self, max_cells: usize) -> Self {
+        self.max_cells = max_cells;
+        self
+    }
+
+    /// Aligns `seq1` (matrix rows) against `seq2` (matrix columns) and
+    /// enumerates every co-optimal traceback.
+    pub fn align(&self, seq1: &[u8], seq2: &[u8]) -> Result<AlignmentResult, AlignmentError> {
         let n = seq1.len();
         let m = seq2.len();
 
@@ -30,7 +41,7 @@ impl GlobalAligner {
         self.scoring.scorer.validate(seq1)?;
         self.scoring.scorer.validate(seq2)?;
 
-        let mut matrix = self.initialize_matrix(n, m);
+        let mut matrix = self.initialize_matrix(n, m)?;
         fill_matrix_linear(&mut matrix, seq1, seq2, &self.scoring, false)?;
 
         let final_score = matrix.get(n, m).score;
@@ -39,21 +50,36 @@ impl GlobalAligner {
             &matrix,
             seq1,
             seq2,
+            &self.scoring.scorer,
             &start_positions,
             |i, j, _| i == 0 && j == 0,
             false,
         );
 
         Ok(AlignmentResult {
-            matrix,
+            matrix: Some(matrix),
             traceback_paths,
             alignments,
             final_score,
         })
     }
 
-    fn initialize_matrix(&self, n: usize, m: usize) -> DPMatrix {
-        let mut matrix = DPMatrix::new(n + 1, m + 1);
+    /// Computes the score and a single optimal alignment in linear memory.
+    ///
+    /// Used as a fallback when the DP matrix would exceed the cell limit.
+    pub fn align_without_matrix(
+        &self,
+        seq1: &[u8],
+        seq2: &[u8],
+    ) -> Result<AlignmentResult, AlignmentError> {
+        self.scoring.ensure_linear()?;
+        self.scoring.scorer.validate(seq1)?;
+        self.scoring.scorer.validate(seq2)?;
+        linear_space::align_global(seq1, seq2, &self.scoring)
+    }
+
+    fn initialize_matrix(&self, n: usize, m: usize) -> Result<DPMatrix, AlignmentError> {
+        let mut matrix = DPMatrix::try_new(n + 1, m + 1, self.max_cells)?;
         matrix.set(0, 0, Cell::new(0));
 
         for i in 1..=n {
@@ -70,7 +96,7 @@ impl GlobalAligner {
             matrix.set(0, j, Cell::with_arrows(score, arrows));
         }
 
-        matrix
+        Ok(matrix)
     }
 }
 
@@ -156,6 +182,19 @@ mod global_tests {
         assert!(result.final_score > 0);
     }
 
+    #[test]
+    fn test_similarity_markup_follows_matrix_scores() {
+        let scoring = ScoringConfig::with_matrix(BuiltinMatrix::Blosum62, -10, -10);
+        let aligner = GlobalAligner::new(scoring);
+        let result = aligner.align(b"IDAW", b"VETW").unwrap();
+
+        // I/V and D/E score positive, A/T scores zero in BLOSUM62.
+        assert_eq!(result.alignments[0].similarity, "::.|");
+
+        let scorer = &aligner.scoring.scorer;
+        assert_eq!(crate::similarity_markup("A-Wk", "RCWK", scorer), "  ||");
+    }
+
     #[test]
     fn test_pam1_forbidden_alignment() {
         let scoring =
@@ -176,6 +215,31 @@ mod global_tests {
         }
     }
 
+    #[test]
+    fn test_matrix_over_cell_limit_is_rejected() {
+        let aligner = GlobalAligner::new(ScoringConfig::default()).with_max_cells(20);
+        let result = aligner.align(b"ACGTA", b"ACGT");
+
+        assert!(matches!(
+            result,
+            Err(AlignmentError::MatrixTooLarge {
+                rows: 6,
+                cols: 5,
+                max_cells: 20
+            })
+        ));
+    }
+
+    #[test]
+    fn test_align_without_matrix_ignores_cell_limit() {
+        let aligner = GlobalAligner::new(ScoringConfig::default()).with_max_cells(1);
+        let result = aligner.align_without_matrix(b"ACGT", b"ACGT").unwrap();
+
+        assert_eq!(result.final_score, 12);
+        assert!(result.matrix.is_none());
+        assert_eq!(result.alignments[0].seq1_aligned, "ACGT");
+    }
+
     #[test]
     fn test_invalid_character_alignment() {
         let scoring = ScoringConfig::with_matrix(BuiltinMatrix::Ednafull, -2, -2);
@@ -190,20 +254,29 @@ mod global_tests {
 
 /// Local alignment algorithm.
 #[derive(Debug, Clone)]
-pub(crate) struct LocalAligner {
+pub struct LocalAligner {
     scoring: ScoringConfig,
+    max_cells: usize,
 }
 
 impl LocalAligner {
-    pub(crate) fn new(scoring: ScoringConfig) -> Self {
-        Self { scoring }
+    /// Creates an aligner with the default cell limit, [`DEFAULT_MAX_CELLS`].
+    pub fn new(scoring: ScoringConfig) -> Self {
+        Self {
+            scoring,
+            max_cells: DEFAULT_MAX_CELLS,
+        }
     }
 
-    pub(crate) fn align(
-        &self,
-        seq1: &[u8],
-        seq2: &[u8],
-    ) -> Result<AlignmentResult, AlignmentError> {
+    /// Sets the largest DP matrix, in cells, that `align` may allocate.
+    pub fn with_max_cells(mut self, max_cells: usize) -> Self {
+        self.max_cells = max_cells;
+        self
+    }
+
+    /// Aligns `seq1` (matrix rows) against `seq2` (matrix columns) and
+    /// enumerates every co-optimal traceback.
+    pub fn align(&self, seq1: &[u8], seq2: &[u8]) -> Result<AlignmentResult, AlignmentError> {
         let n = seq1.len();
         let m = seq2.len();
 
@@ -213,7 +286,7 @@ impl LocalAligner {
         self.scoring.scorer.validate(seq1)?;
         self.scoring.scorer.validate(seq2)?;
 
-        let mut matrix = self.initialize_matrix(n, m);
+        let mut matrix = self.initialize_matrix(n, m)?;
         let fill_result = fill_matrix_linear(&mut matrix, seq1, seq2, &self.scoring, true)?;
         let final_score = fill_result.max_score;
         let max_positions = fill_result.max_positions;
@@ -223,6 +296,7 @@ impl LocalAligner {
                 &matrix,
                 seq1,
                 seq2,
+                &self.scoring.scorer,
                 &max_positions,
                 |i, j, cell| cell.score == 0 || (i == 0 && j == 0),
                 true,
@@ -233,17 +307,31 @@ impl LocalAligner {
         };
 
         Ok(AlignmentResult {
-            matrix,
+            matrix: Some(matrix),
             traceback_paths,
             alignments,
             final_score,
         })
     }
 
-    fn initialize_matrix(&self, n: usize, m: usize) -> DPMatrix {
+    /// Computes the score and a single optimal alignment in linear memory.
+    ///
+    /// Used as a fallback when the DP matrix would exceed the cell limit.
+    pub fn align_without_matrix(
+        &self,
+        seq1: &[u8],
+        seq2: &[u8],
+    ) -> Result<AlignmentResult, AlignmentError> {
+        self.scoring.ensure_linear()?;
+        self.scoring.scorer.validate(seq1)?;
+        self.scoring.scorer.validate(seq2)?;
+        linear_space::align_local(seq1, seq2, &self.scoring)
+    }
+
+    fn initialize_matrix(&self, n: usize, m: usize) -> Result<DPMatrix, AlignmentError> {
         // In local alignment, first row and column are initialized to 0
         // (no arrows needed - they represent the option to start fresh)
-        let mut matrix = DPMatrix::new(n + 1, m + 1);
+        let mut matrix = DPMatrix::try_new(n + 1, m + 1, self.max_cells)?;
 
         for i in 0..=n {
             matrix.set(i, 0, Cell::new(0));
@@ -252,7 +340,7 @@ impl LocalAligner {
             matrix.set(0, j, Cell::new(0));
         }
 
-        matrix
+        Ok(matrix)
     }
 }
 
@@ -297,7 +385,7 @@ mod local_tests {
         let result = aligner.align(b"ACGT", b"TGCA").unwrap();
 
         // All cells should have score >= 0
-        for cell in &result.matrix.cells {
+        for cell in &result.matrix.unwrap().cells {
             assert!(cell.score >= 0, "Found negative score: {}", cell.score);
         }
     }
@@ -2,32 +2,45 @@
 
 use crate::scoring::{AlignmentError, ScoringConfig, SubstitutionScorer};
 
+/// Default upper bound on the number of DP cells allocated for one alignment.
+pub const DEFAULT_MAX_CELLS: usize = 4_000_000;
+
 /// Arrow directions stored as a 3-bit bitmask.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub(crate) struct Arrows(u8);
+pub struct Arrows(u8);
 
 impl Arrows {
-    pub(crate) const DIAGONAL: u8 = 1;
-    pub(crate) const UP: u8 = 2;
-    pub(crate) const LEFT: u8 = 4;
+    /// Bit set when the cell is reached from the upper-left cell (a match or
+    /// mismatch).
+    pub const DIAGONAL: u8 = 1;
+    /// Bit set when the cell is reached from the cell above (a gap in the
+    /// second sequence).
+    pub const UP: u8 = 2;
+    /// Bit set when the cell is reached from the cell to the left (a gap in
+    /// the first sequence).
+    pub const LEFT: u8 = 4;
 
     pub(crate) fn new() -> Self {
         Self(0)
     }
 
-    pub(crate) fn bits(&self) -> u8 {
+    /// The raw bitmask of [`Self::DIAGONAL`], [`Self::UP`] and [`Self::LEFT`].
+    pub fn bits(&self) -> u8 {
         self.0
     }
 
-    pub(crate) fn has_diagonal(&self) -> bool {
+    /// Whether the diagonal arrow is set.
+    pub fn has_diagonal(&self) -> bool {
         (self.0 & 1) != 0
     }
 
-    pub(crate) fn has_up(&self) -> bool {
+    /// Whether the up arrow is set.
+    pub fn has_up(&self) -> bool {
         (self.0 & 2) != 0
     }
 
-    pub(crate) fn has_left(&self) -> bool {
+    /// Whether the left arrow is set.
+    pub fn has_left(&self) -> bool {
         (self.0 & 4) != 0
     }
 
@@ -47,9 +60,11 @@ impl Arrows {
 
 /// A cell in the dynamic programming matrix.
 #[derive(Debug, Clone, Copy)]
-pub(crate) struct Cell {
-    pub(crate) score: i32,
-    pub(crate) arrows: Arrows,
+pub struct Cell {
+    /// Best score of an alignment ending in this cell.
+    pub score: i32,
+    /// The neighbouring cells that reach this score.
+    pub arrows: Arrows,
 }
 
 impl Cell {
@@ -76,10 +91,13 @@ impl Default for Cell {
 
 /// The dynamic programming matrix.
 #[derive(Debug, Clone)]
-pub(crate) struct DPMatrix {
-    pub(crate) rows: usize,
-    pub(crate) cols: usize,
-    pub(crate) cells: Vec<Cell>,
+pub struct DPMatrix {
+    /// Length of the first sequence plus one.
+    pub rows: usize,
+    /// Length of the second sequence plus one.
+    pub cols: usize,
+    /// Row-major cells, `rows * cols` long.
+    pub cells: Vec<Cell>,
 }
 
 impl DPMatrix {
@@ -91,7 +109,24 @@ impl DPMatrix {
         }
     }
 
-    pub(crate) fn get(&self, i: usize, j: usize) -> &Cell {
+    /// Allocates a matrix only if `rows * cols` does not exceed `max_cells`.
+    pub(crate) fn try_new(
+        rows: usize,
+        cols: usize,
+        max_cells: usize,
+    ) -> Result<Self, AlignmentError> {
+        match rows.checked_mul(cols) {
+            Some(cells) if cells <= max_cells => Ok(Self::new(rows, cols)),
+            _ => Err(AlignmentError::MatrixTooLarge {
+                rows,
+                cols,
+                max_cells,
+            }),
+        }
+    }
+
+    /// The cell at row `i` and column `j`.
+    pub fn get(&self, i: usize, j: usize) -> &Cell {
         &self.cells[i * self.cols + j]
     }
 
@@ -102,15 +137,18 @@ impl DPMatrix {
 
 /// A step in the traceback path.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) struct TracebackStep {
-    pub(crate) i: usize,
-    pub(crate) j: usize,
+pub struct TracebackStep {
+    /// Row of the cell, i.e. residues of the first sequence consumed.
+    pub i: usize,
+    /// Column of the cell, i.e. residues of the second sequence consumed.
+    pub j: usize,
 }
 
-/// A complete traceback path.
+/// A complete traceback path, in end-to-start order.
 #[derive(Debug, Clone)]
-pub(crate) struct TracebackPath {
-    pub(crate) steps: Vec<TracebackStep>,
+pub struct TracebackPath {
+    /// Visited cells, from the end cell back to the start cell.
+    pub steps: Vec<TracebackStep>,
 }
 
 impl TracebackPath {
@@ -121,18 +159,74 @@ impl TracebackPath {
 
 /// A pair of aligned sequences.
 #[derive(Debug, Clone)]
-pub(crate) struct AlignedPair {
-    pub(crate) seq1_aligned: String,
-    pub(crate) seq2_aligned: String,
+pub struct AlignedPair {
+    /// The first sequence with `-` for gaps.
+    pub seq1_aligned: String,
+    /// The second sequence with `-` for gaps.
+    pub seq2_aligned: String,
+    /// Similarity class of each column; see [`similarity_markup`].
+    pub similarity: String,
+}
+
+impl AlignedPair {
+    pub(crate) fn new(
+        seq1_aligned: String,
+        seq2_aligned: String,
+        scorer: &SubstitutionScorer,
+    ) -> Self {
+        let similarity = similarity_markup(&seq1_aligned, &seq2_aligned, scorer);
+        Self {
+            seq1_aligned,
+            seq2_aligned,
+            similarity,
+        }
+    }
+}
+
+/// EMBOSS-style similarity markup for two aligned sequences, one character
+/// per column: `|` for identical residues, `:` for a positive substitution
+/// score, `.` for a score of zero, and a space for negative scores and gaps.
+pub fn similarity_markup(
+    seq1_aligned: &str,
+    seq2_aligned: &str,
+    scorer: &SubstitutionScorer,
+) -> String {
+    seq1_aligned
+        .chars()
+        .zip(seq2_aligned.chars())
+        .map(|(a, b)| {
+            if a == '-' || b == '-' {
+                return ' ';
+            }
+            if a.eq_ignore_ascii_case(&b) {
+                return '|';
+            }
+            let score = (a.is_ascii() && b.is_ascii())
+                .then(|| scorer.score(a as u8, b as u8))
+                .flatten();
+            match score {
+                Some(score) if score > 0 => ':',
+                Some(0) => '.',
+                _ => ' ',
+            }
+        })
+        .collect()
 }
 
 /// The complete result of an alignment operation.
+///
+/// `matrix` is `None` when the alignment was computed in linear memory
+/// because the full DP matrix exceeded the cell limit.
 #[derive(Debug, Clone)]
-pub(crate) struct AlignmentResult {
-    pub(crate) matrix: DPMatrix,
-    pub(crate) traceback_paths: Vec<TracebackPath>,
-    pub(crate) alignments: Vec<AlignedPair>,
-    pub(crate) final_score: i32,
+pub struct AlignmentResult {
+    /// The filled DP matrix.
+    pub matrix: Option<DPMatrix>,
+    /// One traceback path per optimal alignment.
+    pub traceback_paths: Vec<TracebackPath>,
+    /// The optimal alignments, in the order of `traceback_paths`.
+    pub alignments: Vec<AlignedPair>,
+    /// Score of the optimal alignments.
+    pub final_score: i32,
 }
 
 #[derive(Debug, Clone)]
@@ -193,6 +287,16 @@ pub(crate) fn fill_matrix_linear(
             scoring.gap_open,
             local,
         ),
+        SubstitutionScorer::Custom(matrix_scorer) => fill_matrix_linear_matrix(
+            matrix,
+            seq1,
+            seq2,
+            matrix_scorer.lookup_map(),
+            matrix_scorer.scores(),
+            matrix_scorer.score_dimension(),
+            scoring.gap_open,
+            local,
+        ),
     }
 }
 
@@ -332,7 +436,7 @@ fn uppercase_ascii_if_needed(seq: &[u8]) -> Option<Vec<u8>> {
         .then(|| seq.iter().map(|byte| byte.to_ascii_uppercase()).collect())
 }
 
-fn encode_matrix_sequence(
+pub(crate) fn encode_matrix_sequence(
     seq: &[u8],
     lookup_map: &[Option<u8>; 256],
 ) -> Result<Vec<usize>, AlignmentError> {
@@ -351,6 +455,7 @@ struct TracebackContext<'a> {
     seq2: &'a [u8],
     all_paths: &'a mut Vec<TracebackPath>,
     all_alignments: &'a mut Vec<AlignedPair>,
+    scorer: &'a SubstitutionScorer,
     stop_condition: Box<dyn Fn(usize, usize, &Cell) -> bool + 'a>,
     stop_on_no_arrows: bool,
 }
@@ -359,6 +464,7 @@ pub(crate) fn traceback_all_paths(
     matrix: &DPMatrix,
     seq1: &[u8],
     seq2: &[u8],
+    scorer: &SubstitutionScorer,
     start_positions: &[(usize, usize)],
     stop_condition: impl Fn(usize, usize, &Cell) -> bool + Copy,
     stop_on_no_arrows: bool,
@@ -378,6 +484,7 @@ pub(crate) fn traceback_all_paths(
         seq2,
         all_paths: &mut all_paths,
         all_alignments: &mut all_alignments,
+        scorer,
         stop_condition: Box::new(stop_condition),
         stop_on_no_arrows,
     };
@@ -413,10 +520,11 @@ fn traceback_recursive(
 
     let cell = ctx.matrix.get(i, j);
     if (ctx.stop_condition)(i, j, cell) || (ctx.stop_on_no_arrows && cell.arrows.bits() == 0) {
-        let pair = AlignedPair {
-            seq1_aligned: reversed_utf8_string(current_aln1),
-            seq2_aligned: reversed_utf8_string(current_aln2),
-        };
+        let pair = AlignedPair::new(
+            reversed_utf8_string(current_aln1),
+            reversed_utf8_string(current_aln2),
+            ctx.scorer,
+        );
 
         ctx.all_paths.push(TracebackPath {
             steps: current_path.steps.clone(),
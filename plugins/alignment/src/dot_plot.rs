@@ -0,0 +1,434 @@
+//! Dot plots as lists of diagonal segments.
+//!
+//! Two methods find matching regions. `kmer` reports exact word matches,
+//! like EMBOSS dottup, using a sorted index of the second sequence, so whole
+//! genomes can be compared without visiting every cell. `window` scores every
+//! window of `window` residue pairs along each diagonal and keeps those scoring
+//! at least `threshold`, like EMBOSS dotmatcher. Without a matrix, residues
+//! score 1 when identical and 0 otherwise, so the threshold is a minimum
+//! identity. Overlapping hits on the same diagonal are merged into one segment.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::matrices::BuiltinMatrix;
+use crate::scoring::{AlignmentError, SubstitutionScorer};
+use crate::strand::{Strand, StrandMode, reverse_complement};
+
+/// Default cell limit for the `window` method, which visits every cell but
+/// stores none of them, and word-match limit for the `kmer` method.
+pub const DEFAULT_MAX_DOT_PLOT_CELLS: usize = 100_000_000;
+
+/// How matching regions are found.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum DotPlotMethod {
+    /// Exact matches of `word_size` residues.
+    #[default]
+    Kmer,
+    /// Windows of `window` residue pairs scoring at least `threshold`.
+    Window,
+}
+
+/// Configuration for [`compute_dot_plot`], deserialized from JSON or CBOR.
+#[derive(Deserialize, Clone, Debug)]
+pub struct DotPlotConfig {
+    /// Exact k-mer matches or scored windows.
+    #[serde(default)]
+    pub method: DotPlotMethod,
+    /// K-mer length for the `kmer` method.
+    #[serde(default = "default_length")]
+    pub word_size: usize,
+    /// Window length for the `window` method.
+    #[serde(default = "default_length")]
+    pub window: usize,
+    /// Minimum window score; required by the `window` method.
+    #[serde(default)]
+    pub threshold: Option<i32>,
+    /// Substitution matrix scoring windows of the `window` method.
+    #[serde(default)]
+    pub matrix: Option<BuiltinMatrix>,
+    /// Score for identical residues in windows, used with `mismatch_score`.
+    #[serde(default)]
+    pub match_score: Option<i32>,
+    /// Score for different residues in windows, used with `match_score`.
+    #[serde(default)]
+    pub mismatch_score: Option<i32>,
+    /// With [`StrandMode::Both`], also compare against the reverse
+    /// complement of the second sequence.
+    #[serde(default)]
+    pub strand: StrandMode,
+    /// Shortest segment to report, in residues.
+    #[serde(default)]
+    pub min_length: usize,
+    /// Cell limit for the `window` method and limit on word matches, summed
+    /// over strands, for the `kmer` method; defaults to
+    /// [`DEFAULT_MAX_DOT_PLOT_CELLS`].
+    #[serde(default)]
+    pub max_cells: Option<usize>,
+}
+
+fn default_length() -> usize {
+    10
+}
+
+/// A run of matching residue pairs along one diagonal.
+///
+/// Coordinates are 1-based and inclusive, `x` on the first sequence and `y` on
+/// the second. On the reverse strand `y` decreases from start to end.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DotPlotSegment {
+    /// Strand of the second sequence the segment lies on.
+    pub strand: Strand,
+    /// First position on the first sequence.
+    pub x_start: usize,
+    /// First position on the second sequence.
+    pub y_start: usize,
+    /// Last position on the first sequence.
+    pub x_end: usize,
+    /// Last position on the second sequence.
+    pub y_end: usize,
+    /// Number of residue pairs in the segment.
+    pub length: usize,
+}
+
+/// Result of [`compute_dot_plot`].
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct DotPlot {
+    /// Length of the first sequence.
+    pub seq1_length: usize,
+    /// Length of the second sequence.
+    pub seq2_length: usize,
+    /// Matching segments on both strands, ordered by diagonal.
+    pub segments: Vec<DotPlotSegment>,
+}
+
+/// Merges hits that overlap on the same diagonal into runs.
+struct RunMerger {
+    span: usize,
+    /// Open run per diagonal: start in each sequence and start of the last hit.
+    open: HashMap<isize, (usize, usize, usize)>,
+    /// Finished runs as 0-based starts and a length.
+    runs: Vec<(usize, usize, usize)>,
+}
+
+impl RunMerger {
+    fn new(span: usize) -> Self {
+        Self {
+            span,
+            open: HashMap::new(),
+            runs: Vec::new(),
+        }
+    }
+
+    /// Adds a hit covering `span` pairs from `(i, j)`. Hits on one diagonal
+    /// must arrive in increasing `i`.
+    fn add(&mut self, i: usize, j: usize) {
+        let diagonal = i as isize - j as isize;
+        match self.open.get_mut(&diagonal) {
+            Some(run) if i < run.2 + self.span => run.2 = i,
+            Some(run) => {
+                self.runs.push((run.0, run.1, run.2 + self.span - run.0));
+                *run = (i, j, i);
+            }
+            None => {
+                self.open.insert(diagonal, (i, j, i));
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<(usize, usize, usize)> {
+        let span = self.span;
+        self.runs.extend(
+            self.open
+                .into_values()
+                .map(|(i, j, last)| (i, j, last + span - i)),
+        );
+        self.runs
+    }
+}
+
+/// Exact word matches between `seq1` and `seq2`, both already uppercase.
+///
+/// Low-complexity sequences can match almost every word pair, so at most
+/// `budget` matches are visited; the budget is reduced by those found.
+fn kmer_runs(
+    seq1: &[u8],
+    seq2: &[u8],
+    k: usize,
+    budget: &mut usize,
+    max_hits: usize,
+) -> Result<Vec<(usize, usize, usize)>, AlignmentError> {
+    let mut merger = RunMerger::new(k);
+    if seq1.len() < k || seq2.len() < k {
+        return Ok(merger.finish());
+    }
+    let word = |j: usize| &seq2[j..j + k];
+    // Stable sort keeps positions of equal words in increasing order.
+    let mut index: Vec<usize> = (0..=seq2.len() - k).collect();
+    index.sort_by(|&a, &b| word(a).cmp(word(b)));
+
+    for i in 0..=seq1.len() - k {
+        let query = &seq1[i..i + k];
+        let first = index.partition_point(|&j| word(j) < query);
+        let count = index[first..].partition_point(|&j| word(j) == query);
+        *budget = budget.checked_sub(count).ok_or_else(|| {
+            AlignmentError::Other(format!(
+                "Dot plot has more than {} word matches; increase 'word_size' or 'max_cells'",
+                max_hits
+            ))
+        })?;
+        for &j in &index[first..first + count] {
+            merger.add(i, j);
+        }
+    }
+    Ok(merger.finish())
+}
+
+/// Windows scoring at least `threshold` along every diagonal.
+fn window_runs(
+    seq1: &[u8],
+    seq2: &[u8],
+    window: usize,
+    threshold: i32,
+    scorer: &SubstitutionScorer,
+) -> Vec<(usize, usize, usize)> {
+    let mut merger = RunMerger::new(window);
+    if seq1.len() < window || seq2.len() < window {
+        return merger.finish();
+    }
+    let score = |i: usize, j: usize| scorer.score(seq1[i], seq2[j]).unwrap_or(0);
+    let starts = (0..=seq2.len() - window)
+        .rev()
+        .map(|j| (0, j))
+        .chain((1..=seq1.len() - window).map(|i| (i, 0)));
+    for (i0, j0) in starts {
+        let length = (seq1.len() - i0).min(seq2.len() - j0);
+        let mut sum = 0;
+        for t in 0..length {
+            sum += score(i0 + t, j0 + t);
+            if t >= window {
+                sum -= score(i0 + t - window, j0 + t - window);
+            }
+            if t + 1 >= window && sum >= threshold {
+                merger.add(i0 + t + 1 - window, j0 + t + 1 - window);
+            }
+        }
+    }
+    merger.finish()
+}
+
+/// Computes the diagonal segments of a dot plot of `seq1` against `seq2`.
+///
+/// Matching ignores case. Segments are sorted by position on `seq1`.
+pub fn compute_dot_plot(
+    seq1: &[u8],
+    seq2: &[u8],
+    config: &DotPlotConfig,
+) -> Result<DotPlot, AlignmentError> {
+    if !seq1.is_ascii() || !seq2.is_ascii() {
+        return Err(AlignmentError::Other(
+            "Dot plot sequences must contain only ASCII characters".into(),
+        ));
+    }
+    let scorer = match (config.matrix, config.match_score, config.mismatch_score) {
+        (Some(_), Some(_), _) | (Some(_), _, Some(_)) => {
+            return Err(AlignmentError::Other(
+                "Cannot use both 'matrix' and 'match_score'/'mismatch_score' - they are mutually exclusive".into(),
+            ));
+        }
+        (Some(matrix), None, None) => SubstitutionScorer::Matrix(matrix),
+        (None, None, None) => SubstitutionScorer::Simple {
+            match_score: 1,
+            mismatch_score: 0,
+        },
+        (None, Some(match_score), Some(mismatch_score)) => SubstitutionScorer::Simple {
+            match_score,
+            mismatch_score,
+        },
+        (None, _, _) => {
+            return Err(AlignmentError::Other(
+                "Both 'match_score' and 'mismatch_score' are required when not using a matrix"
+                    .into(),
+            ));
+        }
+    };
+    let threshold = match config.method {
+        DotPlotMethod::Kmer if config.word_size == 0 => {
+            return Err(AlignmentError::Other("'word_size' must be positive".into()));
+        }
+        DotPlotMethod::Kmer => None,
+        DotPlotMethod::Window if config.window == 0 => {
+            return Err(AlignmentError::Other("'window' must be positive".into()));
+        }
+        DotPlotMethod::Window => {
+            let threshold = config.threshold.ok_or_else(|| {
+                AlignmentError::Other("'threshold' is required by the window method".into())
+            })?;
+            let max_cells = config.max_cells.unwrap_or(DEFAULT_MAX_DOT_PLOT_CELLS);
+            let strands = if config.strand == StrandMode::Both {
+                2
+            } else {
+                1
+            };
+            if seq1
+                .len()
+                .saturating_mul(seq2.len())
+                .saturating_mul(strands)
+                > max_cells
+            {
+                return Err(AlignmentError::MatrixTooLarge {
+                    rows: seq1.len(),
+                    cols: seq2.len() * strands,
+                    max_cells,
+                });
+            }
+            scorer.validate(seq1)?;
+            scorer.validate(seq2)?;
+            Some(threshold)
+        }
+    };
+
+    let seq1_upper = seq1.to_ascii_uppercase();
+    let mut strands = vec![(Strand::Forward, seq2.to_ascii_uppercase())];
+    if config.strand == StrandMode::Both {
+        strands.push((Strand::Reverse, reverse_complement(&strands[0].1)?));
+    }
+
+    let max_hits = config.max_cells.unwrap_or(DEFAULT_MAX_DOT_PLOT_CELLS);
+    let mut budget = max_hits;
+    let mut segments = Vec::new();
+    for (strand, target) in &strands {
+        let runs = match threshold {
+            None => kmer_runs(&seq1_upper, target, config.word_size, &mut budget, max_hits)?,
+            Some(threshold) => window_runs(&seq1_upper, target, config.window, threshold, &scorer),
+        };
+        let len = target.len();
+        segments.extend(
+            runs.into_iter()
+                .filter(|&(_, _, length)| length >= config.min_length)
+                .map(|(i, j, length)| {
+                    let (y_start, y_end) = match strand {
+                        Strand::Forward => (j + 1, j + length),
+                        Strand::Reverse => (len - j, len - j - length + 1),
+                    };
+                    DotPlotSegment {
+                        strand: *strand,
+                        x_start: i + 1,
+                        y_start,
+                        x_end: i + length,
+                        y_end,
+                        length,
+                    }
+                }),
+        );
+    }
+    segments.sort_by_key(|s| (s.x_start, s.y_start, s.strand == Strand::Reverse));
+
+    Ok(DotPlot {
+        seq1_length: seq1.len(),
+        seq2_length: seq2.len(),
+        segments,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(method: DotPlotMethod) -> DotPlotConfig {
+        DotPlotConfig {
+            method,
+            word_size: 4,
+            window: 4,
+            threshold: None,
+            matrix: None,
+            match_score: None,
+            mismatch_score: None,
+            strand: StrandMode::Forward,
+            min_length: 0,
+            max_cells: None,
+        }
+    }
+
+    #[test]
+    fn test_kmer_hits_merge_into_segments() {
+        let plot =
+            compute_dot_plot(b"ttACGTACGGcc", b"ACGTACGG", &config(DotPlotMethod::Kmer)).unwrap();
+        let segments: Vec<_> = plot
+            .segments
+            .iter()
+            .map(|s| (s.x_start, s.y_start, s.x_end, s.y_end))
+            .collect();
+
+        // The full match, plus TACG, which also starts at position 2.
+        assert_eq!(segments, [(2, 4, 5, 7), (3, 1, 10, 8)]);
+        assert_eq!(plot.seq1_length, 12);
+    }
+
+    #[test]
+    fn test_kmer_limits_word_matches_of_low_complexity_sequences() {
+        let poly_a = vec![b'A'; 200];
+        let mut cfg = config(DotPlotMethod::Kmer);
+        cfg.word_size = 4;
+
+        // 197 x 197 word matches merge into one segment per diagonal.
+        let plot = compute_dot_plot(&poly_a, &poly_a, &cfg).unwrap();
+        assert_eq!(plot.segments.len(), 2 * 197 - 1);
+
+        cfg.max_cells = Some(197 * 197 - 1);
+        let err = compute_dot_plot(&poly_a, &poly_a, &cfg).unwrap_err();
+        assert!(err.to_string().contains("word matches"), "{}", err);
+
+        // The limit covers both strands together.
+        cfg.max_cells = Some(197 * 197);
+        cfg.strand = StrandMode::Both;
+        assert!(compute_dot_plot(&poly_a, b"TTTTTT", &cfg).is_ok());
+        assert!(compute_dot_plot(&poly_a, &[b'T'; 201], &cfg).is_err());
+    }
+
+    #[test]
+    fn test_reverse_strand_segments_use_original_coordinates() {
+        let mut cfg = config(DotPlotMethod::Kmer);
+        cfg.word_size = 5;
+        cfg.strand = StrandMode::Both;
+        // GGCTA is the reverse complement of TAGCC at positions 3..=7.
+        let plot = compute_dot_plot(b"GGCTA", b"ttTAGCCtt", &cfg).unwrap();
+
+        assert_eq!(
+            plot.segments,
+            [DotPlotSegment {
+                strand: Strand::Reverse,
+                x_start: 1,
+                y_start: 7,
+                x_end: 5,
+                y_end: 3,
+                length: 5,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_window_threshold_tolerates_mismatches() {
+        let mut cfg = config(DotPlotMethod::Window);
+        cfg.threshold = Some(3);
+        cfg.min_length = 8;
+        let plot = compute_dot_plot(b"AAACGTTCGTAAA", b"CCCCGATCGTCC", &cfg).unwrap();
+
+        // CGTTCGTA against CGATCGTC: windows starting at 4 to 8 have at
+        // least 3 identities, so the segment includes the edge mismatches.
+        // A shorter segment at (2, 6) falls below `min_length`.
+        let segment = plot.segments[0];
+        assert_eq!(plot.segments.len(), 1);
+        assert_eq!((segment.x_start, segment.y_start), (4, 4));
+        assert_eq!((segment.x_end, segment.y_end), (11, 11));
+
+        cfg.threshold = None;
+        assert!(compute_dot_plot(b"ACGT", b"ACGT", &cfg).is_err());
+        cfg.threshold = Some(3);
+        assert!(compute_dot_plot("ACGTá".as_bytes(), b"ACGT", &cfg).is_err());
+        cfg.max_cells = Some(10);
+        assert!(compute_dot_plot(b"ACGT", b"ACGT", &cfg).is_err());
+    }
+}
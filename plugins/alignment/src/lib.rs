@@ -1,52 +1,128 @@
 //! seq-align: Pairwise sequence alignment WASM plugin
+//!
+//! The crate also builds as a native library. [`align_sequences`] runs the
+//! same code path as the `align` WASM entry point, and the aligners, scoring
+//! types and built-in matrices are re-exported for direct use.
+
+// `wasm_func` generates undocumented export shims, so only lint native builds.
+#![cfg_attr(not(target_arch = "wasm32"), warn(missing_docs))]
 
 mod aligners;
 mod alignment;
+mod dot_plot;
+mod linear_space;
+mod log_odds;
 mod matrices;
+mod matrix_stats;
 mod output;
+mod pam;
 mod scoring;
+mod strand;
+mod substitution_matrix;
 
-use aligners::{GlobalAligner, LocalAligner};
-use matrices::BuiltinMatrix;
-use scoring::{AlignmentError, ScoringConfig};
-use serde::Deserialize;
-#[cfg(target_arch = "wasm32")]
+pub use aligners::{GlobalAligner, LocalAligner};
+pub use alignment::{
+    AlignedPair, AlignmentResult, Arrows, Cell, DEFAULT_MAX_CELLS, DPMatrix, TracebackPath,
+    TracebackStep, similarity_markup,
+};
+pub use dot_plot::{
+    DEFAULT_MAX_DOT_PLOT_CELLS, DotPlot, DotPlotConfig, DotPlotMethod, DotPlotSegment,
+    compute_dot_plot,
+};
+pub use log_odds::{LogOddsConfig, Rounding, STANDARD_AMINO_ACIDS, derive_log_odds_matrix};
+pub use matrices::BuiltinMatrix;
+pub use matrix_stats::{BackgroundSource, MatrixStatistics, matrix_statistics};
+pub use output::{serialize_alignment_result, serialize_strand_alignments};
+pub use pam::{DAYHOFF_ALPHABET, PamConfig, extrapolate_pam};
+pub use plugin_io::WireFormat;
+pub use scoring::{AlignmentError, ScoringConfig, SubstitutionScorer};
+use serde::{Deserialize, Serialize};
+pub use strand::{Strand, StrandAlignment, StrandMode, StrandReport, reverse_complement};
+pub use substitution_matrix::SubstitutionMatrix;
+#[cfg(all(target_arch = "wasm32", feature = "plugin"))]
 use wasm_minimal_protocol::*;
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", feature = "plugin"))]
 initiate_protocol!();
 
+/// Global (end-to-end) or local (best-matching region) alignment.
 #[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
 #[serde(rename_all = "kebab-case")]
-enum AlignMode {
+pub enum AlignMode {
+    /// Needleman-Wunsch alignment of both sequences end to end.
     #[default]
     Global,
+    /// Smith-Waterman alignment of the best-scoring pair of regions.
     Local,
 }
 
-/// Configuration for alignment, deserialized from JSON.
-#[derive(Deserialize)]
-struct AlignConfig {
+/// What to do when the DP matrix would exceed `max_cells`.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum OversizePolicy {
+    /// Fail with an error naming the matrix size and the limit.
+    #[default]
+    Error,
+    /// Return the score and one alignment computed in linear memory,
+    /// without the DP matrix.
+    AlignmentOnly,
+}
+
+/// Configuration for alignment, deserialized from JSON or CBOR.
+///
+/// Exactly one scoring method is allowed: `matrix`, `custom_matrix`, or both
+/// `match_score` and `mismatch_score`. `max_cells` defaults to
+/// [`DEFAULT_MAX_CELLS`]. `strand` and `strand_report` only apply to
+/// nucleotide sequences; see [`align_strands`].
+#[derive(Deserialize, Clone, Debug)]
+pub struct AlignConfig {
+    /// Global or local alignment.
+    #[serde(default)]
+    pub mode: AlignMode,
+    /// Built-in substitution matrix, such as BLOSUM62.
+    #[serde(default)]
+    pub matrix: Option<BuiltinMatrix>,
+    /// Matrix in the `{name, alphabet, scores}` shape of `matrix_info`.
+    #[serde(default)]
+    pub custom_matrix: Option<SubstitutionMatrix>,
+    /// Score for identical residues, used with `mismatch_score`.
+    #[serde(default)]
+    pub match_score: Option<i32>,
+    /// Score for different residues, used with `match_score`.
+    #[serde(default)]
+    pub mismatch_score: Option<i32>,
+    /// Penalty for the first residue of a gap.
+    pub gap_open: i32,
+    /// Penalty for each further residue of a gap.
+    pub gap_extend: i32,
+    /// Largest DP matrix to allocate, in cells.
     #[serde(default)]
-    mode: AlignMode,
+    pub max_cells: Option<usize>,
+    /// What to do when the DP matrix would exceed `max_cells`.
     #[serde(default)]
-    matrix: Option<BuiltinMatrix>,
+    pub oversize: OversizePolicy,
+    /// Whether to also align the reverse complement of the second sequence.
     #[serde(default)]
-    match_score: Option<i32>,
+    pub strand: StrandMode,
+    /// Which strands to report when aligning both.
     #[serde(default)]
-    mismatch_score: Option<i32>,
-    gap_open: i32,
-    gap_extend: i32,
+    pub strand_report: StrandReport,
 }
 
 impl AlignConfig {
-    fn validate(&self) -> Result<(), AlignmentError> {
-        let has_matrix = self.matrix.is_some();
+    /// Checks that the scoring options are consistent and supported.
+    pub fn validate(&self) -> Result<(), AlignmentError> {
+        if self.matrix.is_some() && self.custom_matrix.is_some() {
+            return Err(AlignmentError::Other(
+                "Cannot use both 'matrix' and 'custom_matrix' - they are mutually exclusive".into(),
+            ));
+        }
+        let has_matrix = self.matrix.is_some() || self.custom_matrix.is_some();
         let has_match = self.match_score.is_some();
         let has_mismatch = self.mismatch_score.is_some();
 
         if has_matrix && (has_match || has_mismatch) {
-            return Err(AlignmentError::Other("Cannot use both 'matrix' and 'match_score'/'mismatch_score' - they are mutually exclusive".into()));
+            return Err(AlignmentError::Other("Cannot use both a matrix and 'match_score'/'mismatch_score' - they are mutually exclusive".into()));
         }
         if !has_matrix && has_match != has_mismatch {
             return Err(AlignmentError::Other(
@@ -55,7 +131,7 @@ impl AlignConfig {
             ));
         }
         if !has_matrix && !has_match {
-            return Err(AlignmentError::Other("Scoring method required: provide either 'matrix' or both 'match_score' and 'mismatch_score'".into()));
+            return Err(AlignmentError::Other("Scoring method required: provide either 'matrix', 'custom_matrix' or both 'match_score' and 'mismatch_score'".into()));
         }
         if self.gap_open != self.gap_extend {
             return Err(AlignmentError::Other(format!(
@@ -65,6 +141,81 @@ impl AlignConfig {
         }
         Ok(())
     }
+
+    /// Builds the scoring configuration. Call [`AlignConfig::validate`] first.
+    pub fn scoring(&self) -> ScoringConfig {
+        if let Some(bm) = self.matrix {
+            ScoringConfig::with_matrix(bm, self.gap_open, self.gap_extend)
+        } else if let Some(matrix) = &self.custom_matrix {
+            ScoringConfig::with_custom_matrix(matrix.clone(), self.gap_open, self.gap_extend)
+        } else {
+            ScoringConfig::linear(
+                self.match_score
+                    .expect("match_score must be Some after validation"),
+                self.mismatch_score
+                    .expect("mismatch_score must be Some after validation"),
+                self.gap_open,
+                self.gap_extend,
+            )
+        }
+    }
+}
+
+/// Aligns two sequences as described by `config`.
+///
+/// This is the native counterpart of the `align` WASM entry point, including
+/// the cell limit and the oversize fallback.
+pub fn align_sequences(
+    seq1: &[u8],
+    seq2: &[u8],
+    config: &AlignConfig,
+) -> Result<AlignmentResult, AlignmentError> {
+    config.validate()?;
+
+    let scoring = config.scoring();
+    let max_cells = config.max_cells.unwrap_or(DEFAULT_MAX_CELLS);
+    match config.mode {
+        AlignMode::Global => {
+            let aligner = GlobalAligner::new(scoring).with_max_cells(max_cells);
+            apply_oversize_policy(aligner.align(seq1, seq2), config.oversize, || {
+                aligner.align_without_matrix(seq1, seq2)
+            })
+        }
+        AlignMode::Local => {
+            let aligner = LocalAligner::new(scoring).with_max_cells(max_cells);
+            apply_oversize_policy(aligner.align(seq1, seq2), config.oversize, || {
+                aligner.align_without_matrix(seq1, seq2)
+            })
+        }
+    }
+}
+
+/// Aligns `seq1` against `seq2` and, with [`StrandMode::Both`], against the
+/// reverse complement of `seq2`.
+///
+/// Results are ordered by score, forward strand first on ties. With
+/// [`StrandReport::Best`] only the first is returned.
+pub fn align_strands(
+    seq1: &[u8],
+    seq2: &[u8],
+    config: &AlignConfig,
+) -> Result<Vec<StrandAlignment>, AlignmentError> {
+    let forward = align_sequences(seq1, seq2, config)?;
+    let mut strands = vec![StrandAlignment::new(
+        Strand::Forward,
+        seq2.to_vec(),
+        forward,
+    )];
+    if config.strand == StrandMode::Both {
+        let reverse_seq2 = reverse_complement(seq2)?;
+        let reverse = align_sequences(seq1, &reverse_seq2, config)?;
+        strands.push(StrandAlignment::new(Strand::Reverse, reverse_seq2, reverse));
+        strands.sort_by_key(|strand| std::cmp::Reverse(strand.result.final_score));
+        if config.strand_report == StrandReport::Best {
+            strands.truncate(1);
+        }
+    }
+    Ok(strands)
 }
 
 /// WASM entry point for sequence alignment (supports both global and local).
@@ -72,63 +223,81 @@ impl AlignConfig {
 /// # Arguments
 /// * `seq1` - First sequence as UTF-8 bytes
 /// * `seq2` - Second sequence as UTF-8 bytes
-/// * `config` - JSON-encoded configuration object
+/// * `config` - JSON- or CBOR-encoded configuration object
 ///
 /// # Returns
-/// JSON bytes of the alignment result payload or an error string.
-#[cfg_attr(target_arch = "wasm32", wasm_func)]
+/// Alignment result payload, encoded like `config`, or an error string. When
+/// `config` asks for both strands, the payload is a `strands` list as built by
+/// [`serialize_strand_alignments`].
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
 pub fn align(seq1: &[u8], seq2: &[u8], config: &[u8]) -> Result<Vec<u8>, String> {
     let seq1_str =
         std::str::from_utf8(seq1).map_err(|e| format!("Invalid UTF-8 in seq1: {}", e))?;
     let seq2_str =
         std::str::from_utf8(seq2).map_err(|e| format!("Invalid UTF-8 in seq2: {}", e))?;
 
-    let config: AlignConfig =
-        serde_json::from_slice(config).map_err(|e| format!("Invalid config JSON: {}", e))?;
-
-    config.validate().map_err(|e| e.to_string())?;
-
-    let scoring = if let Some(bm) = config.matrix {
-        ScoringConfig::with_matrix(bm, config.gap_open, config.gap_extend)
-    } else {
-        ScoringConfig::linear(
-            config
-                .match_score
-                .expect("match_score must be Some after validation"),
-            config
-                .mismatch_score
-                .expect("mismatch_score must be Some after validation"),
-            config.gap_open,
-            config.gap_extend,
-        )
-    };
+    let wire = WireFormat::detect(config);
+    let config: AlignConfig = wire
+        .decode(config)
+        .map_err(|e| format!("Invalid config {}: {}", wire.name(), e))?;
 
-    let result = match config.mode {
-        AlignMode::Global => {
-            let aligner = GlobalAligner::new(scoring);
-            aligner.align(seq1_str.as_bytes(), seq2_str.as_bytes())
-        }
-        AlignMode::Local => {
-            let aligner = LocalAligner::new(scoring);
-            aligner.align(seq1_str.as_bytes(), seq2_str.as_bytes())
-        }
-    };
+    if config.strand == StrandMode::Both {
+        let strands = align_strands(seq1_str.as_bytes(), seq2_str.as_bytes(), &config)
+            .map_err(|e| e.to_string())?;
+        return output::serialize_strand_alignments(&strands, wire)
+            .map_err(|e| format!("Serialization failed: {}", e));
+    }
 
-    match result {
-        Ok(alignment_result) => output::serialize_alignment_result(&alignment_result)
+    match align_sequences(seq1_str.as_bytes(), seq2_str.as_bytes(), &config) {
+        Ok(alignment_result) => output::serialize_alignment_result(&alignment_result, wire)
             .map_err(|e| format!("Serialization failed: {}", e)),
         Err(e) => Err(e.to_string()),
     }
 }
 
+/// Falls back to a matrix-free alignment when the DP matrix was too large and
+/// the policy allows it.
+fn apply_oversize_policy(
+    result: Result<AlignmentResult, AlignmentError>,
+    policy: OversizePolicy,
+    fallback: impl FnOnce() -> Result<AlignmentResult, AlignmentError>,
+) -> Result<AlignmentResult, AlignmentError> {
+    match (result, policy) {
+        (Err(AlignmentError::MatrixTooLarge { .. }), OversizePolicy::AlignmentOnly) => fallback(),
+        (result, _) => result,
+    }
+}
+
+/// WASM entry point for dot plots.
+///
+/// # Arguments
+/// * `seq1` - First sequence as UTF-8 bytes
+/// * `seq2` - Second sequence as UTF-8 bytes
+/// * `config` - JSON- or CBOR-encoded [`DotPlotConfig`]
+///
+/// # Returns
+/// The sequence lengths and diagonal segments, encoded like `config`, or an
+/// error string.
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+pub fn dot_plot(seq1: &[u8], seq2: &[u8], config: &[u8]) -> Result<Vec<u8>, String> {
+    std::str::from_utf8(seq1).map_err(|e| format!("Invalid UTF-8 in seq1: {}", e))?;
+    std::str::from_utf8(seq2).map_err(|e| format!("Invalid UTF-8 in seq2: {}", e))?;
+
+    plugin_io::handle(config, "config", |config: DotPlotConfig| {
+        compute_dot_plot(seq1, seq2, &config).map_err(|e| e.to_string())
+    })
+}
+
 /// WASM entry point for retrieving built-in scoring matrix data.
 ///
 /// # Arguments
 /// * `name` - Matrix name as UTF-8 bytes (e.g., "BLOSUM62")
 ///
 /// # Returns
-/// JSON bytes with matrix data (name, alphabet, scores) or an error string.
-#[cfg_attr(target_arch = "wasm32", wasm_func)]
+/// JSON bytes with matrix data (name, alphabet, scores), the header scale,
+/// entropy and expected score (null when absent), and the implied
+/// `statistics` (null when none apply), or an error string.
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
 pub fn matrix_info(name: &[u8]) -> Result<Vec<u8>, String> {
     let name_str =
         std::str::from_utf8(name).map_err(|e| format!("Invalid UTF-8 in matrix name: {}", e))?;
@@ -140,7 +309,11 @@ pub fn matrix_info(name: &[u8]) -> Result<Vec<u8>, String> {
     let output = serde_json::json!({
         "name": matrix.name(),
         "alphabet": matrix.alphabet().iter().map(|&b| (b as char).to_string()).collect::<Vec<_>>(),
-        "scores": matrix.scores()
+        "scores": matrix.scores(),
+        "scale_bits": matrix.scale_bits(),
+        "reported_entropy": matrix.reported_entropy(),
+        "reported_expected_score": matrix.reported_expected_score(),
+        "statistics": matrix_statistics(matrix.alphabet(), matrix.scores()),
     });
 
     serde_json::to_vec(&output).map_err(|e| format!("Serialization failed: {}", e))
@@ -150,9 +323,187 @@ pub fn matrix_info(name: &[u8]) -> Result<Vec<u8>, String> {
 ///
 /// # Returns
 /// JSON bytes with array of matrix names.
-#[cfg_attr(target_arch = "wasm32", wasm_func)]
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
 pub fn list_matrices() -> Result<Vec<u8>, String> {
     let names = BuiltinMatrix::all_names();
     let result = serde_json::json!({ "matrices": names });
     serde_json::to_vec(&result).map_err(|e| format!("Serialization failed: {}", e))
 }
+
+/// WASM entry point for deriving a log-odds substitution matrix.
+///
+/// # Arguments
+/// * `config` - JSON- or CBOR-encoded [`LogOddsConfig`]
+///
+/// # Returns
+/// Matrix data like `matrix_info`, encoded like `config`, or an error
+/// string. Header values are null; `scale_bits` and `statistics` are
+/// computed from `scale` and the derived scores. The result can be passed
+/// back to `align` as `custom_matrix`.
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+pub fn derive_matrix(config: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(config, "config", |config: LogOddsConfig| {
+        derive_log_odds_matrix(&config).map(|matrix| ComputedMatrix::new(matrix, config.scale))
+    })
+}
+
+/// WASM entry point for extrapolating a PAMn log-odds matrix.
+///
+/// # Arguments
+/// * `config` - JSON- or CBOR-encoded [`PamConfig`]
+///
+/// # Returns
+/// Matrix data like `matrix_info`, encoded like `config`, or an error
+/// string. Header values are null; `scale_bits` and `statistics` are
+/// computed from the scale and the extrapolated scores.
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+pub fn pam_matrix(config: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(config, "config", |config: PamConfig| {
+        extrapolate_pam(&config).map(|matrix| ComputedMatrix::new(matrix, config.scale()))
+    })
+}
+
+/// A computed matrix in the `matrix_info` shape.
+#[derive(Serialize)]
+struct ComputedMatrix {
+    #[serde(flatten)]
+    matrix: SubstitutionMatrix,
+    scale_bits: f64,
+    reported_entropy: Option<f64>,
+    reported_expected_score: Option<f64>,
+    statistics: Option<MatrixStatistics>,
+}
+
+impl ComputedMatrix {
+    fn new(matrix: SubstitutionMatrix, scale: f64) -> Self {
+        let statistics = matrix_statistics(matrix.alphabet(), matrix.scores());
+        Self {
+            matrix,
+            scale_bits: 1.0 / scale,
+            reported_entropy: None,
+            reported_expected_score: None,
+            statistics,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_entry_point_wraps_align_sequences() {
+        let config = AlignConfig {
+            mode: AlignMode::Local,
+            matrix: None,
+            custom_matrix: None,
+            match_score: Some(2),
+            mismatch_score: Some(-1),
+            gap_open: -2,
+            gap_extend: -2,
+            max_cells: None,
+            oversize: OversizePolicy::Error,
+            strand: StrandMode::Forward,
+            strand_report: StrandReport::Best,
+        };
+        let native = align_sequences(b"AAAGCTAAA", b"CGCT", &config).unwrap();
+        let expected = serialize_alignment_result(&native, WireFormat::Json).unwrap();
+
+        let config_json = br#"{"mode": "local", "match_score": 2, "mismatch_score": -1, "gap_open": -2, "gap_extend": -2}"#;
+        let payload = align(b"AAAGCTAAA", b"CGCT", config_json).unwrap();
+
+        assert_eq!(payload, expected);
+    }
+
+    #[test]
+    fn test_derived_matrix_is_usable_as_custom_matrix() {
+        let derived = derive_matrix(
+            br#"{"name": "TOY", "alphabet": "ACGT", "sequences": ["ACGTAC", "ACGTAA", "ACCTAC"], "pseudocount": 1}"#,
+        )
+        .unwrap();
+        let matrix: serde_json::Value = serde_json::from_slice(&derived).unwrap();
+        assert_eq!(matrix["name"], "TOY");
+        assert_eq!(matrix["alphabet"], serde_json::json!(["A", "C", "G", "T"]));
+        assert_eq!(matrix["scale_bits"], 0.5);
+        assert!(matrix["reported_entropy"].is_null());
+        assert!(matrix["statistics"]["lambda"].as_f64().unwrap() > 0.0);
+
+        let config = serde_json::json!({
+            "custom_matrix": matrix,
+            "gap_open": -4,
+            "gap_extend": -4,
+        });
+        let payload = align(b"ACGT", b"acgt", config.to_string().as_bytes()).unwrap();
+        let result: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(result["alignments"][0]["seq1"], "ACGT");
+
+        let both = serde_json::json!({
+            "matrix": "BLOSUM62",
+            "custom_matrix": matrix,
+            "gap_open": -4,
+            "gap_extend": -4,
+        });
+        assert!(align(b"A", b"A", both.to_string().as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_pam_matrix_reports_scale_and_statistics() {
+        let payload = pam_matrix(br#"{"distance": 250}"#).unwrap();
+        let matrix: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(matrix["name"], "PAM250");
+        assert!((matrix["scale_bits"].as_f64().unwrap() - 1.0 / 3.0).abs() < 1e-12);
+        assert!(matrix["reported_expected_score"].is_null());
+        assert!(matrix["statistics"]["relative_entropy"].as_f64().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_align_both_strands_reports_reverse_hit() {
+        let config = br#"{"mode": "local", "match_score": 2, "mismatch_score": -3, "gap_open": -5, "gap_extend": -5, "strand": "both", "strand_report": "all"}"#;
+        let payload = align(b"GGCT", b"TTAGCCA", config).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        let strands = value["strands"].as_array().unwrap();
+
+        assert_eq!(strands.len(), 2);
+        assert_eq!(strands[0]["strand"], "reverse");
+        assert_eq!(strands[0]["seq2"], "TGGCTAA");
+        assert_eq!(strands[0]["alignment_score"], 8);
+        assert_eq!(strands[0]["seq2_ranges"], serde_json::json!([[6, 3]]));
+        assert_eq!(strands[1]["strand"], "forward");
+
+        let best = br#"{"match_score": 2, "mismatch_score": -3, "gap_open": -5, "gap_extend": -5, "strand": "both"}"#;
+        let payload = align(b"GGCT", b"TTAGCCA", best).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(value["strands"].as_array().unwrap().len(), 1);
+
+        assert!(align(b"GGCT", b"PEPTIDE", best).is_err());
+    }
+
+    #[test]
+    fn test_matrix_info_reports_header_and_statistics() {
+        let info: serde_json::Value =
+            serde_json::from_slice(&matrix_info(b"BLOSUM62").unwrap()).unwrap();
+        assert_eq!(info["scale_bits"], 0.5);
+        assert_eq!(info["reported_entropy"], 0.6979);
+        let expected = info["reported_expected_score"].as_f64().unwrap();
+        assert!((expected - -1.0418).abs() < 1e-9);
+        assert_eq!(info["statistics"]["background_source"], "implied");
+        assert_eq!(
+            info["statistics"]["target_frequencies"]
+                .as_array()
+                .unwrap()
+                .len(),
+            400
+        );
+
+        let pam: serde_json::Value =
+            serde_json::from_slice(&matrix_info(b"PAM250").unwrap()).unwrap();
+        assert!((pam["scale_bits"].as_f64().unwrap() - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(pam["reported_expected_score"], -0.844);
+        assert_eq!(pam["statistics"]["background_source"], "robinson-robinson");
+
+        let dna: serde_json::Value =
+            serde_json::from_slice(&matrix_info(b"EDNAFULL").unwrap()).unwrap();
+        assert!(dna["scale_bits"].is_null());
+        assert!(dna["reported_entropy"].is_null());
+    }
+}
@@ -0,0 +1,435 @@
+//! Linear-memory alignment used when the full DP matrix is too large.
+//!
+//! Scores are computed one row at a time and a single optimal alignment is
+//! recovered with Hirschberg's divide-and-conquer strategy, so memory grows
+//! with `n + m` instead of `n * m`. No DP matrix is returned.
+
+use std::iter;
+
+use crate::alignment::{AlignedPair, AlignmentResult, TracebackPath, encode_matrix_sequence};
+use crate::scoring::{AlignmentError, ScoringConfig, SubstitutionScorer};
+
+/// A single alignment column, named after the DP arrow it corresponds to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    /// Residue from both sequences
+    Diagonal,
+    /// Residue from seq1 against a gap
+    Up,
+    /// Gap against a residue from seq2
+    Left,
+}
+
+/// Substitution scores over pre-encoded residues.
+enum Substitution<'a> {
+    Simple {
+        match_score: i32,
+        mismatch_score: i32,
+    },
+    Matrix {
+        table: &'a [i32],
+        dimension: usize,
+    },
+}
+
+impl<'a> Substitution<'a> {
+    fn new(scorer: &'a SubstitutionScorer) -> Self {
+        match scorer {
+            SubstitutionScorer::Simple {
+                match_score,
+                mismatch_score,
+            } => Self::Simple {
+                match_score: *match_score,
+                mismatch_score: *mismatch_score,
+            },
+            SubstitutionScorer::Matrix(matrix) => Self::Matrix {
+                table: matrix.scores(),
+                dimension: matrix.score_dimension(),
+            },
+            SubstitutionScorer::Custom(matrix) => Self::Matrix {
+                table: matrix.scores(),
+                dimension: matrix.score_dimension(),
+            },
+        }
+    }
+
+    fn score(&self, a: usize, b: usize) -> i32 {
+        match self {
+            Self::Simple {
+                match_score,
+                mismatch_score,
+            } => {
+                if a == b {
+                    *match_score
+                } else {
+                    *mismatch_score
+                }
+            }
+            Self::Matrix { table, dimension } => table[a * dimension + b],
+        }
+    }
+}
+
+/// Encodes a sequence as residue indices for [`Substitution::score`].
+fn encode(scorer: &SubstitutionScorer, seq: &[u8]) -> Result<Vec<usize>, AlignmentError> {
+    match scorer {
+        SubstitutionScorer::Simple { .. } => Ok(seq
+            .iter()
+            .map(|byte| byte.to_ascii_uppercase() as usize)
+            .collect()),
+        SubstitutionScorer::Matrix(matrix) => encode_matrix_sequence(seq, matrix.lookup_map()),
+        SubstitutionScorer::Custom(matrix) => encode_matrix_sequence(seq, matrix.lookup_map()),
+    }
+}
+
+/// Computes one optimal global alignment without storing the DP matrix.
+pub(crate) fn align_global(
+    seq1: &[u8],
+    seq2: &[u8],
+    scoring: &ScoringConfig,
+) -> Result<AlignmentResult, AlignmentError> {
+    let substitution = Substitution::new(&scoring.scorer);
+    let a = encode(&scoring.scorer, seq1)?;
+    let b = encode(&scoring.scorer, seq2)?;
+
+    let mut ops = Vec::with_capacity(a.len() + b.len());
+    hirschberg(&a, &b, &substitution, scoring.gap_open, &mut ops);
+    let final_score = path_score(&a, &b, &ops, &substitution, scoring.gap_open);
+
+    Ok(build_result(
+        seq1,
+        seq2,
+        &scoring.scorer,
+        (0, 0),
+        &ops,
+        final_score,
+    ))
+}
+
+/// Computes one optimal local alignment without storing the DP matrix.
+///
+/// A forward pass locates the end of the best local alignment and a pass over
+/// the reversed prefixes locates its start; the enclosed region is then
+/// aligned globally.
+pub(crate) fn align_local(
+    seq1: &[u8],
+    seq2: &[u8],
+    scoring: &ScoringConfig,
+) -> Result<AlignmentResult, AlignmentError> {
+    let substitution = Substitution::new(&scoring.scorer);
+    let gap = scoring.gap_open;
+    let a = encode(&scoring.scorer, seq1)?;
+    let b = encode(&scoring.scorer, seq2)?;
+
+    let (final_score, (end_i, end_j)) = best_local_end(&a, &b, &substitution, gap);
+    if final_score <= 0 {
+        return Ok(AlignmentResult {
+            matrix: None,
+            traceback_paths: Vec::new(),
+            alignments: Vec::new(),
+            final_score,
+        });
+    }
+
+    let a_rev: Vec<usize> = a[..end_i].iter().rev().copied().collect();
+    let b_rev: Vec<usize> = b[..end_j].iter().rev().copied().collect();
+    let (_, (len_i, len_j)) = best_local_end(&a_rev, &b_rev, &substitution, gap);
+    let (start_i, start_j) = (end_i - len_i, end_j - len_j);
+
+    let mut ops = Vec::with_capacity(len_i + len_j);
+    hirschberg(
+        &a[start_i..end_i],
+        &b[start_j..end_j],
+        &substitution,
+        gap,
+        &mut ops,
+    );
+
+    Ok(build_result(
+        seq1,
+        seq2,
+        &scoring.scorer,
+        (start_i, start_j),
+        &ops,
+        final_score,
+    ))
+}
+
+/// Returns the last row of the global DP matrix for `a` against `b`.
+fn last_row(a: &[usize], b: &[usize], substitution: &Substitution, gap: i32) -> Vec<i32> {
+    let mut prev: Vec<i32> = (0..=b.len())
+        .map(|j| gap.saturating_mul(j as i32))
+        .collect();
+    let mut row = vec![0; b.len() + 1];
+
+    for &residue in a {
+        row[0] = prev[0].saturating_add(gap);
+        for j in 1..=b.len() {
+            let diag_score = prev[j - 1].saturating_add(substitution.score(residue, b[j - 1]));
+            let up_score = prev[j].saturating_add(gap);
+            let left_score = row[j - 1].saturating_add(gap);
+            row[j] = diag_score.max(up_score).max(left_score);
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+
+    prev
+}
+
+/// Returns the best local score and the first (row-major) cell reaching it.
+fn best_local_end(
+    a: &[usize],
+    b: &[usize],
+    substitution: &Substitution,
+    gap: i32,
+) -> (i32, (usize, usize)) {
+    let mut prev = vec![0i32; b.len() + 1];
+    let mut row = vec![0i32; b.len() + 1];
+    let mut best = (0, (0, 0));
+
+    for (i, &residue) in a.iter().enumerate() {
+        for j in 1..=b.len() {
+            let diag_score = prev[j - 1].saturating_add(substitution.score(residue, b[j - 1]));
+            let up_score = prev[j].saturating_add(gap);
+            let left_score = row[j - 1].saturating_add(gap);
+            row[j] = diag_score.max(up_score).max(left_score).max(0);
+            if row[j] > best.0 {
+                best = (row[j], (i + 1, j));
+            }
+        }
+        std::mem::swap(&mut prev, &mut row);
+    }
+
+    best
+}
+
+fn hirschberg(a: &[usize], b: &[usize], substitution: &Substitution, gap: i32, ops: &mut Vec<Op>) {
+    if a.is_empty() {
+        ops.extend(iter::repeat_n(Op::Left, b.len()));
+        return;
+    }
+    if b.is_empty() {
+        ops.extend(iter::repeat_n(Op::Up, a.len()));
+        return;
+    }
+    if a.len() == 1 {
+        align_single_residue(a[0], b, substitution, gap, ops);
+        return;
+    }
+
+    let mid = a.len() / 2;
+    let upper = last_row(&a[..mid], b, substitution, gap);
+    let a_rev: Vec<usize> = a[mid..].iter().rev().copied().collect();
+    let b_rev: Vec<usize> = b.iter().rev().copied().collect();
+    let lower = last_row(&a_rev, &b_rev, substitution, gap);
+
+    let m = b.len();
+    let mut split = 0;
+    let mut best = i32::MIN;
+    for j in 0..=m {
+        let score = upper[j].saturating_add(lower[m - j]);
+        if score > best || j == 0 {
+            best = score;
+            split = j;
+        }
+    }
+
+    hirschberg(&a[..mid], &b[..split], substitution, gap, ops);
+    hirschberg(&a[mid..], &b[split..], substitution, gap, ops);
+}
+
+/// Aligns a single residue against `b`, either paired with its best partner
+/// or placed against a gap.
+fn align_single_residue(
+    residue: usize,
+    b: &[usize],
+    substitution: &Substitution,
+    gap: i32,
+    ops: &mut Vec<Op>,
+) {
+    let m = b.len();
+    let (best_j, best_substitution) = b
+        .iter()
+        .enumerate()
+        .map(|(j, &other)| (j, substitution.score(residue, other)))
+        .fold((0, i32::MIN), |best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
+        });
+
+    let paired_score = best_substitution.saturating_add(gap.saturating_mul(m as i32 - 1));
+    let gapped_score = gap.saturating_mul(m as i32 + 1);
+
+    if paired_score >= gapped_score {
+        ops.extend(iter::repeat_n(Op::Left, best_j));
+        ops.push(Op::Diagonal);
+        ops.extend(iter::repeat_n(Op::Left, m - best_j - 1));
+    } else {
+        ops.push(Op::Up);
+        ops.extend(iter::repeat_n(Op::Left, m));
+    }
+}
+
+fn path_score(a: &[usize], b: &[usize], ops: &[Op], substitution: &Substitution, gap: i32) -> i32 {
+    let (mut i, mut j, mut score) = (0, 0, 0i32);
+    for op in ops {
+        match op {
+            Op::Diagonal => {
+                score = score.saturating_add(substitution.score(a[i], b[j]));
+                i += 1;
+                j += 1;
+            }
+            Op::Up => {
+                score = score.saturating_add(gap);
+                i += 1;
+            }
+            Op::Left => {
+                score = score.saturating_add(gap);
+                j += 1;
+            }
+        }
+    }
+    score
+}
+
+/// Builds the aligned strings and the end-to-start traceback path.
+fn build_result(
+    seq1: &[u8],
+    seq2: &[u8],
+    scorer: &SubstitutionScorer,
+    start: (usize, usize),
+    ops: &[Op],
+    final_score: i32,
+) -> AlignmentResult {
+    let (mut i, mut j) = start;
+    let mut aln1 = Vec::with_capacity(ops.len());
+    let mut aln2 = Vec::with_capacity(ops.len());
+    let mut steps = Vec::with_capacity(ops.len() + 1);
+    steps.push((i, j));
+
+    for op in ops {
+        match op {
+            Op::Diagonal => {
+                aln1.push(seq1[i]);
+                aln2.push(seq2[j]);
+                i += 1;
+                j += 1;
+            }
+            Op::Up => {
+                aln1.push(seq1[i]);
+                aln2.push(b'-');
+                i += 1;
+            }
+            Op::Left => {
+                aln1.push(b'-');
+                aln2.push(seq2[j]);
+                j += 1;
+            }
+        }
+        steps.push((i, j));
+    }
+
+    let mut path = TracebackPath {
+        steps: Vec::with_capacity(steps.len()),
+    };
+    for &(i, j) in steps.iter().rev() {
+        path.push(i, j);
+    }
+
+    AlignmentResult {
+        matrix: None,
+        traceback_paths: vec![path],
+        alignments: vec![AlignedPair::new(
+            String::from_utf8_lossy(&aln1).into_owned(),
+            String::from_utf8_lossy(&aln2).into_owned(),
+            scorer,
+        )],
+        final_score,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aligners::{GlobalAligner, LocalAligner};
+    use crate::matrices::BuiltinMatrix;
+
+    fn ungapped(aligned: &str) -> String {
+        aligned.chars().filter(|&c| c != '-').collect()
+    }
+
+    #[test]
+    fn test_global_matches_full_dp_score() {
+        let cases: [(&[u8], &[u8]); 4] = [
+            (b"ACGT", b"ACGT"),
+            (b"ACGTTGCA", b"AGTTCA"),
+            (b"AAA", b"AA"),
+            (b"GATTACA", b"GCATGCT"),
+        ];
+        let scoring = ScoringConfig::linear(2, -1, -2, -2);
+        for (seq1, seq2) in cases {
+            let full = GlobalAligner::new(scoring.clone())
+                .align(seq1, seq2)
+                .unwrap();
+            let linear = align_global(seq1, seq2, &scoring).unwrap();
+
+            assert_eq!(linear.final_score, full.final_score);
+            assert!(linear.matrix.is_none());
+            let pair = &linear.alignments[0];
+            assert_eq!(ungapped(&pair.seq1_aligned).as_bytes(), seq1);
+            assert_eq!(ungapped(&pair.seq2_aligned).as_bytes(), seq2);
+        }
+    }
+
+    #[test]
+    fn test_global_with_matrix_matches_full_dp_score() {
+        let scoring = ScoringConfig::with_matrix(BuiltinMatrix::Blosum62, -4, -4);
+        let full = GlobalAligner::new(scoring.clone())
+            .align(b"HEAGAWGHEE", b"PAWHEAE")
+            .unwrap();
+        let linear = align_global(b"HEAGAWGHEE", b"PAWHEAE", &scoring).unwrap();
+
+        assert_eq!(linear.final_score, full.final_score);
+    }
+
+    #[test]
+    fn test_global_path_runs_end_to_start() {
+        let scoring = ScoringConfig::default();
+        let result = align_global(b"ACGT", b"AGT", &scoring).unwrap();
+        let steps = &result.traceback_paths[0].steps;
+
+        assert_eq!((steps[0].i, steps[0].j), (4, 3));
+        let last = steps.last().unwrap();
+        assert_eq!((last.i, last.j), (0, 0));
+    }
+
+    #[test]
+    fn test_local_matches_full_dp() {
+        let scoring = ScoringConfig::linear(2, -1, -2, -2);
+        let full = LocalAligner::new(scoring.clone())
+            .align(b"AAAGCTAAA", b"CGCT")
+            .unwrap();
+        let linear = align_local(b"AAAGCTAAA", b"CGCT", &scoring).unwrap();
+
+        assert_eq!(linear.final_score, full.final_score);
+        assert_eq!(linear.alignments[0].seq1_aligned, "GCT");
+        assert_eq!(linear.alignments[0].seq2_aligned, "GCT");
+        let steps = &linear.traceback_paths[0].steps;
+        assert_eq!((steps[0].i, steps[0].j), (6, 4));
+        let last = steps.last().unwrap();
+        assert_eq!((last.i, last.j), (3, 1));
+    }
+
+    #[test]
+    fn test_local_without_positive_score() {
+        let scoring = ScoringConfig::linear(1, -1, -1, -1);
+        let result = align_local(b"AAAA", b"TTTT", &scoring).unwrap();
+
+        assert_eq!(result.final_score, 0);
+        assert!(result.alignments.is_empty());
+        assert!(result.traceback_paths.is_empty());
+    }
+}
@@ -0,0 +1,451 @@
+//! BLOSUM-style log-odds matrices derived from alignments or pair counts.
+//!
+//! Residue pairs are counted as ordered pairs, so the count table is
+//! symmetric and `q[i][j]` sums to one over all cells. Background frequencies
+//! are the marginals `p[i] = sum_j q[i][j]` and each score is
+//! `scale * log2(q[i][j] / (p[i] * p[j]))`, rounded to an integer. This is
+//! the construction of Henikoff & Henikoff (1992).
+
+use serde::Deserialize;
+
+use crate::substitution_matrix::SubstitutionMatrix;
+
+/// Residues of BLOSUM matrices, in their conventional order.
+pub const STANDARD_AMINO_ACIDS: &str = "ARNDCQEGHILKMFPSTWYV";
+
+/// How scaled log-odds values are turned into integer scores.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rounding {
+    /// Round to the nearest integer, halves away from zero.
+    #[default]
+    Nearest,
+    /// Round towards negative infinity.
+    Floor,
+    /// Round towards positive infinity.
+    Ceil,
+}
+
+impl Rounding {
+    /// Rounds a finite scaled log-odds value to a score, keeping it clear of
+    /// the `i32::MIN`/`i32::MAX` infinity sentinels.
+    pub fn to_score(self, value: f64) -> i32 {
+        let rounded = match self {
+            Rounding::Nearest => value.round(),
+            Rounding::Floor => value.floor(),
+            Rounding::Ceil => value.ceil(),
+        };
+        rounded.clamp((i32::MIN + 1) as f64, (i32::MAX - 1) as f64) as i32
+    }
+}
+
+/// Configuration for [`derive_log_odds_matrix`], deserialized from JSON or CBOR.
+///
+/// Exactly one of `sequences` (an MSA whose rows all have the same length)
+/// and `pair_counts` (a row-major count table over `alphabet`) is required.
+/// A count table may be full and symmetric or hold each unordered pair once;
+/// it is symmetrized by averaging `c[i][j]` and `c[j][i]`.
+#[derive(Deserialize, Clone, Debug)]
+pub struct LogOddsConfig {
+    /// Name of the resulting matrix. Defaults to `CUSTOM`.
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// Residues of the resulting matrix. Defaults to [`STANDARD_AMINO_ACIDS`].
+    #[serde(default)]
+    pub alphabet: Option<String>,
+    /// Aligned sequences to count residue pairs from.
+    #[serde(default)]
+    pub sequences: Option<Vec<String>>,
+    /// Residue pair counts, row-major over `alphabet`.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float_vec")]
+    pub pair_counts: Option<Vec<f64>>,
+    /// Identity fraction in `(0, 1]` at which sequences are clustered before
+    /// counting, as for BLOSUM62 at 0.62. Only used with `sequences`.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub cluster_identity: Option<f64>,
+    /// Multiplier on log2 odds: 2 gives half-bit units as in BLOSUM62.
+    #[serde(
+        default = "default_scale",
+        deserialize_with = "plugin_io::lenient::float"
+    )]
+    pub scale: f64,
+    /// How scaled values are turned into integer scores.
+    #[serde(default)]
+    pub rounding: Rounding,
+    /// Added to every cell of the symmetric count table.
+    #[serde(default, deserialize_with = "plugin_io::lenient::float")]
+    pub pseudocount: f64,
+}
+
+fn default_name() -> String {
+    "CUSTOM".into()
+}
+
+fn default_scale() -> f64 {
+    2.0
+}
+
+impl Default for LogOddsConfig {
+    fn default() -> Self {
+        Self {
+            name: default_name(),
+            alphabet: None,
+            sequences: None,
+            pair_counts: None,
+            cluster_identity: None,
+            scale: default_scale(),
+            rounding: Rounding::default(),
+            pseudocount: 0.0,
+        }
+    }
+}
+
+impl LogOddsConfig {
+    /// Checks that the options are consistent and in range.
+    pub fn validate(&self) -> Result<(), String> {
+        match (&self.sequences, &self.pair_counts) {
+            (Some(_), Some(_)) => {
+                return Err(
+                    "Cannot use both 'sequences' and 'pair_counts' - they are mutually exclusive"
+                        .into(),
+                );
+            }
+            (None, None) => return Err("Provide either 'sequences' or 'pair_counts'".into()),
+            _ => {}
+        }
+        if let Some(identity) = self.cluster_identity {
+            if self.sequences.is_none() {
+                return Err("'cluster_identity' requires 'sequences'".into());
+            }
+            if !(identity > 0.0 && identity <= 1.0) {
+                return Err(format!(
+                    "'cluster_identity' must be in (0, 1], got {identity}"
+                ));
+            }
+        }
+        if !(self.scale.is_finite() && self.scale > 0.0) {
+            return Err(format!(
+                "'scale' must be a positive number, got {}",
+                self.scale
+            ));
+        }
+        if !(self.pseudocount.is_finite() && self.pseudocount >= 0.0) {
+            return Err(format!(
+                "'pseudocount' must be a non-negative number, got {}",
+                self.pseudocount
+            ));
+        }
+        Ok(())
+    }
+
+    fn alphabet_bytes(&self) -> Vec<u8> {
+        self.alphabet
+            .as_deref()
+            .unwrap_or(STANDARD_AMINO_ACIDS)
+            .bytes()
+            .map(|b| b.to_ascii_uppercase())
+            .collect()
+    }
+}
+
+/// Derives a log-odds substitution matrix as described by `config`.
+pub fn derive_log_odds_matrix(config: &LogOddsConfig) -> Result<SubstitutionMatrix, String> {
+    config.validate()?;
+
+    let alphabet = config.alphabet_bytes();
+    // Validates the alphabet before any counting happens.
+    SubstitutionMatrix::new(&config.name, &alphabet, vec![0; alphabet.len().pow(2)])
+        .map_err(|e| e.to_string())?;
+
+    let mut counts = match (&config.sequences, &config.pair_counts) {
+        (Some(sequences), _) => {
+            count_alignment_pairs(sequences, &alphabet, config.cluster_identity)?
+        }
+        (_, Some(pair_counts)) => symmetrize_counts(pair_counts, alphabet.len())?,
+        (None, None) => unreachable!("validated config has a data source"),
+    };
+    for count in &mut counts {
+        *count += config.pseudocount;
+    }
+
+    let scores = log_odds_scores(&counts, &alphabet, config.scale, config.rounding)?;
+    SubstitutionMatrix::new(&config.name, &alphabet, scores).map_err(|e| e.to_string())
+}
+
+/// Counts ordered residue pairs in each alignment column.
+///
+/// With clustering, pairs within a cluster are skipped and each pair across
+/// clusters is weighted by `1 / (|A| * |B|)`, so every cluster counts as one
+/// sequence.
+fn count_alignment_pairs(
+    sequences: &[String],
+    alphabet: &[u8],
+    cluster_identity: Option<f64>,
+) -> Result<Vec<f64>, String> {
+    let Some(first) = sequences.first() else {
+        return Err("'sequences' must not be empty".into());
+    };
+    let width = first.len();
+    if let Some((index, sequence)) = sequences
+        .iter()
+        .enumerate()
+        .find(|(_, sequence)| sequence.len() != width)
+    {
+        return Err(format!(
+            "Aligned sequences must have the same length: sequence {} has {} columns, expected {}",
+            index + 1,
+            sequence.len(),
+            width
+        ));
+    }
+
+    let mut index_of = [None; 256];
+    for (i, &residue) in alphabet.iter().enumerate() {
+        index_of[residue as usize] = Some(i);
+        index_of[residue.to_ascii_lowercase() as usize] = Some(i);
+    }
+    let encoded: Vec<Vec<Option<usize>>> = sequences
+        .iter()
+        .map(|sequence| sequence.bytes().map(|b| index_of[b as usize]).collect())
+        .collect();
+
+    let clusters = match cluster_identity {
+        Some(threshold) => cluster_by_identity(sequences, threshold),
+        None => (0..sequences.len()).collect(),
+    };
+    let mut cluster_sizes = vec![0usize; sequences.len()];
+    for &cluster in &clusters {
+        cluster_sizes[cluster] += 1;
+    }
+
+    let dim = alphabet.len();
+    let mut counts = vec![0.0; dim * dim];
+    for s in 0..encoded.len() {
+        for t in (s + 1)..encoded.len() {
+            if clusters[s] == clusters[t] {
+                continue;
+            }
+            let weight = 1.0 / (cluster_sizes[clusters[s]] * cluster_sizes[clusters[t]]) as f64;
+            for (a, b) in encoded[s].iter().zip(&encoded[t]) {
+                if let (Some(a), Some(b)) = (a, b) {
+                    counts[a * dim + b] += weight;
+                    counts[b * dim + a] += weight;
+                }
+            }
+        }
+    }
+    Ok(counts)
+}
+
+/// Assigns each sequence a cluster index using single linkage: sequences
+/// sharing at least `threshold` identity end up in the same cluster.
+fn cluster_by_identity(sequences: &[String], threshold: f64) -> Vec<usize> {
+    let mut parent: Vec<usize> = (0..sequences.len()).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for s in 0..sequences.len() {
+        for t in (s + 1)..sequences.len() {
+            if pairwise_identity(sequences[s].as_bytes(), sequences[t].as_bytes()) >= threshold {
+                let (a, b) = (find(&mut parent, s), find(&mut parent, t));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+    }
+    (0..sequences.len()).map(|i| find(&mut parent, i)).collect()
+}
+
+/// Fraction of identical residues over columns where neither sequence has a gap.
+fn pairwise_identity(a: &[u8], b: &[u8]) -> f64 {
+    let mut compared = 0usize;
+    let mut identical = 0usize;
+    for (&x, &y) in a.iter().zip(b) {
+        if is_gap(x) || is_gap(y) {
+            continue;
+        }
+        compared += 1;
+        if x.eq_ignore_ascii_case(&y) {
+            identical += 1;
+        }
+    }
+    if compared == 0 {
+        0.0
+    } else {
+        identical as f64 / compared as f64
+    }
+}
+
+fn is_gap(residue: u8) -> bool {
+    matches!(residue, b'-' | b'.')
+}
+
+fn symmetrize_counts(pair_counts: &[f64], dim: usize) -> Result<Vec<f64>, String> {
+    if pair_counts.len() != dim * dim {
+        return Err(format!(
+            "'pair_counts' has {} values, expected {} for {} residues",
+            pair_counts.len(),
+            dim * dim,
+            dim
+        ));
+    }
+    if let Some(count) = pair_counts.iter().find(|c| !(c.is_finite() && **c >= 0.0)) {
+        return Err(format!(
+            "'pair_counts' must be non-negative numbers, got {count}"
+        ));
+    }
+    let mut counts = vec![0.0; dim * dim];
+    for i in 0..dim {
+        for j in 0..dim {
+            counts[i * dim + j] = (pair_counts[i * dim + j] + pair_counts[j * dim + i]) / 2.0;
+        }
+    }
+    Ok(counts)
+}
+
+fn log_odds_scores(
+    counts: &[f64],
+    alphabet: &[u8],
+    scale: f64,
+    rounding: Rounding,
+) -> Result<Vec<i32>, String> {
+    let dim = alphabet.len();
+    let total: f64 = counts.iter().sum();
+    if total <= 0.0 {
+        return Err("No residue pairs were counted".into());
+    }
+
+    let background: Vec<f64> = (0..dim)
+        .map(|i| counts[i * dim..(i + 1) * dim].iter().sum::<f64>() / total)
+        .collect();
+    if let Some(i) = background.iter().position(|&p| p == 0.0) {
+        return Err(format!(
+            "Residue '{}' was never observed; add a pseudocount or leave it out of the alphabet",
+            alphabet[i] as char
+        ));
+    }
+
+    let mut scores = Vec::with_capacity(dim * dim);
+    for i in 0..dim {
+        for j in 0..dim {
+            let observed = counts[i * dim + j] / total;
+            if observed == 0.0 {
+                scores.push(i32::MIN);
+                continue;
+            }
+            let value = scale * (observed / (background[i] * background[j])).log2();
+            scores.push(rounding.to_score(value));
+        }
+    }
+    Ok(scores)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_for(sequences: &[&str], alphabet: &str) -> LogOddsConfig {
+        LogOddsConfig {
+            alphabet: Some(alphabet.into()),
+            sequences: Some(sequences.iter().map(|s| s.to_string()).collect()),
+            ..LogOddsConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_scores_follow_henikoff_formula() {
+        // Column pairs: AA, AA, AC -> ordered counts AA=4, AC=CA=1.
+        let config = config_for(&["AAA", "AAC"], "AC");
+        let matrix = derive_log_odds_matrix(&config).unwrap();
+
+        // q_AA = 4/6, q_AC = 1/6, p_A = 5/6, p_C = 1/6, q_CC = 0.
+        let aa = 2.0 * ((4.0_f64 / 6.0) / (25.0 / 36.0)).log2();
+        let ac = 2.0 * ((1.0_f64 / 6.0) / (5.0 / 36.0)).log2();
+        assert_eq!(matrix.score(b'A', b'A'), Some(aa.round() as i32));
+        assert_eq!(matrix.score(b'A', b'C'), Some(ac.round() as i32));
+        assert_eq!(matrix.score(b'C', b'C'), Some(i32::MIN));
+    }
+
+    #[test]
+    fn test_triangular_and_full_count_tables_agree() {
+        let full = LogOddsConfig {
+            alphabet: Some("AC".into()),
+            pair_counts: Some(vec![40.0, 10.0, 10.0, 20.0]),
+            ..LogOddsConfig::default()
+        };
+        let triangular = LogOddsConfig {
+            pair_counts: Some(vec![20.0, 10.0, 0.0, 10.0]),
+            ..full.clone()
+        };
+
+        assert_eq!(
+            derive_log_odds_matrix(&full).unwrap().scores(),
+            derive_log_odds_matrix(&triangular).unwrap().scores()
+        );
+    }
+
+    #[test]
+    fn test_clustering_counts_each_cluster_once() {
+        let sequences = ["AAAA", "AAAA", "CCCC"];
+        let clustered =
+            count_alignment_pairs(&sequences.map(String::from), b"AC", Some(0.62)).unwrap();
+        let unclustered = count_alignment_pairs(&sequences.map(String::from), b"AC", None).unwrap();
+
+        // Two identical sequences form one cluster: only A-C pairs, weight 1/2.
+        assert_eq!(clustered, vec![0.0, 4.0, 4.0, 0.0]);
+        assert_eq!(unclustered, vec![8.0, 8.0, 8.0, 0.0]);
+    }
+
+    #[test]
+    fn test_gaps_and_unknown_residues_are_skipped() {
+        let counts =
+            count_alignment_pairs(&["A-XA".to_string(), "AAAa".to_string()], b"A", None).unwrap();
+        assert_eq!(counts, vec![4.0]);
+    }
+
+    #[test]
+    fn test_reports_unobserved_residue_and_bad_input() {
+        let missing = config_for(&["AA", "AA"], "AC");
+        assert!(
+            derive_log_odds_matrix(&missing)
+                .unwrap_err()
+                .contains("Residue 'C' was never observed")
+        );
+
+        let with_pseudocount = LogOddsConfig {
+            pseudocount: 0.5,
+            ..missing
+        };
+        assert!(derive_log_odds_matrix(&with_pseudocount).is_ok());
+
+        let ragged = config_for(&["AA", "A"], "A");
+        assert!(derive_log_odds_matrix(&ragged).is_err());
+    }
+
+    #[test]
+    fn test_floor_and_ceil_bracket_nearest() {
+        let base = config_for(&["ACGTAC", "ACGAAT", "TCGTAA"], "ACGT");
+        let score = |rounding| {
+            derive_log_odds_matrix(&LogOddsConfig {
+                rounding,
+                scale: 3.0,
+                ..base.clone()
+            })
+            .unwrap()
+            .scores()
+            .to_vec()
+        };
+        let (floor, nearest, ceil) = (
+            score(Rounding::Floor),
+            score(Rounding::Nearest),
+            score(Rounding::Ceil),
+        );
+        for i in 0..floor.len() {
+            assert!(floor[i] <= nearest[i] && nearest[i] <= ceil[i]);
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! Information-theoretic summaries of substitution matrices.
+//!
+//! A log-odds matrix `s` with scale `λ` implies target frequencies
+//! `q[i][j] = p[i] * p[j] * exp(λ * s[i][j])` for background frequencies `p`
+//! (Altschul, 1991). The background is the one implied by the matrix itself
+//! (Yu & Altschul, 2005) when that solution is a valid distribution; for
+//! some matrices, such as PAM250, every solution has negative entries.
+//! Otherwise a standard background is assumed: Robinson & Robinson (1991)
+//! amino acid frequencies, or equal A/C/G/T frequencies for nucleotide
+//! matrices, and `λ` is the positive root of `sum q[i][j] = 1`.
+
+use serde::Serialize;
+
+/// Robinson & Robinson amino acid frequencies, as used by BLAST.
+const ROBINSON_FREQUENCIES: [(u8, f64); 20] = [
+    (b'A', 0.07805),
+    (b'R', 0.05129),
+    (b'N', 0.04487),
+    (b'D', 0.05364),
+    (b'C', 0.01925),
+    (b'Q', 0.04264),
+    (b'E', 0.06295),
+    (b'G', 0.07377),
+    (b'H', 0.02199),
+    (b'I', 0.05142),
+    (b'L', 0.09019),
+    (b'K', 0.05744),
+    (b'M', 0.02243),
+    (b'F', 0.03856),
+    (b'P', 0.05203),
+    (b'S', 0.07120),
+    (b'T', 0.05841),
+    (b'W', 0.01330),
+    (b'Y', 0.03216),
+    (b'V', 0.06441),
+];
+
+const NUCLEOTIDES: [u8; 4] = [b'A', b'C', b'G', b'T'];
+
+/// Where the background frequencies of [`MatrixStatistics`] come from.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackgroundSource {
+    /// Implied by the matrix scores.
+    Implied,
+    /// Robinson & Robinson amino acid frequencies.
+    RobinsonRobinson,
+    /// Equal A/C/G/T frequencies; other symbols get zero.
+    UniformNucleotide,
+}
+
+/// Scale, frequencies and entropy implied by a log-odds matrix.
+#[derive(Serialize, Clone, Debug)]
+pub struct MatrixStatistics {
+    /// Scale in nats per score unit.
+    pub lambda: f64,
+    /// Where the background frequencies came from.
+    pub background_source: BackgroundSource,
+    /// Background frequencies in alphabet order.
+    pub background_frequencies: Vec<f64>,
+    /// Row-major target frequencies.
+    pub target_frequencies: Vec<f64>,
+    /// Relative entropy of the target frequencies, in bits.
+    pub relative_entropy: f64,
+    /// Expected score of a random pair, in score units; `None` when a pair
+    /// with non-zero background probability scores -inf.
+    pub expected_score: Option<f64>,
+}
+
+/// Computes [`MatrixStatistics`] for a square row-major score table.
+///
+/// Returns `None` when no background applies, the matrix holds +inf, or the
+/// scores do not admit a positive `λ` (for example, a non-negative expected
+/// score).
+pub fn matrix_statistics(alphabet: &[u8], scores: &[i32]) -> Option<MatrixStatistics> {
+    let dim = alphabet.len();
+    if dim == 0 || scores.len() != dim * dim || scores.contains(&i32::MAX) {
+        return None;
+    }
+
+    let (lambda, background, source) = match implied_background(scores, dim) {
+        Some((lambda, background)) => (lambda, background, BackgroundSource::Implied),
+        None => {
+            let (background, source) = standard_background(alphabet)?;
+            let lambda = karlin_altschul_lambda(scores, &background)?;
+            (lambda, background, source)
+        }
+    };
+
+    let mut target = vec![0.0; dim * dim];
+    let mut entropy_nats = 0.0;
+    let mut expected = Some(0.0);
+    for i in 0..dim {
+        for j in 0..dim {
+            let pair = background[i] * background[j];
+            let score = scores[i * dim + j];
+            if score == i32::MIN {
+                if pair > 0.0 {
+                    expected = None;
+                }
+                continue;
+            }
+            let q = pair * (lambda * f64::from(score)).exp();
+            target[i * dim + j] = q;
+            entropy_nats += q * lambda * f64::from(score);
+            expected = expected.map(|e| e + pair * f64::from(score));
+        }
+    }
+
+    Some(MatrixStatistics {
+        lambda,
+        background_source: source,
+        background_frequencies: background,
+        target_frequencies: target,
+        relative_entropy: entropy_nats / std::f64::consts::LN_2,
+        expected_score: expected,
+    })
+}
+
+/// Finds `λ` and `p` with `sum_j p[j] * exp(λ * s[i][j]) = 1` for every `i`
+/// and `sum p = 1`, accepting only solutions with all `p` positive.
+fn implied_background(scores: &[i32], dim: usize) -> Option<(f64, Vec<f64>)> {
+    let excess = |lambda: f64| -> Option<(f64, Vec<f64>)> {
+        let background = solve_background(scores, dim, lambda)?;
+        Some((background.iter().sum::<f64>() - 1.0, background))
+    };
+
+    // Scan λ geometrically for a sign change between two valid solutions.
+    let mut previous: Option<(f64, f64)> = None;
+    for step in 0..240 {
+        let lambda = 1e-3 * 10f64.powf(f64::from(step) / 60.0);
+        let Some((value, background)) = excess(lambda) else {
+            previous = None;
+            continue;
+        };
+        if background.iter().any(|&p| p <= 0.0) {
+            previous = None;
+            continue;
+        }
+        if let Some((low, low_value)) = previous
+            && (low_value > 0.0) != (value > 0.0)
+        {
+            let lambda = bisect(low, lambda, |x| excess(x).map(|(v, _)| v))?;
+            let background = solve_background(scores, dim, lambda)?;
+            let total: f64 = background.iter().sum();
+            if background.iter().all(|&p| p > 0.0) {
+                return Some((lambda, background.iter().map(|p| p / total).collect()));
+            }
+            return None;
+        }
+        previous = Some((lambda, value));
+    }
+    None
+}
+
+/// Solves `E p = 1` with `E[i][j] = exp(λ * s[i][j])` by Gaussian elimination.
+fn solve_background(scores: &[i32], dim: usize, lambda: f64) -> Option<Vec<f64>> {
+    let width = dim + 1;
+    let mut system = vec![0.0; dim * width];
+    for i in 0..dim {
+        for j in 0..dim {
+            let score = scores[i * dim + j];
+            system[i * width + j] = if score == i32::MIN {
+                0.0
+            } else {
+                (lambda * f64::from(score)).exp()
+            };
+        }
+        system[i * width + dim] = 1.0;
+    }
+
+    for col in 0..dim {
+        let pivot = (col..dim).max_by(|&a, &b| {
+            system[a * width + col]
+                .abs()
+                .total_cmp(&system[b * width + col].abs())
+        })?;
+        if system[pivot * width + col].abs() < 1e-12 {
+            return None;
+        }
+        for k in 0..width {
+            system.swap(col * width + k, pivot * width + k);
+        }
+        for row in (col + 1)..dim {
+            let factor = system[row * width + col] / system[col * width + col];
+            if factor != 0.0 {
+                for k in col..width {
+                    system[row * width + k] -= factor * system[col * width + k];
+                }
+            }
+        }
+    }
+
+    let mut solution = vec![0.0; dim];
+    for row in (0..dim).rev() {
+        let tail: f64 = ((row + 1)..dim)
+            .map(|k| system[row * width + k] * solution[k])
+            .sum();
+        solution[row] = (system[row * width + dim] - tail) / system[row * width + row];
+    }
+    solution.iter().all(|p| p.is_finite()).then_some(solution)
+}
+
+fn standard_background(alphabet: &[u8]) -> Option<(Vec<f64>, BackgroundSource)> {
+    let upper: Vec<u8> = alphabet.iter().map(|b| b.to_ascii_uppercase()).collect();
+    if ROBINSON_FREQUENCIES
+        .iter()
+        .all(|(residue, _)| upper.contains(residue))
+    {
+        let background = upper
+            .iter()
+            .map(|residue| {
+                ROBINSON_FREQUENCIES
+                    .iter()
+                    .find(|(r, _)| r == residue)
+                    .map_or(0.0, |(_, f)| *f)
+            })
+            .collect::<Vec<_>>();
+        let total: f64 = background.iter().sum();
+        return Some((
+            background.iter().map(|f| f / total).collect(),
+            BackgroundSource::RobinsonRobinson,
+        ));
+    }
+    if NUCLEOTIDES.iter().all(|residue| upper.contains(residue)) {
+        let background = upper
+            .iter()
+            .map(|residue| {
+                if NUCLEOTIDES.contains(residue) {
+                    0.25
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        return Some((background, BackgroundSource::UniformNucleotide));
+    }
+    None
+}
+
+/// Positive root of `sum p[i] p[j] exp(λ s[i][j]) = 1`.
+fn karlin_altschul_lambda(scores: &[i32], background: &[f64]) -> Option<f64> {
+    let dim = background.len();
+    let excess = |lambda: f64| -> Option<f64> {
+        let mut total = 0.0;
+        for i in 0..dim {
+            for j in 0..dim {
+                let score = scores[i * dim + j];
+                if score != i32::MIN {
+                    total += background[i] * background[j] * (lambda * f64::from(score)).exp();
+                }
+            }
+        }
+        Some(total - 1.0)
+    };
+
+    // The sum dips below one just above zero when the expected score is
+    // negative, and grows without bound if any reachable score is positive.
+    let mut high = 1.0;
+    while excess(high)? <= 0.0 {
+        high *= 2.0;
+        if high > 1e6 {
+            return None;
+        }
+    }
+    let mut low = high;
+    while excess(low)? > 0.0 {
+        low /= 2.0;
+        if low < 1e-9 {
+            return None;
+        }
+    }
+    bisect(low, high, excess)
+}
+
+/// Bisects a sign change of `f` between `low` and `high`.
+fn bisect(mut low: f64, mut high: f64, f: impl Fn(f64) -> Option<f64>) -> Option<f64> {
+    let low_positive = f(low)? > 0.0;
+    for _ in 0..100 {
+        let mid = 0.5 * (low + high);
+        if (f(mid)? > 0.0) == low_positive {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Some(0.5 * (low + high))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrices::BuiltinMatrix;
+
+    fn stats(name: &str) -> MatrixStatistics {
+        let matrix = BuiltinMatrix::from_name(name).unwrap();
+        matrix_statistics(matrix.alphabet(), matrix.scores()).unwrap()
+    }
+
+    #[test]
+    fn test_blosum62_implied_statistics_are_consistent() {
+        let stats = stats("BLOSUM62");
+        assert_eq!(stats.background_source, BackgroundSource::Implied);
+        // Half-bit units: λ close to ln(2) / 2.
+        assert!((stats.lambda - 0.3466).abs() < 0.05, "{}", stats.lambda);
+        assert!((stats.background_frequencies.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!((stats.target_frequencies.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+        assert!((stats.relative_entropy - 0.6979).abs() < 0.1);
+        assert!(stats.expected_score.unwrap() < 0.0);
+    }
+
+    #[test]
+    fn test_falls_back_to_standard_backgrounds() {
+        let pam250 = stats("PAM250");
+        assert_eq!(pam250.background_source, BackgroundSource::RobinsonRobinson);
+        assert!((pam250.lambda - std::f64::consts::LN_2 / 3.0).abs() < 0.03);
+        assert!((pam250.target_frequencies.iter().sum::<f64>() - 1.0).abs() < 1e-6);
+
+        let pam1 = stats("PAM1");
+        assert_eq!(pam1.expected_score, None);
+
+        let dna = stats("EDNAFULL");
+        assert_eq!(dna.background_source, BackgroundSource::UniformNucleotide);
+        assert!(dna.lambda > 0.0);
+    }
+
+    #[test]
+    fn test_rejects_matrices_without_positive_lambda() {
+        assert!(matrix_statistics(b"AC", &[1, 1, 1, 1]).is_none());
+        assert!(matrix_statistics(b"AC", &[i32::MAX, 0, 0, 1]).is_none());
+    }
+}
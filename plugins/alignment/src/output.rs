@@ -1,11 +1,15 @@
-//! JSON output serialization for alignment results.
+//! Output serialization for alignment results.
 
+use std::borrow::Cow;
+
+use plugin_io::WireFormat;
 use serde::Serialize;
 use serde::ser::{SerializeSeq, Serializer};
 
 use crate::alignment::{AlignedPair, AlignmentResult, DPMatrix, TracebackPath};
+use crate::strand::{Strand, StrandAlignment};
 
-/// JSON-serializable DP matrix output.
+/// Serializable DP matrix output.
 /// Scores and arrows are emitted as dense row-major arrays.
 #[derive(Debug, Serialize)]
 struct DPMatrixOutput {
@@ -34,16 +38,45 @@ impl From<&DPMatrix> for DPMatrixOutput {
     }
 }
 
-/// Serialize an alignment result into the JSON payload expected by Typst.
-pub(crate) fn serialize_alignment_result(
+/// Serialize an alignment result into the payload expected by Typst.
+pub fn serialize_alignment_result(
     result: &AlignmentResult,
-) -> Result<Vec<u8>, serde_json::Error> {
-    serde_json::to_vec(&AlignmentResultOutputRef {
-        alignment_score: result.final_score,
-        alignments: AlignmentsRef(&result.alignments),
-        traceback_paths: TracebackPathsRef(&result.traceback_paths),
-        dp_matrix: DPMatrixOutput::from(&result.matrix),
-    })
+    wire: WireFormat,
+) -> Result<Vec<u8>, String> {
+    wire.encode(&AlignmentResultOutputRef::from(result))
+}
+
+/// Serialize both-strand alignments as a `strands` list. Each entry holds
+/// the fields of [`serialize_alignment_result`] plus `strand`, the aligned
+/// `seq2` and its `seq2_ranges` on the original sequence.
+pub fn serialize_strand_alignments(
+    strands: &[StrandAlignment],
+    wire: WireFormat,
+) -> Result<Vec<u8>, String> {
+    let strands: Vec<StrandOutputRef> = strands
+        .iter()
+        .map(|strand| StrandOutputRef {
+            strand: strand.strand,
+            seq2: String::from_utf8_lossy(&strand.seq2),
+            seq2_ranges: &strand.seq2_ranges,
+            result: AlignmentResultOutputRef::from(&strand.result),
+        })
+        .collect();
+    wire.encode(&StrandsOutputRef { strands })
+}
+
+#[derive(Serialize)]
+struct StrandsOutputRef<'a> {
+    strands: Vec<StrandOutputRef<'a>>,
+}
+
+#[derive(Serialize)]
+struct StrandOutputRef<'a> {
+    strand: Strand,
+    seq2: Cow<'a, str>,
+    seq2_ranges: &'a [Option<(usize, usize)>],
+    #[serde(flatten)]
+    result: AlignmentResultOutputRef<'a>,
 }
 
 #[derive(Serialize)]
@@ -51,7 +84,18 @@ struct AlignmentResultOutputRef<'a> {
     alignment_score: i32,
     alignments: AlignmentsRef<'a>,
     traceback_paths: TracebackPathsRef<'a>,
-    dp_matrix: DPMatrixOutput,
+    dp_matrix: Option<DPMatrixOutput>,
+}
+
+impl<'a> From<&'a AlignmentResult> for AlignmentResultOutputRef<'a> {
+    fn from(result: &'a AlignmentResult) -> Self {
+        Self {
+            alignment_score: result.final_score,
+            alignments: AlignmentsRef(&result.alignments),
+            traceback_paths: TracebackPathsRef(&result.traceback_paths),
+            dp_matrix: result.matrix.as_ref().map(DPMatrixOutput::from),
+        }
+    }
 }
 
 struct AlignmentsRef<'a>(&'a [AlignedPair]);
@@ -66,6 +110,7 @@ impl Serialize for AlignmentsRef<'_> {
             seq.serialize_element(&AlignmentOutputRef {
                 seq1: &pair.seq1_aligned,
                 seq2: &pair.seq2_aligned,
+                similarity: &pair.similarity,
             })?;
         }
         seq.end()
@@ -76,6 +121,7 @@ impl Serialize for AlignmentsRef<'_> {
 struct AlignmentOutputRef<'a> {
     seq1: &'a str,
     seq2: &'a str,
+    similarity: &'a str,
 }
 
 struct TracebackPathsRef<'a>(&'a [TracebackPath]);
@@ -121,7 +167,7 @@ mod tests {
         let aligner = GlobalAligner::new(ScoringConfig::default());
         let result = aligner.align(b"AC", b"AC").unwrap();
 
-        let output = DPMatrixOutput::from(&result.matrix);
+        let output = DPMatrixOutput::from(result.matrix.as_ref().unwrap());
 
         // Check dp_matrix has dense row-major scores and arrow bitmasks
         assert_eq!(output.rows, 3); // len("AC") + 1
@@ -139,7 +185,7 @@ mod tests {
         let aligner = GlobalAligner::new(ScoringConfig::default());
         let result = aligner.align(b"AC", b"AC").unwrap();
 
-        let json = serialize_alignment_result(&result).unwrap();
+        let json = serialize_alignment_result(&result, WireFormat::Json).unwrap();
         let value: Value = serde_json::from_slice(&json).unwrap();
         let traceback_paths = value["traceback_paths"].as_array().unwrap();
 
@@ -154,6 +200,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cbor_output_matches_json_output() {
+        let aligner = GlobalAligner::new(ScoringConfig::default());
+        let result = aligner.align(b"ACG", b"AG").unwrap();
+
+        let json = serialize_alignment_result(&result, WireFormat::Json).unwrap();
+        let cbor = serialize_alignment_result(&result, WireFormat::Cbor).unwrap();
+        let from_json: Value = serde_json::from_slice(&json).unwrap();
+        let from_cbor: Value = ciborium::from_reader(cbor.as_slice()).unwrap();
+
+        assert_eq!(from_cbor, from_json);
+    }
+
+    #[test]
+    fn test_alignment_without_matrix_serializes_null_dp_matrix() {
+        let aligner = GlobalAligner::new(ScoringConfig::default());
+        let result = aligner.align_without_matrix(b"AC", b"AC").unwrap();
+
+        let json = serialize_alignment_result(&result, WireFormat::Json).unwrap();
+        let value: Value = serde_json::from_slice(&json).unwrap();
+
+        assert!(value["dp_matrix"].is_null());
+        assert_eq!(value["alignment_score"], 6);
+        assert_eq!(value["traceback_paths"].as_array().unwrap().len(), 1);
+    }
+
     #[test]
     fn test_collect_arrow_bits_preserves_multiple_directions() {
         let mut matrix = DPMatrix::new(2, 2);
@@ -0,0 +1,389 @@
+//! PAMn matrices extrapolated from a PAM1 mutation probability matrix.
+//!
+//! `M[i][j]` is the probability that residue `j` is replaced by residue `i`
+//! over one PAM unit of evolution. PAMn uses `M^n`, and the score for a pair
+//! is `scale * log2(M^n[i][j] / f[i])`, averaged over both directions so the
+//! matrix is symmetric.
+//!
+//! The default model is the PAM1 matrix and amino acid frequencies published
+//! by Dayhoff et al. (1978). Its probabilities are rounded to 1e-4, so rare
+//! substitutions can differ by a few units from the bundled PAM matrices,
+//! which were computed from unrounded counts.
+
+use serde::Deserialize;
+
+use crate::log_odds::Rounding;
+use crate::substitution_matrix::SubstitutionMatrix;
+
+/// Residues of the Dayhoff model, in the order of the bundled PAM matrices.
+pub const DAYHOFF_ALPHABET: &str = "ARNDCQEGHILKMFPSTWYV";
+
+/// Dayhoff PAM1 mutation probabilities times 10,000; rows are replacement
+/// residues and columns original residues.
+#[rustfmt::skip]
+const DAYHOFF_PAM1: [[u16; 20]; 20] = [
+    [9867,    2,    9,   10,    3,    8,   17,   21,    2,    6,    4,    2,    6,    2,   22,   35,   32,    0,    2,   18],
+    [   1, 9913,    1,    0,    1,   10,    0,    0,   10,    3,    1,   19,    4,    1,    4,    6,    1,    8,    0,    1],
+    [   4,    1, 9822,   36,    0,    4,    6,    6,   21,    3,    1,   13,    0,    1,    2,   20,    9,    1,    4,    1],
+    [   6,    0,   42, 9859,    0,    6,   53,    6,    4,    1,    0,    3,    0,    0,    1,    5,    3,    0,    0,    1],
+    [   1,    1,    0,    0, 9973,    0,    0,    0,    1,    1,    0,    0,    0,    0,    1,    5,    1,    0,    3,    2],
+    [   3,    9,    4,    5,    0, 9876,   27,    1,   23,    1,    3,    6,    4,    0,    6,    2,    2,    0,    0,    1],
+    [  10,    0,    7,   56,    0,   35, 9865,    4,    2,    3,    1,    4,    1,    0,    3,    4,    2,    0,    1,    2],
+    [  21,    1,   12,   11,    1,    3,    7, 9935,    1,    0,    1,    2,    1,    1,    3,   21,    3,    0,    0,    5],
+    [   1,    8,   18,    3,    1,   20,    1,    0, 9912,    0,    1,    1,    0,    2,    3,    1,    1,    1,    4,    1],
+    [   2,    2,    3,    1,    2,    1,    2,    0,    0, 9872,    9,    2,   12,    7,    0,    1,    7,    0,    1,   33],
+    [   3,    1,    3,    0,    0,    6,    1,    1,    4,   22, 9947,    2,   45,   13,    3,    1,    3,    4,    2,   15],
+    [   2,   37,   25,    6,    0,   12,    7,    2,    2,    4,    1, 9926,   20,    0,    3,    8,   11,    0,    1,    1],
+    [   1,    1,    0,    0,    0,    2,    0,    0,    0,    5,    8,    4, 9874,    1,    0,    1,    2,    0,    0,    4],
+    [   1,    1,    1,    0,    0,    0,    0,    1,    2,    8,    6,    0,    4, 9946,    0,    2,    1,    3,   28,    0],
+    [  13,    5,    2,    1,    1,    8,    3,    2,    5,    1,    2,    2,    1,    1, 9926,   12,    4,    0,    0,    2],
+    [  28,   11,   34,    7,   11,    4,    6,   16,    2,    2,    1,    7,    4,    3,   17, 9840,   38,    5,    2,    2],
+    [  22,    2,   13,    4,    1,    3,    2,    2,    1,   11,    2,    8,    6,    1,    5,   32, 9871,    0,    2,    9],
+    [   0,    2,    0,    0,    0,    0,    0,    0,    0,    0,    0,    0,    0,    1,    0,    1,    0, 9976,    1,    0],
+    [   1,    0,    3,    0,    3,    0,    1,    0,    4,    1,    1,    0,    0,   21,    0,    1,    1,    2, 9945,    1],
+    [  13,    2,    1,    1,    3,    2,    2,    3,    3,   57,   11,    1,   17,    1,    3,    2,   10,    0,    2, 9901],
+];
+
+/// Dayhoff amino acid frequencies, in [`DAYHOFF_ALPHABET`] order.
+const DAYHOFF_FREQUENCIES: [f64; 20] = [
+    0.087, 0.041, 0.040, 0.047, 0.033, 0.038, 0.050, 0.089, 0.034, 0.037, 0.085, 0.081, 0.015,
+    0.040, 0.051, 0.070, 0.058, 0.010, 0.030, 0.065,
+];
+
+/// Shortest distance the bundled PAM matrices give in third-bit units.
+const THIRD_BIT_DISTANCE: u32 = 180;
+
+/// Configuration for [`extrapolate_pam`], deserialized from JSON or CBOR.
+///
+/// `mutation_probabilities` (row-major, columns are original residues),
+/// `frequencies` and `alphabet` replace the Dayhoff model; they must be given
+/// together. Columns and frequencies are normalized to sum to one.
+#[derive(Deserialize, Clone, Debug)]
+pub struct PamConfig {
+    /// Evolutionary distance in PAM units.
+    pub distance: u32,
+    /// Defaults to `PAM<distance>`.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Multiplier on log2 odds: 2 gives half-bit units. Defaults to the
+    /// bundled PAM convention, see [`PamConfig::scale`].
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub scale: Option<f64>,
+    /// How scaled values are turned into integer scores.
+    #[serde(default)]
+    pub rounding: Rounding,
+    /// Residues of a custom PAM1 model.
+    #[serde(default)]
+    pub alphabet: Option<String>,
+    /// PAM1 mutation probability matrix over `alphabet`.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float_vec")]
+    pub mutation_probabilities: Option<Vec<f64>>,
+    /// Background residue frequencies over `alphabet`.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float_vec")]
+    pub frequencies: Option<Vec<f64>>,
+}
+
+impl PamConfig {
+    /// PAM `distance` from the Dayhoff model in the bundled units.
+    pub fn new(distance: u32) -> Self {
+        Self {
+            distance,
+            name: None,
+            scale: None,
+            rounding: Rounding::default(),
+            alphabet: None,
+            mutation_probabilities: None,
+            frequencies: None,
+        }
+    }
+
+    /// Multiplier on log2 odds: `scale` if given, otherwise 2 (half bits)
+    /// below PAM180 and 3 (third bits) from PAM180 on, as in the bundled PAM
+    /// matrices.
+    pub fn scale(&self) -> f64 {
+        self.scale.unwrap_or(if self.distance < THIRD_BIT_DISTANCE {
+            2.0
+        } else {
+            3.0
+        })
+    }
+
+    /// Checks that the options are consistent and in range.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.distance == 0 {
+            return Err("'distance' must be at least 1".into());
+        }
+        if let Some(scale) = self.scale
+            && !(scale.is_finite() && scale > 0.0)
+        {
+            return Err(format!("'scale' must be a positive number, got {scale}"));
+        }
+        let custom = [
+            self.alphabet.is_some(),
+            self.mutation_probabilities.is_some(),
+            self.frequencies.is_some(),
+        ];
+        if custom.iter().any(|&given| given) && !custom.iter().all(|&given| given) {
+            return Err(
+                "'alphabet', 'mutation_probabilities' and 'frequencies' must be given together"
+                    .into(),
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Mutation model: a column-stochastic PAM1 matrix and background frequencies.
+struct PamModel {
+    alphabet: Vec<u8>,
+    probabilities: Vec<f64>,
+    frequencies: Vec<f64>,
+}
+
+impl PamModel {
+    fn dayhoff() -> Self {
+        Self {
+            alphabet: DAYHOFF_ALPHABET.bytes().collect(),
+            probabilities: DAYHOFF_PAM1
+                .iter()
+                .flatten()
+                .map(|&p| f64::from(p))
+                .collect(),
+            frequencies: DAYHOFF_FREQUENCIES.to_vec(),
+        }
+    }
+
+    fn from_config(config: &PamConfig) -> Result<Self, String> {
+        match (
+            &config.alphabet,
+            &config.mutation_probabilities,
+            &config.frequencies,
+        ) {
+            (Some(alphabet), Some(probabilities), Some(frequencies)) => {
+                let dim = alphabet.len();
+                if probabilities.len() != dim * dim {
+                    return Err(format!(
+                        "'mutation_probabilities' has {} values, expected {} for {} residues",
+                        probabilities.len(),
+                        dim * dim,
+                        dim
+                    ));
+                }
+                if frequencies.len() != dim {
+                    return Err(format!(
+                        "'frequencies' has {} values, expected {}",
+                        frequencies.len(),
+                        dim
+                    ));
+                }
+                Ok(Self {
+                    alphabet: alphabet.bytes().map(|b| b.to_ascii_uppercase()).collect(),
+                    probabilities: probabilities.clone(),
+                    frequencies: frequencies.clone(),
+                })
+            }
+            _ => Ok(Self::dayhoff()),
+        }
+    }
+
+    /// Rescales each column and the frequencies to sum to one.
+    fn normalize(&mut self) -> Result<(), String> {
+        let dim = self.alphabet.len();
+        let all_values = self.probabilities.iter().chain(&self.frequencies);
+        if let Some(value) = all_values
+            .into_iter()
+            .find(|v| !(v.is_finite() && **v >= 0.0))
+        {
+            return Err(format!(
+                "Probabilities and frequencies must be non-negative numbers, got {value}"
+            ));
+        }
+        for j in 0..dim {
+            let total: f64 = (0..dim).map(|i| self.probabilities[i * dim + j]).sum();
+            if total == 0.0 {
+                return Err(format!(
+                    "Mutation probabilities for residue '{}' sum to zero",
+                    self.alphabet[j] as char
+                ));
+            }
+            for i in 0..dim {
+                self.probabilities[i * dim + j] /= total;
+            }
+        }
+        let total: f64 = self.frequencies.iter().sum();
+        if let Some(i) = self.frequencies.iter().position(|&f| f == 0.0) {
+            return Err(format!(
+                "Frequency of residue '{}' must be positive",
+                self.alphabet[i] as char
+            ));
+        }
+        for frequency in &mut self.frequencies {
+            *frequency /= total;
+        }
+        Ok(())
+    }
+}
+
+/// Computes the PAMn log-odds matrix described by `config`.
+pub fn extrapolate_pam(config: &PamConfig) -> Result<SubstitutionMatrix, String> {
+    config.validate()?;
+
+    let mut model = PamModel::from_config(config)?;
+    let name = config
+        .name
+        .clone()
+        .unwrap_or_else(|| format!("PAM{}", config.distance));
+    // Validates the alphabet before the matrix power is computed.
+    SubstitutionMatrix::new(&name, &model.alphabet, vec![0; model.alphabet.len().pow(2)])
+        .map_err(|e| e.to_string())?;
+    model.normalize()?;
+
+    let dim = model.alphabet.len();
+    let scale = config.scale();
+    let transitions = matrix_power(&model.probabilities, dim, config.distance);
+    let frequencies = &model.frequencies;
+
+    let mut scores = Vec::with_capacity(dim * dim);
+    for i in 0..dim {
+        for j in 0..dim {
+            let odds = (transitions[i * dim + j] / frequencies[i]
+                + transitions[j * dim + i] / frequencies[j])
+                / 2.0;
+            if odds == 0.0 {
+                scores.push(i32::MIN);
+                continue;
+            }
+            scores.push(config.rounding.to_score(scale * odds.log2()));
+        }
+    }
+    SubstitutionMatrix::new(name, &model.alphabet, scores).map_err(|e| e.to_string())
+}
+
+/// Raises a row-major `dim` x `dim` matrix to the `exponent` by squaring.
+fn matrix_power(matrix: &[f64], dim: usize, mut exponent: u32) -> Vec<f64> {
+    let mut result: Vec<f64> = (0..dim * dim)
+        .map(|k| if k / dim == k % dim { 1.0 } else { 0.0 })
+        .collect();
+    let mut base = matrix.to_vec();
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = matrix_product(&result, &base, dim);
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = matrix_product(&base, &base, dim);
+        }
+    }
+    result
+}
+
+fn matrix_product(a: &[f64], b: &[f64], dim: usize) -> Vec<f64> {
+    let mut product = vec![0.0; dim * dim];
+    for i in 0..dim {
+        for k in 0..dim {
+            let a_ik = a[i * dim + k];
+            if a_ik == 0.0 {
+                continue;
+            }
+            for j in 0..dim {
+                product[i * dim + j] += a_ik * b[k * dim + j];
+            }
+        }
+    }
+    product
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrices::BuiltinMatrix;
+
+    #[test]
+    fn test_dayhoff_columns_are_probabilities() {
+        for j in 0..20 {
+            let total: u32 = DAYHOFF_PAM1.iter().map(|row| u32::from(row[j])).sum();
+            assert!(
+                (9998..=10002).contains(&total),
+                "column {j} sums to {total}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pam250_tracks_bundled_matrix() {
+        let derived = extrapolate_pam(&PamConfig::new(250)).unwrap();
+        let bundled = BuiltinMatrix::from_name("PAM250").unwrap();
+
+        assert_eq!(derived.name(), "PAM250");
+        assert_eq!(derived.alphabet(), bundled.alphabet());
+        let close = derived
+            .scores()
+            .iter()
+            .zip(bundled.scores())
+            .filter(|(a, b)| (*a - *b).abs() <= 1)
+            .count();
+        assert!(close >= 390, "only {close} of 400 scores within one unit");
+        for i in 0..20 {
+            for j in 0..20 {
+                assert_eq!(derived.scores()[i * 20 + j], derived.scores()[j * 20 + i]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_scale_follows_bundled_units() {
+        assert_eq!(PamConfig::new(160).scale(), 2.0);
+        assert_eq!(PamConfig::new(180).scale(), 3.0);
+
+        for name in ["PAM120", "PAM250"] {
+            let bundled = BuiltinMatrix::from_name(name).unwrap();
+            let distance = name[3..].parse().unwrap();
+            let derived = extrapolate_pam(&PamConfig::new(distance)).unwrap();
+            assert_eq!(derived.scores()[0], bundled.scores()[0], "{name} A/A");
+            for (a, b) in derived.scores()[..20].iter().zip(&bundled.scores()[..20]) {
+                assert!((a - b).abs() <= 1, "{name} row A: {a} vs {b}");
+            }
+        }
+
+        let half_bits = PamConfig {
+            scale: Some(2.0),
+            ..PamConfig::new(250)
+        };
+        let derived = extrapolate_pam(&half_bits).unwrap();
+        assert_eq!(derived.score(b'A', b'A'), Some(1));
+    }
+
+    #[test]
+    fn test_unobserved_pam1_substitutions_are_negative_infinity() {
+        let matrix = extrapolate_pam(&PamConfig::new(1)).unwrap();
+        assert_eq!(matrix.score(b'W', b'A'), Some(i32::MIN));
+        assert!(matrix.score(b'W', b'W').unwrap() > 0);
+
+        let distant = extrapolate_pam(&PamConfig::new(400)).unwrap();
+        assert!(distant.scores().iter().all(|&s| s != i32::MIN));
+    }
+
+    #[test]
+    fn test_custom_model_and_validation() {
+        // Two-state model with symmetric 1% substitution per PAM unit.
+        let config = PamConfig {
+            alphabet: Some("ab".into()),
+            mutation_probabilities: Some(vec![0.99, 0.01, 0.01, 0.99]),
+            frequencies: Some(vec![0.5, 0.5]),
+            ..PamConfig::new(1)
+        };
+        let matrix = extrapolate_pam(&config).unwrap();
+        assert_eq!(matrix.alphabet(), b"AB");
+        assert_eq!(
+            matrix.score(b'A', b'A'),
+            Some((2.0 * 1.98f64.log2()).round() as i32)
+        );
+
+        assert!(extrapolate_pam(&PamConfig::new(0)).is_err());
+        let partial = PamConfig {
+            frequencies: Some(vec![0.5, 0.5]),
+            ..PamConfig::new(1)
+        };
+        assert!(extrapolate_pam(&partial).is_err());
+    }
+}
@@ -3,12 +3,22 @@
 use std::fmt;
 
 use crate::matrices::BuiltinMatrix;
+use crate::substitution_matrix::SubstitutionMatrix;
 
 /// Error type for alignment and scoring.
 #[derive(Debug, Clone)]
-pub(crate) enum AlignmentError {
+pub enum AlignmentError {
     /// Character not found in substitution matrix
     InvalidCharacter(u8),
+    /// DP matrix would exceed the configured cell limit
+    MatrixTooLarge {
+        /// Rows the matrix would need
+        rows: usize,
+        /// Columns the matrix would need
+        cols: usize,
+        /// The configured cell limit
+        max_cells: usize,
+    },
     /// Other error
     Other(String),
 }
@@ -19,6 +29,15 @@ impl fmt::Display for AlignmentError {
             AlignmentError::InvalidCharacter(c) => {
                 write!(f, "Invalid character in sequence: '{}'", *c as char)
             }
+            AlignmentError::MatrixTooLarge {
+                rows,
+                cols,
+                max_cells,
+            } => write!(
+                f,
+                "DP matrix of {} x {} cells exceeds the limit of {} cells",
+                rows, cols, max_cells
+            ),
             AlignmentError::Other(s) => write!(f, "{}", s),
         }
     }
@@ -28,40 +47,71 @@ impl std::error::Error for AlignmentError {}
 
 /// Substitution scoring source: either simple match/mismatch or a matrix.
 #[derive(Debug, Clone)]
-pub(crate) enum SubstitutionScorer {
+pub enum SubstitutionScorer {
     /// Simple match/mismatch scoring
     Simple {
+        /// Score for identical residues
         match_score: i32,
+        /// Score for different residues
         mismatch_score: i32,
     },
     /// Built-in substitution matrix
     Matrix(BuiltinMatrix),
+    /// Substitution matrix supplied at run time
+    Custom(SubstitutionMatrix),
 }
 
 impl SubstitutionScorer {
-    /// Validates that all characters in a sequence are valid for this scorer.
-    pub(crate) fn validate(&self, seq: &[u8]) -> Result<(), AlignmentError> {
+    /// Score for aligning residue `a` with `b` (either case), or `None` if
+    /// either is not in the matrix.
+    pub fn score(&self, a: u8, b: u8) -> Option<i32> {
         match self {
-            SubstitutionScorer::Simple { .. } => Ok(()), // All bytes are theoretically valid for simple
+            SubstitutionScorer::Simple {
+                match_score,
+                mismatch_score,
+            } => Some(if a.eq_ignore_ascii_case(&b) {
+                *match_score
+            } else {
+                *mismatch_score
+            }),
             SubstitutionScorer::Matrix(bm) => {
                 let map = bm.lookup_map();
-                for &c in seq {
-                    if map[c as usize].is_none() {
-                        return Err(AlignmentError::InvalidCharacter(c));
-                    }
-                }
-                Ok(())
+                let i = map[a as usize]? as usize;
+                let j = map[b as usize]? as usize;
+                Some(bm.scores()[i * bm.score_dimension() + j])
             }
+            SubstitutionScorer::Custom(matrix) => matrix.score(a, b),
+        }
+    }
+
+    /// Validates that all characters in a sequence are valid for this scorer.
+    pub fn validate(&self, seq: &[u8]) -> Result<(), AlignmentError> {
+        match self {
+            SubstitutionScorer::Simple { .. } => Ok(()), // All bytes are theoretically valid for simple
+            SubstitutionScorer::Matrix(bm) => validate_residues(seq, bm.lookup_map()),
+            SubstitutionScorer::Custom(matrix) => validate_residues(seq, matrix.lookup_map()),
         }
     }
 }
 
+fn validate_residues(seq: &[u8], map: &[Option<u8>; 256]) -> Result<(), AlignmentError> {
+    for &c in seq {
+        if map[c as usize].is_none() {
+            return Err(AlignmentError::InvalidCharacter(c));
+        }
+    }
+    Ok(())
+}
+
 /// Combined scoring configuration for alignment algorithms.
 #[derive(Debug, Clone)]
-pub(crate) struct ScoringConfig {
-    pub(crate) scorer: SubstitutionScorer,
-    pub(crate) gap_open: i32,
-    pub(crate) gap_extend: i32,
+pub struct ScoringConfig {
+    /// Score for aligning two residues.
+    pub scorer: SubstitutionScorer,
+    /// Penalty for the first residue of a gap, usually negative.
+    pub gap_open: i32,
+    /// Penalty for each further residue of a gap, usually negative.
+    pub gap_extend: i32,
 }
 
 impl Default for ScoringConfig {
@@ -78,12 +128,8 @@ impl Default for ScoringConfig {
 }
 
 impl ScoringConfig {
-    pub(crate) fn linear(
-        match_score: i32,
-        mismatch_score: i32,
-        gap_open: i32,
-        gap_extend: i32,
-    ) -> Self {
+    /// Match/mismatch scoring with the given gap penalties.
+    pub fn linear(match_score: i32, mismatch_score: i32, gap_open: i32, gap_extend: i32) -> Self {
         Self {
             scorer: SubstitutionScorer::Simple {
                 match_score,
@@ -94,7 +140,8 @@ impl ScoringConfig {
         }
     }
 
-    pub(crate) fn with_matrix(matrix: BuiltinMatrix, gap_open: i32, gap_extend: i32) -> Self {
+    /// Substitution-matrix scoring with the given gap penalties.
+    pub fn with_matrix(matrix: BuiltinMatrix, gap_open: i32, gap_extend: i32) -> Self {
         Self {
             scorer: SubstitutionScorer::Matrix(matrix),
             gap_open,
@@ -102,11 +149,22 @@ impl ScoringConfig {
         }
     }
 
-    pub(crate) fn is_affine(&self) -> bool {
+    /// Scoring with a run-time substitution matrix and the given gap penalties.
+    pub fn with_custom_matrix(matrix: SubstitutionMatrix, gap_open: i32, gap_extend: i32) -> Self {
+        Self {
+            scorer: SubstitutionScorer::Custom(matrix),
+            gap_open,
+            gap_extend,
+        }
+    }
+
+    /// Whether opening a gap costs differently from extending it.
+    pub fn is_affine(&self) -> bool {
         self.gap_open != self.gap_extend
     }
 
-    pub(crate) fn gap_penalty(&self, length: usize) -> i32 {
+    /// Total penalty for a gap of `length` residues.
+    pub fn gap_penalty(&self, length: usize) -> i32 {
         if length == 0 {
             0
         } else if self.is_affine() {
@@ -117,7 +175,8 @@ impl ScoringConfig {
         }
     }
 
-    pub(crate) fn ensure_linear(&self) -> Result<(), AlignmentError> {
+    /// Rejects affine gap penalties, which the aligners do not implement yet.
+    pub fn ensure_linear(&self) -> Result<(), AlignmentError> {
         if self.is_affine() {
             Err(AlignmentError::Other(format!(
                 "Affine gap penalties are not supported yet (gap_open={}, gap_extend={})",
@@ -0,0 +1,212 @@
+//! Both-strand nucleotide alignment.
+//!
+//! The second sequence is aligned as given and as its reverse complement.
+//! Results on the reverse strand keep the reverse complement in their aligned
+//! sequences, traceback paths and DP matrix, and report where the alignment
+//! falls on the original sequence through [`StrandAlignment::seq2_ranges`].
+
+use serde::{Deserialize, Serialize};
+
+use crate::alignment::AlignmentResult;
+use crate::scoring::AlignmentError;
+
+/// Which strands of the second sequence to align.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum StrandMode {
+    /// Align the second sequence as given.
+    #[default]
+    Forward,
+    /// Also align the reverse complement of the second sequence.
+    Both,
+}
+
+/// Which results to return when aligning both strands.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum StrandReport {
+    /// Only the higher-scoring strand (forward on ties).
+    #[default]
+    Best,
+    /// Both strands, higher-scoring first.
+    All,
+}
+
+/// Orientation of the second sequence in an alignment.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strand {
+    /// The second sequence as given.
+    Forward,
+    /// The reverse complement of the second sequence.
+    Reverse,
+}
+
+/// An alignment against one strand of the second sequence.
+#[derive(Debug)]
+pub struct StrandAlignment {
+    /// The strand of the second sequence that was aligned.
+    pub strand: Strand,
+    /// The aligned second sequence: the input, or its reverse complement.
+    pub seq2: Vec<u8>,
+    /// The alignment of the first sequence against `seq2`.
+    pub result: AlignmentResult,
+    /// For each alignment, the 1-based first and last aligned positions of
+    /// the original second sequence, or `None` if no residue was aligned.
+    /// On the reverse strand the first position is the larger one.
+    pub seq2_ranges: Vec<Option<(usize, usize)>>,
+}
+
+impl StrandAlignment {
+    pub(crate) fn new(strand: Strand, seq2: Vec<u8>, result: AlignmentResult) -> Self {
+        let len = seq2.len();
+        let seq2_ranges = result
+            .traceback_paths
+            .iter()
+            .map(|path| {
+                // Paths run from the end cell back to the start cell.
+                let end = path.steps.first()?.j;
+                let start = path.steps.last()?.j;
+                (end > start).then(|| match strand {
+                    Strand::Forward => (start + 1, end),
+                    Strand::Reverse => (len - start, len - end + 1),
+                })
+            })
+            .collect();
+        Self {
+            strand,
+            seq2,
+            result,
+            seq2_ranges,
+        }
+    }
+}
+
+/// IUPAC complement of a nucleotide, preserving case. `U` pairs with `A`;
+/// `A` complements to `T`, or to `U` when `rna` is set.
+fn complement(base: u8, rna: bool) -> Option<u8> {
+    let complement = match base.to_ascii_uppercase() {
+        b'A' if rna => b'U',
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'T' | b'U' => b'A',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'S' => b'S',
+        b'W' => b'W',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        b'N' => b'N',
+        b'-' => b'-',
+        _ => return None,
+    };
+    Some(if base.is_ascii_lowercase() {
+        complement.to_ascii_lowercase()
+    } else {
+        complement
+    })
+}
+
+/// Reverse complement of a nucleotide sequence with IUPAC ambiguity codes.
+///
+/// Sequences containing `U` but no `T` are treated as RNA.
+pub fn reverse_complement(seq: &[u8]) -> Result<Vec<u8>, AlignmentError> {
+    let rna = seq.iter().any(|b| b.eq_ignore_ascii_case(&b'U'))
+        && !seq.iter().any(|b| b.eq_ignore_ascii_case(&b'T'));
+    seq.iter()
+        .rev()
+        .map(|&base| {
+            complement(base, rna).ok_or_else(|| {
+                AlignmentError::Other(format!(
+                    "Cannot reverse-complement '{}': aligning both strands requires nucleotide sequences",
+                    char::from(base)
+                ))
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aligners::LocalAligner;
+    use crate::scoring::ScoringConfig;
+
+    #[test]
+    fn test_reverse_complement_iupac() {
+        assert_eq!(
+            reverse_complement(b"ACGTRYKMBDHVNSW").unwrap(),
+            b"WSNBDHVKMRYACGT"
+        );
+        assert_eq!(reverse_complement(b"acgU").unwrap(), b"Acgu");
+        assert_eq!(reverse_complement(b"AUGC").unwrap(), b"GCAU");
+        assert!(reverse_complement(b"ACGE").is_err());
+    }
+
+    #[test]
+    fn test_reverse_complement_edge_cases() {
+        assert_eq!(reverse_complement(b"").unwrap(), b"");
+        assert_eq!(reverse_complement(b"AC-gt").unwrap(), b"ac-GT");
+        // With both U and T present the sequence is read as DNA.
+        assert_eq!(reverse_complement(b"AUT").unwrap(), b"AAT");
+        assert!(reverse_complement(b"AC.G").is_err());
+    }
+
+    #[test]
+    fn test_ranges_cover_whole_sequence_and_empty_alignments() {
+        let seq2 = b"ACGT".to_vec();
+        let result = LocalAligner::new(ScoringConfig::linear(2, -3, -5, -5))
+            .align(b"ACGT", &seq2)
+            .unwrap();
+        let forward = StrandAlignment::new(Strand::Forward, seq2.clone(), result);
+        assert_eq!(forward.seq2_ranges, [Some((1, 4))]);
+
+        let reverse = reverse_complement(&seq2).unwrap();
+        let result = LocalAligner::new(ScoringConfig::linear(2, -3, -5, -5))
+            .align(b"ACGT", &reverse)
+            .unwrap();
+        let reverse = StrandAlignment::new(Strand::Reverse, reverse, result);
+        assert_eq!(reverse.seq2_ranges, [Some((4, 1))]);
+
+        // A local alignment without a positive score aligns no residues.
+        let result = LocalAligner::new(ScoringConfig::linear(2, -3, -5, -5))
+            .align(b"AAAA", b"CCCC")
+            .unwrap();
+        let empty = StrandAlignment::new(Strand::Forward, b"CCCC".to_vec(), result);
+        assert!(empty.seq2_ranges.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_palindromic_target_ties_keep_forward_first() {
+        let config: crate::AlignConfig = serde_json::from_str(
+            r#"{"mode": "local", "match_score": 2, "mismatch_score": -3, "gap_open": -5, "gap_extend": -5, "strand": "both", "strand_report": "all"}"#,
+        )
+        .unwrap();
+        // ACGT is its own reverse complement.
+        let strands = crate::align_strands(b"ACGT", b"ACGT", &config).unwrap();
+        assert_eq!(strands.len(), 2);
+        assert_eq!(strands[0].strand, Strand::Forward);
+        assert_eq!(strands[1].strand, Strand::Reverse);
+        assert_eq!(strands[0].result.final_score, strands[1].result.final_score);
+    }
+
+    #[test]
+    fn test_reverse_ranges_map_to_original_positions() {
+        let seq2 = b"TTAGCCA".to_vec();
+        let reverse = reverse_complement(&seq2).unwrap();
+        // Reverse complement is TGGCTAA; GGCT is at positions 2..=5 of it.
+        let result = LocalAligner::new(ScoringConfig::linear(2, -3, -5, -5))
+            .align(b"GGCT", &reverse)
+            .unwrap();
+        let stranded = StrandAlignment::new(Strand::Reverse, reverse, result);
+
+        assert_eq!(stranded.result.alignments[0].seq2_aligned, "GGCT");
+        // On the original sequence, AGCC spans positions 6 down to 3.
+        assert_eq!(stranded.seq2_ranges, [Some((6, 3))]);
+    }
+}
@@ -0,0 +1,205 @@
+//! Substitution matrices supplied at run time.
+
+use serde::{Deserialize, Serialize};
+
+use crate::matrices::BuiltinMatrix;
+use crate::scoring::AlignmentError;
+
+/// A substitution matrix that is not bundled with the crate, such as one
+/// derived from alignment data.
+///
+/// Serializes to and from the `{name, alphabet, scores}` shape returned by
+/// `matrix_info`: `alphabet` is a list of single-character strings and
+/// `scores` is row-major, with `i32::MIN`/`i32::MAX` encoding -inf/+inf.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "MatrixData", into = "MatrixData")]
+pub struct SubstitutionMatrix {
+    name: String,
+    alphabet: Vec<u8>,
+    scores: Vec<i32>,
+    lookup_map: Box<[Option<u8>; 256]>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct MatrixData {
+    name: String,
+    alphabet: Vec<String>,
+    scores: Vec<i32>,
+}
+
+impl SubstitutionMatrix {
+    /// Builds a matrix from residues and row-major scores.
+    ///
+    /// Residues must be distinct single ASCII characters; letters are
+    /// uppercased and matched case-insensitively, as for built-in matrices.
+    pub fn new(
+        name: impl Into<String>,
+        alphabet: &[u8],
+        scores: Vec<i32>,
+    ) -> Result<Self, AlignmentError> {
+        let name = name.into();
+        if alphabet.is_empty() {
+            return Err(AlignmentError::Other(format!(
+                "Matrix '{}' has an empty alphabet",
+                name
+            )));
+        }
+        if alphabet.len() > u8::MAX as usize {
+            return Err(AlignmentError::Other(format!(
+                "Matrix '{}' has more than {} residues",
+                name,
+                u8::MAX
+            )));
+        }
+        let dim = alphabet.len();
+        if scores.len() != dim * dim {
+            return Err(AlignmentError::Other(format!(
+                "Matrix '{}' has {} scores, expected {} for {} residues",
+                name,
+                scores.len(),
+                dim * dim,
+                dim
+            )));
+        }
+
+        let mut normalized = Vec::with_capacity(dim);
+        let mut lookup_map = Box::new([None; 256]);
+        for (i, &residue) in alphabet.iter().enumerate() {
+            if !residue.is_ascii() || residue.is_ascii_whitespace() {
+                return Err(AlignmentError::Other(format!(
+                    "Matrix '{}' has an invalid residue: {:?}",
+                    name, residue as char
+                )));
+            }
+            let residue = residue.to_ascii_uppercase();
+            if lookup_map[residue as usize].is_some() {
+                return Err(AlignmentError::Other(format!(
+                    "Matrix '{}' lists residue '{}' more than once",
+                    name, residue as char
+                )));
+            }
+            lookup_map[residue as usize] = Some(i as u8);
+            lookup_map[residue.to_ascii_lowercase() as usize] = Some(i as u8);
+            normalized.push(residue);
+        }
+
+        Ok(Self {
+            name,
+            alphabet: normalized,
+            scores,
+            lookup_map,
+        })
+    }
+
+    /// Matrix name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Number of residues along each matrix axis.
+    pub fn score_dimension(&self) -> usize {
+        self.alphabet.len()
+    }
+
+    /// Row-major scores; `i32::MIN`/`i32::MAX` encode -inf/+inf.
+    pub fn scores(&self) -> &[i32] {
+        &self.scores
+    }
+
+    /// Maps a residue byte (either case) to its matrix index.
+    pub fn lookup_map(&self) -> &[Option<u8>; 256] {
+        &self.lookup_map
+    }
+
+    /// Residues in matrix order.
+    pub fn alphabet(&self) -> &[u8] {
+        &self.alphabet
+    }
+
+    /// Score for a pair of residues, or `None` if either is not in the
+    /// alphabet.
+    pub fn score(&self, a: u8, b: u8) -> Option<i32> {
+        let i = self.lookup_map[a as usize]? as usize;
+        let j = self.lookup_map[b as usize]? as usize;
+        Some(self.scores[i * self.score_dimension() + j])
+    }
+}
+
+impl From<BuiltinMatrix> for SubstitutionMatrix {
+    fn from(matrix: BuiltinMatrix) -> Self {
+        Self::new(matrix.name(), matrix.alphabet(), matrix.scores().to_vec())
+            .expect("built-in matrices are validated at build time")
+    }
+}
+
+impl TryFrom<MatrixData> for SubstitutionMatrix {
+    type Error = AlignmentError;
+
+    fn try_from(data: MatrixData) -> Result<Self, Self::Error> {
+        let mut alphabet = Vec::with_capacity(data.alphabet.len());
+        for residue in &data.alphabet {
+            match residue.as_bytes() {
+                [byte] => alphabet.push(*byte),
+                _ => {
+                    return Err(AlignmentError::Other(format!(
+                        "Matrix '{}' has non-ASCII or multi-character residue: {:?}",
+                        data.name, residue
+                    )));
+                }
+            }
+        }
+        Self::new(data.name, &alphabet, data.scores)
+    }
+}
+
+impl From<SubstitutionMatrix> for MatrixData {
+    fn from(matrix: SubstitutionMatrix) -> Self {
+        Self {
+            alphabet: matrix
+                .alphabet
+                .iter()
+                .map(|&b| (b as char).to_string())
+                .collect(),
+            name: matrix.name,
+            scores: matrix.scores,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trips_matrix_info_shape() {
+        let json = r#"{"name":"TOY","alphabet":["a","C"],"scores":[2,-1,-1,3]}"#;
+        let matrix: SubstitutionMatrix = serde_json::from_str(json).unwrap();
+
+        assert_eq!(matrix.alphabet(), b"AC");
+        assert_eq!(matrix.score(b'a', b'c'), Some(-1));
+        assert_eq!(matrix.score(b'C', b'C'), Some(3));
+        assert_eq!(matrix.score(b'G', b'C'), None);
+        assert_eq!(
+            serde_json::to_string(&matrix).unwrap(),
+            r#"{"name":"TOY","alphabet":["A","C"],"scores":[2,-1,-1,3]}"#
+        );
+    }
+
+    #[test]
+    fn test_rejects_malformed_matrices() {
+        let duplicate = r#"{"name":"X","alphabet":["A","a"],"scores":[1,0,0,1]}"#;
+        let wrong_size = r#"{"name":"X","alphabet":["A","C"],"scores":[1,0,0]}"#;
+        let multi_char = r#"{"name":"X","alphabet":["AC"],"scores":[1]}"#;
+
+        for json in [duplicate, wrong_size, multi_char] {
+            assert!(serde_json::from_str::<SubstitutionMatrix>(json).is_err());
+        }
+    }
+
+    #[test]
+    fn test_builtin_conversion_keeps_scores() {
+        let matrix = SubstitutionMatrix::from(BuiltinMatrix::Blosum62);
+        assert_eq!(matrix.name(), "BLOSUM62");
+        assert_eq!(matrix.score(b'W', b'W'), Some(11));
+    }
+}
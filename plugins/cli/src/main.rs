@@ -0,0 +1,579 @@
+//! genotypst: run the plugin entry points natively on files.
+//!
+//! Each subcommand calls the same function as the corresponding WASM entry
+//! point and writes the payload the Typst side consumes. This makes it
+//! possible to cache expensive results in CI and to reproduce layouts
+//! without building the plugins or compiling a document.
+//!
+//! Config files may be JSON or CBOR. The output uses the encoding of the
+//! config unless `--format` asks for a specific one.
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use seq_align::WireFormat;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "genotypst",
+    version,
+    about = "Pre-compute genotypst plugin outputs"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Pairwise alignment (`align`).
+    Align {
+        /// File holding the first sequence, optionally gzip-compressed. Lines
+        /// starting with `>` are skipped.
+        seq1: PathBuf,
+        /// File holding the second sequence, optionally gzip-compressed. Lines
+        /// starting with `>` are skipped.
+        seq2: PathBuf,
+        /// Alignment config (`mode`, `matrix`, `gap_open`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Built-in scoring matrix with its header values and statistics
+    /// (`matrix_info`).
+    MatrixInfo {
+        /// Matrix name, e.g. `BLOSUM62`. Case-insensitive.
+        name: String,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Log-odds matrix derived from an alignment or pair counts
+    /// (`derive_matrix`).
+    DeriveMatrix {
+        /// Derivation config (`sequences` or `pair_counts`, `alphabet`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// PAM matrix extrapolated to any distance (`pam_matrix`).
+    PamMatrix {
+        /// Extrapolation config (`distance`, `scale`, `rounding`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Dot plot segments (`dot_plot`).
+    DotPlot {
+        /// File holding the first sequence, optionally gzip-compressed. Lines
+        /// starting with `>` are skipped.
+        seq1: PathBuf,
+        /// File holding the second sequence, optionally gzip-compressed. Lines
+        /// starting with `>` are skipped.
+        seq2: PathBuf,
+        /// Dot plot config (`method`, `word_size`, `strand`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// GFF3 parsing (`parse_gff`).
+    ParseGff {
+        /// GFF3 file, optionally gzip- or BGZF-compressed.
+        input: PathBuf,
+        /// Parser filter config. Defaults to no filtering.
+        #[arg(long)]
+        config: Option<PathBuf>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// FASTA/FASTQ parsing (`parse_sequences`).
+    ParseSequences {
+        /// FASTA or FASTQ file, optionally gzip- or BGZF-compressed.
+        input: PathBuf,
+        /// Parser config (`format`, `duplicates`, `quality`, ...). Defaults to
+        /// format detection and rejecting duplicate identifiers.
+        #[arg(long)]
+        config: Option<PathBuf>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Multiple sequence alignment parsing (`parse_msa`).
+    ParseMsa {
+        /// Stockholm, Clustal, PHYLIP, FASTA or A2M/A3M file, optionally
+        /// gzip- or BGZF-compressed.
+        input: PathBuf,
+        /// Parser config (`format`, `duplicates`, `insertions`). Defaults to
+        /// format detection.
+        #[arg(long)]
+        config: Option<PathBuf>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// MSA column statistics (`column_stats`).
+    ColumnStats {
+        /// Column statistics request (`sequences`, `alphabet`, `start`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Alignment sequence weights (`sequence_weights`).
+    SequenceWeights {
+        /// Weights request (`sequences`, `alphabet`, `weighting`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// MSA column trimming (`trim_msa_columns`).
+    TrimMsaColumns {
+        /// Trimming request (`sequences`, `alphabet`, `max_gap_fraction`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// MSA redundancy filtering (`filter_msa_sequences`).
+    FilterMsaSequences {
+        /// Filtering request (`sequences`, `alphabet`, `max_identity`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Pairwise percent-identity matrix of an MSA (`identity_matrix`).
+    IdentityMatrix {
+        /// Identity request (`sequences`, `alphabet`, `definition`, `linkage`).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Position weight matrix from a motif alignment (`motif_pwm`).
+    MotifPwm {
+        /// PWM request (`sequences`, `alphabet`, `background`, `pseudocount`).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Motif hits as genome-map features (`scan_motif_hits`).
+    ScanMotifHits {
+        /// Scan request (`scores`, `background`, `sequence`, `max_p_value`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Relative-entropy sequence-logo heights (`logo_heights`).
+    LogoHeights {
+        /// Logo request (`sequences`, `alphabet`, `background`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Sequence-logo heights from column frequencies (`frequency_logo_heights`).
+    FrequencyLogoHeights {
+        /// Logo request (`frequencies`, `alphabet`, `background`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Population-genetics statistics for nucleotide MSAs (`popgen_stats`).
+    PopgenStats {
+        /// Request (`sequences`, `missing`, `window`, `step`).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Variant sites relative to a reference sequence (`snp_sites`).
+    SnpSites {
+        /// Request (`sequences`, `reference`, `snp_alignment`).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// MEME, JASPAR and TRANSFAC motif parsing (`parse_motif_matrices`).
+    ParseMotifMatrices {
+        /// MEME minimal, JASPAR, PFM or TRANSFAC file, optionally gzip- or
+        /// BGZF-compressed.
+        input: PathBuf,
+        /// Parser config (`format`). Defaults to format detection.
+        #[arg(long)]
+        config: Option<PathBuf>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Genome-map label layout (`layout_labels`).
+    LayoutLabels {
+        /// Label layout request with measured label geometry.
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Translation of one reading frame (`translate_sequence`).
+    TranslateSequence {
+        /// Translation request (`sequence`, `table`, `frame`, `stop`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Six-frame translation (`six_frame_translation`).
+    SixFrameTranslation {
+        /// Translation request (`sequence`, `table`).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Open reading frames as genome-map features (`orfs`).
+    Orfs {
+        /// ORF request (`sequence`, `table`, `start_codons`, `min_length`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Sliding-window GC content, GC skew and complexity (`composition_track`).
+    CompositionTrack {
+        /// Composition request (`sequence`, `window`, `step`, `k`).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Newick parsing (`parse_newick`).
+    ParseNewick {
+        /// Newick file, optionally gzip- or BGZF-compressed.
+        input: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Tree layout preparation (`prepare_layout`).
+    PrepareLayout {
+        /// Prepare-layout request (`tree-data`, `layout-kind`, ...).
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Tree fitting (`fit_tree`).
+    FitTree {
+        /// Fit request produced from a prepared layout.
+        #[arg(long)]
+        config: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+#[derive(Args)]
+struct OutputArgs {
+    /// Output file. Defaults to standard output.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Output encoding. Defaults to the encoding of the config.
+    #[arg(long, value_enum)]
+    format: Option<Format>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+enum Format {
+    Json,
+    Cbor,
+}
+
+impl From<Format> for WireFormat {
+    fn from(format: Format) -> Self {
+        match format {
+            Format::Json => WireFormat::Json,
+            Format::Cbor => WireFormat::Cbor,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> Result<(), String> {
+    let (payload, output) = match command {
+        Command::Align {
+            seq1,
+            seq2,
+            config,
+            output,
+        } => {
+            let seq1 = read_sequence(&seq1)?;
+            let seq2 = read_sequence(&seq2)?;
+            let config = read_config(&config, output.format)?;
+            (
+                seq_align::align(seq1.as_bytes(), seq2.as_bytes(), &config)?,
+                output,
+            )
+        }
+        Command::MatrixInfo { name, output } => {
+            let json = seq_align::matrix_info(name.as_bytes())?;
+            let target = output.format.map_or(WireFormat::Json, WireFormat::from);
+            (transcode(&json, WireFormat::Json, target)?, output)
+        }
+        Command::DeriveMatrix { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (seq_align::derive_matrix(&config)?, output)
+        }
+        Command::PamMatrix { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (seq_align::pam_matrix(&config)?, output)
+        }
+        Command::DotPlot {
+            seq1,
+            seq2,
+            config,
+            output,
+        } => {
+            let seq1 = read_sequence(&seq1)?;
+            let seq2 = read_sequence(&seq2)?;
+            let config = read_config(&config, output.format)?;
+            (
+                seq_align::dot_plot(seq1.as_bytes(), seq2.as_bytes(), &config)?,
+                output,
+            )
+        }
+        Command::ParseGff {
+            input,
+            config,
+            output,
+        } => {
+            let data = read_file(&input)?;
+            let config = match config {
+                Some(path) => read_config(&path, output.format)?,
+                None => empty_config(output.format)?,
+            };
+            (genome_map::parse_gff(&data, &config)?, output)
+        }
+        Command::ParseSequences {
+            input,
+            config,
+            output,
+        } => {
+            let data = read_file(&input)?;
+            let config = match config {
+                Some(path) => read_config(&path, output.format)?,
+                None => empty_config(output.format)?,
+            };
+            (sequence::parse_sequences(&data, &config)?, output)
+        }
+        Command::ParseMsa {
+            input,
+            config,
+            output,
+        } => {
+            let data = read_file(&input)?;
+            let config = match config {
+                Some(path) => read_config(&path, output.format)?,
+                None => empty_config(output.format)?,
+            };
+            (sequence::parse_msa(&data, &config)?, output)
+        }
+        Command::ColumnStats { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (sequence::column_stats(&config)?, output)
+        }
+        Command::SequenceWeights { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (sequence::sequence_weights(&config)?, output)
+        }
+        Command::TrimMsaColumns { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (sequence::trim_msa_columns(&config)?, output)
+        }
+        Command::FilterMsaSequences { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (sequence::filter_msa_sequences(&config)?, output)
+        }
+        Command::IdentityMatrix { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (sequence::identity_matrix(&config)?, output)
+        }
+        Command::MotifPwm { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (sequence::motif_pwm(&config)?, output)
+        }
+        Command::ScanMotifHits { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (sequence::scan_motif_hits(&config)?, output)
+        }
+        Command::LogoHeights { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (sequence::logo_heights(&config)?, output)
+        }
+        Command::FrequencyLogoHeights { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (sequence::frequency_logo_heights(&config)?, output)
+        }
+        Command::PopgenStats { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (sequence::popgen_stats(&config)?, output)
+        }
+        Command::SnpSites { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (sequence::snp_sites(&config)?, output)
+        }
+        Command::ParseMotifMatrices {
+            input,
+            config,
+            output,
+        } => {
+            let data = read_file(&input)?;
+            let config = match config {
+                Some(path) => read_config(&path, output.format)?,
+                None => empty_config(output.format)?,
+            };
+            (sequence::parse_motif_matrices(&data, &config)?, output)
+        }
+        Command::LayoutLabels { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (genome_map::layout_labels(&config)?, output)
+        }
+        Command::TranslateSequence { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (seq_utils::translate_sequence(&config)?, output)
+        }
+        Command::SixFrameTranslation { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (seq_utils::six_frame_translation(&config)?, output)
+        }
+        Command::Orfs { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (seq_utils::orfs(&config)?, output)
+        }
+        Command::CompositionTrack { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (seq_utils::composition_track(&config)?, output)
+        }
+        Command::ParseNewick { input, output } => {
+            let data = read_file(&input)?;
+            let json = tree::parse_newick(&data)?;
+            let target = output.format.map_or(WireFormat::Json, WireFormat::from);
+            (transcode(&json, WireFormat::Json, target)?, output)
+        }
+        Command::PrepareLayout { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (tree::prepare_layout(&config)?, output)
+        }
+        Command::FitTree { config, output } => {
+            let config = read_config(&config, output.format)?;
+            (tree::fit_tree(&config)?, output)
+        }
+    };
+    write_output(output.output.as_deref(), &payload)
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// Reads a sequence the way the Typst side cleans it: header lines are
+/// dropped, whitespace is removed and residues are uppercased. Gzip and BGZF
+/// files are decompressed first.
+fn read_sequence(path: &Path) -> Result<String, String> {
+    let data = read_file(path)?;
+    let data = genome_map::decompress(&data).map_err(|e| format!("{}: {e}", path.display()))?;
+    let text = std::str::from_utf8(&data).map_err(|e| format!("{}: {e}", path.display()))?;
+    clean_sequence(text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn clean_sequence(text: &str) -> Result<String, String> {
+    let sequence: String = text
+        .lines()
+        .filter(|line| !line.starts_with('>'))
+        .flat_map(|line| line.chars().filter(|c| !c.is_whitespace()))
+        .collect();
+    if sequence.is_empty() {
+        return Err("sequence must not be empty".into());
+    }
+    if !sequence.is_ascii() {
+        return Err("sequence must contain only ASCII characters".into());
+    }
+    Ok(sequence.to_ascii_uppercase())
+}
+
+/// Reads a config file and re-encodes it when a different output encoding
+/// is requested, so the plugin answers in that encoding.
+fn read_config(path: &Path, format: Option<Format>) -> Result<Vec<u8>, String> {
+    let config = read_file(path)?;
+    let source = WireFormat::detect(&config);
+    let target = format.map_or(source, WireFormat::from);
+    transcode(&config, source, target).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn empty_config(format: Option<Format>) -> Result<Vec<u8>, String> {
+    let target = format.map_or(WireFormat::Json, WireFormat::from);
+    target.encode(&serde_json::json!({}))
+}
+
+fn transcode(payload: &[u8], source: WireFormat, target: WireFormat) -> Result<Vec<u8>, String> {
+    if source == target {
+        return Ok(payload.to_vec());
+    }
+    let value: serde_json::Value = source.decode(payload)?;
+    target.encode(&value)
+}
+
+fn write_output(path: Option<&Path>, payload: &[u8]) -> Result<(), String> {
+    match path {
+        Some(path) => fs::write(path, payload).map_err(|e| format!("{}: {e}", path.display())),
+        None => io::stdout()
+            .lock()
+            .write_all(payload)
+            .map_err(|e| format!("Failed to write output: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_sequence_matches_typst_cleaning() {
+        assert_eq!(clean_sequence(">seq1 desc\nacg t\nTT\n").unwrap(), "ACGTTT");
+        assert!(clean_sequence(">only a header\n").is_err());
+        assert!(clean_sequence("ACGÜ").is_err());
+    }
+
+    #[test]
+    fn test_transcode_round_trips_between_encodings() {
+        let json = br#"{"gap_open":-2,"mode":"local"}"#;
+        let cbor = transcode(json, WireFormat::Json, WireFormat::Cbor).unwrap();
+
+        assert_eq!(WireFormat::detect(&cbor), WireFormat::Cbor);
+        assert_eq!(
+            transcode(&cbor, WireFormat::Cbor, WireFormat::Json).unwrap(),
+            json
+        );
+    }
+
+    #[test]
+    fn test_cli_definition_is_consistent() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+}
@@ -0,0 +1,93 @@
+//! Runs the `genotypst` binary on the fixtures in `tests/fixtures`.
+
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// Runs the binary and parses its JSON output.
+fn run(args: &[&str]) -> Value {
+    let output = Command::new(env!("CARGO_BIN_EXE_genotypst"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+fn path(name: &str) -> String {
+    fixture(name).to_str().unwrap().to_owned()
+}
+
+#[test]
+fn test_align_reads_fasta_files() {
+    let result = run(&[
+        "align",
+        &path("seq1.fa"),
+        &path("seq2.fa"),
+        "--config",
+        &path("align.json"),
+    ]);
+
+    assert_eq!(result["alignment_score"], 8);
+    assert_eq!(result["alignments"][0]["seq1"], "GATTACAGATTACA");
+    assert_eq!(result["alignments"][0]["seq2"], "GATT-CAGATTAC-");
+}
+
+#[test]
+fn test_parse_gff_without_config() {
+    let features = run(&["parse-gff", &path("features.gff3")]);
+    let features = features.as_array().unwrap();
+
+    assert_eq!(features.len(), 2);
+    assert_eq!(features[0]["feature-type"], "gene");
+    assert_eq!(features[0]["start"], 100);
+    assert_eq!(features[1]["label"], "cds1");
+}
+
+#[test]
+fn test_parse_newick_writes_json() {
+    let tree = run(&["parse-newick", &path("tree.nwk")]);
+
+    assert_eq!(tree["rooted"], true);
+    assert_eq!(tree["name"], "root");
+    assert_eq!(tree["children"][1]["children"][0]["name"], "B");
+}
+
+#[test]
+fn test_fit_tree_fills_resolved_width() {
+    let fit = run(&["fit-tree", "--config", &path("fit.json")]);
+
+    assert_eq!(fit["width_unresolved"], false);
+    assert_eq!(fit["tree_viewport_width_pt"], 200.0);
+    assert_eq!(fit["tree_lines"].as_array().unwrap().len(), 2);
+    assert_eq!(fit["tree_labels"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_matrix_subcommands_share_the_matrix_info_shape() {
+    let pam250 = run(&["matrix-info", "pam250"]);
+    assert_eq!(pam250["name"], "PAM250");
+    assert_eq!(
+        pam250["statistics"]["background_source"],
+        "robinson-robinson"
+    );
+
+    let extrapolated = run(&["pam-matrix", "--config", &path("pam.json")]);
+    assert_eq!(extrapolated["name"], "PAM250");
+    assert_eq!(extrapolated["scale_bits"], pam250["scale_bits"]);
+    assert_eq!(extrapolated["scores"][0], pam250["scores"][0]);
+
+    let derived = run(&["derive-matrix", "--config", &path("derive.json")]);
+    assert_eq!(derived["alphabet"], serde_json::json!(["A", "C", "G", "T"]));
+    assert_eq!(derived["scale_bits"], 0.5);
+}
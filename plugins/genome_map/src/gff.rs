@@ -6,31 +6,46 @@ use std::borrow::Cow;
 use std::collections::{BTreeMap, HashSet};
 use std::io::Cursor;
 
-#[derive(Deserialize)]
-struct ParseGffConfig {
+/// Filters applied while parsing GFF3 records.
+///
+/// The default keeps every feature and labels it with its `ID` attribute.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParseGffConfig {
+    /// Feature types to keep; `None` keeps all types.
     #[serde(default)]
-    feature_types: Option<Vec<String>>,
+    pub feature_types: Option<Vec<String>>,
+    /// Region to keep; features overlapping its edges are clipped.
     #[serde(default)]
-    range: Option<RangeFilter>,
+    pub range: Option<RangeFilter>,
+    /// Strand to keep; `None` keeps both strands and unstranded features.
     #[serde(default)]
-    strand: Option<StrandFilter>,
+    pub strand: Option<StrandFilter>,
+    /// Drops features clipped by `range`.
     #[serde(default)]
-    exclude_partial: bool,
+    pub exclude_partial: bool,
+    /// Attribute used as the feature label.
     #[serde(default = "default_label_attribute")]
-    label_attribute: String,
+    pub label_attribute: String,
 }
 
-#[derive(Deserialize)]
-struct RangeFilter {
-    accession: String,
-    start: Option<u64>,
-    end: Option<u64>,
+/// A 1-based, inclusive region on one sequence.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RangeFilter {
+    /// Sequence ID (GFF3 column 1) of the region.
+    pub accession: String,
+    /// First position; `None` starts at the beginning of the sequence.
+    pub start: Option<u64>,
+    /// Last position; `None` runs to the end of the sequence.
+    pub end: Option<u64>,
 }
 
+/// Strand selected by [`ParseGffConfig::strand`].
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
-enum StrandFilter {
+pub enum StrandFilter {
+    /// Features on the `+` strand.
     Positive,
+    /// Features on the `-` strand.
     Negative,
 }
 
@@ -40,29 +55,59 @@ struct NormalizedRange {
     end: Option<u64>,
 }
 
-#[derive(Serialize)]
+/// A feature in the shape `render-genome-map` draws.
+///
+/// Coordinates are 1-based and inclusive; `start`/`end` are clipped to the
+/// requested range while `original_start`/`original_end` are not.
+#[derive(Debug, Clone, Serialize)]
 #[cfg_attr(test, derive(Deserialize))]
-struct GenomeMapFeature {
-    start: u64,
-    end: u64,
-    strand: Option<i8>,
-    label: Option<String>,
-    partial: bool,
-    accession: String,
+pub struct GenomeMapFeature {
+    /// First position, clipped to the requested range.
+    pub start: u64,
+    /// Last position, clipped to the requested range.
+    pub end: u64,
+    /// `1` for `+`, `-1` for `-`, or `None` for `.` and `?`.
+    pub strand: Option<i8>,
+    /// Value of the configured label attribute, if the feature has one.
+    pub label: Option<String>,
+    /// Whether the requested range clipped the feature.
+    pub partial: bool,
+    /// Sequence ID from column 1.
+    pub accession: String,
+    /// Feature type from column 3, such as `gene` or `CDS`.
     #[serde(rename = "feature-type")]
-    feature_type: String,
-    source: String,
-    score: Option<f64>,
-    phase: Option<u8>,
-    attributes: BTreeMap<String, Vec<String>>,
+    pub feature_type: String,
+    /// Source from column 2.
+    pub source: String,
+    /// Score from column 6, or `None` for `.`.
+    pub score: Option<f64>,
+    /// CDS phase from column 8, or `None` for `.`.
+    pub phase: Option<u8>,
+    /// Column 9 attributes; a key may carry several comma-separated values.
+    pub attributes: BTreeMap<String, Vec<String>>,
+    /// First position before clipping.
     #[serde(rename = "original-start")]
-    original_start: u64,
+    pub original_start: u64,
+    /// Last position before clipping.
     #[serde(rename = "original-end")]
-    original_end: u64,
+    pub original_end: u64,
+}
+
+impl Default for ParseGffConfig {
+    fn default() -> Self {
+        Self {
+            feature_types: None,
+            range: None,
+            strand: None,
+            exclude_partial: false,
+            label_attribute: default_label_attribute(),
+        }
+    }
 }
 
 impl ParseGffConfig {
-    fn validate(&self) -> Result<(), String> {
+    /// Checks that feature types are non-empty and the range is well formed.
+    pub fn validate(&self) -> Result<(), String> {
         if let Some(feature_types) = &self.feature_types {
             for (index, feature_type) in feature_types.iter().enumerate() {
                 if feature_type.is_empty() {
@@ -385,19 +430,21 @@ fn parse_records(data: &str, config: &ParseGffConfig) -> Result<Vec<GenomeMapFea
     Ok(features)
 }
 
-pub fn parse_gff(data: &[u8], config: &[u8]) -> Result<Vec<u8>, String> {
-    let data = std::str::from_utf8(data).map_err(|e| format!("Invalid UTF-8 in GFF3 data: {e}"))?;
-    let config: ParseGffConfig =
-        serde_json::from_slice(config).map_err(|e| format!("Invalid config JSON: {e}"))?;
+/// Parses GFF3 text into genome-map features, applying `config`'s filters.
+///
+/// Anything after a `##FASTA` directive is ignored.
+pub fn parse_gff_features(
+    data: &str,
+    config: &ParseGffConfig,
+) -> Result<Vec<GenomeMapFeature>, String> {
     config.validate()?;
-
-    let features = parse_records(data, &config)?;
-    serde_json::to_vec(&features).map_err(|e| format!("Serialization failed: {e}"))
+    parse_records(data, config)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse_gff;
     use serde_json::Value;
 
     const SIMPLE_GFF: &str = "\
@@ -769,4 +816,41 @@ chr1	src	gene	1	10	.	+	.	ID=gene1;same%5Fkey=a;same_key=b
 
         assert!(error.contains("Invalid config JSON"));
     }
+
+    #[test]
+    fn native_parser_matches_default_config() {
+        let features = parse_gff_features(SIMPLE_GFF, &ParseGffConfig::default()).unwrap();
+
+        assert_eq!(features.len(), 5);
+        assert_eq!(features[0].label.as_deref(), Some("gene1"));
+    }
+
+    #[test]
+    fn cbor_config_returns_cbor_features() {
+        let mut config = Vec::new();
+        ciborium::into_writer(&default_config(), &mut config).unwrap();
+
+        let bytes = parse_gff(SIMPLE_GFF.as_bytes(), &config).unwrap();
+        let features: Vec<GenomeMapFeature> = ciborium::from_reader(bytes.as_slice()).unwrap();
+
+        assert_eq!(features.len(), 5);
+        assert_eq!(features[1].score, Some(3.5));
+    }
+
+    #[test]
+    fn parses_gzip_compressed_input() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(SIMPLE_GFF.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let config = config_json(default_config());
+        assert_eq!(
+            parse_gff(&compressed, &config).unwrap(),
+            parse_gff(SIMPLE_GFF.as_bytes(), &config).unwrap()
+        );
+    }
 }
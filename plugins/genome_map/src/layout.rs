@@ -10,49 +10,88 @@ const Y_EPSILON_PT: f64 = 1e-9;
 /// Typst measures label text and sends horizontal geometry. Rust handles:
 /// sorting, first-fit level packing, vertical geometry, and leader routing.
 #[derive(Debug, Clone, Deserialize)]
-pub(crate) struct LayoutRequest {
-    label_height_pt: f64,
-    label_horizontal_gap_pt: f64,
-    label_vertical_gap_pt: f64,
-    label_line_distance_pt: f64,
-    label_track_gap_pt: f64,
-    label_leader_offset_pt: f64,
-    labels: Vec<MeasuredLabel>,
+pub struct LayoutRequest {
+    /// Height of one label line.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub label_height_pt: f64,
+    /// Smallest horizontal gap between labels on the same level.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub label_horizontal_gap_pt: f64,
+    /// Vertical gap between label levels.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub label_vertical_gap_pt: f64,
+    /// Horizontal clearance a leader line keeps from labels it passes.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub label_line_distance_pt: f64,
+    /// Distance from the lowest label level to the feature track.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub label_track_gap_pt: f64,
+    /// Gap left between the end of a leader line and the feature track.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub label_leader_offset_pt: f64,
+    /// Labels in feature order.
+    pub labels: Vec<MeasuredLabel>,
 }
 
 /// Per-label measured geometry sent from Typst (in original gene order).
 #[derive(Debug, Clone, Deserialize)]
-struct MeasuredLabel {
-    center_pt: f64,
-    left_pt: f64,
-    right_pt: f64,
-    dodge_left_pt: f64,
-    dodge_right_pt: f64,
-    packing_span_pt: f64,
-    gene_center_pt: f64,
+pub struct MeasuredLabel {
+    /// Centre of the label text.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub center_pt: f64,
+    /// Left edge of the label text.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub left_pt: f64,
+    /// Right edge of the label text.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub right_pt: f64,
+    /// Left edge of the span the label needs on its level, including its
+    /// leader.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub dodge_left_pt: f64,
+    /// Right edge of the span the label needs on its level, including its
+    /// leader.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub dodge_right_pt: f64,
+    /// Width used to order labels for packing; wider labels are placed first.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub packing_span_pt: f64,
+    /// Centre of the labelled feature, where the leader line is drawn.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub gene_center_pt: f64,
 }
 
 /// Response to Typst from the full layout pipeline.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub(crate) struct LayoutResponse {
-    level_count: usize,
-    level_block_height_pt: f64,
-    labels: Vec<PositionedLabel>,
+pub struct LayoutResponse {
+    /// Number of label levels used.
+    pub level_count: usize,
+    /// Height of all label levels together.
+    pub level_block_height_pt: f64,
+    /// Labels sorted by centre.
+    pub labels: Vec<PositionedLabel>,
 }
 
 /// Per-label positioned output returned to Typst.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct PositionedLabel {
-    source_index: usize,
-    top_pt: f64,
-    underline_y_pt: f64,
-    leader_segments: Vec<LeaderSegment>,
+pub struct PositionedLabel {
+    /// Index of the label in [`LayoutRequest::labels`].
+    pub source_index: usize,
+    /// Top of the label, measured down from the top of the label block.
+    pub top_pt: f64,
+    /// Vertical position of the label's underline.
+    pub underline_y_pt: f64,
+    /// Visible parts of the leader line from the underline to the track.
+    pub leader_segments: Vec<LeaderSegment>,
 }
 
+/// A visible piece of a label's leader line.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct LeaderSegment {
-    top_pt: f64,
-    length_pt: f64,
+pub struct LeaderSegment {
+    /// Top of the segment, measured down from the top of the label block.
+    pub top_pt: f64,
+    /// Length of the segment.
+    pub length_pt: f64,
 }
 
 /// Internal working struct that carries packing-relevant fields through both
@@ -305,7 +344,9 @@ fn precompute_level_geometries(layout: &LayoutGeometry, level_count: usize) -> V
     geometries
 }
 
-pub(crate) fn compute_layout(request: &LayoutRequest) -> Result<LayoutResponse, String> {
+/// Packs labels into levels, computes their vertical positions, and routes
+/// leader lines around labels on lower levels.
+pub fn compute_layout(request: &LayoutRequest) -> Result<LayoutResponse, String> {
     validate_layout_request(request)?;
 
     if request.labels.is_empty() {
@@ -1,12 +1,23 @@
 //! genome-map: Genome map layout and GFF3 parsing WASM plugin
+//!
+//! The crate also builds as a native library: [`parse_gff_features`] and
+//! [`compute_layout`] are the typed functions behind the WASM entry points.
+
+#![cfg_attr(not(target_arch = "wasm32"), warn(missing_docs))]
 
 mod gff;
 mod layout;
 
-#[cfg(target_arch = "wasm32")]
+pub use gff::{GenomeMapFeature, ParseGffConfig, RangeFilter, StrandFilter, parse_gff_features};
+pub use layout::{
+    LayoutRequest, LayoutResponse, LeaderSegment, MeasuredLabel, PositionedLabel, compute_layout,
+};
+pub use plugin_io::{WireFormat, decompress, is_gzip};
+
+#[cfg(all(target_arch = "wasm32", feature = "plugin"))]
 use wasm_minimal_protocol::*;
 
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", feature = "plugin"))]
 initiate_protocol!();
 
 #[cfg(target_arch = "wasm32")]
@@ -17,28 +28,34 @@ fn unsupported_getrandom(_: &mut [u8]) -> Result<(), getrandom::Error> {
 #[cfg(target_arch = "wasm32")]
 getrandom::register_custom_getrandom!(unsupported_getrandom);
 
-#[cfg_attr(target_arch = "wasm32", wasm_func)]
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
 /// Full label layout pipeline: packing, vertical geometry, and leader routing.
 ///
 /// Typst sends measured label geometry. Rust sorts by packing key, assigns
 /// dodge levels with first-fit, computes vertical positions, routes leader
-/// segments, and returns positioned labels.
+/// segments, and returns positioned labels. The request may be JSON or CBOR;
+/// the response uses the same encoding.
 pub fn layout_labels(config: &[u8]) -> Result<Vec<u8>, String> {
-    let request: layout::LayoutRequest =
-        serde_json::from_slice(config).map_err(|e| format!("Invalid config JSON: {e}"))?;
-    let response = layout::compute_layout(&request)?;
-    serde_json::to_vec(&response).map_err(|e| format!("Serialization failed: {e}"))
+    plugin_io::handle(config, "config", |request: LayoutRequest| {
+        compute_layout(&request)
+    })
 }
 
-#[cfg_attr(target_arch = "wasm32", wasm_func)]
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
 /// Parses GFF3 feature data into genome-map feature dictionaries.
 ///
 /// # Arguments
-/// * `data` - GFF3 source as UTF-8 bytes
-/// * `config` - JSON-encoded parser filter configuration
+/// * `data` - GFF3 source as UTF-8 bytes, optionally gzip- or BGZF-compressed
+/// * `config` - JSON- or CBOR-encoded parser filter configuration
 ///
 /// # Returns
-/// JSON bytes of genome-map-compatible feature dictionaries or an error string.
+/// Genome-map-compatible feature dictionaries, encoded like `config`, or an
+/// error string.
 pub fn parse_gff(data: &[u8], config: &[u8]) -> Result<Vec<u8>, String> {
-    gff::parse_gff(data, config)
+    let data = decompress(data)?;
+    let data =
+        std::str::from_utf8(&data).map_err(|e| format!("Invalid UTF-8 in GFF3 data: {e}"))?;
+    plugin_io::handle(config, "config", |config: ParseGffConfig| {
+        parse_gff_features(data, &config)
+    })
 }
@@ -0,0 +1,66 @@
+//! Transparent decompression of plugin inputs.
+//!
+//! Inputs starting with the gzip magic bytes are decompressed before parsing.
+//! BGZF files are a series of gzip members, so they are handled the same way.
+
+use flate2::read::MultiGzDecoder;
+use std::borrow::Cow;
+use std::io::Read;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Returns whether `data` starts with the gzip magic bytes.
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&GZIP_MAGIC)
+}
+
+/// Decompresses gzip or BGZF input and passes any other input through.
+pub fn decompress(data: &[u8]) -> Result<Cow<'_, [u8]>, String> {
+    if !is_gzip(data) {
+        return Ok(Cow::Borrowed(data));
+    }
+    let mut decompressed = Vec::new();
+    MultiGzDecoder::new(data)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| format!("Failed to decompress gzip input: {e}"))?;
+    Ok(Cow::Owned(decompressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_plain_input_is_borrowed() {
+        let data = b"plain text\n";
+        assert!(matches!(decompress(data).unwrap(), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_concatenated_members_decompress_like_bgzf() {
+        let mut data = gzip(b"first block\n");
+        data.extend(gzip(b"second block\n"));
+        data.extend(gzip(b""));
+
+        assert_eq!(
+            decompress(&data).unwrap().as_ref(),
+            b"first block\nsecond block\n"
+        );
+    }
+
+    #[test]
+    fn test_truncated_gzip_is_an_error() {
+        let data = gzip(b"some text that will be cut short");
+        let error = decompress(&data[..data.len() / 2]).unwrap_err();
+        assert!(error.starts_with("Failed to decompress gzip input"));
+    }
+}
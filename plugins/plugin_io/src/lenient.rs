@@ -0,0 +1,127 @@
+//! Float deserializers that also accept integers.
+//!
+//! JSON has a single number type, but CBOR encodes `2` and `2.0` differently
+//! and `ciborium` rejects an integer where a float is expected. Typst encodes
+//! whole numbers written without a decimal point as integers, so every float
+//! field of a request uses one of these functions through
+//! `#[serde(deserialize_with = "...")]`. Optional fields also need
+//! `#[serde(default)]`.
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+
+/// A number decoded from either an integer or a float.
+struct Float(f64);
+
+impl<'de> Deserialize<'de> for Float {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FloatVisitor;
+
+        impl Visitor<'_> for FloatVisitor {
+            type Value = Float;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a number")
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Float, E> {
+                Ok(Float(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Float, E> {
+                Ok(Float(value as f64))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Float, E> {
+                Ok(Float(value as f64))
+            }
+
+            fn visit_i128<E: de::Error>(self, value: i128) -> Result<Float, E> {
+                Ok(Float(value as f64))
+            }
+
+            fn visit_u128<E: de::Error>(self, value: u128) -> Result<Float, E> {
+                Ok(Float(value as f64))
+            }
+        }
+
+        deserializer.deserialize_any(FloatVisitor)
+    }
+}
+
+fn floats(values: Vec<Float>) -> Vec<f64> {
+    values.into_iter().map(|Float(value)| value).collect()
+}
+
+/// Deserializes an `f64`.
+pub fn float<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Float::deserialize(deserializer).map(|Float(value)| value)
+}
+
+/// Deserializes an `Option<f64>`.
+pub fn option_float<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    Option::<Float>::deserialize(deserializer).map(|value| value.map(|Float(value)| value))
+}
+
+/// Deserializes a `Vec<f64>`.
+pub fn float_vec<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<f64>, D::Error> {
+    Vec::<Float>::deserialize(deserializer).map(floats)
+}
+
+/// Deserializes an `Option<Vec<f64>>`.
+pub fn option_float_vec<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<f64>>, D::Error> {
+    Option::<Vec<Float>>::deserialize(deserializer).map(|values| values.map(floats))
+}
+
+/// Deserializes a `Vec<Vec<f64>>`, such as a matrix given row by row.
+pub fn float_matrix<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Vec<f64>>, D::Error> {
+    Vec::<Vec<Float>>::deserialize(deserializer).map(|rows| rows.into_iter().map(floats).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use crate::WireFormat;
+
+    #[derive(Debug, Deserialize)]
+    struct Request {
+        #[serde(deserialize_with = "super::float")]
+        scale: f64,
+        #[serde(default, deserialize_with = "super::option_float")]
+        threshold: Option<f64>,
+        #[serde(default, deserialize_with = "super::option_float")]
+        missing: Option<f64>,
+        #[serde(deserialize_with = "super::float_matrix")]
+        scores: Vec<Vec<f64>>,
+    }
+
+    #[test]
+    fn test_integers_and_floats_decode_in_both_formats() {
+        let value = serde_json::json!({ "scale": 2, "threshold": 0.5, "scores": [[1, -2.5]] });
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&value, &mut cbor).unwrap();
+
+        for (format, payload) in [
+            (WireFormat::Json, value.to_string().into_bytes()),
+            (WireFormat::Cbor, cbor),
+        ] {
+            let request: Request = format.decode(&payload).unwrap();
+            assert_eq!(request.scale, 2.0);
+            assert_eq!(request.threshold, Some(0.5));
+            assert_eq!(request.missing, None);
+            assert_eq!(request.scores, [[1.0, -2.5]]);
+        }
+    }
+
+    #[test]
+    fn test_non_numbers_are_rejected() {
+        let error = WireFormat::Json
+            .decode::<Request>(br#"{"scale": "2", "scores": []}"#)
+            .unwrap_err();
+        assert!(error.contains("expected a number"));
+    }
+}
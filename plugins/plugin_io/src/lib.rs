@@ -0,0 +1,17 @@
+//! Input and output helpers shared by the genotypst plugins.
+//!
+//! [`WireFormat`] decodes JSON or CBOR requests and encodes responses in the
+//! same format, [`handle`] wraps a WASM entry point around that round trip,
+//! [`lenient`] lets float fields accept integers in either format, and with
+//! the `gzip` feature [`decompress`] unpacks gzip and BGZF inputs.
+
+#![warn(missing_docs)]
+
+#[cfg(feature = "gzip")]
+mod compression;
+pub mod lenient;
+mod wire;
+
+#[cfg(feature = "gzip")]
+pub use compression::{decompress, is_gzip};
+pub use wire::{WireFormat, handle};
@@ -0,0 +1,147 @@
+//! Request and response encodings shared by the WASM entry points.
+//!
+//! Requests may be JSON or CBOR. The encoding is detected from the first
+//! byte and the response is written in the same encoding. The Typst backends
+//! send CBOR, which avoids building large JSON strings on either side.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Encoding of a plugin request or response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireFormat {
+    /// UTF-8 JSON text.
+    Json,
+    /// Binary CBOR (RFC 8949).
+    Cbor,
+}
+
+impl WireFormat {
+    /// Detects the encoding of a request payload.
+    ///
+    /// Requests are always maps, which CBOR encodes with major type 5
+    /// (`0xa0..=0xbf`). No JSON document can start with one of those bytes.
+    pub fn detect(payload: &[u8]) -> Self {
+        match payload.first() {
+            Some(0xa0..=0xbf) => Self::Cbor,
+            _ => Self::Json,
+        }
+    }
+
+    /// Name of the encoding for error messages.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Cbor => "CBOR",
+        }
+    }
+
+    /// Decodes a request payload.
+    ///
+    /// CBOR keeps integers and floats apart, so float fields that should also
+    /// accept integers need one of the [`crate::lenient`] deserializers.
+    pub fn decode<T: DeserializeOwned>(self, payload: &[u8]) -> Result<T, String> {
+        match self {
+            Self::Json => serde_json::from_slice(payload).map_err(|e| e.to_string()),
+            Self::Cbor => ciborium::from_reader(payload).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Encodes a response payload.
+    pub fn encode<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            Self::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(value, &mut bytes).map_err(|e| e.to_string())?;
+                Ok(bytes)
+            }
+        }
+    }
+}
+
+/// Decodes a request, runs `handler` on it, and encodes the response in the
+/// request's encoding.
+///
+/// `what` names the payload in decode errors, as in
+/// `"Invalid fit config CBOR: ..."`.
+pub fn handle<Req, Resp>(
+    payload: &[u8],
+    what: &str,
+    handler: impl FnOnce(Req) -> Result<Resp, String>,
+) -> Result<Vec<u8>, String>
+where
+    Req: DeserializeOwned,
+    Resp: Serialize,
+{
+    let wire = WireFormat::detect(payload);
+    let request = wire
+        .decode(payload)
+        .map_err(|e| format!("Invalid {what} {}: {e}", wire.name()))?;
+    let response = handler(request)?;
+    wire.encode(&response)
+        .map_err(|e| format!("Serialization failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Payload {
+        name: String,
+        #[serde(deserialize_with = "crate::lenient::float")]
+        size: f64,
+    }
+
+    #[test]
+    fn test_detects_json_and_cbor_maps() {
+        let payload = Payload {
+            name: "a".into(),
+            size: 1.5,
+        };
+        let json = WireFormat::Json.encode(&payload).unwrap();
+        let cbor = WireFormat::Cbor.encode(&payload).unwrap();
+
+        assert_eq!(WireFormat::detect(&json), WireFormat::Json);
+        assert_eq!(WireFormat::detect(b"  {}"), WireFormat::Json);
+        assert_eq!(WireFormat::detect(&cbor), WireFormat::Cbor);
+        assert_eq!(WireFormat::Cbor.decode::<Payload>(&cbor).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_cbor_integer_decodes_as_float() {
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&serde_json::json!({ "name": "a", "size": 2 }), &mut cbor).unwrap();
+
+        let payload: Payload = WireFormat::Cbor.decode(&cbor).unwrap();
+        assert_eq!(payload.size, 2.0);
+    }
+
+    #[test]
+    fn test_handle_answers_in_the_request_encoding() {
+        let double = |payload: Payload| {
+            Ok(Payload {
+                size: payload.size * 2.0,
+                ..payload
+            })
+        };
+        let request = Payload {
+            name: "a".into(),
+            size: 1.5,
+        };
+
+        let cbor = handle(
+            &WireFormat::Cbor.encode(&request).unwrap(),
+            "config",
+            double,
+        )
+        .unwrap();
+        let response: Payload = WireFormat::Cbor.decode(&cbor).unwrap();
+        assert_eq!(response.size, 3.0);
+
+        let error = handle(b"{}", "config", double).unwrap_err();
+        assert!(error.starts_with("Invalid config JSON:"), "{error}");
+    }
+}
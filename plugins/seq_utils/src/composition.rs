@@ -0,0 +1,339 @@
+//! Sliding-window nucleotide composition.
+//!
+//! Windows are reported in 1-based, inclusive coordinates like GFF3
+//! features. Only `A`, `C`, `G` and `T`/`U` are counted, except that `S`
+//! (G or C) and `W` (A or T) still count towards GC content. Linguistic
+//! complexity follows Troyanskaya et al. (2002): the number of distinct
+//! substrings of every length in the window, over the largest number
+//! possible for a window of that length over four letters.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+fn default_k() -> usize {
+    3
+}
+
+/// Request for [`compute_composition`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompositionRequest {
+    /// Nucleotide sequence to scan.
+    pub sequence: String,
+    /// Window length in bases.
+    pub window: usize,
+    /// Distance between window starts.
+    pub step: usize,
+    /// K-mer length for `kmer_entropy`.
+    #[serde(default = "default_k")]
+    pub k: usize,
+}
+
+/// Composition of one window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompositionWindow {
+    /// First position of the window.
+    pub start: u64,
+    /// Last position of the window; the last window may be shorter.
+    pub end: u64,
+    /// `(G + C) / (A + C + G + T)`, or `None` without counted bases.
+    pub gc_content: Option<f64>,
+    /// `(G - C) / (G + C)`, or `None` without G or C.
+    pub gc_skew: Option<f64>,
+    /// Sum of `gc_skew` over this and all previous windows.
+    pub cumulative_gc_skew: f64,
+    /// Between 0 and 1, or `None` without counted bases.
+    pub linguistic_complexity: Option<f64>,
+    /// Shannon entropy of the window's k-mers in bits, or `None` without a
+    /// k-mer of unambiguous bases.
+    pub kmer_entropy: Option<f64>,
+}
+
+/// Uppercase base code, with `U` read as `T`; `None` for anything but ACGT.
+fn canonical_base(base: u8) -> Option<u8> {
+    match base.to_ascii_uppercase() {
+        b'U' => Some(b'T'),
+        base @ (b'A' | b'C' | b'G' | b'T') => Some(base),
+        _ => None,
+    }
+}
+
+fn gc_stats(window: &[u8]) -> (Option<f64>, Option<f64>) {
+    let (mut g, mut c, mut strong, mut weak) = (0usize, 0usize, 0usize, 0usize);
+    for &base in window {
+        match base.to_ascii_uppercase() {
+            b'G' => g += 1,
+            b'C' => c += 1,
+            b'S' => strong += 1,
+            b'A' | b'T' | b'U' | b'W' => weak += 1,
+            _ => {}
+        }
+    }
+    let gc = g + c + strong;
+    let content = (gc + weak > 0).then(|| gc as f64 / (gc + weak) as f64);
+    let skew = (g + c > 0).then(|| (g as f64 - c as f64) / (g + c) as f64);
+    (content, skew)
+}
+
+/// Suffix automaton over several strings, one state per class of substrings
+/// with the same end positions.
+struct SuffixAutomaton {
+    len: Vec<usize>,
+    link: Vec<Option<usize>>,
+    next: Vec<[Option<usize>; 4]>,
+}
+
+impl SuffixAutomaton {
+    fn new() -> Self {
+        Self {
+            len: vec![0],
+            link: vec![None],
+            next: vec![[None; 4]],
+        }
+    }
+
+    fn add_state(&mut self, len: usize, link: Option<usize>, next: [Option<usize>; 4]) -> usize {
+        self.len.push(len);
+        self.link.push(link);
+        self.next.push(next);
+        self.len.len() - 1
+    }
+
+    /// Splits `q` so that the state reached from `p` by `c` has length
+    /// `len[p] + 1`, and returns that state.
+    fn split(&mut self, p: usize, c: usize, q: usize) -> usize {
+        if self.len[q] == self.len[p] + 1 {
+            return q;
+        }
+        let clone = self.add_state(self.len[p] + 1, self.link[q], self.next[q]);
+        let mut state = Some(p);
+        while let Some(s) = state.filter(|&s| self.next[s][c] == Some(q)) {
+            self.next[s][c] = Some(clone);
+            state = self.link[s];
+        }
+        self.link[q] = Some(clone);
+        clone
+    }
+
+    /// Appends base `c` to the string ending in state `last`.
+    fn extend(&mut self, last: usize, c: usize) -> usize {
+        if let Some(q) = self.next[last][c] {
+            return self.split(last, c, q);
+        }
+        let current = self.add_state(self.len[last] + 1, None, [None; 4]);
+        let mut state = Some(last);
+        while let Some(s) = state.filter(|&s| self.next[s][c].is_none()) {
+            self.next[s][c] = Some(current);
+            state = self.link[s];
+        }
+        self.link[current] = Some(match state {
+            None => 0,
+            Some(p) => {
+                let q = self.next[p][c].expect("transition set by the loop condition");
+                self.split(p, c, q)
+            }
+        });
+        current
+    }
+
+    /// Number of distinct non-empty substrings of all added strings.
+    fn distinct_substrings(&self) -> usize {
+        (1..self.len.len())
+            .map(|s| self.len[s] - self.link[s].map_or(0, |link| self.len[link]))
+            .sum()
+    }
+}
+
+/// Number of distinct substrings of `window` made only of ACGT, with `U`
+/// read as `T`.
+///
+/// The runs between other characters go into one suffix automaton, so this
+/// takes time linear in the window length.
+fn distinct_substrings(window: &[u8]) -> usize {
+    let mut automaton = SuffixAutomaton::new();
+    let mut last = 0;
+    for &base in window {
+        last = match canonical_base(base) {
+            Some(base) => {
+                let c = match base {
+                    b'A' => 0,
+                    b'C' => 1,
+                    b'G' => 2,
+                    _ => 3,
+                };
+                automaton.extend(last, c)
+            }
+            None => 0,
+        };
+    }
+    automaton.distinct_substrings()
+}
+
+/// Ratio of distinct substrings to the most a window of this length could
+/// hold. Substrings with other characters than ACGT are not counted.
+fn linguistic_complexity(window: &[u8]) -> Option<f64> {
+    let observed = distinct_substrings(window);
+    if observed == 0 {
+        return None;
+    }
+
+    let length = window.len();
+    let possible: usize = (1..=length)
+        .map(|k| {
+            let positions = length - k + 1;
+            match 4usize.checked_pow(k as u32) {
+                Some(kmers) => kmers.min(positions),
+                None => positions,
+            }
+        })
+        .sum();
+    Some(observed as f64 / possible as f64)
+}
+
+fn kmer_entropy(window: &[u8], k: usize) -> Option<f64> {
+    let mut counts: HashMap<Vec<u8>, usize> = HashMap::new();
+    let mut total = 0usize;
+    for kmer in window.windows(k) {
+        let Some(kmer) = kmer
+            .iter()
+            .map(|&b| canonical_base(b))
+            .collect::<Option<Vec<u8>>>()
+        else {
+            continue;
+        };
+        *counts.entry(kmer).or_default() += 1;
+        total += 1;
+    }
+    (total > 0).then(|| {
+        counts
+            .values()
+            .map(|&count| {
+                let p = count as f64 / total as f64;
+                -p * p.log2()
+            })
+            .sum()
+    })
+}
+
+/// Computes composition statistics in sliding windows.
+///
+/// Windows start every `step` bases and are `window` bases long; bases after
+/// the last full window are not covered. A sequence shorter than `window`
+/// gives a single window over the whole sequence.
+pub fn compute_composition(request: &CompositionRequest) -> Result<Vec<CompositionWindow>, String> {
+    if request.window == 0 {
+        return Err("window must be at least 1".to_string());
+    }
+    if request.step == 0 {
+        return Err("step must be at least 1".to_string());
+    }
+    if request.k == 0 {
+        return Err("k must be at least 1".to_string());
+    }
+    let sequence = request.sequence.as_bytes();
+    if sequence.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let window = request.window.min(sequence.len());
+    let mut cumulative = 0.0;
+    let mut windows = Vec::new();
+    let mut start = 0;
+    while start + window <= sequence.len() {
+        let bases = &sequence[start..start + window];
+        let (gc_content, gc_skew) = gc_stats(bases);
+        cumulative += gc_skew.unwrap_or(0.0);
+        windows.push(CompositionWindow {
+            start: start as u64 + 1,
+            end: (start + window) as u64,
+            gc_content,
+            gc_skew,
+            cumulative_gc_skew: cumulative,
+            linguistic_complexity: linguistic_complexity(bases),
+            kmer_entropy: kmer_entropy(bases, request.k),
+        });
+        start += request.step;
+    }
+    Ok(windows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn request(sequence: &str, window: usize, step: usize) -> CompositionRequest {
+        CompositionRequest {
+            sequence: sequence.to_string(),
+            window,
+            step,
+            k: 1,
+        }
+    }
+
+    #[test]
+    fn test_windows_gc_and_skew() {
+        let windows = compute_composition(&request("GGGCAATTNNCC", 4, 4)).unwrap();
+        let coordinates: Vec<(u64, u64)> = windows.iter().map(|w| (w.start, w.end)).collect();
+        assert_eq!(coordinates, [(1, 4), (5, 8), (9, 12)]);
+        assert_eq!(windows[0].gc_content, Some(1.0));
+        assert_eq!(windows[0].gc_skew, Some(0.5));
+        assert_eq!(windows[1].gc_content, Some(0.0));
+        assert_eq!(windows[1].gc_skew, None);
+        assert_eq!(windows[2].gc_content, Some(1.0));
+        assert_eq!(windows[2].gc_skew, Some(-1.0));
+        assert_eq!(windows[2].cumulative_gc_skew, -0.5);
+
+        let short = compute_composition(&request("ACG", 10, 5)).unwrap();
+        assert_eq!((short.len(), short[0].end), (1, 3));
+        assert!(compute_composition(&request("ACG", 10, 0)).is_err());
+    }
+
+    #[test]
+    fn test_linguistic_complexity() {
+        // All 4 letters, 3 of 3 dinucleotides, 2 of 2 trinucleotides, ...
+        assert_eq!(linguistic_complexity(b"ACGT"), Some(1.0));
+        // A homopolymer has one substring of each length: 4 of 4+3+2+1.
+        assert_eq!(linguistic_complexity(b"AAAA"), Some(0.4));
+        // N splits the window: A, C, AC out of 4+3+2+1.
+        assert_eq!(linguistic_complexity(b"ACNN"), Some(0.3));
+        assert_eq!(linguistic_complexity(b"NN"), None);
+    }
+
+    #[test]
+    fn test_linguistic_complexity_counts_every_distinct_substring() {
+        // Every window of up to 6 characters from A, C, G, U and N.
+        let alphabet = b"ACGUN";
+        for length in 1..=6u32 {
+            for code in 0..alphabet.len().pow(length) {
+                let window: Vec<u8> = (0..length)
+                    .map(|i| alphabet[code / alphabet.len().pow(i) % alphabet.len()])
+                    .collect();
+                let bases: Vec<Option<u8>> = window.iter().map(|&b| canonical_base(b)).collect();
+                let mut substrings = HashSet::new();
+                for start in 0..bases.len() {
+                    for end in start + 1..=bases.len() {
+                        if let Some(substring) = bases[start..end]
+                            .iter()
+                            .copied()
+                            .collect::<Option<Vec<u8>>>()
+                        {
+                            substrings.insert(substring);
+                        }
+                    }
+                }
+                assert_eq!(distinct_substrings(&window), substrings.len(), "{window:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_kmer_entropy() {
+        assert_eq!(kmer_entropy(b"ACGT", 1), Some(2.0));
+        assert_eq!(kmer_entropy(b"AAAA", 2), Some(0.0));
+        let expected =
+            -(2.0 / 3.0f64) * (2.0 / 3.0f64).log2() - (1.0 / 3.0f64) * (1.0 / 3.0f64).log2();
+        assert!((kmer_entropy(b"ACAC", 2).unwrap() - expected).abs() < 1e-12);
+        assert_eq!(kmer_entropy(b"ANA", 2), None);
+    }
+}
@@ -0,0 +1,346 @@
+//! NCBI genetic code tables and codon lookup.
+//!
+//! Tables follow NCBI's `gc.prt`: amino acids and start codons are listed
+//! for the 64 codons in `TCAG` order of the first, second and third base.
+//! Codons with IUPAC ambiguity codes are expanded and translate to a single
+//! amino acid only when every expansion agrees.
+
+/// One NCBI translation table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeneticCode {
+    /// NCBI table id, such as 1 for the standard code.
+    pub id: u8,
+    /// NCBI table name.
+    pub name: &'static str,
+    /// Amino acid per codon, `*` for stop.
+    pub amino_acids: &'static str,
+    /// `M` for codons that can initiate translation, `-` otherwise.
+    pub starts: &'static str,
+}
+
+/// All NCBI translation tables, by ascending id.
+pub const GENETIC_CODES: &[GeneticCode] = &[
+    GeneticCode {
+        id: 1,
+        name: "Standard",
+        amino_acids: "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "---M---------------M---------------M----------------------------",
+    },
+    GeneticCode {
+        id: 2,
+        name: "Vertebrate Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+        starts: "--------------------------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 3,
+        name: "Yeast Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "----------------------------------MM---------------M------------",
+    },
+    GeneticCode {
+        id: 4,
+        name: "Mold, Protozoan, and Coelenterate Mitochondrial and Mycoplasma/Spiroplasma",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "--MM---------------M------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 5,
+        name: "Invertebrate Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+        starts: "---M----------------------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 6,
+        name: "Ciliate, Dasycladacean and Hexamita Nuclear",
+        amino_acids: "FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 9,
+        name: "Echinoderm and Flatworm Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 10,
+        name: "Euplotid Nuclear",
+        amino_acids: "FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 11,
+        name: "Bacterial, Archaeal and Plant Plastid",
+        amino_acids: "FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "---M---------------M------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 12,
+        name: "Alternative Yeast Nuclear",
+        amino_acids: "FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-------------------M---------------M----------------------------",
+    },
+    GeneticCode {
+        id: 13,
+        name: "Ascidian Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+        starts: "---M------------------------------MM---------------M------------",
+    },
+    GeneticCode {
+        id: 14,
+        name: "Alternative Flatworm Mitochondrial",
+        amino_acids: "FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 15,
+        name: "Blepharisma Nuclear",
+        amino_acids: "FFLLSSSSYY*QCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 16,
+        name: "Chlorophycean Mitochondrial",
+        amino_acids: "FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 21,
+        name: "Trematode Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 22,
+        name: "Scenedesmus obliquus Mitochondrial",
+        amino_acids: "FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 23,
+        name: "Thraustochytrium Mitochondrial",
+        amino_acids: "FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "--------------------------------M--M---------------M------------",
+    },
+    GeneticCode {
+        id: 24,
+        name: "Rhabdopleuridae Mitochondrial",
+        amino_acids: "FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        starts: "---M---------------M---------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 25,
+        name: "Candidate Division SR1 and Gracilibacteria",
+        amino_acids: "FFLLSSSSYY**CCGWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "---M-------------------------------M---------------M------------",
+    },
+    GeneticCode {
+        id: 26,
+        name: "Pachysolen tannophilus Nuclear",
+        amino_acids: "FFLLSSSSYY**CC*WLLLAPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-------------------M---------------M----------------------------",
+    },
+    GeneticCode {
+        id: 27,
+        name: "Karyorelict Nuclear",
+        amino_acids: "FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 28,
+        name: "Condylostoma Nuclear",
+        amino_acids: "FFLLSSSSYYQQCCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 29,
+        name: "Mesodinium Nuclear",
+        amino_acids: "FFLLSSSSYYYYCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 30,
+        name: "Peritrich Nuclear",
+        amino_acids: "FFLLSSSSYYEECC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 31,
+        name: "Blastocrithidia Nuclear",
+        amino_acids: "FFLLSSSSYYEECCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "-----------------------------------M----------------------------",
+    },
+    GeneticCode {
+        id: 32,
+        name: "Balanophoraceae Plastid",
+        amino_acids: "FFLLSSSSYY*WCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: "---M---------------M------------MMMM---------------M------------",
+    },
+    GeneticCode {
+        id: 33,
+        name: "Cephalodiscidae Mitochondrial",
+        amino_acids: "FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        starts: "---M---------------M---------------M---------------M------------",
+    },
+];
+
+/// Looks up a translation table by its NCBI id.
+pub fn genetic_code(id: u8) -> Result<&'static GeneticCode, String> {
+    GENETIC_CODES
+        .iter()
+        .find(|code| code.id == id)
+        .ok_or_else(|| format!("unknown genetic code {id}"))
+}
+
+/// Bases matched by a nucleotide code, as bits in `TCAG` order.
+fn base_mask(base: u8) -> Option<u8> {
+    const T: u8 = 1;
+    const C: u8 = 2;
+    const A: u8 = 4;
+    const G: u8 = 8;
+    Some(match base.to_ascii_uppercase() {
+        b'T' | b'U' => T,
+        b'C' => C,
+        b'A' => A,
+        b'G' => G,
+        b'R' => A | G,
+        b'Y' => C | T,
+        b'S' => C | G,
+        b'W' => A | T,
+        b'K' => G | T,
+        b'M' => A | C,
+        b'B' => C | G | T,
+        b'D' => A | G | T,
+        b'H' => A | C | T,
+        b'V' => A | C | G,
+        b'N' => A | C | G | T,
+        _ => return None,
+    })
+}
+
+/// Table indices of every codon a possibly ambiguous codon stands for.
+fn codon_indices(codon: &[u8]) -> Option<Vec<usize>> {
+    let [first, second, third] = codon else {
+        return None;
+    };
+    let masks = [base_mask(*first)?, base_mask(*second)?, base_mask(*third)?];
+    let mut indices = vec![0usize];
+    for mask in masks {
+        indices = indices
+            .iter()
+            .flat_map(|index| {
+                (0..4)
+                    .filter(move |bit| mask & (1 << bit) != 0)
+                    .map(move |bit| index * 4 + bit)
+            })
+            .collect();
+    }
+    Some(indices)
+}
+
+impl GeneticCode {
+    /// Translates one codon. Ambiguous codons give `X` unless all their
+    /// expansions agree; codons with other characters give `X`.
+    pub fn translate(&self, codon: &[u8]) -> u8 {
+        let amino_acids = self.amino_acids.as_bytes();
+        let Some(indices) = codon_indices(codon) else {
+            return b'X';
+        };
+        let first = amino_acids[indices[0]];
+        if indices.iter().all(|&index| amino_acids[index] == first) {
+            first
+        } else {
+            b'X'
+        }
+    }
+
+    /// Whether every expansion of `codon` is a start codon.
+    pub fn is_start(&self, codon: &[u8]) -> bool {
+        let starts = self.starts.as_bytes();
+        codon_indices(codon).is_some_and(|indices| indices.iter().all(|&i| starts[i] == b'M'))
+    }
+
+    /// Whether every expansion of `codon` is a stop codon.
+    pub fn is_stop(&self, codon: &[u8]) -> bool {
+        self.translate(codon) == b'*'
+    }
+}
+
+/// Complement of an IUPAC nucleotide code, keeping case. `U` complements to
+/// `A` and `A` to `T`; other characters are returned unchanged.
+pub fn complement(base: u8) -> u8 {
+    let complemented = match base.to_ascii_uppercase() {
+        b'A' => b'T',
+        b'T' | b'U' => b'A',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        other => other,
+    };
+    if base.is_ascii_lowercase() {
+        complemented.to_ascii_lowercase()
+    } else {
+        complemented
+    }
+}
+
+/// Reverse complement of a nucleotide sequence.
+pub fn reverse_complement(sequence: &[u8]) -> Vec<u8> {
+    sequence
+        .iter()
+        .rev()
+        .map(|&base| complement(base))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables_are_complete() {
+        let ids: Vec<u8> = GENETIC_CODES.iter().map(|code| code.id).collect();
+        let expected: Vec<u8> = (1..=33)
+            .filter(|id| !matches!(id, 7 | 8 | 17..=20))
+            .collect();
+        assert_eq!(ids, expected);
+        for code in GENETIC_CODES {
+            assert_eq!(code.amino_acids.len(), 64, "table {}", code.id);
+            assert_eq!(code.starts.len(), 64, "table {}", code.id);
+            assert!(code.is_start(b"ATG"), "table {}", code.id);
+        }
+        assert!(genetic_code(7).is_err());
+    }
+
+    #[test]
+    fn test_codon_lookup() {
+        let standard = genetic_code(1).unwrap();
+        assert_eq!(standard.translate(b"ATG"), b'M');
+        assert_eq!(standard.translate(b"aug"), b'M');
+        assert!(standard.is_stop(b"TGA"));
+        assert!(standard.is_start(b"TTG"));
+        assert!(!standard.is_start(b"GTG"));
+        // Fourfold-degenerate site and a two-fold ambiguity.
+        assert_eq!(standard.translate(b"GCN"), b'A');
+        assert_eq!(standard.translate(b"TAR"), b'*');
+        assert_eq!(standard.translate(b"ATN"), b'X');
+        assert_eq!(standard.translate(b"A-G"), b'X');
+
+        let mito = genetic_code(2).unwrap();
+        assert_eq!(mito.translate(b"TGA"), b'W');
+        assert!(mito.is_stop(b"AGA"));
+    }
+
+    #[test]
+    fn test_reverse_complement() {
+        assert_eq!(reverse_complement(b"ACGTRYn-"), b"-nRYACGT");
+        assert_eq!(reverse_complement(b"AUG"), b"CAT");
+    }
+}
@@ -0,0 +1,68 @@
+//! seq-utils: Nucleotide sequence utilities WASM plugin
+//!
+//! The crate also builds as a native library: [`translate`],
+//! [`translate_six_frames`], [`find_orfs`] and [`compute_composition`] are
+//! the typed functions behind the WASM entry points.
+
+#![cfg_attr(not(target_arch = "wasm32"), warn(missing_docs))]
+
+mod composition;
+mod genetic_code;
+mod translation;
+
+pub use composition::{CompositionRequest, CompositionWindow, compute_composition};
+pub use genetic_code::{GENETIC_CODES, GeneticCode, complement, genetic_code, reverse_complement};
+pub use plugin_io::WireFormat;
+pub use translation::{
+    FindOrfsRequest, FrameTranslation, OrfStart, OrfStrand, SixFrameRequest, StopHandling,
+    TranslateRequest, find_orfs, translate, translate_six_frames,
+};
+
+#[cfg(all(target_arch = "wasm32", feature = "plugin"))]
+use wasm_minimal_protocol::*;
+
+#[cfg(all(target_arch = "wasm32", feature = "plugin"))]
+initiate_protocol!();
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Translates one frame of a nucleotide sequence with an NCBI genetic code.
+///
+/// The request may be JSON or CBOR; the response (the protein string) uses
+/// the same encoding.
+pub fn translate_sequence(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: TranslateRequest| {
+        translate(&request)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Translates all six reading frames of a nucleotide sequence.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn six_frame_translation(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: SixFrameRequest| {
+        translate_six_frames(&request)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Finds open reading frames and returns them as genome-map feature
+/// dictionaries.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn orfs(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: FindOrfsRequest| {
+        find_orfs(&request)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Computes GC content, GC skew, cumulative GC skew, linguistic complexity
+/// and k-mer entropy in sliding windows, in 1-based coordinates.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn composition_track(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: CompositionRequest| {
+        compute_composition(&request)
+    })
+}
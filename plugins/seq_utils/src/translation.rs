@@ -0,0 +1,447 @@
+//! Translation, six-frame translation and open reading frame detection.
+//!
+//! Frames are numbered like EMBOSS: `+1`..`+3` start at the first, second
+//! and third base of the sequence, `-1`..`-3` at the first, second and third
+//! base of its reverse complement. Trailing bases that do not fill a codon
+//! are not translated. ORFs are reported as genome-map features in 1-based,
+//! inclusive forward-strand coordinates.
+
+use std::collections::BTreeMap;
+
+use genome_map::GenomeMapFeature;
+use serde::{Deserialize, Serialize};
+
+use crate::genetic_code::{GeneticCode, genetic_code, reverse_complement};
+
+fn default_table() -> u8 {
+    1
+}
+
+/// What to do with stop codons during translation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StopHandling {
+    /// Emit `*`.
+    #[default]
+    Keep,
+    /// End the protein before the first stop codon.
+    Truncate,
+    /// Leave stop codons out.
+    Remove,
+}
+
+/// Request for [`translate`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranslateRequest {
+    /// Nucleotide sequence to translate.
+    pub sequence: String,
+    /// NCBI translation table id.
+    #[serde(default = "default_table")]
+    pub table: u8,
+    /// Frame `+1`..`+3` or `-1`..`-3`.
+    #[serde(default = "default_frame")]
+    pub frame: i8,
+    /// Translates a first codon that is a start codon of the table (for
+    /// example `TTG` or `GTG`) as `M`.
+    #[serde(default)]
+    pub initiator: bool,
+    /// What to do with stop codons.
+    #[serde(default)]
+    pub stop: StopHandling,
+}
+
+fn default_frame() -> i8 {
+    1
+}
+
+/// Request for [`translate_six_frames`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct SixFrameRequest {
+    /// Nucleotide sequence to translate.
+    pub sequence: String,
+    /// NCBI translation table id.
+    #[serde(default = "default_table")]
+    pub table: u8,
+}
+
+/// Translation of one frame.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrameTranslation {
+    /// Frame `+1`..`+3` or `-1`..`-3`.
+    pub frame: i8,
+    /// Protein sequence of the frame.
+    pub protein: String,
+}
+
+/// Codons that may begin an ORF.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrfStart {
+    /// `ATG` only.
+    #[default]
+    Atg,
+    /// `ATG` and the alternative start codons of the table.
+    Table,
+    /// Any sense codon, giving stop-to-stop ORFs.
+    Any,
+}
+
+/// Strands searched for ORFs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OrfStrand {
+    /// Both strands.
+    #[default]
+    Both,
+    /// The given strand only.
+    Positive,
+    /// The reverse complement only.
+    Negative,
+}
+
+fn default_accession() -> String {
+    "sequence".to_string()
+}
+
+fn default_min_length() -> usize {
+    75
+}
+
+/// Request for [`find_orfs`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct FindOrfsRequest {
+    /// Nucleotide sequence to search.
+    pub sequence: String,
+    /// Accession written into the returned features.
+    #[serde(default = "default_accession")]
+    pub accession: String,
+    /// NCBI translation table id.
+    #[serde(default = "default_table")]
+    pub table: u8,
+    /// Codons that may begin an ORF.
+    #[serde(default)]
+    pub start_codons: OrfStart,
+    /// Shortest ORF in nucleotides, including the stop codon.
+    #[serde(default = "default_min_length")]
+    pub min_length: usize,
+    /// Strands to search.
+    #[serde(default)]
+    pub strand: OrfStrand,
+    /// Also reports ORFs that reach the end of the sequence without a stop
+    /// codon. They are marked `partial`.
+    #[serde(default)]
+    pub partial: bool,
+}
+
+/// Bases of `sequence` read in `frame`, on the strand the frame belongs to.
+fn frame_bases(sequence: &[u8], frame: i8) -> Result<(Vec<u8>, usize), String> {
+    let offset = match frame {
+        1..=3 | -3..=-1 => usize::from(frame.unsigned_abs()) - 1,
+        _ => return Err(format!("frame must be 1, 2, 3, -1, -2 or -3, got {frame}")),
+    };
+    let bases = if frame > 0 {
+        sequence.to_vec()
+    } else {
+        reverse_complement(sequence)
+    };
+    Ok((bases, offset))
+}
+
+fn translate_frame(
+    code: &GeneticCode,
+    bases: &[u8],
+    offset: usize,
+    initiator: bool,
+    stop: StopHandling,
+) -> String {
+    let mut protein = String::with_capacity(bases.len().saturating_sub(offset) / 3);
+    for (index, codon) in bases
+        .get(offset..)
+        .unwrap_or_default()
+        .chunks_exact(3)
+        .enumerate()
+    {
+        let amino_acid = if index == 0 && initiator && code.is_start(codon) {
+            b'M'
+        } else {
+            code.translate(codon)
+        };
+        if amino_acid == b'*' {
+            match stop {
+                StopHandling::Keep => {}
+                StopHandling::Truncate => break,
+                StopHandling::Remove => continue,
+            }
+        }
+        protein.push(char::from(amino_acid));
+    }
+    protein
+}
+
+/// Translates one frame of a nucleotide sequence.
+pub fn translate(request: &TranslateRequest) -> Result<String, String> {
+    let code = genetic_code(request.table)?;
+    let (bases, offset) = frame_bases(request.sequence.as_bytes(), request.frame)?;
+    Ok(translate_frame(
+        code,
+        &bases,
+        offset,
+        request.initiator,
+        request.stop,
+    ))
+}
+
+/// Translates all six frames, `+1`, `+2`, `+3`, `-1`, `-2`, `-3` in order.
+pub fn translate_six_frames(request: &SixFrameRequest) -> Result<Vec<FrameTranslation>, String> {
+    let code = genetic_code(request.table)?;
+    let forward = request.sequence.as_bytes();
+    let reverse = reverse_complement(forward);
+    Ok([1, 2, 3, -1, -2, -3]
+        .into_iter()
+        .map(|frame: i8| {
+            let bases = if frame > 0 { forward } else { &reverse };
+            let offset = usize::from(frame.unsigned_abs()) - 1;
+            FrameTranslation {
+                frame,
+                protein: translate_frame(code, bases, offset, false, StopHandling::Keep),
+            }
+        })
+        .collect())
+}
+
+/// An ORF found on one strand, in 0-based half-open coordinates of the
+/// bases it was read from.
+struct StrandOrf {
+    begin: usize,
+    end: usize,
+    frame: usize,
+    protein: String,
+    partial: bool,
+}
+
+/// Finds the longest ORF ending at each stop codon in the three frames of
+/// `bases`.
+fn strand_orfs(code: &GeneticCode, bases: &[u8], request: &FindOrfsRequest) -> Vec<StrandOrf> {
+    let mut orfs = Vec::new();
+    for frame in 0..3 {
+        let mut open: Option<usize> = None;
+        let mut position = frame;
+        while position + 3 <= bases.len() {
+            let codon = &bases[position..position + 3];
+            if code.is_stop(codon) {
+                if let Some(begin) = open.take() {
+                    orfs.push(StrandOrf {
+                        begin,
+                        end: position + 3,
+                        frame,
+                        protein: String::new(),
+                        partial: false,
+                    });
+                }
+            } else if open.is_none() {
+                let starts = match request.start_codons {
+                    OrfStart::Atg => codon
+                        .iter()
+                        .map(|base| match base.to_ascii_uppercase() {
+                            b'U' => b'T',
+                            base => base,
+                        })
+                        .eq(*b"ATG"),
+                    OrfStart::Table => code.is_start(codon),
+                    OrfStart::Any => true,
+                };
+                if starts {
+                    open = Some(position);
+                }
+            }
+            position += 3;
+        }
+        if let (Some(begin), true) = (open, request.partial) {
+            orfs.push(StrandOrf {
+                begin,
+                end: position,
+                frame,
+                protein: String::new(),
+                partial: true,
+            });
+        }
+    }
+
+    orfs.retain(|orf| orf.end - orf.begin >= request.min_length);
+    for orf in &mut orfs {
+        let initiator = request.start_codons != OrfStart::Any;
+        orf.protein = translate_frame(
+            code,
+            &bases[orf.begin..orf.end],
+            0,
+            initiator,
+            StopHandling::Truncate,
+        );
+    }
+    orfs
+}
+
+/// Finds open reading frames and returns them as genome-map features.
+///
+/// Each stop codon ends at most one ORF, starting at the most upstream start
+/// codon in frame. Features have type `ORF`, the frame and translation as
+/// attributes, and are sorted by start coordinate.
+pub fn find_orfs(request: &FindOrfsRequest) -> Result<Vec<GenomeMapFeature>, String> {
+    let code = genetic_code(request.table)?;
+    let forward = request.sequence.as_bytes();
+    let length = forward.len();
+
+    let mut found: Vec<(u64, u64, i8, StrandOrf)> = Vec::new();
+    if request.strand != OrfStrand::Negative {
+        for orf in strand_orfs(code, forward, request) {
+            found.push((orf.begin as u64 + 1, orf.end as u64, 1, orf));
+        }
+    }
+    if request.strand != OrfStrand::Positive {
+        for orf in strand_orfs(code, &reverse_complement(forward), request) {
+            let start = (length - orf.end) as u64 + 1;
+            let end = (length - orf.begin) as u64;
+            found.push((start, end, -1, orf));
+        }
+    }
+    found.sort_by_key(|&(start, end, strand, _)| (start, end, -strand));
+
+    Ok(found
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start, end, strand, orf))| {
+            let id = format!("ORF{}", index + 1);
+            let frame = if strand > 0 {
+                format!("+{}", orf.frame + 1)
+            } else {
+                format!("-{}", orf.frame + 1)
+            };
+            let attributes = BTreeMap::from([
+                ("ID".to_string(), vec![id.clone()]),
+                ("frame".to_string(), vec![frame]),
+                ("translation".to_string(), vec![orf.protein]),
+            ]);
+            GenomeMapFeature {
+                start,
+                end,
+                strand: Some(strand),
+                label: Some(id),
+                partial: orf.partial,
+                accession: request.accession.clone(),
+                feature_type: "ORF".to_string(),
+                source: "seq-utils".to_string(),
+                score: None,
+                phase: Some(0),
+                attributes,
+                original_start: start,
+                original_end: end,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orf_request(sequence: &str) -> FindOrfsRequest {
+        FindOrfsRequest {
+            sequence: sequence.to_string(),
+            accession: default_accession(),
+            table: 1,
+            start_codons: OrfStart::Atg,
+            min_length: 9,
+            strand: OrfStrand::Both,
+            partial: false,
+        }
+    }
+
+    #[test]
+    fn test_translate_frames_and_stops() {
+        let mut request = TranslateRequest {
+            sequence: "TTGGCCTAAGGGtga".to_string(),
+            table: 1,
+            frame: 1,
+            initiator: false,
+            stop: StopHandling::Keep,
+        };
+        assert_eq!(translate(&request).unwrap(), "LA*G*");
+        request.initiator = true;
+        request.stop = StopHandling::Truncate;
+        assert_eq!(translate(&request).unwrap(), "MA");
+        request.stop = StopHandling::Remove;
+        assert_eq!(translate(&request).unwrap(), "MAG");
+        request.table = 2;
+        request.initiator = false;
+        assert_eq!(translate(&request).unwrap(), "LAGW");
+        request.frame = 4;
+        assert!(translate(&request).is_err());
+    }
+
+    #[test]
+    fn test_six_frames() {
+        let frames = translate_six_frames(&SixFrameRequest {
+            sequence: "ATGGCCTGA".to_string(),
+            table: 1,
+        })
+        .unwrap();
+        let proteins: Vec<(i8, &str)> = frames
+            .iter()
+            .map(|frame| (frame.frame, frame.protein.as_str()))
+            .collect();
+        // Reverse complement: TCAGGCCAT.
+        assert_eq!(
+            proteins,
+            [
+                (1, "MA*"),
+                (2, "WP"),
+                (3, "GL"),
+                (-1, "SGH"),
+                (-2, "QA"),
+                (-3, "RP")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_orfs_on_both_strands() {
+        // Forward ORF at 1..12; the reverse complement, ATGGGTAGGTTAGGGTTTCAT,
+        // has no stop in frame, so it only appears as a partial ORF.
+        let mut request = orf_request("ATGAAACCCTAACCTACCCAT");
+        let orfs = find_orfs(&request).unwrap();
+        assert_eq!(orfs.len(), 1);
+        assert_eq!(
+            (orfs[0].start, orfs[0].end, orfs[0].strand),
+            (1, 12, Some(1))
+        );
+        assert_eq!(orfs[0].attributes["translation"], ["MKP"]);
+        assert_eq!(orfs[0].attributes["frame"], ["+1"]);
+
+        request.partial = true;
+        let orfs = find_orfs(&request).unwrap();
+        let reverse = orfs.iter().find(|orf| orf.strand == Some(-1)).unwrap();
+        assert_eq!((reverse.start, reverse.end), (1, 21));
+        assert!(reverse.partial);
+        assert_eq!(reverse.attributes["translation"], ["MGRLGFH"]);
+
+        request.min_length = 22;
+        assert!(find_orfs(&request).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_orf_start_rules() {
+        // TTG is a start in table 11 but not an ATG start.
+        let mut request = orf_request("TTGAAACCCGGGTAA");
+        request.strand = OrfStrand::Positive;
+        assert!(find_orfs(&request).unwrap().is_empty());
+        request.table = 11;
+        request.start_codons = OrfStart::Table;
+        let orfs = find_orfs(&request).unwrap();
+        assert_eq!(orfs[0].attributes["translation"], ["MKPG"]);
+        request.start_codons = OrfStart::Any;
+        request.sequence = "CCCTAAGGGAAATTTCCCTGA".to_string();
+        let orfs = find_orfs(&request).unwrap();
+        assert_eq!((orfs[0].start, orfs[0].end), (7, 21));
+        assert_eq!(orfs[0].attributes["translation"], ["GKFP"]);
+    }
+}
@@ -0,0 +1,356 @@
+//! Per-column statistics of a multiple sequence alignment.
+//!
+//! Residues are matched case-insensitively against the alphabet; gaps and any
+//! other symbols are not counted. Conservation follows Schneider & Stephens
+//! (1990) by default: the information content of a column, optionally with
+//! the small sample correction, scaled by the fraction of sequences with a
+//! residue. Other scores are described in [`crate::msa_stats`].
+//!
+//! With sequence weighting, residue counts are sums of sequence weights and
+//! the sample size is the effective number of sequences (see
+//! [`crate::weights`]). Without weighting the results equal the raw counts.
+
+use serde::{Deserialize, Serialize};
+
+use crate::logo::BackgroundSpec;
+use crate::msa_stats::{ConservationOptions, ConservationScore, ConservationScorer};
+use crate::weights::{DEFAULT_IDENTITY_THRESHOLD, SequenceWeighting, weights_for};
+
+/// Request for [`compute_column_stats`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColumnStatsRequest {
+    /// Aligned sequences. Shorter sequences contribute nothing past their end.
+    pub sequences: Vec<String>,
+    /// Residues counted, as single characters.
+    pub alphabet: Vec<String>,
+    /// First column (0-based, inclusive).
+    #[serde(default)]
+    pub start: usize,
+    /// Last column (0-based, exclusive); `None` uses the longest sequence.
+    #[serde(default)]
+    pub end: Option<usize>,
+    /// Subtracts the small sample correction from the Shannon conservation.
+    #[serde(default)]
+    pub sampling_correction: bool,
+    /// Computes `conservation` for each column.
+    #[serde(default = "default_compute_conservation")]
+    pub compute_conservation: bool,
+    /// Sequence weighting applied to the counts.
+    #[serde(default)]
+    pub weighting: SequenceWeighting,
+    /// Identity at which sequences count as neighbors for
+    /// [`SequenceWeighting::Identity`], as a fraction.
+    #[serde(
+        default = "default_identity_threshold",
+        deserialize_with = "plugin_io::lenient::float"
+    )]
+    pub identity_threshold: f64,
+    /// Conservation score to compute.
+    #[serde(default)]
+    pub conservation_score: ConservationScore,
+    /// Background for [`ConservationScore::JensenShannon`].
+    #[serde(default)]
+    pub background: BackgroundSpec,
+    /// Residue classes for [`ConservationScore::PropertyEntropy`].
+    #[serde(default)]
+    pub property_groups: Option<Vec<String>>,
+    /// Built-in matrix for [`ConservationScore::SumOfPairs`].
+    #[serde(default)]
+    pub matrix: Option<String>,
+}
+
+fn default_compute_conservation() -> bool {
+    true
+}
+
+fn default_identity_threshold() -> f64 {
+    DEFAULT_IDENTITY_THRESHOLD
+}
+
+/// Statistics for one alignment column.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnStats {
+    /// Residues in the order they first occur down the column.
+    pub residue_order: Vec<String>,
+    /// Weighted count of each residue in `residue_order`.
+    pub counts: Vec<f64>,
+    /// Number of sequences with a residue in this column.
+    pub total_non_gap: u64,
+    /// Sum of `counts`.
+    pub total_weight: f64,
+    /// Weighted fraction of sequences with a residue in this column.
+    pub occupancy: f64,
+    /// Conservation under the requested score, if requested.
+    pub conservation: Option<f64>,
+    /// Residue with the highest weighted count, ties going to the first
+    /// seen, or `-`.
+    pub consensus: String,
+}
+
+/// Response of [`compute_column_stats`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnStatsResponse {
+    /// Statistics for each column of the window.
+    pub columns: Vec<ColumnStats>,
+    /// Concatenated column consensus residues.
+    pub consensus: String,
+    /// Effective number of sequences under the requested weighting.
+    pub effective_sequences: f64,
+    /// Highest conservation the requested score can reach.
+    pub max_conservation: f64,
+}
+
+/// Counts residues and computes conservation for each column of a window.
+pub fn compute_column_stats(request: &ColumnStatsRequest) -> Result<ColumnStatsResponse, String> {
+    let lookup = residue_lookup(&request.alphabet)?;
+    let alphabet_size = request.alphabet.len();
+    let sequences: Vec<&[u8]> = request.sequences.iter().map(|s| s.as_bytes()).collect();
+    let weights = weights_for(
+        &sequences,
+        &lookup,
+        alphabet_size,
+        request.weighting,
+        request.identity_threshold,
+    )?;
+    let effective_sequences = weights.effective_sequences;
+    let scorer = if request.compute_conservation {
+        Some(ConservationScorer::new(
+            &request.alphabet,
+            &ConservationOptions {
+                score: request.conservation_score,
+                sampling_correction: request.sampling_correction,
+                background: request.background.clone(),
+                property_groups: request.property_groups.clone(),
+                matrix: request.matrix.clone(),
+            },
+        )?)
+    } else {
+        None
+    };
+    let sum_squares: f64 = weights.weights.iter().map(|w| w * w).sum();
+    let pair_weight = (effective_sequences * effective_sequences - sum_squares) / 2.0;
+
+    let end = request
+        .end
+        .unwrap_or_else(|| sequences.iter().map(|s| s.len()).max().unwrap_or(0));
+    if request.start > end {
+        return Err(format!(
+            "start ({}) must not be greater than end ({end})",
+            request.start
+        ));
+    }
+
+    let mut columns = Vec::with_capacity(end - request.start);
+    let mut counts = vec![0.0; alphabet_size];
+    let mut squared_weights = vec![0.0; alphabet_size];
+    let mut seen = vec![false; alphabet_size];
+    let mut order = Vec::with_capacity(alphabet_size);
+    for pos in request.start..end {
+        counts.fill(0.0);
+        squared_weights.fill(0.0);
+        seen.fill(false);
+        order.clear();
+        let mut total_non_gap = 0u64;
+        for (seq, &weight) in sequences.iter().zip(&weights.weights) {
+            let Some(&byte) = seq.get(pos) else { continue };
+            let Some(index) = lookup[byte as usize] else {
+                continue;
+            };
+            if !seen[index] {
+                seen[index] = true;
+                order.push(index);
+            }
+            counts[index] += weight;
+            squared_weights[index] += weight * weight;
+            total_non_gap += 1;
+        }
+
+        let ordered_counts: Vec<f64> = order.iter().map(|&i| counts[i]).collect();
+        let total_weight: f64 = ordered_counts.iter().sum();
+        let conservation = scorer.as_ref().map(|scorer| {
+            scorer.score(&counts, &squared_weights, effective_sequences, pair_weight)
+        });
+
+        let mut consensus = None;
+        let mut best = 0.0;
+        for (&index, &count) in order.iter().zip(&ordered_counts) {
+            if count > best {
+                consensus = Some(index);
+                best = count;
+            }
+        }
+
+        columns.push(ColumnStats {
+            residue_order: order
+                .iter()
+                .map(|&i| request.alphabet[i].to_ascii_uppercase())
+                .collect(),
+            counts: ordered_counts,
+            total_non_gap,
+            total_weight,
+            occupancy: if effective_sequences == 0.0 {
+                0.0
+            } else {
+                total_weight / effective_sequences
+            },
+            conservation,
+            consensus: consensus.map_or_else(
+                || "-".to_string(),
+                |i| request.alphabet[i].to_ascii_uppercase(),
+            ),
+        });
+    }
+
+    let consensus = columns.iter().map(|c| c.consensus.as_str()).collect();
+    Ok(ColumnStatsResponse {
+        columns,
+        consensus,
+        effective_sequences,
+        max_conservation: scorer.map_or((alphabet_size as f64).log2(), |s| s.max_score()),
+    })
+}
+
+/// Occupancy-scaled information content of a column, in bits.
+///
+/// `counts` are the (weighted) residue counts in the column and `total` their
+/// sum. The small sample correction is `(s - 1) / (2 n ln 2)` for alphabet
+/// size `s` and `n = total` residues.
+pub fn sequence_conservation(
+    counts: impl IntoIterator<Item = f64>,
+    total: f64,
+    num_sequences: f64,
+    sampling_correction: bool,
+    max_bits: f64,
+    alphabet_size: usize,
+) -> f64 {
+    if total == 0.0 {
+        return 0.0;
+    }
+
+    let mut entropy = 0.0;
+    for count in counts {
+        let p = count / total;
+        if p > 0.0 {
+            entropy -= p * p.log2();
+        }
+    }
+
+    let correction = if sampling_correction {
+        (alphabet_size as f64 - 1.0) / (2.0 * total * std::f64::consts::LN_2)
+    } else {
+        0.0
+    };
+
+    let information = (max_bits - (entropy + correction)).max(0.0);
+    total / num_sequences * information
+}
+
+/// Maps each byte (either case) to its alphabet index.
+pub(crate) fn residue_lookup(alphabet: &[String]) -> Result<Box<[Option<usize>; 256]>, String> {
+    let mut lookup = Box::new([None; 256]);
+    for (index, residue) in alphabet.iter().enumerate() {
+        let &[byte] = residue.as_bytes() else {
+            return Err(format!(
+                "alphabet entries must be single ASCII characters, got {residue:?}"
+            ));
+        };
+        if lookup[byte.to_ascii_uppercase() as usize].is_some() {
+            return Err(format!("alphabet lists '{residue}' more than once"));
+        }
+        lookup[byte.to_ascii_uppercase() as usize] = Some(index);
+        lookup[byte.to_ascii_lowercase() as usize] = Some(index);
+    }
+    Ok(lookup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(sequences: &[&str], alphabet: &str) -> ColumnStatsRequest {
+        ColumnStatsRequest {
+            sequences: sequences.iter().map(|s| s.to_string()).collect(),
+            alphabet: alphabet.chars().map(String::from).collect(),
+            start: 0,
+            end: None,
+            sampling_correction: false,
+            compute_conservation: true,
+            weighting: SequenceWeighting::None,
+            identity_threshold: DEFAULT_IDENTITY_THRESHOLD,
+            conservation_score: ConservationScore::Shannon,
+            background: BackgroundSpec::default(),
+            property_groups: None,
+            matrix: None,
+        }
+    }
+
+    #[test]
+    fn test_counts_follow_first_seen_order() {
+        let response = compute_column_stats(&request(&["Ca-", "AA-", "cTN"], "ACGT")).unwrap();
+        let first = &response.columns[0];
+
+        assert_eq!(first.residue_order, ["C", "A"]);
+        assert_eq!(first.counts, [2.0, 1.0]);
+        assert_eq!(first.total_non_gap, 3);
+        assert_eq!(first.consensus, "C");
+        assert_eq!(response.columns[1].consensus, "A");
+        assert_eq!(response.columns[2].total_non_gap, 0);
+        assert_eq!(response.consensus, "CA-");
+    }
+
+    #[test]
+    fn test_conservation_matches_schneider_stephens() {
+        let mut req = request(&["A", "A", "A", "-"], "ACGT");
+        let column = &compute_column_stats(&req).unwrap().columns[0];
+        assert_eq!(column.occupancy, 0.75);
+        assert_eq!(column.conservation, Some(0.75 * 2.0));
+
+        req.sampling_correction = true;
+        let column = &compute_column_stats(&req).unwrap().columns[0];
+        let correction = 3.0 / (2.0 * 3.0 * std::f64::consts::LN_2);
+        assert_eq!(column.conservation, Some(0.75 * (2.0 - correction)));
+
+        let split = compute_column_stats(&request(&["A", "C"], "ACGT")).unwrap();
+        assert_eq!(split.columns[0].conservation, Some(1.0));
+    }
+
+    #[test]
+    fn test_window_bounds() {
+        let mut req = request(&["ACGT", "AC"], "ACGT");
+        req.start = 1;
+        req.end = Some(3);
+        let response = compute_column_stats(&req).unwrap();
+        assert_eq!(response.consensus, "CG");
+        assert_eq!(response.columns[1].occupancy, 0.5);
+
+        req.start = 4;
+        assert!(compute_column_stats(&req).is_err());
+    }
+
+    #[test]
+    fn test_weighting_reduces_redundant_sequences() {
+        let mut req = request(&["A", "A", "A", "C"], "ACGT");
+        let plain = compute_column_stats(&req).unwrap();
+        assert_eq!(plain.effective_sequences, 4.0);
+
+        req.weighting = SequenceWeighting::Identity;
+        let weighted = compute_column_stats(&req).unwrap();
+        assert!((weighted.effective_sequences - 2.0).abs() < 1e-12);
+        let column = &weighted.columns[0];
+        assert_eq!(column.total_non_gap, 4);
+        assert!((column.counts[0] - column.counts[1]).abs() < 1e-12);
+        assert!((column.occupancy - 1.0).abs() < 1e-12);
+        // An even A/C split carries less information than 3:1.
+        assert!(column.conservation < plain.columns[0].conservation);
+    }
+
+    #[test]
+    fn test_sum_of_pairs_conservation() {
+        let mut req = request(&["WA", "WA", "C-"], "ARNDCQEGHILKMFPSTWYV");
+        req.conservation_score = ConservationScore::SumOfPairs;
+        let response = compute_column_stats(&req).unwrap();
+        assert_eq!(response.max_conservation, 11.0);
+        assert!((response.columns[0].conservation.unwrap() - 7.0 / 3.0).abs() < 1e-12);
+        assert!((response.columns[1].conservation.unwrap() - 4.0 / 3.0).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,504 @@
+//! FASTA and FASTQ parsing.
+//!
+//! Lines may end in `\n` or `\r\n`. FASTQ records may wrap their sequence
+//! and quality over several lines; the quality block ends once it is as long
+//! as the sequence. Errors name the 1-based line they refer to.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Options for [`parse_sequence_records`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParseSequencesConfig {
+    /// FASTA, FASTQ, or detected from the first line.
+    #[serde(default)]
+    pub format: SequenceFormat,
+    /// What to do with a repeated ID.
+    #[serde(default)]
+    pub duplicates: DuplicatePolicy,
+    /// Splits headers at the first whitespace into an ID and a description.
+    /// Otherwise the whole header is the ID.
+    #[serde(default = "default_split_description")]
+    pub split_description: bool,
+    /// How FASTQ qualities are returned.
+    #[serde(default)]
+    pub quality: QualityEncoding,
+    /// ASCII offset of FASTQ quality characters.
+    #[serde(default = "default_phred_offset")]
+    pub phred_offset: u8,
+    /// Rejects FASTA data before the first header and empty headers, and
+    /// skips `;` comment lines. Otherwise FASTA is read as the Typst
+    /// `parse-fasta` always did: data before the first header is ignored, an
+    /// empty header gives an empty ID, and `;` lines are sequence data.
+    #[serde(default = "default_strict")]
+    pub strict: bool,
+}
+
+impl Default for ParseSequencesConfig {
+    fn default() -> Self {
+        Self {
+            format: SequenceFormat::default(),
+            duplicates: DuplicatePolicy::default(),
+            split_description: default_split_description(),
+            quality: QualityEncoding::default(),
+            phred_offset: default_phred_offset(),
+            strict: default_strict(),
+        }
+    }
+}
+
+fn default_split_description() -> bool {
+    true
+}
+
+fn default_phred_offset() -> u8 {
+    33
+}
+
+fn default_strict() -> bool {
+    true
+}
+
+/// Input format; `Auto` looks at the first non-blank line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SequenceFormat {
+    /// Detects the format from the first non-blank line.
+    #[default]
+    Auto,
+    /// FASTA; `>` starts a record.
+    Fasta,
+    /// FASTQ; `@` starts a four-line record.
+    Fastq,
+}
+
+/// What to do with a record whose ID was already seen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DuplicatePolicy {
+    /// Rejects the input.
+    #[default]
+    Error,
+    /// Appends `_2`, `_3`, ... to the ID until it is unique.
+    Rename,
+    /// Keeps the first record and drops later ones.
+    KeepFirst,
+}
+
+/// How FASTQ qualities are returned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QualityEncoding {
+    /// The quality string as written in the file.
+    #[default]
+    String,
+    /// Phred scores, i.e. character codes minus `phred_offset`.
+    Phred,
+}
+
+/// One parsed FASTA or FASTQ record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SequenceRecord {
+    /// Header up to the first whitespace, or the whole header.
+    pub id: String,
+    /// Rest of the header, when descriptions are split off.
+    pub description: Option<String>,
+    /// Sequence with line breaks removed.
+    pub sequence: String,
+    /// FASTQ quality; `None` for FASTA records.
+    pub quality: Option<Quality>,
+}
+
+/// FASTQ quality in the requested [`QualityEncoding`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Quality {
+    /// The quality string as written in the file.
+    String(String),
+    /// Phred scores.
+    Phred(Vec<u8>),
+}
+
+/// Parses FASTA or FASTQ text into records in file order.
+pub fn parse_sequence_records(
+    input: &str,
+    config: &ParseSequencesConfig,
+) -> Result<Vec<SequenceRecord>, String> {
+    let mut lines = Lines::new(input);
+    let format = match config.format {
+        SequenceFormat::Auto => match lines.peek_non_blank() {
+            None => return Ok(Vec::new()),
+            Some((_, line)) if line.starts_with('@') => SequenceFormat::Fastq,
+            Some((_, line)) if line.starts_with('>') || line.starts_with(';') => {
+                SequenceFormat::Fasta
+            }
+            Some((number, _)) => {
+                return Err(format!(
+                    "line {number}: expected a FASTA ('>') or FASTQ ('@') header"
+                ));
+            }
+        },
+        format => format,
+    };
+
+    let mut records = Records::new(config);
+    match format {
+        SequenceFormat::Fastq => parse_fastq(&mut lines, config, &mut records)?,
+        _ => parse_fasta(&mut lines, config, &mut records)?,
+    }
+    Ok(records.records)
+}
+
+fn parse_fasta(
+    lines: &mut Lines<'_>,
+    config: &ParseSequencesConfig,
+    records: &mut Records,
+) -> Result<(), String> {
+    let mut current: Option<(usize, String, Option<String>, String)> = None;
+    for (number, line) in lines.by_ref() {
+        let line = line.trim();
+        if line.is_empty() || (config.strict && line.starts_with(';')) {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('>') {
+            if let Some((header_line, id, description, sequence)) = current.take() {
+                records.push(header_line, id, description, sequence, None)?;
+            }
+            let (id, description) = if !config.strict && header.trim().is_empty() {
+                (String::new(), None)
+            } else {
+                split_header(header, number, config, "FASTA")?
+            };
+            current = Some((number, id, description, String::new()));
+        } else if let Some((_, _, _, sequence)) = current.as_mut() {
+            sequence.push_str(line);
+        } else if config.strict {
+            return Err(format!(
+                "line {number}: sequence data before the first FASTA header"
+            ));
+        }
+    }
+    if let Some((header_line, id, description, sequence)) = current {
+        records.push(header_line, id, description, sequence, None)?;
+    }
+    Ok(())
+}
+
+fn parse_fastq(
+    lines: &mut Lines<'_>,
+    config: &ParseSequencesConfig,
+    records: &mut Records,
+) -> Result<(), String> {
+    while let Some((header_line, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some(header) = line.strip_prefix('@') else {
+            return Err(format!(
+                "line {header_line}: expected a FASTQ header starting with '@'"
+            ));
+        };
+        let (id, description) = split_header(header, header_line, config, "FASTQ")?;
+
+        let mut sequence = String::new();
+        loop {
+            let Some((number, line)) = lines.next() else {
+                return Err(format!(
+                    "line {header_line}: FASTQ record '{id}' has no '+' separator line"
+                ));
+            };
+            let line = line.trim();
+            if let Some(repeat) = line.strip_prefix('+') {
+                let repeat = repeat.trim();
+                if !repeat.is_empty() && repeat != header.trim() {
+                    return Err(format!(
+                        "line {number}: FASTQ separator does not repeat the header of '{id}'"
+                    ));
+                }
+                break;
+            }
+            sequence.push_str(line);
+        }
+
+        let expected = sequence.chars().count();
+        let mut quality = String::new();
+        while quality.len() < expected {
+            let Some((number, line)) = lines.next() else {
+                return Err(format!(
+                    "line {header_line}: quality of FASTQ record '{id}' has {} characters, expected {expected}",
+                    quality.len()
+                ));
+            };
+            let line = line.trim();
+            if let Some(c) = line
+                .chars()
+                .find(|&c| !c.is_ascii_graphic() || (c as u8) < config.phred_offset)
+            {
+                return Err(format!(
+                    "line {number}: invalid quality character {c:?} in FASTQ record '{id}'"
+                ));
+            }
+            quality.push_str(line);
+            if quality.len() > expected {
+                return Err(format!(
+                    "line {number}: quality of FASTQ record '{id}' has {} characters, expected {expected}",
+                    quality.len()
+                ));
+            }
+        }
+
+        let quality = match config.quality {
+            QualityEncoding::String => Quality::String(quality),
+            QualityEncoding::Phred => {
+                Quality::Phred(quality.bytes().map(|b| b - config.phred_offset).collect())
+            }
+        };
+        records.push(header_line, id, description, sequence, Some(quality))?;
+    }
+    Ok(())
+}
+
+fn split_header(
+    header: &str,
+    number: usize,
+    config: &ParseSequencesConfig,
+    format: &str,
+) -> Result<(String, Option<String>), String> {
+    let header = header.trim();
+    if header.is_empty() {
+        return Err(format!("line {number}: empty {format} header"));
+    }
+    if !config.split_description {
+        return Ok((header.to_string(), None));
+    }
+    match header.split_once(char::is_whitespace) {
+        Some((id, description)) => {
+            let description = description.trim_start();
+            Ok((
+                id.to_string(),
+                (!description.is_empty()).then(|| description.to_string()),
+            ))
+        }
+        None => Ok((header.to_string(), None)),
+    }
+}
+
+/// Collects records while applying the duplicate policy.
+pub(crate) struct Records {
+    policy: DuplicatePolicy,
+    pub(crate) records: Vec<SequenceRecord>,
+    /// Line of the header that introduced each ID.
+    seen: HashMap<String, usize>,
+}
+
+impl Records {
+    pub(crate) fn new(config: &ParseSequencesConfig) -> Self {
+        Self {
+            policy: config.duplicates,
+            records: Vec::new(),
+            seen: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn push(
+        &mut self,
+        line: usize,
+        id: String,
+        description: Option<String>,
+        sequence: String,
+        quality: Option<Quality>,
+    ) -> Result<(), String> {
+        let id = match self.seen.get(&id) {
+            None => id,
+            Some(&first) => match self.policy {
+                DuplicatePolicy::Error => {
+                    return Err(format!(
+                        "line {line}: duplicate sequence identifier '{id}' (first seen on line {first})"
+                    ));
+                }
+                DuplicatePolicy::KeepFirst => return Ok(()),
+                DuplicatePolicy::Rename => (2..)
+                    .map(|n| format!("{id}_{n}"))
+                    .find(|candidate| !self.seen.contains_key(candidate))
+                    .expect("an unused suffix exists"),
+            },
+        };
+        self.seen.insert(id.clone(), line);
+        self.records.push(SequenceRecord {
+            id,
+            description,
+            sequence,
+            quality,
+        });
+        Ok(())
+    }
+}
+
+/// Line iterator yielding 1-based line numbers without line endings.
+pub(crate) struct Lines<'a> {
+    inner: std::iter::Peekable<std::iter::Enumerate<std::str::Split<'a, char>>>,
+}
+
+impl<'a> Lines<'a> {
+    pub(crate) fn new(input: &'a str) -> Self {
+        Self {
+            inner: input.split('\n').enumerate().peekable(),
+        }
+    }
+
+    /// Skips blank lines and returns the next line without consuming it.
+    pub(crate) fn peek_non_blank(&mut self) -> Option<(usize, &'a str)> {
+        while let Some((_, line)) = self.inner.peek() {
+            if line.trim().is_empty() {
+                self.inner.next();
+            } else {
+                break;
+            }
+        }
+        self.inner
+            .peek()
+            .map(|(index, line)| (index + 1, line.trim()))
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|(index, line)| (index + 1, line.strip_suffix('\r').unwrap_or(line)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str, config: ParseSequencesConfig) -> Result<Vec<SequenceRecord>, String> {
+        parse_sequence_records(input, &config)
+    }
+
+    #[test]
+    fn test_parses_fasta_with_crlf_and_descriptions() {
+        let input = ">seq1 first sequence\r\nACGT\r\nTT\r\n\r\n>seq2\r\nGG\r\n";
+        let records = parse(input, ParseSequencesConfig::default()).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "seq1");
+        assert_eq!(records[0].description.as_deref(), Some("first sequence"));
+        assert_eq!(records[0].sequence, "ACGTTT");
+        assert_eq!(records[1].description, None);
+        assert_eq!(records[1].quality, None);
+
+        let whole = ParseSequencesConfig {
+            split_description: false,
+            ..Default::default()
+        };
+        assert_eq!(parse(input, whole).unwrap()[0].id, "seq1 first sequence");
+    }
+
+    #[test]
+    fn test_parses_wrapped_fastq_with_phred_scores() {
+        let input = "@read1 lane 1\nACGT\nAC\n+read1 lane 1\nII#\n@!I\n@read2\nA\n+\n5\n";
+        let records = parse(input, ParseSequencesConfig::default()).unwrap();
+        assert_eq!(records[0].sequence, "ACGTAC");
+        assert_eq!(
+            records[0].quality,
+            Some(Quality::String("II#@!I".to_string()))
+        );
+
+        let phred = ParseSequencesConfig {
+            quality: QualityEncoding::Phred,
+            ..Default::default()
+        };
+        let records = parse(input, phred).unwrap();
+        assert_eq!(records[1].id, "read2");
+        assert_eq!(records[1].quality, Some(Quality::Phred(vec![20])));
+    }
+
+    #[test]
+    fn test_duplicate_policies() {
+        let input = ">a\nAA\n>b\nCC\n>a\nGG\n>a\nTT\n";
+        let error = parse(input, ParseSequencesConfig::default()).unwrap_err();
+        assert_eq!(
+            error,
+            "line 5: duplicate sequence identifier 'a' (first seen on line 1)"
+        );
+
+        let keep_first = ParseSequencesConfig {
+            duplicates: DuplicatePolicy::KeepFirst,
+            ..Default::default()
+        };
+        let records = parse(input, keep_first).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].sequence, "AA");
+
+        let rename = ParseSequencesConfig {
+            duplicates: DuplicatePolicy::Rename,
+            ..Default::default()
+        };
+        let ids: Vec<_> = parse(input, rename)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.id)
+            .collect();
+        assert_eq!(ids, ["a", "b", "a_2", "a_3"]);
+    }
+
+    #[test]
+    fn test_errors_report_line_numbers() {
+        let cases = [
+            ("ACGT\n>a\nAC\n", "line 1: expected a FASTA"),
+            (">a\nAC\n>\nAC\n", "line 3: empty FASTA header"),
+            (
+                "@r\nACGT\n+\nII\n",
+                "line 1: quality of FASTQ record 'r' has 2",
+            ),
+            (
+                "@r\nAC\n+\nIII\n",
+                "line 4: quality of FASTQ record 'r' has 3",
+            ),
+            ("@r\nAC\n+x\nII\n", "line 3: FASTQ separator"),
+            ("@r\nAC\n", "line 1: FASTQ record 'r' has no '+'"),
+            ("@r\nAC\n+\nIé\n", "line 4: invalid quality character"),
+        ];
+        for (input, expected) in cases {
+            let error = parse(input, ParseSequencesConfig::default()).unwrap_err();
+            assert!(error.starts_with(expected), "{input:?}: {error}");
+        }
+
+        let fasta = ParseSequencesConfig {
+            format: SequenceFormat::Fasta,
+            ..Default::default()
+        };
+        let error = parse("\nACGT\n", fasta).unwrap_err();
+        assert_eq!(error, "line 2: sequence data before the first FASTA header");
+    }
+
+    #[test]
+    fn test_lenient_fasta_keeps_the_typst_parse_fasta_rules() {
+        let input = "ACGT\n>\nAC\n;GG\n>seq2\nTT\n";
+        let lenient = ParseSequencesConfig {
+            format: SequenceFormat::Fasta,
+            strict: false,
+            ..Default::default()
+        };
+        let records = parse(input, lenient).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "");
+        assert_eq!(records[0].sequence, "AC;GG");
+        assert_eq!(records[1].sequence, "TT");
+    }
+
+    #[test]
+    fn test_empty_input_has_no_records() {
+        assert!(
+            parse("\n\r\n", ParseSequencesConfig::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Pairwise percent identity within a multiple sequence alignment.
+//!
+//! Identity counts columns where two sequences have the same residue, and is
+//! divided by one of several lengths (see [`IdentityDefinition`]). Rows can
+//! be ordered by agglomerative clustering on `1 - identity`, so similar
+//! sequences end up next to each other in a heatmap.
+
+use serde::{Deserialize, Serialize};
+
+use crate::column_stats::residue_lookup;
+
+/// Denominator of the identity fraction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdentityDefinition {
+    /// Columns where both sequences have a residue.
+    #[default]
+    Aligned,
+    /// Residues in the shorter of the two sequences.
+    Shorter,
+    /// All alignment columns.
+    Columns,
+}
+
+/// Cluster distance used when merging.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Linkage {
+    /// Mean pairwise distance (UPGMA).
+    #[default]
+    Average,
+    /// Smallest pairwise distance.
+    Single,
+    /// Largest pairwise distance.
+    Complete,
+}
+
+/// Request for [`compute_identity_matrix`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdentityMatrixRequest {
+    /// Aligned sequences.
+    pub sequences: Vec<String>,
+    /// Residues compared, as single characters; anything else is a gap.
+    pub alphabet: Vec<String>,
+    /// Denominator of the identity fraction.
+    #[serde(default)]
+    pub definition: IdentityDefinition,
+    /// Clusters sequences to order rows; `None` keeps the input order.
+    #[serde(default)]
+    pub linkage: Option<Linkage>,
+}
+
+/// Response of [`compute_identity_matrix`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IdentityMatrixResponse {
+    /// Identity fraction for each pair, in input order. The diagonal is 1
+    /// unless a sequence has no residues.
+    pub identity: Vec<Vec<f64>>,
+    /// Input indices in leaf order of the clustering.
+    pub order: Vec<usize>,
+}
+
+/// Computes the pairwise identity matrix and a row order.
+pub fn compute_identity_matrix(
+    request: &IdentityMatrixRequest,
+) -> Result<IdentityMatrixResponse, String> {
+    let lookup = residue_lookup(&request.alphabet)?;
+    let encoded: Vec<Vec<Option<usize>>> = request
+        .sequences
+        .iter()
+        .map(|s| s.bytes().map(|b| lookup[b as usize]).collect())
+        .collect();
+    let residues: Vec<usize> = encoded
+        .iter()
+        .map(|s| s.iter().filter(|r| r.is_some()).count())
+        .collect();
+    let length = encoded.iter().map(Vec::len).max().unwrap_or(0);
+
+    let n = encoded.len();
+    let mut identity = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i..n {
+            let mut aligned = 0usize;
+            let mut matches = 0usize;
+            for (a, b) in encoded[i].iter().zip(&encoded[j]) {
+                if let (Some(a), Some(b)) = (a, b) {
+                    aligned += 1;
+                    matches += usize::from(a == b);
+                }
+            }
+            let denominator = match request.definition {
+                IdentityDefinition::Aligned => aligned,
+                IdentityDefinition::Shorter => residues[i].min(residues[j]),
+                IdentityDefinition::Columns => length,
+            };
+            let value = if denominator == 0 {
+                0.0
+            } else {
+                matches as f64 / denominator as f64
+            };
+            identity[i][j] = value;
+            identity[j][i] = value;
+        }
+    }
+
+    let order = match request.linkage {
+        Some(linkage) => cluster_order(&identity, linkage),
+        None => (0..n).collect(),
+    };
+    Ok(IdentityMatrixResponse { identity, order })
+}
+
+/// Leaf order of agglomerative clustering on `1 - identity`.
+///
+/// The closest pair of clusters is merged first, ties going to the lowest
+/// indices. Each merge places the cluster holding the lower input index
+/// first.
+fn cluster_order(identity: &[Vec<f64>], linkage: Linkage) -> Vec<usize> {
+    let n = identity.len();
+    let mut distance: Vec<Vec<f64>> = identity
+        .iter()
+        .map(|row| row.iter().map(|v| 1.0 - v).collect())
+        .collect();
+    // Leaves of each active cluster, or `None` once merged away.
+    let mut clusters: Vec<Option<Vec<usize>>> = (0..n).map(|i| Some(vec![i])).collect();
+
+    for _ in 1..n {
+        let mut best: Option<(f64, usize, usize)> = None;
+        for a in 0..n {
+            if clusters[a].is_none() {
+                continue;
+            }
+            for b in (a + 1)..n {
+                if clusters[b].is_none() {
+                    continue;
+                }
+                if best.is_none_or(|(d, _, _)| distance[a][b] < d) {
+                    best = Some((distance[a][b], a, b));
+                }
+            }
+        }
+        let Some((_, a, b)) = best else { break };
+
+        let size_a = clusters[a].as_ref().map_or(0, Vec::len) as f64;
+        let size_b = clusters[b].as_ref().map_or(0, Vec::len) as f64;
+        for c in 0..n {
+            if c == a || c == b || clusters[c].is_none() {
+                continue;
+            }
+            let merged = match linkage {
+                Linkage::Average => {
+                    (size_a * distance[a][c] + size_b * distance[b][c]) / (size_a + size_b)
+                }
+                Linkage::Single => distance[a][c].min(distance[b][c]),
+                Linkage::Complete => distance[a][c].max(distance[b][c]),
+            };
+            distance[a][c] = merged;
+            distance[c][a] = merged;
+        }
+        let leaves_b = clusters[b].take().unwrap_or_default();
+        if let Some(leaves_a) = clusters[a].as_mut() {
+            leaves_a.extend(leaves_b);
+        }
+    }
+
+    clusters.into_iter().flatten().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(sequences: &[&str], definition: IdentityDefinition) -> IdentityMatrixRequest {
+        IdentityMatrixRequest {
+            sequences: sequences.iter().map(|s| s.to_string()).collect(),
+            alphabet: "ACGT".chars().map(String::from).collect(),
+            definition,
+            linkage: None,
+        }
+    }
+
+    #[test]
+    fn test_identity_definitions() {
+        let sequences = ["ACGTAC--", "ACGAAC-T", "AC------"];
+        let aligned = compute_identity_matrix(&request(&sequences, IdentityDefinition::Aligned))
+            .unwrap()
+            .identity;
+        assert_eq!(aligned[0][0], 1.0);
+        assert_eq!(aligned[0][1], 5.0 / 6.0);
+        assert_eq!(aligned[0][2], 1.0);
+
+        let shorter = compute_identity_matrix(&request(&sequences, IdentityDefinition::Shorter))
+            .unwrap()
+            .identity;
+        assert_eq!(shorter[0][1], 5.0 / 6.0);
+        assert_eq!(shorter[1][2], 1.0);
+
+        let columns = compute_identity_matrix(&request(&sequences, IdentityDefinition::Columns))
+            .unwrap()
+            .identity;
+        assert_eq!(columns[0][1], 5.0 / 8.0);
+        assert_eq!(columns[2][2], 2.0 / 8.0);
+    }
+
+    #[test]
+    fn test_sequences_without_shared_residues_have_zero_identity() {
+        // An all-gap row, no overlap, lowercase and a residue outside the
+        // alphabet, which counts as a gap.
+        let sequences = ["----", "AC--", "--gt", "ACNN"];
+        for definition in [
+            IdentityDefinition::Aligned,
+            IdentityDefinition::Shorter,
+            IdentityDefinition::Columns,
+        ] {
+            let identity = compute_identity_matrix(&request(&sequences, definition))
+                .unwrap()
+                .identity;
+            assert_eq!(identity[0], [0.0; 4], "{definition:?}");
+            assert_eq!(identity[1][2], 0.0, "{definition:?}");
+        }
+        let aligned = compute_identity_matrix(&request(&sequences, IdentityDefinition::Aligned))
+            .unwrap()
+            .identity;
+        assert_eq!(aligned[2][2], 1.0);
+        assert_eq!(aligned[1][3], 1.0);
+    }
+
+    #[test]
+    fn test_empty_and_single_sequence_inputs() {
+        let mut req = request(&[], IdentityDefinition::Aligned);
+        req.linkage = Some(Linkage::Average);
+        let response = compute_identity_matrix(&req).unwrap();
+        assert!(response.identity.is_empty());
+        assert!(response.order.is_empty());
+
+        let mut req = request(&["ACGT"], IdentityDefinition::Aligned);
+        req.linkage = Some(Linkage::Single);
+        let response = compute_identity_matrix(&req).unwrap();
+        assert_eq!(response.identity, [[1.0]]);
+        assert_eq!(response.order, [0]);
+
+        // Identical sequences tie at distance 0 and keep input order.
+        req.sequences = vec!["ACGT".into(); 3];
+        assert_eq!(compute_identity_matrix(&req).unwrap().order, [0, 1, 2]);
+
+        req.alphabet = vec!["AC".into()];
+        assert!(compute_identity_matrix(&req).is_err());
+    }
+
+    #[test]
+    fn test_clustering_groups_similar_sequences() {
+        let mut req = request(
+            &["AAAAAAAA", "CCCCCCCC", "AAAAAAAC", "CCCCCCCA", "AAAAAACC"],
+            IdentityDefinition::Aligned,
+        );
+        assert_eq!(
+            compute_identity_matrix(&req).unwrap().order,
+            [0, 1, 2, 3, 4]
+        );
+        for linkage in [Linkage::Average, Linkage::Single, Linkage::Complete] {
+            req.linkage = Some(linkage);
+            assert_eq!(
+                compute_identity_matrix(&req).unwrap().order,
+                [0, 2, 4, 1, 3],
+                "{linkage:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! sequence: Sequence parsing and alignment statistics WASM plugin
+//!
+//! The crate also builds as a native library: [`parse_sequence_records`],
+//! [`parse_alignment`], [`compute_column_stats`], [`compute_sequence_weights`],
+//! [`compute_logo`], [`compute_frequency_logo`], [`trim_columns`],
+//! [`filter_sequences`], [`compute_identity_matrix`], [`build_pwm`],
+//! [`scan_motif`], [`parse_motifs`], [`compute_popgen_stats`] and
+//! [`find_snp_sites`] are the typed functions behind the WASM entry points.
+
+#![cfg_attr(not(target_arch = "wasm32"), warn(missing_docs))]
+
+mod column_stats;
+mod fastx;
+mod identity_matrix;
+mod logo;
+mod motif;
+mod motif_formats;
+mod msa_filter;
+mod msa_formats;
+mod msa_stats;
+mod popgen;
+mod snp_sites;
+mod weights;
+
+pub use column_stats::{
+    ColumnStats, ColumnStatsRequest, ColumnStatsResponse, compute_column_stats,
+    sequence_conservation,
+};
+pub use fastx::{
+    DuplicatePolicy, ParseSequencesConfig, Quality, QualityEncoding, SequenceFormat,
+    SequenceRecord, parse_sequence_records,
+};
+pub use identity_matrix::{
+    IdentityDefinition, IdentityMatrixRequest, IdentityMatrixResponse, Linkage,
+    compute_identity_matrix,
+};
+pub use logo::{
+    BackgroundSpec, FrequencyLogoRequest, LogoColumn, LogoRequest, LogoResponse, PseudocountMethod,
+    compute_frequency_logo, compute_logo,
+};
+pub use motif::{BuildPwmRequest, Pwm, ScanMotifRequest, ScanStrand, build_pwm, scan_motif};
+pub use motif_formats::{Motif, MotifFormat, ParseMotifsConfig, parse_motifs};
+pub use msa_filter::{
+    FilterSequencesRequest, FilterSequencesResponse, TrimColumnsRequest, TrimColumnsResponse,
+    filter_sequences, trim_columns,
+};
+pub use msa_formats::{
+    AlignedSequence, AlignmentFormat, InsertionPolicy, ParseAlignmentConfig, ParsedAlignment,
+    parse_alignment,
+};
+pub use msa_stats::{
+    AMINO_ACID_CLASSES, ConservationOptions, ConservationScore, ConservationScorer,
+};
+pub use plugin_io::{WireFormat, decompress, is_gzip};
+pub use popgen::{
+    MissingDataPolicy, PopgenRequest, PopgenResponse, PopgenStats, PopgenWindow,
+    compute_popgen_stats,
+};
+pub use snp_sites::{SnpSite, SnpSitesRequest, SnpSitesResponse, find_snp_sites};
+pub use weights::{
+    DEFAULT_IDENTITY_THRESHOLD, SequenceWeighting, SequenceWeights, compute_sequence_weights,
+};
+
+#[cfg(all(target_arch = "wasm32", feature = "plugin"))]
+use wasm_minimal_protocol::*;
+
+#[cfg(all(target_arch = "wasm32", feature = "plugin"))]
+initiate_protocol!();
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Parses FASTA or FASTQ data into sequence records.
+///
+/// # Arguments
+/// * `data` - FASTA or FASTQ source as UTF-8 bytes, optionally gzip- or
+///   BGZF-compressed
+/// * `config` - JSON- or CBOR-encoded parser configuration
+///
+/// # Returns
+/// Records in file order (`id`, `description`, `sequence`, `quality`),
+/// encoded like `config`, or an error string.
+pub fn parse_sequences(data: &[u8], config: &[u8]) -> Result<Vec<u8>, String> {
+    let data = decompress(data)?;
+    let data =
+        std::str::from_utf8(&data).map_err(|e| format!("Invalid UTF-8 in sequence data: {e}"))?;
+    plugin_io::handle(config, "config", |config: ParseSequencesConfig| {
+        parse_sequence_records(data, &config)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Parses a Stockholm, Clustal, PHYLIP, aligned FASTA or A2M/A3M alignment.
+///
+/// # Arguments
+/// * `data` - Alignment source as UTF-8 bytes, optionally gzip- or
+///   BGZF-compressed
+/// * `config` - JSON- or CBOR-encoded parser configuration
+///
+/// # Returns
+/// Sequences in file order with column, residue and file annotations,
+/// encoded like `config`, or an error string.
+pub fn parse_msa(data: &[u8], config: &[u8]) -> Result<Vec<u8>, String> {
+    let data = decompress(data)?;
+    let data =
+        std::str::from_utf8(&data).map_err(|e| format!("Invalid UTF-8 in alignment data: {e}"))?;
+    plugin_io::handle(config, "config", |config: ParseAlignmentConfig| {
+        parse_alignment(data, &config)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Computes per-column residue counts, occupancy, conservation and consensus
+/// for a window of a multiple sequence alignment.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn column_stats(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: ColumnStatsRequest| {
+        compute_column_stats(&request)
+    })
+}
+
+/// Request for the `sequence_weights` entry point.
+#[derive(serde::Deserialize)]
+struct SequenceWeightsRequest {
+    sequences: Vec<String>,
+    alphabet: Vec<String>,
+    #[serde(default)]
+    weighting: SequenceWeighting,
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    identity_threshold: Option<f64>,
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Computes sequence weights and the effective number of sequences for an
+/// alignment.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn sequence_weights(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: SequenceWeightsRequest| {
+        compute_sequence_weights(
+            &request.sequences,
+            &request.alphabet,
+            request.weighting,
+            request
+                .identity_threshold
+                .unwrap_or(DEFAULT_IDENTITY_THRESHOLD),
+        )
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Computes relative-entropy sequence-logo heights for a window of a multiple
+/// sequence alignment, against background frequencies and with optional
+/// pseudocounts.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn logo_heights(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: LogoRequest| {
+        compute_logo(&request)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Computes relative-entropy sequence-logo heights from per-column residue
+/// frequencies, such as a motif matrix, against background frequencies.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn frequency_logo_heights(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: FrequencyLogoRequest| {
+        compute_frequency_logo(&request)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Removes alignment columns by gap fraction, conservation or an automated
+/// gap threshold, returning the trimmed sequences and the kept columns.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn trim_msa_columns(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: TrimColumnsRequest| {
+        trim_columns(&request)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Removes sequences above a pairwise identity or below a query coverage,
+/// like hhfilter.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn filter_msa_sequences(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: FilterSequencesRequest| {
+        filter_sequences(&request)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Computes pairwise percent identity between aligned sequences, with an
+/// optional clustering order for heatmap rows.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn identity_matrix(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: IdentityMatrixRequest| {
+        compute_identity_matrix(&request)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Builds a position weight matrix from an ungapped motif alignment.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn motif_pwm(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: BuildPwmRequest| {
+        build_pwm(&request)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Scans a sequence with a position weight matrix and returns hits above a
+/// score or p-value threshold as genome-map feature dictionaries.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn scan_motif_hits(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: ScanMotifRequest| {
+        scan_motif(&request)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Parses MEME minimal, JASPAR, PFM or TRANSFAC motif matrices.
+///
+/// # Arguments
+/// * `data` - Motif file as UTF-8 bytes, optionally gzip- or
+///   BGZF-compressed
+/// * `config` - JSON- or CBOR-encoded parser configuration
+///
+/// # Returns
+/// Motifs in file order with per-position residue frequencies, encoded
+/// like `config`, or an error string.
+pub fn parse_motif_matrices(data: &[u8], config: &[u8]) -> Result<Vec<u8>, String> {
+    let data = decompress(data)?;
+    let data =
+        std::str::from_utf8(&data).map_err(|e| format!("Invalid UTF-8 in motif data: {e}"))?;
+    plugin_io::handle(config, "config", |config: ParseMotifsConfig| {
+        parse_motifs(data, &config)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Computes segregating sites, nucleotide diversity, Watterson's estimator,
+/// Tajima's D and haplotype statistics for a nucleotide alignment, overall
+/// and in sliding windows.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn popgen_stats(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: PopgenRequest| {
+        compute_popgen_stats(&request)
+    })
+}
+
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+/// Finds the variant columns of a nucleotide alignment relative to a
+/// reference sequence, with reference positions, alleles and genotypes, and
+/// optionally the alignment restricted to those columns.
+///
+/// The request may be JSON or CBOR; the response uses the same encoding.
+pub fn snp_sites(request: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(request, "request", |request: SnpSitesRequest| {
+        find_snp_sites(&request)
+    })
+}
@@ -0,0 +1,512 @@
+//! Sequence-logo heights as relative entropy against a background.
+//!
+//! Each column's residue frequencies `q` (optionally with pseudocounts) are
+//! compared with background frequencies `p`. The stack height is the relative
+//! entropy `sum q log2(q / p)` scaled by column occupancy, shared among
+//! residues by frequency. With `show_depleted`, each residue instead gets the
+//! signed term `q log2(q / p)`, so residues below their background frequency
+//! extend below the axis.
+
+use serde::{Deserialize, Serialize};
+
+use seq_align::{BuiltinMatrix, matrix_statistics};
+
+use crate::column_stats::residue_lookup;
+use crate::weights::{DEFAULT_IDENTITY_THRESHOLD, SequenceWeighting, weights_for};
+
+/// Request for [`compute_logo`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogoRequest {
+    /// Aligned sequences.
+    pub sequences: Vec<String>,
+    /// Residues shown in the logo, as single characters.
+    pub alphabet: Vec<String>,
+    /// First column (0-based, inclusive).
+    #[serde(default)]
+    pub start: usize,
+    /// Last column (0-based, exclusive); `None` uses the longest sequence.
+    #[serde(default)]
+    pub end: Option<usize>,
+    /// Sequence weighting applied to the counts.
+    #[serde(default)]
+    pub weighting: SequenceWeighting,
+    /// Identity at which sequences count as neighbors for
+    /// [`SequenceWeighting::Identity`], as a fraction.
+    #[serde(
+        default = "default_identity_threshold",
+        deserialize_with = "plugin_io::lenient::float"
+    )]
+    pub identity_threshold: f64,
+    /// Background frequencies the column frequencies are compared with.
+    #[serde(default)]
+    pub background: BackgroundSpec,
+    /// Pseudocounts added to the column frequencies.
+    #[serde(default)]
+    pub pseudocounts: PseudocountMethod,
+    /// Pseudocount weight; defaults to 1 for Dirichlet and 10 for BLOSUM62.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub pseudocount_weight: Option<f64>,
+    /// Returns signed per-residue terms instead of stacked heights.
+    #[serde(default)]
+    pub show_depleted: bool,
+}
+
+fn default_identity_threshold() -> f64 {
+    DEFAULT_IDENTITY_THRESHOLD
+}
+
+/// Request for [`compute_frequency_logo`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct FrequencyLogoRequest {
+    /// Residue frequencies per column, aligned to the alphabet; each column
+    /// is normalized to sum to one.
+    #[serde(deserialize_with = "plugin_io::lenient::float_matrix")]
+    pub frequencies: Vec<Vec<f64>>,
+    /// Residues shown in the logo, as single characters.
+    pub alphabet: Vec<String>,
+    /// Background frequencies the column frequencies are compared with.
+    #[serde(default)]
+    pub background: BackgroundSpec,
+    /// Returns signed per-residue terms instead of stacked heights.
+    #[serde(default)]
+    pub show_depleted: bool,
+}
+
+/// Background residue frequencies.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum BackgroundSpec {
+    /// `"uniform"`, or `"blosum62"` for the amino acid frequencies implied
+    /// by BLOSUM62.
+    Named(String),
+    /// Frequencies aligned to the alphabet; normalized to sum to one.
+    Frequencies(Vec<f64>),
+}
+
+impl Default for BackgroundSpec {
+    fn default() -> Self {
+        Self::Named("uniform".into())
+    }
+}
+
+/// Pseudocounts added to observed residue frequencies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PseudocountMethod {
+    /// Observed frequencies only.
+    #[default]
+    None,
+    /// A Dirichlet prior with total weight `w` spread by background
+    /// frequency: `q = (c + w p) / (n + w)`.
+    Dirichlet,
+    /// Substitution-derived pseudocounts as in PSI-BLAST: observed
+    /// frequencies are mixed with `g_a = sum_b f_b q(a | b)` from BLOSUM62
+    /// target frequencies, weighted `n - 1` to `w`.
+    Blosum62,
+}
+
+/// Logo data for one column.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogoColumn {
+    /// Residues with a non-zero frequency, in alphabet order.
+    pub residues: Vec<String>,
+    /// Frequency of each residue after pseudocounts.
+    pub frequencies: Vec<f64>,
+    /// Letter height in bits; negative for depleted residues.
+    pub heights: Vec<f64>,
+    /// Weighted fraction of sequences with a residue in this column.
+    pub occupancy: f64,
+    /// Relative entropy to the background, in bits.
+    pub relative_entropy: f64,
+}
+
+/// Response of [`compute_logo`] and [`compute_frequency_logo`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogoResponse {
+    /// Logo data for each column of the window.
+    pub columns: Vec<LogoColumn>,
+    /// Background frequencies aligned to the alphabet.
+    pub background: Vec<f64>,
+    /// Effective number of sequences under the requested weighting.
+    pub effective_sequences: f64,
+}
+
+/// Computes relative-entropy logo heights for a window of an alignment.
+pub fn compute_logo(request: &LogoRequest) -> Result<LogoResponse, String> {
+    let lookup = residue_lookup(&request.alphabet)?;
+    let size = request.alphabet.len();
+    if size == 0 {
+        return Err("alphabet must not be empty".into());
+    }
+    let background = resolve_background(&request.background, &request.alphabet)?;
+    let conditional = match request.pseudocounts {
+        PseudocountMethod::Blosum62 => Some(blosum62_conditionals(&request.alphabet)?),
+        _ => None,
+    };
+    let pseudocount_weight = request
+        .pseudocount_weight
+        .unwrap_or(match request.pseudocounts {
+            PseudocountMethod::Blosum62 => 10.0,
+            _ => 1.0,
+        });
+    if !(pseudocount_weight.is_finite() && pseudocount_weight >= 0.0) {
+        return Err(format!(
+            "pseudocount_weight must be a non-negative number, got {pseudocount_weight}"
+        ));
+    }
+
+    let sequences: Vec<&[u8]> = request.sequences.iter().map(|s| s.as_bytes()).collect();
+    let weights = weights_for(
+        &sequences,
+        &lookup,
+        size,
+        request.weighting,
+        request.identity_threshold,
+    )?;
+    let end = request
+        .end
+        .unwrap_or_else(|| sequences.iter().map(|s| s.len()).max().unwrap_or(0));
+    if request.start > end {
+        return Err(format!(
+            "start ({}) must not be greater than end ({end})",
+            request.start
+        ));
+    }
+
+    let mut columns = Vec::with_capacity(end - request.start);
+    let mut counts = vec![0.0; size];
+    for pos in request.start..end {
+        counts.fill(0.0);
+        for (seq, &weight) in sequences.iter().zip(&weights.weights) {
+            if let Some(index) = seq.get(pos).and_then(|&b| lookup[b as usize]) {
+                counts[index] += weight;
+            }
+        }
+        let total: f64 = counts.iter().sum();
+        if total == 0.0 {
+            columns.push(LogoColumn {
+                residues: Vec::new(),
+                frequencies: Vec::new(),
+                heights: Vec::new(),
+                occupancy: 0.0,
+                relative_entropy: 0.0,
+            });
+            continue;
+        }
+
+        let frequencies: Vec<f64> = match (request.pseudocounts, &conditional) {
+            (PseudocountMethod::Dirichlet, _) => counts
+                .iter()
+                .zip(&background)
+                .map(|(c, p)| (c + pseudocount_weight * p) / (total + pseudocount_weight))
+                .collect(),
+            (PseudocountMethod::Blosum62, Some(conditional)) => {
+                let observed: Vec<f64> = counts.iter().map(|c| c / total).collect();
+                let alpha = (total - 1.0).max(0.0);
+                let beta = pseudocount_weight;
+                (0..size)
+                    .map(|a| {
+                        let g: f64 = (0..size)
+                            .map(|b| observed[b] * conditional[b * size + a])
+                            .sum();
+                        if alpha + beta == 0.0 {
+                            g
+                        } else {
+                            (alpha * observed[a] + beta * g) / (alpha + beta)
+                        }
+                    })
+                    .collect()
+            }
+            _ => counts.iter().map(|c| c / total).collect(),
+        };
+
+        let occupancy = if weights.effective_sequences == 0.0 {
+            0.0
+        } else {
+            total / weights.effective_sequences
+        };
+        columns.push(logo_column(
+            &request.alphabet,
+            &frequencies,
+            &background,
+            occupancy,
+            request.show_depleted,
+        ));
+    }
+
+    Ok(LogoResponse {
+        columns,
+        background,
+        effective_sequences: weights.effective_sequences,
+    })
+}
+
+/// Computes relative-entropy logo heights from column frequencies, such as
+/// a motif matrix, instead of from sequences.
+///
+/// Every column has full occupancy, and `effective_sequences` is reported
+/// as zero since no sequences are involved.
+pub fn compute_frequency_logo(request: &FrequencyLogoRequest) -> Result<LogoResponse, String> {
+    if request.alphabet.is_empty() {
+        return Err("alphabet must not be empty".into());
+    }
+    let background = resolve_background(&request.background, &request.alphabet)?;
+    let columns = request
+        .frequencies
+        .iter()
+        .enumerate()
+        .map(|(position, column)| {
+            if column.len() != request.alphabet.len() {
+                return Err(format!(
+                    "column {position} has {} frequencies, expected {} for the alphabet",
+                    column.len(),
+                    request.alphabet.len()
+                ));
+            }
+            if column.iter().any(|f| !(f.is_finite() && *f >= 0.0)) {
+                return Err(format!(
+                    "column {position} has a negative or invalid frequency"
+                ));
+            }
+            let total: f64 = column.iter().sum();
+            if total == 0.0 {
+                return Err(format!("column {position} has no non-zero frequency"));
+            }
+            let frequencies: Vec<f64> = column.iter().map(|f| f / total).collect();
+            Ok(logo_column(
+                &request.alphabet,
+                &frequencies,
+                &background,
+                1.0,
+                request.show_depleted,
+            ))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(LogoResponse {
+        columns,
+        background,
+        effective_sequences: 0.0,
+    })
+}
+
+/// Letter heights for one column of frequencies aligned to `alphabet`.
+fn logo_column(
+    alphabet: &[String],
+    frequencies: &[f64],
+    background: &[f64],
+    occupancy: f64,
+    show_depleted: bool,
+) -> LogoColumn {
+    let terms: Vec<f64> = frequencies
+        .iter()
+        .zip(background)
+        .map(|(&q, &p)| if q > 0.0 { q * (q / p).log2() } else { 0.0 })
+        .collect();
+    let relative_entropy: f64 = terms.iter().sum::<f64>().max(0.0);
+
+    let mut column = LogoColumn {
+        residues: Vec::new(),
+        frequencies: Vec::new(),
+        heights: Vec::new(),
+        occupancy,
+        relative_entropy,
+    };
+    for (index, (&q, &term)) in frequencies.iter().zip(&terms).enumerate() {
+        if q <= 0.0 {
+            continue;
+        }
+        column.residues.push(alphabet[index].to_ascii_uppercase());
+        column.frequencies.push(q);
+        column.heights.push(if show_depleted {
+            occupancy * term
+        } else {
+            occupancy * q * relative_entropy
+        });
+    }
+    column
+}
+
+pub(crate) fn resolve_background(
+    spec: &BackgroundSpec,
+    alphabet: &[String],
+) -> Result<Vec<f64>, String> {
+    let size = alphabet.len();
+    let frequencies = match spec {
+        BackgroundSpec::Named(name) => match name.to_ascii_lowercase().as_str() {
+            "uniform" => vec![1.0; size],
+            "blosum62" => {
+                let (matrix, stats) = blosum62()?;
+                alphabet
+                    .iter()
+                    .map(|residue| {
+                        matrix_index(&matrix, residue)
+                            .map(|i| stats.background_frequencies[i])
+                            .ok_or_else(|| blosum62_residue_error(residue))
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            _ => {
+                return Err(format!(
+                    "Unknown background '{name}'; expected \"uniform\", \"blosum62\" or a list of frequencies"
+                ));
+            }
+        },
+        BackgroundSpec::Frequencies(frequencies) => {
+            if frequencies.len() != size {
+                return Err(format!(
+                    "background has {} frequencies, expected {size} for the alphabet",
+                    frequencies.len()
+                ));
+            }
+            frequencies.clone()
+        }
+    };
+    if let Some(bad) = frequencies.iter().find(|f| !(f.is_finite() && **f > 0.0)) {
+        return Err(format!(
+            "background frequencies must be positive, got {bad}"
+        ));
+    }
+    let total: f64 = frequencies.iter().sum();
+    Ok(frequencies.iter().map(|f| f / total).collect())
+}
+
+/// Row-major `q(a | b)` at `[b * size + a]` from BLOSUM62 target
+/// frequencies restricted to `alphabet`.
+fn blosum62_conditionals(alphabet: &[String]) -> Result<Vec<f64>, String> {
+    let (matrix, stats) = blosum62()?;
+    let dim = matrix.score_dimension();
+    let indices = alphabet
+        .iter()
+        .map(|residue| {
+            matrix_index(&matrix, residue).ok_or_else(|| blosum62_residue_error(residue))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let size = alphabet.len();
+    let mut conditional = vec![0.0; size * size];
+    for (b, &j) in indices.iter().enumerate() {
+        let row: Vec<f64> = indices
+            .iter()
+            .map(|&i| stats.target_frequencies[j * dim + i])
+            .collect();
+        let total: f64 = row.iter().sum();
+        for (a, value) in row.iter().enumerate() {
+            conditional[b * size + a] = value / total;
+        }
+    }
+    Ok(conditional)
+}
+
+fn blosum62() -> Result<(BuiltinMatrix, seq_align::MatrixStatistics), String> {
+    let matrix = BuiltinMatrix::Blosum62;
+    let stats = matrix_statistics(matrix.alphabet(), matrix.scores())
+        .ok_or("BLOSUM62 target frequencies are unavailable")?;
+    Ok((matrix, stats))
+}
+
+pub(crate) fn matrix_index(matrix: &BuiltinMatrix, residue: &str) -> Option<usize> {
+    let &[byte] = residue.as_bytes() else {
+        return None;
+    };
+    matrix.lookup_map()[byte as usize].map(usize::from)
+}
+
+fn blosum62_residue_error(residue: &str) -> String {
+    format!("BLOSUM62 has no residue '{residue}'; use it only with amino acid alphabets")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(sequences: &[&str], alphabet: &str) -> LogoRequest {
+        LogoRequest {
+            sequences: sequences.iter().map(|s| s.to_string()).collect(),
+            alphabet: alphabet.chars().map(String::from).collect(),
+            start: 0,
+            end: None,
+            weighting: SequenceWeighting::None,
+            identity_threshold: DEFAULT_IDENTITY_THRESHOLD,
+            background: BackgroundSpec::default(),
+            pseudocounts: PseudocountMethod::None,
+            pseudocount_weight: None,
+            show_depleted: false,
+        }
+    }
+
+    #[test]
+    fn test_uniform_background_matches_shannon_information() {
+        let response = compute_logo(&request(&["A", "A", "C", "-"], "ACGT")).unwrap();
+        let column = &response.columns[0];
+        let entropy = -(2.0 / 3.0 * (2.0f64 / 3.0).log2() + 1.0 / 3.0 * (1.0f64 / 3.0).log2());
+        assert!((column.relative_entropy - (2.0 - entropy)).abs() < 1e-12);
+        assert_eq!(column.residues, ["A", "C"]);
+        let stack: f64 = column.heights.iter().sum();
+        assert!((stack - 0.75 * column.relative_entropy).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_skewed_background_and_depletion() {
+        let mut req = request(&["A", "A", "G", "C"], "ACGT");
+        req.background = BackgroundSpec::Frequencies(vec![0.3, 0.2, 0.2, 0.3]);
+        req.show_depleted = true;
+        let column = &compute_logo(&req).unwrap().columns[0];
+
+        // A is enriched (0.5 vs 0.3); C and G sit at 0.25 vs 0.2.
+        assert!(column.heights.iter().all(|&h| h > 0.0));
+        let net: f64 = column.heights.iter().sum();
+        assert!((net - column.relative_entropy).abs() < 1e-12);
+
+        req.sequences = vec!["A".into(), "A".into(), "A".into(), "G".into()];
+        req.pseudocounts = PseudocountMethod::Dirichlet;
+        req.pseudocount_weight = Some(4.0);
+        let column = &compute_logo(&req).unwrap().columns[0];
+        // T is unobserved but gets a pseudocount and is depleted.
+        let t = column.residues.iter().position(|r| r == "T").unwrap();
+        assert!(column.heights[t] < 0.0);
+        assert!((column.frequencies.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_blosum62_pseudocounts() {
+        let mut req = request(&["W"], "ARNDCQEGHILKMFPSTWYV");
+        req.background = BackgroundSpec::Named("blosum62".into());
+        req.pseudocounts = PseudocountMethod::Blosum62;
+        let response = compute_logo(&req).unwrap();
+        let column = &response.columns[0];
+        assert_eq!(column.residues.len(), 20);
+        let w = column.residues.iter().position(|r| r == "W").unwrap();
+        let best = column
+            .frequencies
+            .iter()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max);
+        assert_eq!(column.frequencies[w], best);
+
+        let dna = LogoRequest {
+            pseudocounts: PseudocountMethod::Blosum62,
+            ..request(&["A"], "ACGU")
+        };
+        assert!(compute_logo(&dna).is_err());
+    }
+
+    #[test]
+    fn test_frequency_logo() {
+        let request = FrequencyLogoRequest {
+            frequencies: vec![vec![1.0, 0.0, 0.0, 0.0], vec![2.0, 2.0, 0.0, 0.0]],
+            alphabet: "ACGT".chars().map(String::from).collect(),
+            background: BackgroundSpec::default(),
+            show_depleted: false,
+        };
+        let response = compute_frequency_logo(&request).unwrap();
+        assert_eq!(response.columns[0].heights, [2.0]);
+        assert_eq!(response.columns[1].residues, ["A", "C"]);
+        assert_eq!(response.columns[1].heights, [0.5, 0.5]);
+
+        let ragged = FrequencyLogoRequest {
+            frequencies: vec![vec![1.0]],
+            ..request
+        };
+        assert!(compute_frequency_logo(&ragged).is_err());
+    }
+}
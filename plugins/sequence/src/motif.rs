@@ -0,0 +1,495 @@
+//! Position weight matrices and motif scanning.
+//!
+//! A PWM is built from an ungapped motif alignment: column counts get a
+//! pseudocount spread over the residues by background frequency, and scores
+//! are log2 odds of the smoothed frequencies against the background.
+//! Scanning reports windows of a sequence whose score passes a threshold, on
+//! one or both strands, as genome-map features. P-values are exact for the
+//! scores rounded to 0.01 bits, from the score distribution of background
+//! sequences.
+
+use std::collections::BTreeMap;
+
+use genome_map::GenomeMapFeature;
+use serde::{Deserialize, Serialize};
+
+use crate::column_stats::residue_lookup;
+use crate::logo::{BackgroundSpec, resolve_background};
+
+/// Score steps per bit for p-value computation.
+const P_VALUE_RESOLUTION: f64 = 100.0;
+
+fn default_pseudocount() -> f64 {
+    0.8
+}
+
+/// Request for [`build_pwm`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct BuildPwmRequest {
+    /// Ungapped motif instances, all the same length.
+    pub sequences: Vec<String>,
+    /// Residues counted, as single characters.
+    pub alphabet: Vec<String>,
+    /// Background frequencies for the pseudocounts and log odds.
+    #[serde(default)]
+    pub background: BackgroundSpec,
+    /// Total pseudocount added to each column.
+    #[serde(
+        default = "default_pseudocount",
+        deserialize_with = "plugin_io::lenient::float"
+    )]
+    pub pseudocount: f64,
+}
+
+/// A position weight matrix. Rows are motif positions, columns follow
+/// `alphabet`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Pwm {
+    /// Residues, in the order of the inner arrays.
+    pub alphabet: Vec<String>,
+    /// Background frequencies aligned to `alphabet`, summing to one.
+    pub background: Vec<f64>,
+    /// Residue counts per position.
+    pub counts: Vec<Vec<f64>>,
+    /// Frequencies per position after pseudocounts.
+    pub frequencies: Vec<Vec<f64>>,
+    /// Log2 odds of `frequencies` against `background`.
+    pub scores: Vec<Vec<f64>>,
+}
+
+/// Builds a PWM from an ungapped motif alignment.
+///
+/// Residues outside the alphabet, such as `N`, are not counted; gaps are an
+/// error.
+pub fn build_pwm(request: &BuildPwmRequest) -> Result<Pwm, String> {
+    if request.pseudocount.is_nan() || request.pseudocount <= 0.0 {
+        return Err("pseudocount must be positive".to_string());
+    }
+    let lookup = residue_lookup(&request.alphabet)?;
+    let background = resolve_background(&request.background, &request.alphabet)?;
+    let Some(first) = request.sequences.first() else {
+        return Err("motif alignment must contain at least one sequence".to_string());
+    };
+    let width = first.len();
+    if width == 0 {
+        return Err("motif sequences must not be empty".to_string());
+    }
+
+    let size = request.alphabet.len();
+    let mut counts = vec![vec![0.0; size]; width];
+    for (index, sequence) in request.sequences.iter().enumerate() {
+        if sequence.len() != width {
+            return Err(format!(
+                "motif sequence {} has length {}, expected {width}",
+                index + 1,
+                sequence.len()
+            ));
+        }
+        for (column, byte) in sequence.bytes().enumerate() {
+            if matches!(byte, b'-' | b'.') {
+                return Err(format!(
+                    "motif sequence {} has a gap at position {}; the motif alignment must be ungapped",
+                    index + 1,
+                    column + 1
+                ));
+            }
+            if let Some(residue) = lookup[byte as usize] {
+                counts[column][residue] += 1.0;
+            }
+        }
+    }
+
+    pwm_from_counts(&request.alphabet, background, counts, request.pseudocount)
+}
+
+/// Smooths column counts with a background-weighted pseudocount and turns
+/// them into log-odds scores.
+pub(crate) fn pwm_from_counts(
+    alphabet: &[String],
+    background: Vec<f64>,
+    counts: Vec<Vec<f64>>,
+    pseudocount: f64,
+) -> Result<Pwm, String> {
+    let mut frequencies = Vec::with_capacity(counts.len());
+    let mut scores = Vec::with_capacity(counts.len());
+    for column in &counts {
+        let total: f64 = column.iter().sum::<f64>() + pseudocount;
+        if total <= 0.0 {
+            return Err("motif column has no counts".to_string());
+        }
+        let column_frequencies: Vec<f64> = column
+            .iter()
+            .zip(&background)
+            .map(|(count, bg)| (count + pseudocount * bg) / total)
+            .collect();
+        scores.push(
+            column_frequencies
+                .iter()
+                .zip(&background)
+                .map(|(frequency, bg)| (frequency / bg).log2())
+                .collect(),
+        );
+        frequencies.push(column_frequencies);
+    }
+    Ok(Pwm {
+        alphabet: alphabet.to_vec(),
+        background,
+        counts,
+        frequencies,
+        scores,
+    })
+}
+
+/// Strands scanned by [`scan_motif`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScanStrand {
+    /// Both strands.
+    #[default]
+    Both,
+    /// The given strand only.
+    Positive,
+    /// The reverse complement only.
+    Negative,
+}
+
+fn default_accession() -> String {
+    "sequence".to_string()
+}
+
+fn default_feature_type() -> String {
+    "sequence_motif".to_string()
+}
+
+/// Request for [`scan_motif`]. `alphabet`, `background` and `scores` are
+/// read from a [`Pwm`]; its other fields are ignored.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScanMotifRequest {
+    /// Residues, in the order of the inner arrays of `scores`.
+    pub alphabet: Vec<String>,
+    /// Background frequencies aligned to `alphabet`, for p-values.
+    #[serde(deserialize_with = "plugin_io::lenient::float_vec")]
+    pub background: Vec<f64>,
+    /// Log2-odds score per position and residue.
+    #[serde(deserialize_with = "plugin_io::lenient::float_matrix")]
+    pub scores: Vec<Vec<f64>>,
+    /// Sequence to scan.
+    pub sequence: String,
+    /// Accession written into the returned features.
+    #[serde(default = "default_accession")]
+    pub accession: String,
+    /// Strands to scan.
+    #[serde(default)]
+    pub strand: ScanStrand,
+    /// Lowest score reported, in bits.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub min_score: Option<f64>,
+    /// Highest p-value reported.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub max_p_value: Option<f64>,
+    /// Label of the returned features; `None` leaves them unlabelled.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Feature type of the returned features.
+    #[serde(default = "default_feature_type")]
+    pub feature_type: String,
+}
+
+/// Complementary residue index for each alphabet entry, for nucleotide
+/// alphabets.
+fn complement_indices(alphabet: &[String]) -> Result<Vec<usize>, String> {
+    let position = |residue: &str| {
+        alphabet
+            .iter()
+            .position(|other| other.eq_ignore_ascii_case(residue))
+    };
+    alphabet
+        .iter()
+        .map(|residue| {
+            let complement = match residue.to_ascii_uppercase().as_str() {
+                "A" => position("T").or_else(|| position("U")),
+                "T" | "U" => position("A"),
+                "C" => position("G"),
+                "G" => position("C"),
+                _ => None,
+            };
+            complement.ok_or_else(|| {
+                "scanning the negative strand needs a DNA or RNA alphabet".to_string()
+            })
+        })
+        .collect()
+}
+
+/// Probability that a background sequence scores at least each rounded
+/// score, indexed by rounded score minus the lowest possible one.
+struct ScoreDistribution {
+    offset: i64,
+    tail: Vec<f64>,
+}
+
+impl ScoreDistribution {
+    fn new(rounded: &[Vec<i64>], background: &[f64]) -> Self {
+        let mut offset = 0i64;
+        let mut probabilities = vec![1.0];
+        for column in rounded {
+            let low = column.iter().copied().min().unwrap_or(0);
+            let high = column.iter().copied().max().unwrap_or(0);
+            let mut next = vec![0.0; probabilities.len() + (high - low) as usize];
+            for (score, probability) in probabilities.iter().enumerate() {
+                if *probability == 0.0 {
+                    continue;
+                }
+                for (residue, bg) in column.iter().zip(background) {
+                    next[score + (residue - low) as usize] += probability * bg;
+                }
+            }
+            offset += low;
+            probabilities = next;
+        }
+        let mut tail = probabilities;
+        for index in (0..tail.len().saturating_sub(1)).rev() {
+            tail[index] += tail[index + 1];
+        }
+        Self { offset, tail }
+    }
+
+    fn p_value(&self, rounded_score: i64) -> f64 {
+        let index = (rounded_score - self.offset).max(0) as usize;
+        self.tail.get(index).copied().unwrap_or(0.0).min(1.0)
+    }
+}
+
+/// Scans a sequence for motif hits and returns them as genome-map features.
+///
+/// Windows with residues outside the alphabet are skipped. Negative-strand
+/// hits score the reverse complement of the window and are reported in
+/// forward coordinates. Each feature carries its score, and its p-value and
+/// matched sequence (read on the hit's strand) as attributes.
+pub fn scan_motif(request: &ScanMotifRequest) -> Result<Vec<GenomeMapFeature>, String> {
+    if request.min_score.is_none() && request.max_p_value.is_none() {
+        return Err("scanning needs a score or p-value threshold".to_string());
+    }
+    let size = request.alphabet.len();
+    if request.background.len() != size {
+        return Err(format!(
+            "background has {} frequencies, expected {size} for the alphabet",
+            request.background.len()
+        ));
+    }
+    if let Some(bad) = request
+        .background
+        .iter()
+        .find(|f| !(f.is_finite() && **f > 0.0))
+    {
+        return Err(format!(
+            "background frequencies must be positive, got {bad}"
+        ));
+    }
+    let width = request.scores.len();
+    if width == 0 {
+        return Err("motif has no positions".to_string());
+    }
+    if let Some(row) = request.scores.iter().position(|row| row.len() != size) {
+        return Err(format!(
+            "motif position {} has {} scores, expected {size}",
+            row + 1,
+            request.scores[row].len()
+        ));
+    }
+    if let Some(bad) = request
+        .scores
+        .iter()
+        .flatten()
+        .find(|score| !score.is_finite())
+    {
+        return Err(format!("motif scores must be finite, got {bad}"));
+    }
+    let lookup = residue_lookup(&request.alphabet)?;
+    let complement = match request.strand {
+        ScanStrand::Positive => None,
+        _ => Some(complement_indices(&request.alphabet)?),
+    };
+
+    let total: f64 = request.background.iter().sum();
+    let background: Vec<f64> = request.background.iter().map(|f| f / total).collect();
+    let rounded: Vec<Vec<i64>> = request
+        .scores
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|score| (score * P_VALUE_RESOLUTION).round() as i64)
+                .collect()
+        })
+        .collect();
+    let distribution = ScoreDistribution::new(&rounded, &background);
+
+    let sequence = request.sequence.as_bytes();
+    let residues: Vec<Option<usize>> = sequence.iter().map(|&b| lookup[b as usize]).collect();
+    let mut hits = Vec::new();
+    for start in 0..(sequence.len() + 1).saturating_sub(width) {
+        let Some(window) = residues[start..start + width]
+            .iter()
+            .copied()
+            .collect::<Option<Vec<usize>>>()
+        else {
+            continue;
+        };
+        let strands: &[i8] = match request.strand {
+            ScanStrand::Both => &[1, -1],
+            ScanStrand::Positive => &[1],
+            ScanStrand::Negative => &[-1],
+        };
+        for &strand in strands {
+            // Residue index at each motif position.
+            let read: Vec<usize> = match (strand, &complement) {
+                (-1, Some(complement)) => window.iter().rev().map(|&r| complement[r]).collect(),
+                _ => window.clone(),
+            };
+            let score: f64 = read
+                .iter()
+                .enumerate()
+                .map(|(position, &residue)| request.scores[position][residue])
+                .sum();
+            let rounded_score: i64 = read
+                .iter()
+                .enumerate()
+                .map(|(position, &residue)| rounded[position][residue])
+                .sum();
+            let p_value = distribution.p_value(rounded_score);
+            if request.min_score.is_some_and(|min| score < min)
+                || request.max_p_value.is_some_and(|max| p_value > max)
+            {
+                continue;
+            }
+            let matched: String = read
+                .iter()
+                .map(|&residue| request.alphabet[residue].as_str())
+                .collect();
+            let attributes = BTreeMap::from([
+                ("p-value".to_string(), vec![format!("{p_value:e}")]),
+                ("sequence".to_string(), vec![matched]),
+            ]);
+            hits.push(GenomeMapFeature {
+                start: start as u64 + 1,
+                end: (start + width) as u64,
+                strand: Some(strand),
+                label: request.label.clone(),
+                partial: false,
+                accession: request.accession.clone(),
+                feature_type: request.feature_type.clone(),
+                source: "sequence".to_string(),
+                score: Some(score),
+                phase: None,
+                attributes,
+                original_start: start as u64 + 1,
+                original_end: (start + width) as u64,
+            });
+        }
+    }
+    Ok(hits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dna() -> Vec<String> {
+        "ACGT".chars().map(String::from).collect()
+    }
+
+    fn pwm(sequences: &[&str]) -> Pwm {
+        build_pwm(&BuildPwmRequest {
+            sequences: sequences.iter().map(|s| s.to_string()).collect(),
+            alphabet: dna(),
+            background: BackgroundSpec::default(),
+            pseudocount: 0.8,
+        })
+        .unwrap()
+    }
+
+    fn scan(pwm: &Pwm, sequence: &str, strand: ScanStrand) -> ScanMotifRequest {
+        ScanMotifRequest {
+            alphabet: pwm.alphabet.clone(),
+            background: pwm.background.clone(),
+            scores: pwm.scores.clone(),
+            sequence: sequence.to_string(),
+            accession: default_accession(),
+            strand,
+            min_score: None,
+            max_p_value: None,
+            label: Some("site".to_string()),
+            feature_type: default_feature_type(),
+        }
+    }
+
+    #[test]
+    fn test_build_pwm() {
+        let pwm = pwm(&["GAT", "GAC", "GNT"]);
+        assert_eq!(pwm.counts[1], [2.0, 0.0, 0.0, 0.0]);
+        // Three G plus 0.2 pseudocount of 3.8 in total.
+        assert!((pwm.frequencies[0][2] - 3.2 / 3.8).abs() < 1e-12);
+        assert!((pwm.scores[0][2] - (4.0 * 3.2 / 3.8f64).log2()).abs() < 1e-12);
+        assert!(pwm.scores[0][0] < 0.0);
+
+        let gapped = build_pwm(&BuildPwmRequest {
+            sequences: vec!["GA-".to_string()],
+            alphabet: dna(),
+            background: BackgroundSpec::default(),
+            pseudocount: 0.8,
+        });
+        assert!(gapped.unwrap_err().contains("ungapped"));
+    }
+
+    #[test]
+    fn test_scan_both_strands() {
+        let pwm = pwm(&["GATA", "GATA", "GATA"]);
+        let mut request = scan(&pwm, "CCGATACCTATCC", ScanStrand::Both);
+        request.min_score = Some(4.0);
+        let hits = scan_motif(&request).unwrap();
+        let found: Vec<(u64, u64, Option<i8>)> =
+            hits.iter().map(|h| (h.start, h.end, h.strand)).collect();
+        assert_eq!(found, [(3, 6, Some(1)), (9, 12, Some(-1))]);
+        assert_eq!(hits[1].attributes["sequence"], ["GATA"]);
+        assert_eq!(hits[0].label.as_deref(), Some("site"));
+
+        request.strand = ScanStrand::Positive;
+        assert_eq!(scan_motif(&request).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_p_values() {
+        let pwm = pwm(&["GATA", "GATA", "GATA"]);
+        let mut request = scan(&pwm, "GATACCCC", ScanStrand::Positive);
+        request.max_p_value = Some(1.0);
+        let hits = scan_motif(&request).unwrap();
+        assert_eq!(hits.len(), 5);
+        // GATA is the single best of 256 equally likely 4-mers.
+        let best: f64 = hits[0].attributes["p-value"][0].parse().unwrap();
+        assert!((best - 1.0 / 256.0).abs() < 1e-12);
+
+        request.max_p_value = Some(0.01);
+        assert_eq!(scan_motif(&request).unwrap().len(), 1);
+        request.max_p_value = None;
+        assert!(scan_motif(&request).is_err());
+    }
+
+    #[test]
+    fn test_non_finite_scores_and_background_are_rejected() {
+        let pwm = pwm(&["GATA", "GATA", "GATA"]);
+        let mut request = scan(&pwm, "GATACCCC", ScanStrand::Positive);
+        request.min_score = Some(0.0);
+        request.scores[1][0] = f64::NEG_INFINITY;
+        assert_eq!(
+            scan_motif(&request).unwrap_err(),
+            "motif scores must be finite, got -inf"
+        );
+
+        let mut request = scan(&pwm, "GATACCCC", ScanStrand::Positive);
+        request.min_score = Some(0.0);
+        request.background[2] = f64::NAN;
+        assert_eq!(
+            scan_motif(&request).unwrap_err(),
+            "background frequencies must be positive, got NaN"
+        );
+    }
+}
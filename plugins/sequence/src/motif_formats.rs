@@ -0,0 +1,511 @@
+//! MEME, JASPAR and TRANSFAC motif matrix readers.
+//!
+//! Every format is read into per-position residue frequencies aligned to the
+//! motif's alphabet. Count matrices keep their counts alongside; MEME
+//! letter-probability matrices only have frequencies.
+//!
+//! - MEME minimal format: the `ALPHABET=` line or an `ALPHABET ... END
+//!   ALPHABET` block (core letters, sorted), `Background letter
+//!   frequencies`, and one `letter-probability matrix` per `MOTIF`.
+//! - JASPAR: `>` headers followed by `A [ ... ]` rows (`.jaspar`) or bare
+//!   rows of counts in `A`, `C`, `G`, `T` order (`.pfm`, header optional).
+//! - TRANSFAC: `ID`/`AC`/`NA` lines, a `P0` (or `PO`) header naming the
+//!   columns and numbered rows, with records ending at `//`.
+
+use serde::{Deserialize, Serialize};
+
+/// Motif file format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MotifFormat {
+    /// Detected from the first non-blank line.
+    #[default]
+    Auto,
+    /// MEME minimal motif format.
+    Meme,
+    /// JASPAR count matrices with bracketed rows.
+    Jaspar,
+    /// Plain count matrices, one row per residue.
+    Pfm,
+    /// TRANSFAC matrices.
+    Transfac,
+}
+
+/// Options for [`parse_motifs`].
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ParseMotifsConfig {
+    /// File format, or detected from the first non-blank line.
+    #[serde(default)]
+    pub format: MotifFormat,
+}
+
+/// One motif as per-position residue frequencies.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Motif {
+    /// Motif identifier.
+    pub id: String,
+    /// Motif name, when the file gives one.
+    pub name: Option<String>,
+    /// Residues, in the order of the inner arrays.
+    pub alphabet: Vec<String>,
+    /// Frequencies per position, each summing to one.
+    pub frequencies: Vec<Vec<f64>>,
+    /// Counts per position, for count matrices.
+    pub counts: Option<Vec<Vec<f64>>>,
+    /// Number of sites the matrix was built from, when known.
+    pub sites: Option<f64>,
+    /// Background frequencies given in the file, aligned to `alphabet`.
+    pub background: Option<Vec<f64>>,
+}
+
+const NUCLEOTIDES: [&str; 4] = ["A", "C", "G", "T"];
+
+/// Parses every motif in a MEME, JASPAR or TRANSFAC file.
+pub fn parse_motifs(data: &str, config: &ParseMotifsConfig) -> Result<Vec<Motif>, String> {
+    let format = match config.format {
+        MotifFormat::Auto => detect_format(data)?,
+        format => format,
+    };
+    let motifs = match format {
+        MotifFormat::Meme => parse_meme(data)?,
+        MotifFormat::Jaspar | MotifFormat::Pfm => parse_jaspar(data)?,
+        MotifFormat::Transfac => parse_transfac(data)?,
+        MotifFormat::Auto => unreachable!("format was detected above"),
+    };
+    if motifs.is_empty() {
+        return Err("no motifs found".to_string());
+    }
+    Ok(motifs)
+}
+
+fn detect_format(data: &str) -> Result<MotifFormat, String> {
+    let mut lines = data.lines().map(str::trim).filter(|line| !line.is_empty());
+    let Some(first) = lines.next() else {
+        return Err("motif data is empty".to_string());
+    };
+    if first.starts_with("MEME version") {
+        return Ok(MotifFormat::Meme);
+    }
+    if first.starts_with('>') {
+        let bracketed = lines.next().is_some_and(|line| line.contains('['));
+        return Ok(if bracketed {
+            MotifFormat::Jaspar
+        } else {
+            MotifFormat::Pfm
+        });
+    }
+    if parse_numbers(first).is_some() {
+        return Ok(MotifFormat::Pfm);
+    }
+    let code = first.split_whitespace().next().unwrap_or_default();
+    if matches!(
+        code,
+        "AC" | "ID" | "NA" | "DE" | "BF" | "P0" | "PO" | "XX" | "VV" | "//"
+    ) {
+        return Ok(MotifFormat::Transfac);
+    }
+    Err(format!(
+        "could not detect the motif format from the first line {first:?}"
+    ))
+}
+
+/// All whitespace-separated fields as numbers, or `None` if any is not.
+fn parse_numbers(line: &str) -> Option<Vec<f64>> {
+    let values: Option<Vec<f64>> = line.split_whitespace().map(|v| v.parse().ok()).collect();
+    values.filter(|values| !values.is_empty())
+}
+
+/// Divides each row by its sum.
+fn normalize(rows: &[Vec<f64>], id: &str) -> Result<Vec<Vec<f64>>, String> {
+    rows.iter()
+        .enumerate()
+        .map(|(position, row)| {
+            if row.iter().any(|value| !value.is_finite() || *value < 0.0) {
+                return Err(format!(
+                    "motif {id} position {} has a negative or invalid value",
+                    position + 1
+                ));
+            }
+            let total: f64 = row.iter().sum();
+            if total <= 0.0 {
+                return Err(format!("motif {id} position {} is all zero", position + 1));
+            }
+            Ok(row.iter().map(|value| value / total).collect())
+        })
+        .collect()
+}
+
+fn count_motif(
+    id: String,
+    name: Option<String>,
+    alphabet: Vec<String>,
+    counts: Vec<Vec<f64>>,
+) -> Result<Motif, String> {
+    if counts.is_empty() {
+        return Err(format!("motif {id} has no positions"));
+    }
+    let frequencies = normalize(&counts, &id)?;
+    let sites = counts
+        .iter()
+        .map(|row| row.iter().sum::<f64>())
+        .reduce(f64::max);
+    Ok(Motif {
+        id,
+        name,
+        alphabet,
+        frequencies,
+        counts: Some(counts),
+        sites,
+        background: None,
+    })
+}
+
+fn parse_meme(data: &str) -> Result<Vec<Motif>, String> {
+    let lines: Vec<&str> = data.lines().collect();
+    let mut alphabet: Vec<String> = NUCLEOTIDES.iter().map(|s| s.to_string()).collect();
+    let mut background: Option<Vec<(String, f64)>> = None;
+    let mut motifs = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index].trim();
+        index += 1;
+        if let Some(letters) = line.strip_prefix("ALPHABET=") {
+            alphabet = letters.trim().chars().map(String::from).collect();
+        } else if line.starts_with("ALPHABET") {
+            // Custom alphabet block: core letters are followed by a quoted
+            // name, optionally paired with `~`.
+            let mut core = Vec::new();
+            while index < lines.len() && !lines[index].trim().starts_with("END ALPHABET") {
+                let tokens: Vec<&str> = lines[index].split_whitespace().collect();
+                if !tokens.contains(&"=") {
+                    for pair in tokens.windows(2) {
+                        if pair[0].chars().count() == 1 && pair[1].starts_with('"') {
+                            core.push(pair[0].to_ascii_uppercase());
+                        }
+                    }
+                }
+                index += 1;
+            }
+            index += 1;
+            core.sort();
+            core.dedup();
+            alphabet = core;
+        } else if line.starts_with("Background letter frequencies") {
+            let mut pairs = Vec::new();
+            while index < lines.len() && !lines[index].trim().is_empty() {
+                let tokens: Vec<&str> = lines[index].split_whitespace().collect();
+                for pair in tokens.chunks(2) {
+                    let [letter, value] = pair else {
+                        return Err(format!("line {}: unpaired background frequency", index + 1));
+                    };
+                    let value: f64 = value.parse().map_err(|_| {
+                        format!("line {}: invalid background frequency {value:?}", index + 1)
+                    })?;
+                    pairs.push((letter.to_ascii_uppercase(), value));
+                }
+                index += 1;
+            }
+            background = Some(pairs);
+        } else if let Some(rest) = line.strip_prefix("MOTIF") {
+            let mut names = rest.split_whitespace();
+            let Some(id) = names.next() else {
+                return Err(format!("line {index}: MOTIF without an identifier"));
+            };
+            let id = id.to_string();
+            let name = names.next().map(str::to_string);
+
+            while index < lines.len()
+                && !lines[index].trim().starts_with("letter-probability matrix")
+            {
+                if lines[index].trim().starts_with("MOTIF") {
+                    return Err(format!("motif {id} has no letter-probability matrix"));
+                }
+                index += 1;
+            }
+            if index == lines.len() {
+                return Err(format!("motif {id} has no letter-probability matrix"));
+            }
+            let header = lines[index].trim();
+            index += 1;
+            let field = |key: &str| -> Option<f64> {
+                let tokens: Vec<&str> = header.split_whitespace().collect();
+                tokens
+                    .iter()
+                    .position(|token| *token == key)
+                    .and_then(|position| tokens.get(position + 1))
+                    .and_then(|value| value.parse().ok())
+            };
+            let width = field("w=").map(|w| w as usize);
+            let mut rows = Vec::new();
+            while index < lines.len() && width.is_none_or(|w| rows.len() < w) {
+                let row_line = lines[index].trim();
+                if row_line.is_empty() && width.is_some() {
+                    index += 1;
+                    continue;
+                }
+                let Some(row) = parse_numbers(row_line) else {
+                    break;
+                };
+                if row.len() != alphabet.len() {
+                    return Err(format!(
+                        "line {}: expected {} probabilities, found {}",
+                        index + 1,
+                        alphabet.len(),
+                        row.len()
+                    ));
+                }
+                rows.push(row);
+                index += 1;
+            }
+            if let Some(width) = width
+                && rows.len() != width
+            {
+                return Err(format!(
+                    "motif {id} has {} rows, expected w= {width}",
+                    rows.len()
+                ));
+            }
+            if rows.is_empty() {
+                return Err(format!("motif {id} has no positions"));
+            }
+            let frequencies = normalize(&rows, &id)?;
+            let motif_background = match &background {
+                Some(pairs) => Some(
+                    alphabet
+                        .iter()
+                        .map(|letter| {
+                            pairs
+                                .iter()
+                                .find(|(other, _)| other == letter)
+                                .map(|(_, value)| *value)
+                                .ok_or_else(|| {
+                                    format!("background frequencies have no letter {letter}")
+                                })
+                        })
+                        .collect::<Result<Vec<_>, _>>()?,
+                ),
+                None => None,
+            };
+            motifs.push(Motif {
+                id,
+                name,
+                alphabet: alphabet.clone(),
+                frequencies,
+                counts: None,
+                sites: field("nsites="),
+                background: motif_background,
+            });
+        }
+    }
+    Ok(motifs)
+}
+
+fn parse_jaspar(data: &str) -> Result<Vec<Motif>, String> {
+    let mut motifs = Vec::new();
+    // Header and (letter, values) rows of the motif being read.
+    let mut current: Option<(String, Option<String>)> = None;
+    let mut rows: Vec<(Option<String>, Vec<f64>)> = Vec::new();
+
+    let mut finish = |header: Option<(String, Option<String>)>,
+                      rows: &mut Vec<(Option<String>, Vec<f64>)>|
+     -> Result<(), String> {
+        if rows.is_empty() {
+            return match header {
+                Some((id, _)) => Err(format!("motif {id} has no matrix")),
+                None => Ok(()),
+            };
+        }
+        let (id, name) = header.unwrap_or_else(|| (format!("motif{}", motifs.len() + 1), None));
+        let alphabet: Vec<String> = if rows.iter().all(|(letter, _)| letter.is_some()) {
+            rows.iter()
+                .filter_map(|(letter, _)| letter.clone())
+                .collect()
+        } else if rows.len() == NUCLEOTIDES.len() {
+            NUCLEOTIDES.iter().map(|s| s.to_string()).collect()
+        } else {
+            return Err(format!(
+                "motif {id} has {} unlabeled rows, expected 4 (A, C, G, T)",
+                rows.len()
+            ));
+        };
+        let width = rows[0].1.len();
+        if rows.iter().any(|(_, values)| values.len() != width) {
+            return Err(format!("motif {id} has rows of different lengths"));
+        }
+        let counts = (0..width)
+            .map(|position| rows.iter().map(|(_, values)| values[position]).collect())
+            .collect();
+        motifs.push(count_motif(id, name, alphabet, counts)?);
+        rows.clear();
+        Ok(())
+    };
+
+    for (number, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('>') {
+            finish(current.take(), &mut rows)?;
+            let mut fields = header.split_whitespace();
+            let id = fields
+                .next()
+                .ok_or_else(|| format!("line {}: empty motif header", number + 1))?
+                .to_string();
+            let name = fields.collect::<Vec<_>>().join(" ");
+            current = Some((id, (!name.is_empty()).then_some(name)));
+            continue;
+        }
+        let (letter, values) = match line.split_once('[') {
+            Some((letter, values)) => (
+                Some(letter.trim().to_ascii_uppercase()),
+                values.trim_end().trim_end_matches(']'),
+            ),
+            None => (None, line),
+        };
+        let values = parse_numbers(values)
+            .ok_or_else(|| format!("line {}: expected a row of counts", number + 1))?;
+        rows.push((letter, values));
+    }
+    finish(current.take(), &mut rows)?;
+    Ok(motifs)
+}
+
+fn parse_transfac(data: &str) -> Result<Vec<Motif>, String> {
+    let mut motifs = Vec::new();
+    let mut id: Option<String> = None;
+    let mut accession: Option<String> = None;
+    let mut name: Option<String> = None;
+    let mut alphabet: Option<Vec<String>> = None;
+    let mut counts: Vec<Vec<f64>> = Vec::new();
+
+    let mut lines = data.lines().enumerate().peekable();
+    loop {
+        let next = lines.next();
+        let record_ends = next.is_none_or(|(_, line)| line.trim_start().starts_with("//"));
+        if record_ends {
+            if let Some(alphabet) = alphabet.take() {
+                let motif_id = id
+                    .take()
+                    .or_else(|| accession.clone())
+                    .unwrap_or_else(|| format!("motif{}", motifs.len() + 1));
+                motifs.push(count_motif(
+                    motif_id,
+                    name.take(),
+                    alphabet,
+                    std::mem::take(&mut counts),
+                )?);
+            }
+            id = None;
+            accession = None;
+            name = None;
+            counts.clear();
+            if next.is_none() {
+                break;
+            }
+            continue;
+        }
+        let Some((number, line)) = next else { break };
+        let line = line.trim();
+        let (code, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        match code {
+            "ID" => id = Some(rest.to_string()),
+            "AC" => accession = Some(rest.to_string()),
+            "NA" => name = Some(rest.to_string()),
+            "P0" | "PO" => {
+                alphabet = Some(
+                    rest.split_whitespace()
+                        .map(str::to_ascii_uppercase)
+                        .collect(),
+                );
+            }
+            _ => {
+                let Some(columns) = &alphabet else { continue };
+                if code.parse::<u32>().is_err() {
+                    continue;
+                }
+                let values: Vec<f64> = rest
+                    .split_whitespace()
+                    .take(columns.len())
+                    .map(|value| value.parse::<f64>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("line {}: invalid count in matrix row", number + 1))?;
+                if values.len() != columns.len() {
+                    return Err(format!(
+                        "line {}: expected {} counts, found {}",
+                        number + 1,
+                        columns.len(),
+                        values.len()
+                    ));
+                }
+                counts.push(values);
+            }
+        }
+    }
+    Ok(motifs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(data: &str) -> Vec<Motif> {
+        parse_motifs(data, &ParseMotifsConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_meme() {
+        let data = "MEME version 4\n\nALPHABET= ACGT\n\nstrands: + -\n\n\
+                    Background letter frequencies\nA 0.3 C 0.2 G 0.2\nT 0.3\n\n\
+                    MOTIF crp CRP_ECOLI\nletter-probability matrix: alength= 4 w= 2 nsites= 17 E= 4.1e-009\n\
+                    0.5 0.5 0 0\n 0 0 0 1\n\nMOTIF lexA\nletter-probability matrix: alength= 4 w= 1\n0.25 0.25 0.25 0.25\n";
+        let motifs = parse(data);
+        assert_eq!(motifs.len(), 2);
+        assert_eq!(motifs[0].id, "crp");
+        assert_eq!(motifs[0].name.as_deref(), Some("CRP_ECOLI"));
+        assert_eq!(
+            motifs[0].frequencies,
+            [[0.5, 0.5, 0.0, 0.0], [0.0, 0.0, 0.0, 1.0]]
+        );
+        assert_eq!(motifs[0].sites, Some(17.0));
+        assert_eq!(motifs[0].background, Some(vec![0.3, 0.2, 0.2, 0.3]));
+        assert_eq!(motifs[1].frequencies.len(), 1);
+    }
+
+    #[test]
+    fn test_jaspar_and_pfm() {
+        let jaspar =
+            ">MA0004.1 Arnt\nA  [ 4  19  0 ]\nC  [16   0 20 ]\nG  [ 0   1  0 ]\nT  [ 0   0  0 ]\n";
+        let motifs = parse(jaspar);
+        assert_eq!(
+            (motifs[0].id.as_str(), motifs[0].name.as_deref()),
+            ("MA0004.1", Some("Arnt"))
+        );
+        assert_eq!(motifs[0].alphabet, ["A", "C", "G", "T"]);
+        assert_eq!(motifs[0].frequencies[0], [0.2, 0.8, 0.0, 0.0]);
+        assert_eq!(motifs[0].counts.as_ref().unwrap()[1], [19.0, 0.0, 1.0, 0.0]);
+        assert_eq!(motifs[0].sites, Some(20.0));
+
+        let pfm = "1 3\n1 0\n0 1\n2 0\n";
+        let motifs = parse(pfm);
+        assert_eq!(motifs[0].id, "motif1");
+        assert_eq!(motifs[0].frequencies[1], [0.75, 0.0, 0.25, 0.0]);
+
+        assert!(parse_motifs("1 2\n3 4\n", &ParseMotifsConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_transfac() {
+        let data = "AC  M00001\nXX\nID  V$MYOD_01\nNA  MyoD\nXX\nP0      A      C      G      T\n\
+                    01      1      2      2      0      S\n02      2      1      2      0      R\nXX\n//\n\
+                    AC  M00002\nP0 A C G T\n01 0 0 0 4\n//\n";
+        let motifs = parse(data);
+        assert_eq!(motifs.len(), 2);
+        assert_eq!(motifs[0].id, "V$MYOD_01");
+        assert_eq!(motifs[0].name.as_deref(), Some("MyoD"));
+        assert_eq!(motifs[0].frequencies[0], [0.2, 0.4, 0.4, 0.0]);
+        assert_eq!(motifs[1].id, "M00002");
+        assert_eq!(motifs[1].frequencies, [[0.0, 0.0, 0.0, 1.0]]);
+    }
+}
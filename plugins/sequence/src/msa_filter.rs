@@ -0,0 +1,376 @@
+//! Column trimming and redundancy filtering of multiple sequence alignments.
+//!
+//! [`trim_columns`] removes columns by gap fraction, conservation or an
+//! automated gap threshold in the spirit of trimAl's `gappyout`, and reports
+//! which original columns were kept. [`filter_sequences`] removes sequences
+//! that are too similar to one already kept or cover too little of the first
+//! (query) sequence, like hhfilter.
+//!
+//! `-` and `.` are gaps; so are positions past the end of a short sequence.
+
+use serde::{Deserialize, Serialize};
+
+use crate::column_stats::{ColumnStatsRequest, compute_column_stats, residue_lookup};
+use crate::logo::BackgroundSpec;
+use crate::msa_formats::AlignedSequence;
+use crate::msa_stats::ConservationScore;
+use crate::weights::{DEFAULT_IDENTITY_THRESHOLD, SequenceWeighting, identity};
+
+/// Request for [`trim_columns`]. Each criterion that is set must pass for a
+/// column to be kept.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrimColumnsRequest {
+    /// Aligned sequences.
+    pub sequences: Vec<AlignedSequence>,
+    /// Residues used for conservation, as single characters.
+    pub alphabet: Vec<String>,
+    /// Highest fraction of gaps a kept column may have.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub max_gap_fraction: Option<f64>,
+    /// Lowest conservation a kept column may have, as a fraction of the
+    /// highest value the score can reach.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub min_conservation: Option<f64>,
+    /// Removes gappy columns with an automatically chosen gap threshold.
+    #[serde(default)]
+    pub automated: bool,
+    /// Conservation score for `min_conservation`.
+    #[serde(default)]
+    pub conservation_score: ConservationScore,
+    /// Background for [`ConservationScore::JensenShannon`].
+    #[serde(default)]
+    pub background: BackgroundSpec,
+    /// Residue classes for [`ConservationScore::PropertyEntropy`].
+    #[serde(default)]
+    pub property_groups: Option<Vec<String>>,
+    /// Built-in matrix for [`ConservationScore::SumOfPairs`].
+    #[serde(default)]
+    pub matrix: Option<String>,
+}
+
+/// Response of [`trim_columns`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrimColumnsResponse {
+    /// Sequences restricted to the kept columns, in input order.
+    pub sequences: Vec<AlignedSequence>,
+    /// Original 0-based index of each kept column.
+    pub kept_columns: Vec<usize>,
+    /// Gap threshold chosen in automated mode, if any.
+    pub automated_gap_threshold: Option<f64>,
+}
+
+/// Removes alignment columns that fail the requested criteria.
+pub fn trim_columns(request: &TrimColumnsRequest) -> Result<TrimColumnsResponse, String> {
+    for (name, value) in [
+        ("max_gap_fraction", request.max_gap_fraction),
+        ("min_conservation", request.min_conservation),
+    ] {
+        if let Some(value) = value
+            && !(0.0..=1.0).contains(&value)
+        {
+            return Err(format!("{name} must be between 0 and 1, got {value}"));
+        }
+    }
+
+    let rows: Vec<Vec<char>> = request
+        .sequences
+        .iter()
+        .map(|s| s.sequence.chars().collect())
+        .collect();
+    let length = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let count = rows.len();
+    let gap_fractions: Vec<f64> = (0..length)
+        .map(|pos| {
+            let gaps = rows
+                .iter()
+                .filter(|row| matches!(row.get(pos), None | Some('-' | '.')))
+                .count();
+            gaps as f64 / count as f64
+        })
+        .collect();
+
+    let mut keep = vec![true; length];
+    if let Some(max_gap_fraction) = request.max_gap_fraction {
+        for (keep, &fraction) in keep.iter_mut().zip(&gap_fractions) {
+            *keep &= fraction <= max_gap_fraction;
+        }
+    }
+    let automated_gap_threshold = request
+        .automated
+        .then(|| automated_gap_threshold(&gap_fractions));
+    if let Some(threshold) = automated_gap_threshold {
+        for (keep, &fraction) in keep.iter_mut().zip(&gap_fractions) {
+            *keep &= fraction <= threshold;
+        }
+    }
+    if let Some(min_conservation) = request.min_conservation {
+        let stats = compute_column_stats(&ColumnStatsRequest {
+            sequences: request
+                .sequences
+                .iter()
+                .map(|s| s.sequence.clone())
+                .collect(),
+            alphabet: request.alphabet.clone(),
+            start: 0,
+            end: Some(length),
+            sampling_correction: false,
+            compute_conservation: true,
+            weighting: SequenceWeighting::None,
+            identity_threshold: DEFAULT_IDENTITY_THRESHOLD,
+            conservation_score: request.conservation_score,
+            background: request.background.clone(),
+            property_groups: request.property_groups.clone(),
+            matrix: request.matrix.clone(),
+        })?;
+        let max = stats.max_conservation;
+        for (keep, column) in keep.iter_mut().zip(&stats.columns) {
+            let conservation = column.conservation.unwrap_or(0.0);
+            let relative = if max > 0.0 { conservation / max } else { 0.0 };
+            *keep &= relative >= min_conservation;
+        }
+    }
+
+    let kept_columns: Vec<usize> = (0..length).filter(|&pos| keep[pos]).collect();
+    let sequences = request
+        .sequences
+        .iter()
+        .zip(&rows)
+        .map(|(record, row)| AlignedSequence {
+            id: record.id.clone(),
+            sequence: kept_columns
+                .iter()
+                .map(|&pos| row.get(pos).copied().unwrap_or('-'))
+                .collect(),
+        })
+        .collect();
+    Ok(TrimColumnsResponse {
+        sequences,
+        kept_columns,
+        automated_gap_threshold,
+    })
+}
+
+/// Picks a gap threshold from the cumulative distribution of column gap
+/// fractions.
+///
+/// With the distinct gap fractions `x_k` in increasing order and `y_k` the
+/// fraction of columns with at most `x_k` gaps, the threshold is the `x_k`
+/// after which the slope of `y` drops most sharply. Columns are all kept when
+/// there are fewer than three distinct gap fractions.
+fn automated_gap_threshold(gap_fractions: &[f64]) -> f64 {
+    let mut sorted = gap_fractions.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    for (i, &fraction) in sorted.iter().enumerate() {
+        let cumulative = (i + 1) as f64 / sorted.len() as f64;
+        match points.last_mut() {
+            Some(last) if last.0 == fraction => last.1 = cumulative,
+            _ => points.push((fraction, cumulative)),
+        }
+    }
+    if points.len() < 3 {
+        return 1.0;
+    }
+
+    let slopes: Vec<f64> = points
+        .windows(2)
+        .map(|pair| (pair[1].1 - pair[0].1) / (pair[1].0 - pair[0].0))
+        .collect();
+    let mut best = (f64::NEG_INFINITY, 1.0);
+    // slopes[k - 1] leads into points[k] and slopes[k] leaves it.
+    for k in 1..slopes.len() {
+        let ratio = slopes[k - 1] / slopes[k];
+        if ratio > best.0 {
+            best = (ratio, points[k].0);
+        }
+    }
+    best.1
+}
+
+/// Request for [`filter_sequences`]. The first sequence is the query and is
+/// always kept.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FilterSequencesRequest {
+    /// Aligned sequences; the first is the query.
+    pub sequences: Vec<AlignedSequence>,
+    /// Residues compared, as single characters.
+    pub alphabet: Vec<String>,
+    /// Highest identity a kept sequence may share with another kept one,
+    /// over columns where both have a residue.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub max_identity: Option<f64>,
+    /// Lowest fraction of the query's residue columns a kept sequence must
+    /// also have a residue in.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub min_coverage: Option<f64>,
+}
+
+/// Response of [`filter_sequences`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FilterSequencesResponse {
+    /// Kept sequences in input order.
+    pub sequences: Vec<AlignedSequence>,
+    /// Input index of each kept sequence.
+    pub kept: Vec<usize>,
+}
+
+/// Removes redundant and poorly covering sequences.
+///
+/// Sequences are considered greedily from the most to the least residues,
+/// so the most complete member of a redundant group is kept.
+pub fn filter_sequences(
+    request: &FilterSequencesRequest,
+) -> Result<FilterSequencesResponse, String> {
+    for (name, value) in [
+        ("max_identity", request.max_identity),
+        ("min_coverage", request.min_coverage),
+    ] {
+        if let Some(value) = value
+            && !(0.0..=1.0).contains(&value)
+        {
+            return Err(format!("{name} must be between 0 and 1, got {value}"));
+        }
+    }
+    let lookup = residue_lookup(&request.alphabet)?;
+    let encoded: Vec<Vec<Option<usize>>> = request
+        .sequences
+        .iter()
+        .map(|s| s.sequence.bytes().map(|b| lookup[b as usize]).collect())
+        .collect();
+    let Some(query) = encoded.first() else {
+        return Ok(FilterSequencesResponse {
+            sequences: Vec::new(),
+            kept: Vec::new(),
+        });
+    };
+    let query_residues = query.iter().filter(|r| r.is_some()).count();
+
+    let mut order: Vec<usize> = (1..encoded.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(encoded[i].iter().filter(|r| r.is_some()).count()));
+
+    let mut kept = vec![0];
+    for i in order {
+        if let Some(min_coverage) = request.min_coverage {
+            let covered = query
+                .iter()
+                .zip(&encoded[i])
+                .filter(|(q, s)| q.is_some() && s.is_some())
+                .count();
+            let coverage = if query_residues == 0 {
+                0.0
+            } else {
+                covered as f64 / query_residues as f64
+            };
+            if coverage < min_coverage {
+                continue;
+            }
+        }
+        if let Some(max_identity) = request.max_identity
+            && kept
+                .iter()
+                .any(|&j| identity(&encoded[i], &encoded[j]) > max_identity)
+        {
+            continue;
+        }
+        kept.push(i);
+    }
+    kept.sort_unstable();
+
+    Ok(FilterSequencesResponse {
+        sequences: kept.iter().map(|&i| request.sequences[i].clone()).collect(),
+        kept,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(sequences: &[&str]) -> Vec<AlignedSequence> {
+        sequences
+            .iter()
+            .enumerate()
+            .map(|(i, s)| AlignedSequence {
+                id: format!("s{i}"),
+                sequence: s.to_string(),
+            })
+            .collect()
+    }
+
+    fn trim_request(sequences: &[&str]) -> TrimColumnsRequest {
+        TrimColumnsRequest {
+            sequences: records(sequences),
+            alphabet: "ACGT".chars().map(String::from).collect(),
+            max_gap_fraction: None,
+            min_conservation: None,
+            automated: false,
+            conservation_score: ConservationScore::Shannon,
+            background: BackgroundSpec::default(),
+            property_groups: None,
+            matrix: None,
+        }
+    }
+
+    #[test]
+    fn test_trim_by_gaps_and_conservation() {
+        let mut request = trim_request(&["AC-GT", "AA-G.", "AT-GT", "AG-CT"]);
+        request.max_gap_fraction = Some(0.25);
+        let response = trim_columns(&request).unwrap();
+        assert_eq!(response.kept_columns, [0, 1, 3, 4]);
+        assert_eq!(response.sequences[1].sequence, "AAG.");
+
+        request.min_conservation = Some(0.5);
+        let response = trim_columns(&request).unwrap();
+        // Column 1 holds four different residues (0 bits); column 3 is 3:1
+        // (1.19 of 2 bits) and column 4 has 2 bits at 75% occupancy.
+        assert_eq!(response.kept_columns, [0, 3, 4]);
+    }
+
+    #[test]
+    fn test_automated_threshold_drops_gappy_columns() {
+        // Ten sequences: six ungapped columns, two with one gap, two mostly gaps.
+        let mut rows = vec![String::new(); 10];
+        for (col, gaps) in [0, 0, 0, 0, 0, 0, 1, 1, 8, 9].into_iter().enumerate() {
+            for (i, row) in rows.iter_mut().enumerate() {
+                row.push(if i < gaps {
+                    '-'
+                } else {
+                    "ACGT".as_bytes()[(i + col) % 4] as char
+                });
+            }
+        }
+        let rows: Vec<&str> = rows.iter().map(String::as_str).collect();
+        let mut request = trim_request(&rows);
+        request.automated = true;
+        let response = trim_columns(&request).unwrap();
+        assert_eq!(response.kept_columns, (0..8).collect::<Vec<_>>());
+        assert_eq!(response.automated_gap_threshold, Some(0.1));
+    }
+
+    #[test]
+    fn test_filter_by_identity_and_coverage() {
+        let request = FilterSequencesRequest {
+            sequences: records(&[
+                "ACGTACGTAC",
+                "ACGTACGTAA",
+                "ACGTAC----",
+                "TTTTTTTTTT",
+                "ACGTACGTAC",
+            ]),
+            alphabet: "ACGT".chars().map(String::from).collect(),
+            max_identity: Some(0.85),
+            min_coverage: Some(0.8),
+        };
+        let response = filter_sequences(&request).unwrap();
+        // s1 and s4 are too close to the query, s2 covers too little.
+        assert_eq!(response.kept, [0, 3]);
+        assert_eq!(response.sequences[1].id, "s3");
+
+        let loose = FilterSequencesRequest {
+            max_identity: None,
+            min_coverage: None,
+            ..request
+        };
+        assert_eq!(filter_sequences(&loose).unwrap().kept, [0, 1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,704 @@
+//! Readers for multiple sequence alignment formats.
+//!
+//! Stockholm, Clustal, PHYLIP (sequential or interleaved), aligned FASTA and
+//! A2M/A3M are read into aligned sequences of equal length, together with any
+//! annotation tracks the format carries. Stockholm and Clustal join lines
+//! that share a sequence name across blocks. Errors name the 1-based line
+//! they refer to.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::fastx::{
+    DuplicatePolicy, Lines, ParseSequencesConfig, Records, SequenceFormat, parse_sequence_records,
+};
+
+/// Options for [`parse_alignment`].
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ParseAlignmentConfig {
+    /// Alignment format, or detected from the first non-blank line.
+    #[serde(default)]
+    pub format: AlignmentFormat,
+    /// Handling of repeated identifiers in FASTA, A2M/A3M and PHYLIP input.
+    #[serde(default)]
+    pub duplicates: DuplicatePolicy,
+    /// How A2M/A3M insert states are handled.
+    #[serde(default)]
+    pub insertions: InsertionPolicy,
+}
+
+/// Alignment format; `Auto` looks at the first non-blank line.
+///
+/// Auto-detected FASTA input is read as A3M only when its sequences mix
+/// uppercase and lowercase residues; all-lowercase FASTA stays plain FASTA.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlignmentFormat {
+    /// Detects the format from the first non-blank line.
+    #[default]
+    Auto,
+    /// Aligned FASTA, read verbatim.
+    Fasta,
+    /// Stockholm, as written by HMMER and Rfam.
+    Stockholm,
+    /// Clustal and MUSCLE output.
+    Clustal,
+    /// Sequential or interleaved PHYLIP.
+    Phylip,
+    /// A2M, where `.` pads insert columns.
+    A2m,
+    /// A3M, where insertions are not padded.
+    A3m,
+}
+
+/// How A2M/A3M insert states (lowercase residues) are handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InsertionPolicy {
+    /// Pads insertions with `.` so every sequence spans every insert column.
+    #[default]
+    Expand,
+    /// Keeps only match states.
+    Remove,
+}
+
+/// One aligned sequence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlignedSequence {
+    /// Sequence identifier.
+    pub id: String,
+    /// Aligned sequence, with gaps.
+    pub sequence: String,
+}
+
+/// Sequences in file order plus annotation tracks.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ParsedAlignment {
+    /// Aligned sequences in file order.
+    pub sequences: Vec<AlignedSequence>,
+    /// Per-column tracks: Stockholm `#=GC` lines such as `SS_cons` and `RF`,
+    /// and the Clustal conservation line as `conservation`.
+    pub column_annotations: BTreeMap<String, String>,
+    /// Per-residue tracks from Stockholm `#=GR` lines, by sequence and
+    /// feature.
+    pub residue_annotations: BTreeMap<String, BTreeMap<String, String>>,
+    /// Stockholm `#=GF` lines; repeated features are joined with spaces.
+    pub file_annotations: BTreeMap<String, String>,
+}
+
+/// Parses a multiple sequence alignment.
+///
+/// All sequences and per-column or per-residue tracks must have the same
+/// number of columns.
+pub fn parse_alignment(
+    input: &str,
+    config: &ParseAlignmentConfig,
+) -> Result<ParsedAlignment, String> {
+    let format = match config.format {
+        AlignmentFormat::Auto => detect_format(input)?,
+        format => format,
+    };
+    let alignment = match format {
+        AlignmentFormat::Stockholm => parse_stockholm(input)?,
+        AlignmentFormat::Clustal => parse_clustal(input)?,
+        AlignmentFormat::Phylip => parse_phylip(input, config.duplicates)?,
+        AlignmentFormat::A2m | AlignmentFormat::A3m => parse_fasta_alignment(input, config, true)?,
+        AlignmentFormat::Fasta | AlignmentFormat::Auto => {
+            parse_fasta_alignment(input, config, false)?
+        }
+    };
+    check_lengths(&alignment)?;
+    Ok(alignment)
+}
+
+fn detect_format(input: &str) -> Result<AlignmentFormat, String> {
+    let Some((number, line)) = Lines::new(input).peek_non_blank() else {
+        return Ok(AlignmentFormat::Fasta);
+    };
+    if line.starts_with("# STOCKHOLM") {
+        Ok(AlignmentFormat::Stockholm)
+    } else if line.starts_with("CLUSTAL") || line.starts_with("MUSCLE") {
+        Ok(AlignmentFormat::Clustal)
+    } else if phylip_header(line).is_some() {
+        Ok(AlignmentFormat::Phylip)
+    } else if line.starts_with('>') || line.starts_with('#') {
+        // All-lowercase FASTA is common and is not A3M; insert states only
+        // make sense next to uppercase match states.
+        let residues = || {
+            input
+                .lines()
+                .filter(|line| !line.starts_with(['>', '#', ';']))
+                .flat_map(str::bytes)
+        };
+        let has_insertions = residues().any(|b| b.is_ascii_lowercase())
+            && residues().any(|b| b.is_ascii_uppercase());
+        Ok(if has_insertions {
+            AlignmentFormat::A3m
+        } else {
+            AlignmentFormat::Fasta
+        })
+    } else {
+        Err(format!(
+            "line {number}: unrecognized alignment format; expected Stockholm, Clustal, PHYLIP, FASTA or A2M/A3M"
+        ))
+    }
+}
+
+/// Splits a line into its first whitespace-delimited field and the rest.
+fn split_field<'a>(text: &'a str, number: usize, what: &str) -> Result<(&'a str, &'a str), String> {
+    text.trim()
+        .split_once(char::is_whitespace)
+        .map(|(field, rest)| (field, rest.trim()))
+        .ok_or_else(|| format!("line {number}: {what} line has no data"))
+}
+
+/// Appends `data` to the sequence `name`, adding it on first sight.
+fn append_sequence(
+    alignment: &mut ParsedAlignment,
+    index: &mut HashMap<String, usize>,
+    name: &str,
+    data: &str,
+) {
+    match index.get(name) {
+        Some(&i) => alignment.sequences[i].sequence.push_str(data),
+        None => {
+            index.insert(name.to_string(), alignment.sequences.len());
+            alignment.sequences.push(AlignedSequence {
+                id: name.to_string(),
+                sequence: data.to_string(),
+            });
+        }
+    }
+}
+
+/// Reads the first alignment of a Stockholm file. `#=GS` lines and other
+/// comments are skipped.
+fn parse_stockholm(input: &str) -> Result<ParsedAlignment, String> {
+    let mut alignment = ParsedAlignment::default();
+    let mut index = HashMap::new();
+    let mut header_seen = false;
+    for (number, line) in Lines::new(input) {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !header_seen {
+            if !line.starts_with("# STOCKHOLM") {
+                return Err(format!(
+                    "line {number}: expected a '# STOCKHOLM 1.0' header"
+                ));
+            }
+            header_seen = true;
+            continue;
+        }
+        if line.starts_with("//") {
+            break;
+        }
+        if let Some(rest) = line.strip_prefix("#=GF") {
+            let (feature, text) = split_field(rest, number, "#=GF")?;
+            let entry = alignment
+                .file_annotations
+                .entry(feature.to_string())
+                .or_default();
+            if !entry.is_empty() {
+                entry.push(' ');
+            }
+            entry.push_str(text);
+        } else if let Some(rest) = line.strip_prefix("#=GC") {
+            let (feature, data) = split_field(rest, number, "#=GC")?;
+            alignment
+                .column_annotations
+                .entry(feature.to_string())
+                .or_default()
+                .push_str(data);
+        } else if let Some(rest) = line.strip_prefix("#=GR") {
+            let (name, rest) = split_field(rest, number, "#=GR")?;
+            let (feature, data) = split_field(rest, number, "#=GR")?;
+            alignment
+                .residue_annotations
+                .entry(name.to_string())
+                .or_default()
+                .entry(feature.to_string())
+                .or_default()
+                .push_str(data);
+        } else if !line.starts_with('#') {
+            let (name, data) = split_field(line, number, "sequence")?;
+            append_sequence(&mut alignment, &mut index, name, data);
+        }
+    }
+    Ok(alignment)
+}
+
+/// Reads a Clustal file. The conservation line under each block becomes the
+/// `conservation` column track.
+fn parse_clustal(input: &str) -> Result<ParsedAlignment, String> {
+    let mut lines = Lines::new(input);
+    match lines.peek_non_blank() {
+        None => return Ok(ParsedAlignment::default()),
+        Some((_, line)) if line.starts_with("CLUSTAL") || line.starts_with("MUSCLE") => {
+            lines.next();
+        }
+        Some((number, _)) => {
+            return Err(format!("line {number}: expected a CLUSTAL header"));
+        }
+    }
+
+    let mut alignment = ParsedAlignment::default();
+    let mut index = HashMap::new();
+    let mut conservation: Option<String> = None;
+    // Residue offset within the line, width and first column of the block.
+    let mut block: Option<(usize, usize, usize)> = None;
+    for (number, line) in lines {
+        if line.trim().is_empty() {
+            block = None;
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            if let Some((offset, width, start)) = block.take() {
+                let marks: String = line.chars().skip(offset).take(width).collect();
+                let track = conservation.get_or_insert_with(String::new);
+                *track = format!("{track:<start$}{marks:<width$}");
+            }
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let name = fields.next().unwrap_or_default();
+        let Some(data) = fields.next() else {
+            return Err(format!(
+                "line {number}: sequence line for '{name}' has no residues"
+            ));
+        };
+        if let Some(extra) = fields.next()
+            && (extra.parse::<u64>().is_err() || fields.next().is_some())
+        {
+            return Err(format!(
+                "line {number}: unexpected text after the residues of '{name}'"
+            ));
+        }
+        if block.is_none() {
+            let after_name = &line[name.len()..];
+            let offset = name.chars().count() + after_name.len() - after_name.trim_start().len();
+            let start = alignment
+                .sequences
+                .first()
+                .map_or(0, |s| s.sequence.chars().count());
+            block = Some((offset, data.chars().count(), start));
+        }
+        append_sequence(&mut alignment, &mut index, name, data);
+    }
+
+    if let Some(track) = conservation {
+        let length = alignment
+            .sequences
+            .first()
+            .map_or(0, |s| s.sequence.chars().count());
+        alignment
+            .column_annotations
+            .insert("conservation".into(), format!("{track:<length$}"));
+    }
+    Ok(alignment)
+}
+
+/// Number of sequences and columns from a PHYLIP header line.
+fn phylip_header(line: &str) -> Option<(usize, usize)> {
+    let mut fields = line.split_whitespace();
+    let count = fields.next()?.parse().ok()?;
+    let length = fields.next()?.parse().ok()?;
+    Some((count, length))
+}
+
+/// Reads sequential or interleaved PHYLIP.
+///
+/// Names end at the first whitespace (relaxed PHYLIP); a name line without
+/// whitespace is split after 10 characters (strict PHYLIP). The sequential
+/// layout is tried first.
+fn parse_phylip(input: &str, duplicates: DuplicatePolicy) -> Result<ParsedAlignment, String> {
+    let lines: Vec<(usize, &str)> = Lines::new(input)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+    let Some(&(number, header)) = lines.first() else {
+        return Ok(ParsedAlignment::default());
+    };
+    let (count, length) = phylip_header(header).ok_or_else(|| {
+        format!("line {number}: expected a PHYLIP header with the number of sequences and columns")
+    })?;
+    let body = &lines[1..];
+    let entries = phylip_sequential(body, count, length)
+        .or_else(|_| phylip_interleaved(body, count, length))?;
+
+    let mut records = Records::new(&ParseSequencesConfig {
+        duplicates,
+        ..Default::default()
+    });
+    for (line, id, sequence) in entries {
+        records.push(line, id, None, sequence, None)?;
+    }
+    Ok(ParsedAlignment {
+        sequences: records
+            .records
+            .into_iter()
+            .map(|record| AlignedSequence {
+                id: record.id,
+                sequence: record.sequence,
+            })
+            .collect(),
+        ..Default::default()
+    })
+}
+
+fn phylip_name_line(line: &str) -> (String, String) {
+    let line = line.trim();
+    let (name, data) = match line.split_once(char::is_whitespace) {
+        Some((name, data)) => (name.to_string(), data.to_string()),
+        None => {
+            let split = line.char_indices().nth(10).map_or(line.len(), |(i, _)| i);
+            (line[..split].to_string(), line[split..].to_string())
+        }
+    };
+    (name, phylip_residues(&data))
+}
+
+fn phylip_residues(line: &str) -> String {
+    line.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+fn phylip_sequential(
+    body: &[(usize, &str)],
+    count: usize,
+    length: usize,
+) -> Result<Vec<(usize, String, String)>, String> {
+    let mut entries = Vec::with_capacity(count);
+    let mut lines = body.iter();
+    for _ in 0..count {
+        let Some(&(number, line)) = lines.next() else {
+            return Err(format!(
+                "PHYLIP header declares {count} sequences, found {}",
+                entries.len()
+            ));
+        };
+        let (name, mut sequence) = phylip_name_line(line);
+        while sequence.chars().count() < length {
+            let Some(&(_, line)) = lines.next() else {
+                break;
+            };
+            sequence.push_str(&phylip_residues(line));
+        }
+        if sequence.chars().count() != length {
+            return Err(format!(
+                "line {number}: sequence '{name}' has {} columns, expected {length}",
+                sequence.chars().count()
+            ));
+        }
+        entries.push((number, name, sequence));
+    }
+    if let Some(&(number, _)) = lines.next() {
+        return Err(format!(
+            "line {number}: unexpected data after {count} sequences"
+        ));
+    }
+    Ok(entries)
+}
+
+fn phylip_interleaved(
+    body: &[(usize, &str)],
+    count: usize,
+    length: usize,
+) -> Result<Vec<(usize, String, String)>, String> {
+    if body.len() < count {
+        return Err(format!(
+            "PHYLIP header declares {count} sequences, found {}",
+            body.len()
+        ));
+    }
+    let mut entries: Vec<(usize, String, String)> = body[..count]
+        .iter()
+        .map(|&(number, line)| {
+            let (name, sequence) = phylip_name_line(line);
+            (number, name, sequence)
+        })
+        .collect();
+    for (i, &(_, line)) in body[count..].iter().enumerate() {
+        entries[i % count].2.push_str(&phylip_residues(line));
+    }
+    for (number, name, sequence) in &entries {
+        if sequence.chars().count() != length {
+            return Err(format!(
+                "line {number}: sequence '{name}' has {} columns, expected {length}",
+                sequence.chars().count()
+            ));
+        }
+    }
+    Ok(entries)
+}
+
+/// Reads aligned FASTA, or A2M/A3M when `insert_states` is set. Leading `#`
+/// lines of A3M files are skipped.
+fn parse_fasta_alignment(
+    input: &str,
+    config: &ParseAlignmentConfig,
+    insert_states: bool,
+) -> Result<ParsedAlignment, String> {
+    // Blank out comment lines so line numbers in errors stay correct.
+    let cleaned;
+    let input = if insert_states {
+        cleaned = input
+            .split('\n')
+            .map(|line| if line.starts_with('#') { "" } else { line })
+            .collect::<Vec<_>>()
+            .join("\n");
+        &cleaned
+    } else {
+        input
+    };
+    let records = parse_sequence_records(
+        input,
+        &ParseSequencesConfig {
+            format: SequenceFormat::Fasta,
+            duplicates: config.duplicates,
+            ..Default::default()
+        },
+    )?;
+    let sequences = records
+        .into_iter()
+        .map(|record| AlignedSequence {
+            id: record.id,
+            sequence: record.sequence,
+        })
+        .collect();
+    Ok(ParsedAlignment {
+        sequences: if insert_states {
+            expand_insertions(sequences, config.insertions)?
+        } else {
+            sequences
+        },
+        ..Default::default()
+    })
+}
+
+/// Aligns A2M/A3M insert states.
+///
+/// Uppercase residues and `-` are match states; lowercase residues are
+/// insertions and `.` is insertion padding. Every sequence must have the
+/// same number of match states.
+fn expand_insertions(
+    sequences: Vec<AlignedSequence>,
+    policy: InsertionPolicy,
+) -> Result<Vec<AlignedSequence>, String> {
+    // Insertions before each match state and after the last one.
+    let mut split = Vec::with_capacity(sequences.len());
+    let mut match_count = None;
+    for record in &sequences {
+        let mut insertions = vec![String::new()];
+        let mut matches = Vec::new();
+        for c in record.sequence.chars() {
+            if c == '.' {
+                continue;
+            }
+            if c.is_ascii_lowercase() {
+                insertions.last_mut().expect("one slot").push(c);
+            } else {
+                matches.push(c);
+                insertions.push(String::new());
+            }
+        }
+        match match_count {
+            None => match_count = Some(matches.len()),
+            Some(expected) if expected != matches.len() => {
+                return Err(format!(
+                    "sequence '{}' has {} match columns, expected {expected}",
+                    record.id,
+                    matches.len()
+                ));
+            }
+            Some(_) => {}
+        }
+        split.push((insertions, matches));
+    }
+
+    let slots = match_count.map_or(0, |count| count + 1);
+    let widths: Vec<usize> = (0..slots)
+        .map(|slot| {
+            split
+                .iter()
+                .map(|(insertions, _)| insertions[slot].len())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    Ok(sequences
+        .into_iter()
+        .zip(split)
+        .map(|(record, (insertions, matches))| {
+            let mut sequence = String::new();
+            for (slot, width) in widths.iter().enumerate() {
+                if policy == InsertionPolicy::Expand {
+                    sequence.push_str(&format!("{:.<width$}", insertions[slot]));
+                }
+                if let Some(&residue) = matches.get(slot) {
+                    sequence.push(residue);
+                }
+            }
+            AlignedSequence {
+                id: record.id,
+                sequence,
+            }
+        })
+        .collect())
+}
+
+fn check_lengths(alignment: &ParsedAlignment) -> Result<(), String> {
+    let Some(first) = alignment.sequences.first() else {
+        return Ok(());
+    };
+    let expected = first.sequence.chars().count();
+    for record in &alignment.sequences[1..] {
+        let length = record.sequence.chars().count();
+        if length != expected {
+            return Err(format!(
+                "sequence '{}' has {length} columns, expected {expected} as in '{}'",
+                record.id, first.id
+            ));
+        }
+    }
+    for (feature, track) in &alignment.column_annotations {
+        let length = track.chars().count();
+        if length != expected {
+            return Err(format!(
+                "column annotation '{feature}' has {length} columns, expected {expected}"
+            ));
+        }
+    }
+    for (id, tracks) in &alignment.residue_annotations {
+        for (feature, track) in tracks {
+            let length = track.chars().count();
+            if length != expected {
+                return Err(format!(
+                    "annotation '{feature}' of '{id}' has {length} columns, expected {expected}"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<ParsedAlignment, String> {
+        parse_alignment(input, &ParseAlignmentConfig::default())
+    }
+
+    fn pairs(alignment: &ParsedAlignment) -> Vec<(&str, &str)> {
+        alignment
+            .sequences
+            .iter()
+            .map(|s| (s.id.as_str(), s.sequence.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_stockholm_blocks_and_annotations() {
+        let input = "# STOCKHOLM 1.0\n\
+            #=GF ID    tRNA\n\
+            #=GF CC    first\n\
+            #=GF CC    second\n\
+            #=GS seq1  AC P00001\n\
+            seq1       ACG-U\n\
+            #=GR seq1 SS <<.->\n\
+            seq2       AC..U\n\
+            #=GC SS_cons <<-->\n\
+            #=GC RF      xxx.x\n\
+            \n\
+            seq1       AA\n\
+            #=GR seq1 SS >>\n\
+            seq2       A-\n\
+            #=GC SS_cons >>\n\
+            #=GC RF      xx\n\
+            //\n\
+            # STOCKHOLM 1.0\nother AAAA\n//\n";
+        let alignment = parse(input).unwrap();
+        assert_eq!(
+            pairs(&alignment),
+            [("seq1", "ACG-UAA"), ("seq2", "AC..UA-")]
+        );
+        assert_eq!(alignment.column_annotations["SS_cons"], "<<-->>>");
+        assert_eq!(alignment.column_annotations["RF"], "xxx.xxx");
+        assert_eq!(alignment.residue_annotations["seq1"]["SS"], "<<.->>>");
+        assert_eq!(alignment.file_annotations["CC"], "first second");
+
+        let bad = "# STOCKHOLM 1.0\na ACGT\n#=GC SS_cons <<>\n//\n";
+        assert!(parse(bad).unwrap_err().contains("SS_cons"));
+    }
+
+    #[test]
+    fn test_clustal_with_conservation_line() {
+        let input = "CLUSTAL W (1.83) multiple sequence alignment\n\
+            \n\
+            seqA    MKV-LA 5\n\
+            seqB    MKVALS 6\n\
+            \x20       ***:*.\n\
+            \n\
+            seqA    GG 7\n\
+            seqB    GA 8\n\
+            \n";
+        let alignment = parse(input).unwrap();
+        assert_eq!(
+            pairs(&alignment),
+            [("seqA", "MKV-LAGG"), ("seqB", "MKVALSGA")]
+        );
+        assert_eq!(alignment.column_annotations["conservation"], "***:*.  ");
+    }
+
+    #[test]
+    fn test_phylip_layouts() {
+        let sequential = "2 12\nalpha ACGTAC\nGTACGT\nbeta  ACGTAC\nGTAC-T\n";
+        let interleaved = "2 12\nalpha ACGTAC\nbeta  ACGTAC\n\nGTACGT\nGTAC-T\n";
+        let expected = [("alpha", "ACGTACGTACGT"), ("beta", "ACGTACGTAC-T")];
+        assert_eq!(pairs(&parse(sequential).unwrap()), expected);
+        assert_eq!(pairs(&parse(interleaved).unwrap()), expected);
+
+        let strict = " 2 4\nHomo_sapieACGT\nPan_troglo ACGA\n";
+        assert_eq!(
+            pairs(&parse(strict).unwrap()),
+            [("Homo_sapie", "ACGT"), ("Pan_troglo", "ACGA")]
+        );
+        assert!(parse("2 4\na ACGT\n").is_err());
+    }
+
+    #[test]
+    fn test_a3m_insertions() {
+        let input = "#A3M\n>q desc\nAC-D\n>h1\nAcgC-D\n>h2\nA-CaDe\n";
+        let alignment = parse(input).unwrap();
+        assert_eq!(
+            pairs(&alignment),
+            [("q", "A..C-.D."), ("h1", "AcgC-.D."), ("h2", "A..-CaDe")]
+        );
+
+        let config = ParseAlignmentConfig {
+            format: AlignmentFormat::A2m,
+            insertions: InsertionPolicy::Remove,
+            ..Default::default()
+        };
+        let a2m = ">q\nAC..-D\n>h1\nAcgC-D\n";
+        let alignment = parse_alignment(a2m, &config).unwrap();
+        assert_eq!(pairs(&alignment), [("q", "AC-D"), ("h1", "AC-D")]);
+
+        let err = parse(">q\nACD\n>h\nAcD\n").unwrap_err();
+        assert!(err.contains("match columns"), "{err}");
+    }
+
+    #[test]
+    fn test_aligned_fasta_and_detection_errors() {
+        let alignment = parse(">a x\nAC-T\n>b\nA.GT\n").unwrap();
+        assert_eq!(pairs(&alignment), [("a", "AC-T"), ("b", "A.GT")]);
+        let lowercase = parse(">a\nac-t\n>b\nacgt\n").unwrap();
+        assert_eq!(pairs(&lowercase), [("a", "ac-t"), ("b", "acgt")]);
+        let err = parse(">a\nACGT\n>b\nAC\n").unwrap_err();
+        assert_eq!(err, "sequence 'b' has 2 columns, expected 4 as in 'a'");
+        assert!(parse("\nhello\n").unwrap_err().starts_with("line 2:"));
+        assert_eq!(parse("").unwrap(), ParsedAlignment::default());
+    }
+}
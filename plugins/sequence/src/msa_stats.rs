@@ -0,0 +1,345 @@
+//! Column conservation scores for multiple sequence alignments.
+//!
+//! Besides Shannon information content, a column can be scored by its
+//! Jensen-Shannon divergence from a background distribution (Capra & Singh,
+//! 2007), by the entropy of its physicochemical residue classes (Mirny &
+//! Shakhnovich, 1999), or by the mean substitution score over all pairs of
+//! sequences using a built-in matrix from the alignment plugin.
+//!
+//! Counts may be weighted (see [`crate::weights`]). Divergence and class
+//! entropy are scaled by column occupancy like Shannon information; pairs
+//! involving a gap score zero in the sum-of-pairs score.
+
+use serde::Deserialize;
+
+use seq_align::BuiltinMatrix;
+
+use crate::column_stats::sequence_conservation;
+use crate::logo::{BackgroundSpec, matrix_index, resolve_background};
+
+/// Per-column conservation measure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConservationScore {
+    /// Information content in bits (Schneider & Stephens, 1990).
+    #[default]
+    Shannon,
+    /// Jensen-Shannon divergence from the background, between 0 and 1.
+    JensenShannon,
+    /// Information content over residue classes, in bits.
+    PropertyEntropy,
+    /// Mean substitution score over all sequence pairs.
+    SumOfPairs,
+}
+
+/// Amino acid classes used by default for
+/// [`ConservationScore::PropertyEntropy`]: aliphatic, aromatic, polar,
+/// positive, negative and special (Mirny & Shakhnovich, 1999).
+pub const AMINO_ACID_CLASSES: [&str; 6] = ["AVLIMC", "FWYH", "STNQ", "KR", "DE", "GP"];
+
+/// Settings for [`ConservationScorer`].
+#[derive(Debug, Clone, Default)]
+pub struct ConservationOptions {
+    /// Conservation score to compute.
+    pub score: ConservationScore,
+    /// Small sample correction for [`ConservationScore::Shannon`].
+    pub sampling_correction: bool,
+    /// Background for [`ConservationScore::JensenShannon`].
+    pub background: BackgroundSpec,
+    /// Residue classes for [`ConservationScore::PropertyEntropy`], each a
+    /// string of residues; `None` uses [`AMINO_ACID_CLASSES`]. Residues in no
+    /// class form classes of their own.
+    pub property_groups: Option<Vec<String>>,
+    /// Built-in matrix for [`ConservationScore::SumOfPairs`]; `None` uses
+    /// BLOSUM62.
+    pub matrix: Option<String>,
+}
+
+/// Scores alignment columns from alphabet-indexed residue counts.
+#[derive(Debug, Clone)]
+pub struct ConservationScorer {
+    score: ConservationScore,
+    sampling_correction: bool,
+    alphabet_size: usize,
+    background: Vec<f64>,
+    classes: Vec<usize>,
+    class_count: usize,
+    pair_scores: Vec<f64>,
+    max_score: f64,
+}
+
+impl ConservationScorer {
+    /// Resolves the options against `alphabet`.
+    pub fn new(alphabet: &[String], options: &ConservationOptions) -> Result<Self, String> {
+        let size = alphabet.len();
+        let mut scorer = Self {
+            score: options.score,
+            sampling_correction: options.sampling_correction,
+            alphabet_size: size,
+            background: Vec::new(),
+            classes: Vec::new(),
+            class_count: 0,
+            pair_scores: Vec::new(),
+            max_score: (size as f64).log2(),
+        };
+        match options.score {
+            ConservationScore::Shannon => {}
+            ConservationScore::JensenShannon => {
+                scorer.background = resolve_background(&options.background, alphabet)?;
+                scorer.max_score = 1.0;
+            }
+            ConservationScore::PropertyEntropy => {
+                let (classes, class_count) =
+                    residue_classes(alphabet, options.property_groups.as_deref())?;
+                scorer.classes = classes;
+                scorer.class_count = class_count;
+                scorer.max_score = (class_count as f64).log2();
+            }
+            ConservationScore::SumOfPairs => {
+                let name = options.matrix.as_deref().unwrap_or("BLOSUM62");
+                let matrix = BuiltinMatrix::from_name(name)
+                    .ok_or_else(|| format!("Unknown matrix: {name}"))?;
+                let dim = matrix.score_dimension();
+                let indices = alphabet
+                    .iter()
+                    .map(|residue| {
+                        matrix_index(&matrix, residue).ok_or_else(|| {
+                            format!("matrix {} has no residue '{residue}'", matrix.name())
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                scorer.pair_scores = indices
+                    .iter()
+                    .flat_map(|&i| indices.iter().map(move |&j| (i, j)))
+                    .map(|(i, j)| f64::from(matrix.scores()[i * dim + j]))
+                    .collect();
+                scorer.max_score = (0..size)
+                    .map(|a| scorer.pair_scores[a * size + a])
+                    .fold(0.0, f64::max);
+            }
+        }
+        Ok(scorer)
+    }
+
+    /// Highest score a column can reach, for scaling conservation bars.
+    pub fn max_score(&self) -> f64 {
+        self.max_score
+    }
+
+    /// Scores one column.
+    ///
+    /// `counts` and `squared_weights` hold, per alphabet residue, the sum of
+    /// the weights and of the squared weights of the sequences with that
+    /// residue. `effective_sequences` is the weight of all sequences and
+    /// `pair_weight` the summed weight `w_i w_j` of all sequence pairs.
+    pub fn score(
+        &self,
+        counts: &[f64],
+        squared_weights: &[f64],
+        effective_sequences: f64,
+        pair_weight: f64,
+    ) -> f64 {
+        let total: f64 = counts.iter().sum();
+        if total == 0.0 || effective_sequences == 0.0 {
+            return 0.0;
+        }
+        let occupancy = total / effective_sequences;
+        match self.score {
+            ConservationScore::Shannon => sequence_conservation(
+                counts.iter().copied(),
+                total,
+                effective_sequences,
+                self.sampling_correction,
+                self.max_score,
+                self.alphabet_size,
+            ),
+            ConservationScore::JensenShannon => {
+                let mut divergence = 0.0;
+                for (&count, &p) in counts.iter().zip(&self.background) {
+                    let q = count / total;
+                    let mixture = (q + p) / 2.0;
+                    if q > 0.0 {
+                        divergence += q * (q / mixture).log2() / 2.0;
+                    }
+                    divergence += p * (p / mixture).log2() / 2.0;
+                }
+                occupancy * divergence.max(0.0)
+            }
+            ConservationScore::PropertyEntropy => {
+                let mut class_counts = vec![0.0; self.class_count];
+                for (&count, &class) in counts.iter().zip(&self.classes) {
+                    class_counts[class] += count;
+                }
+                let entropy: f64 = class_counts
+                    .iter()
+                    .map(|&count| count / total)
+                    .filter(|&p| p > 0.0)
+                    .map(|p| -p * p.log2())
+                    .sum();
+                occupancy * (self.max_score - entropy).max(0.0)
+            }
+            ConservationScore::SumOfPairs => {
+                if pair_weight == 0.0 {
+                    return 0.0;
+                }
+                let size = self.alphabet_size;
+                let mut sum = 0.0;
+                for a in 0..size {
+                    for b in 0..size {
+                        sum += counts[a] * counts[b] * self.pair_scores[a * size + b];
+                    }
+                    sum -= squared_weights[a] * self.pair_scores[a * size + a];
+                }
+                sum / 2.0 / pair_weight
+            }
+        }
+    }
+}
+
+/// Assigns each alphabet residue a class index.
+fn residue_classes(
+    alphabet: &[String],
+    groups: Option<&[String]>,
+) -> Result<(Vec<usize>, usize), String> {
+    let default_groups: Vec<String>;
+    let groups = match groups {
+        Some(groups) => groups,
+        None => {
+            default_groups = AMINO_ACID_CLASSES.iter().map(|g| g.to_string()).collect();
+            &default_groups
+        }
+    };
+
+    let mut classes = vec![None; alphabet.len()];
+    let mut class_count = 0;
+    for group in groups {
+        let mut used = false;
+        for residue in group.chars() {
+            let Some(index) = alphabet
+                .iter()
+                .position(|r| r.eq_ignore_ascii_case(&residue.to_string()))
+            else {
+                continue;
+            };
+            if classes[index].is_some() {
+                return Err(format!(
+                    "residue '{residue}' appears in more than one property group"
+                ));
+            }
+            classes[index] = Some(class_count);
+            used = true;
+        }
+        if used {
+            class_count += 1;
+        }
+    }
+    let classes = classes
+        .into_iter()
+        .map(|class| {
+            class.unwrap_or_else(|| {
+                class_count += 1;
+                class_count - 1
+            })
+        })
+        .collect();
+    Ok((classes, class_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMINO_ACIDS: &str = "ARNDCQEGHILKMFPSTWYV";
+
+    fn make_scorer(
+        alphabet: &str,
+        options: ConservationOptions,
+    ) -> (ConservationScorer, Vec<String>) {
+        let alphabet: Vec<String> = alphabet.chars().map(String::from).collect();
+        (
+            ConservationScorer::new(&alphabet, &options).unwrap(),
+            alphabet,
+        )
+    }
+
+    /// Unweighted counts and pair weight for one column.
+    fn score_column(scorer: &ConservationScorer, alphabet: &[String], column: &str) -> f64 {
+        let mut counts = vec![0.0; alphabet.len()];
+        for residue in column.chars() {
+            if let Some(index) = alphabet.iter().position(|r| *r == residue.to_string()) {
+                counts[index] += 1.0;
+            }
+        }
+        let n = column.len() as f64;
+        scorer.score(&counts, &counts, n, n * (n - 1.0) / 2.0)
+    }
+
+    #[test]
+    fn test_jensen_shannon_divergence() {
+        let options = ConservationOptions {
+            score: ConservationScore::JensenShannon,
+            ..Default::default()
+        };
+        let (scorer, alphabet) = make_scorer("ACGT", options);
+        assert_eq!(scorer.max_score(), 1.0);
+        assert!(score_column(&scorer, &alphabet, "ACGT").abs() < 1e-12);
+
+        // JSD between a point mass and uniform over four residues.
+        let expected = 0.5 * (1.0 / 0.625f64).log2()
+            + 0.5 * (0.25 * (0.25f64 / 0.625).log2() + 3.0 * 0.25 * 2.0f64.log2());
+        assert!((score_column(&scorer, &alphabet, "AAAA") - expected).abs() < 1e-12);
+        assert!((score_column(&scorer, &alphabet, "AA--") - expected / 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_property_entropy_groups_similar_residues() {
+        let options = ConservationOptions {
+            score: ConservationScore::PropertyEntropy,
+            ..Default::default()
+        };
+        let (scorer, alphabet) = make_scorer(AMINO_ACIDS, options);
+        assert!((scorer.max_score() - 6f64.log2()).abs() < 1e-12);
+        let aliphatic = score_column(&scorer, &alphabet, "ILVM");
+        assert!((aliphatic - scorer.max_score()).abs() < 1e-12);
+        assert!(score_column(&scorer, &alphabet, "IKDW") < aliphatic);
+
+        let nucleotides = ConservationOptions {
+            score: ConservationScore::PropertyEntropy,
+            property_groups: Some(vec!["AG".into(), "CT".into()]),
+            ..Default::default()
+        };
+        let (scorer, alphabet) = make_scorer("ACGT", nucleotides);
+        assert_eq!(scorer.max_score(), 1.0);
+        assert_eq!(score_column(&scorer, &alphabet, "AGAG"), 1.0);
+        assert_eq!(score_column(&scorer, &alphabet, "ACAC"), 0.0);
+    }
+
+    #[test]
+    fn test_sum_of_pairs_uses_matrix_scores() {
+        let options = ConservationOptions {
+            score: ConservationScore::SumOfPairs,
+            ..Default::default()
+        };
+        let (scorer, alphabet) = make_scorer(AMINO_ACIDS, options);
+        assert_eq!(scorer.max_score(), 11.0);
+        assert_eq!(score_column(&scorer, &alphabet, "WWW"), 11.0);
+        // W/W = 11, W/C = -2 twice.
+        assert!((score_column(&scorer, &alphabet, "WWC") - 7.0 / 3.0).abs() < 1e-12);
+        // Pairs with the gap score zero.
+        assert!((score_column(&scorer, &alphabet, "WW-") - 11.0 / 3.0).abs() < 1e-12);
+
+        let dna = ConservationOptions {
+            score: ConservationScore::SumOfPairs,
+            matrix: Some("EDNAFULL".into()),
+            ..Default::default()
+        };
+        let (scorer, alphabet) = make_scorer("ACGU", dna);
+        assert_eq!(score_column(&scorer, &alphabet, "UU"), 5.0);
+        let unknown = ConservationOptions {
+            score: ConservationScore::SumOfPairs,
+            ..Default::default()
+        };
+        let alphabet: Vec<String> = "AX".chars().map(String::from).collect();
+        assert!(ConservationScorer::new(&alphabet, &unknown).is_err());
+    }
+}
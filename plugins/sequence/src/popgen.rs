@@ -0,0 +1,349 @@
+//! Population-genetics summary statistics for nucleotide alignments.
+//!
+//! Only `A`, `C`, `G` and `T`/`U` count as observed bases; gaps, `N` and
+//! the other IUPAC ambiguity codes are missing data. With complete deletion,
+//! every column with missing data in any sequence is dropped. With pairwise
+//! deletion, each column is used with the sequences that have a base there,
+//! and columns with fewer than two are dropped.
+//!
+//! Nucleotide diversity follows Nei and Li (1979), Watterson's estimator
+//! Watterson (1975), and Tajima's D Tajima (1989). Under pairwise deletion,
+//! Watterson's estimator divides each segregating site by `a_n` for that
+//! column's sample size, while Tajima's D uses the constants for all
+//! sequences. Haplotypes are always compared over the columns without
+//! missing data, so that identity between sequences is well defined.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Handling of gaps and ambiguous bases.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MissingDataPolicy {
+    /// Drop columns with missing data in any sequence.
+    #[default]
+    CompleteDeletion,
+    /// Use each column with the sequences that have a base there.
+    PairwiseDeletion,
+}
+
+/// Request for [`compute_popgen_stats`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct PopgenRequest {
+    /// Aligned nucleotide sequences.
+    pub sequences: Vec<String>,
+    /// Handling of gaps and ambiguous bases.
+    #[serde(default)]
+    pub missing: MissingDataPolicy,
+    /// Window length in alignment columns; `None` skips windows.
+    #[serde(default)]
+    pub window: Option<usize>,
+    /// Distance between window starts; defaults to `window`.
+    #[serde(default)]
+    pub step: Option<usize>,
+}
+
+/// Summary statistics over a set of alignment columns.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PopgenStats {
+    /// Columns used after applying the missing-data policy.
+    pub sites: usize,
+    /// Columns with more than one base.
+    pub segregating_sites: usize,
+    /// Average number of differences between two sequences.
+    pub mean_pairwise_differences: f64,
+    /// Nucleotide diversity per site.
+    pub pi: Option<f64>,
+    /// Watterson's estimator per site.
+    pub theta_w: Option<f64>,
+    /// Tajima's D, or `None` when it is undefined.
+    pub tajima_d: Option<f64>,
+    /// Number of distinct sequences over the used columns.
+    pub haplotypes: usize,
+    /// Nei's haplotype diversity.
+    pub haplotype_diversity: Option<f64>,
+}
+
+/// Statistics for one window, in 1-based inclusive alignment columns.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PopgenWindow {
+    /// First column of the window.
+    pub start: u64,
+    /// Last column of the window.
+    pub end: u64,
+    /// Statistics over the window's columns.
+    #[serde(flatten)]
+    pub stats: PopgenStats,
+}
+
+/// Response of [`compute_popgen_stats`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PopgenResponse {
+    /// Number of sequences.
+    pub sequences: usize,
+    /// Statistics over the whole alignment.
+    pub overall: PopgenStats,
+    /// Statistics per window, when windows were requested.
+    pub windows: Vec<PopgenWindow>,
+}
+
+/// Base index 0–3 for ACGT (with `U` as `T`); `None` for missing data.
+fn base_index(byte: u8) -> Option<usize> {
+    match byte.to_ascii_uppercase() {
+        b'A' => Some(0),
+        b'C' => Some(1),
+        b'G' => Some(2),
+        b'T' | b'U' => Some(3),
+        _ => None,
+    }
+}
+
+/// Whether `byte` is a nucleotide, an IUPAC ambiguity code or a gap.
+fn is_nucleotide_symbol(byte: u8) -> bool {
+    b"ACGTURYSWKMBDHVNX-.?".contains(&byte.to_ascii_uppercase())
+}
+
+/// `a_n = sum 1/i` and `sum 1/i^2` for `i` in `1..n`.
+fn harmonic_sums(n: usize) -> (f64, f64) {
+    (1..n).fold((0.0, 0.0), |(a1, a2), i| {
+        let i = i as f64;
+        (a1 + 1.0 / i, a2 + 1.0 / (i * i))
+    })
+}
+
+fn tajima_d(n: usize, segregating: usize, differences: f64) -> Option<f64> {
+    if n < 3 || segregating == 0 {
+        return None;
+    }
+    let (a1, a2) = harmonic_sums(n);
+    let n = n as f64;
+    let s = segregating as f64;
+    let b1 = (n + 1.0) / (3.0 * (n - 1.0));
+    let b2 = 2.0 * (n * n + n + 3.0) / (9.0 * n * (n - 1.0));
+    let c1 = b1 - 1.0 / a1;
+    let c2 = b2 - (n + 2.0) / (a1 * n) + a2 / (a1 * a1);
+    let e1 = c1 / a1;
+    let e2 = c2 / (a1 * a1 + a2);
+    let variance = e1 * s + e2 * s * (s - 1.0);
+    (variance > 0.0).then(|| (differences - s / a1) / variance.sqrt())
+}
+
+fn column_stats(
+    columns: &[Vec<Option<usize>>],
+    range: std::ops::Range<usize>,
+    n: usize,
+    policy: MissingDataPolicy,
+) -> PopgenStats {
+    let mut sites = 0;
+    let mut segregating = 0;
+    let mut differences = 0.0;
+    let mut watterson = 0.0;
+    let mut complete_columns = Vec::new();
+    for index in range {
+        let column = &columns[index];
+        let complete = column.iter().all(Option::is_some);
+        if complete {
+            complete_columns.push(index);
+        }
+        if !complete && policy == MissingDataPolicy::CompleteDeletion {
+            continue;
+        }
+        let mut counts = [0usize; 4];
+        for base in column.iter().flatten() {
+            counts[*base] += 1;
+        }
+        let observed: usize = counts.iter().sum();
+        if observed < 2 {
+            continue;
+        }
+        sites += 1;
+        if counts.iter().filter(|&&c| c > 0).count() > 1 {
+            segregating += 1;
+            watterson += 1.0 / harmonic_sums(observed).0;
+        }
+        // Differing pairs over all pairs of sequences with a base here.
+        let same: usize = counts.iter().map(|c| c * c.saturating_sub(1)).sum();
+        let observed_pairs = observed * (observed - 1);
+        differences += (observed_pairs - same) as f64 / observed_pairs as f64;
+    }
+
+    let mut haplotype_counts: HashMap<Vec<usize>, usize> = HashMap::new();
+    let haplotypes = (0..n).map(|sequence| {
+        complete_columns
+            .iter()
+            .map(|&column| columns[column][sequence].unwrap_or_default())
+            .collect()
+    });
+    for haplotype in haplotypes {
+        *haplotype_counts.entry(haplotype).or_default() += 1;
+    }
+    let haplotype_diversity = (n >= 2).then(|| {
+        let n = n as f64;
+        let homozygosity: f64 = haplotype_counts
+            .values()
+            .map(|&count| (count as f64 / n).powi(2))
+            .sum();
+        n / (n - 1.0) * (1.0 - homozygosity)
+    });
+
+    let per_site = |value: f64| (sites > 0 && n >= 2).then(|| value / sites as f64);
+    PopgenStats {
+        sites,
+        segregating_sites: segregating,
+        mean_pairwise_differences: differences,
+        pi: per_site(differences),
+        theta_w: per_site(watterson),
+        tajima_d: tajima_d(n, segregating, differences),
+        haplotypes: if n == 0 { 0 } else { haplotype_counts.len() },
+        haplotype_diversity,
+    }
+}
+
+/// Computes segregating sites, nucleotide diversity, Watterson's estimator,
+/// Tajima's D and haplotype statistics over the whole alignment and in
+/// sliding windows.
+///
+/// Windows start every `step` columns and are `window` columns long;
+/// columns after the last full window are not covered. An alignment shorter
+/// than `window` gives a single window over all columns.
+pub fn compute_popgen_stats(request: &PopgenRequest) -> Result<PopgenResponse, String> {
+    let n = request.sequences.len();
+    let length = request.sequences.first().map_or(0, String::len);
+    if let Some(sequence) = request.sequences.iter().find(|s| s.len() != length) {
+        return Err(format!(
+            "all sequences must have the same length; found {} and {length}",
+            sequence.len()
+        ));
+    }
+    let rows: Vec<&[u8]> = request.sequences.iter().map(|s| s.as_bytes()).collect();
+    for (index, row) in rows.iter().enumerate() {
+        if let Some(column) = row.iter().position(|&b| !is_nucleotide_symbol(b)) {
+            return Err(format!(
+                "sequence {} column {}: '{}' is not a nucleotide, IUPAC ambiguity code or gap",
+                index + 1,
+                column + 1,
+                row[column].escape_ascii()
+            ));
+        }
+    }
+    let columns: Vec<Vec<Option<usize>>> = (0..length)
+        .map(|column| rows.iter().map(|row| base_index(row[column])).collect())
+        .collect();
+
+    let overall = column_stats(&columns, 0..length, n, request.missing);
+    let mut windows = Vec::new();
+    if let Some(window) = request.window {
+        let step = request.step.unwrap_or(window);
+        if window == 0 {
+            return Err("window must be at least 1".to_string());
+        }
+        if step == 0 {
+            return Err("step must be at least 1".to_string());
+        }
+        let window = window.min(length);
+        let mut start = 0;
+        while length > 0 && start + window <= length {
+            windows.push(PopgenWindow {
+                start: start as u64 + 1,
+                end: (start + window) as u64,
+                stats: column_stats(&columns, start..start + window, n, request.missing),
+            });
+            start += step;
+        }
+    }
+    Ok(PopgenResponse {
+        sequences: n,
+        overall,
+        windows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(sequences: &[&str], missing: MissingDataPolicy) -> PopgenRequest {
+        PopgenRequest {
+            sequences: sequences.iter().map(|s| s.to_string()).collect(),
+            missing,
+            window: None,
+            step: None,
+        }
+    }
+
+    #[test]
+    fn test_diversity_and_tajima_d() {
+        let sequences = [
+            "AAAAAAAAAA",
+            "AAAAAAAAAA",
+            "AAAAAAAAAA",
+            "CAAAAAAAAA",
+            "CCCAAAAAAA",
+        ];
+        let stats = compute_popgen_stats(&request(&sequences, MissingDataPolicy::CompleteDeletion))
+            .unwrap()
+            .overall;
+        assert_eq!((stats.sites, stats.segregating_sites), (10, 3));
+        // Site 1 differs in 2 x 3 of 10 pairs, sites 2 and 3 in 4 of 10.
+        assert!((stats.mean_pairwise_differences - 1.4).abs() < 1e-12);
+        assert!((stats.pi.unwrap() - 0.14).abs() < 1e-12);
+        let a1 = 1.0 + 1.0 / 2.0 + 1.0 / 3.0 + 1.0 / 4.0;
+        assert!((stats.theta_w.unwrap() - 3.0 / a1 / 10.0).abs() < 1e-12);
+        // pi below theta_w: fewer intermediate-frequency variants than expected.
+        assert!(stats.tajima_d.unwrap() < 0.0);
+        assert_eq!(stats.haplotypes, 3);
+        // 5/4 * (1 - (9 + 1 + 1) / 25)
+        assert!((stats.haplotype_diversity.unwrap() - 0.7).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_missing_data_policies() {
+        let sequences = ["AC-T", "ACGT", "GCNT"];
+        let complete =
+            compute_popgen_stats(&request(&sequences, MissingDataPolicy::CompleteDeletion))
+                .unwrap()
+                .overall;
+        assert_eq!((complete.sites, complete.segregating_sites), (3, 1));
+
+        let pairwise =
+            compute_popgen_stats(&request(&sequences, MissingDataPolicy::PairwiseDeletion))
+                .unwrap()
+                .overall;
+        // Column 3 has a single base and is still dropped.
+        assert_eq!((pairwise.sites, pairwise.segregating_sites), (3, 1));
+        assert_eq!(pairwise.haplotypes, 2);
+
+        let sequences = ["AC-", "ACG", "GCA"];
+        let pairwise =
+            compute_popgen_stats(&request(&sequences, MissingDataPolicy::PairwiseDeletion))
+                .unwrap()
+                .overall;
+        // Column 3 is used with two sequences, which differ.
+        assert_eq!((pairwise.sites, pairwise.segregating_sites), (3, 2));
+        assert!((pairwise.mean_pairwise_differences - (2.0 / 3.0 + 1.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_windows() {
+        let mut req = request(
+            &["AAAAAC", "AAAAAA", "CAAAAA"],
+            MissingDataPolicy::default(),
+        );
+        req.window = Some(3);
+        req.step = Some(2);
+        let response = compute_popgen_stats(&req).unwrap();
+        let coordinates: Vec<(u64, u64)> =
+            response.windows.iter().map(|w| (w.start, w.end)).collect();
+        assert_eq!(coordinates, [(1, 3), (3, 5)]);
+        assert_eq!(response.windows[0].stats.segregating_sites, 1);
+        assert_eq!(response.windows[1].stats.segregating_sites, 0);
+        assert_eq!(response.windows[1].stats.tajima_d, None);
+        assert_eq!(response.overall.segregating_sites, 2);
+
+        assert!(
+            compute_popgen_stats(&request(&["AA", "A"], MissingDataPolicy::default())).is_err()
+        );
+        assert!(compute_popgen_stats(&request(&["AE"], MissingDataPolicy::default())).is_err());
+    }
+}
@@ -0,0 +1,279 @@
+//! Variant sites of a nucleotide alignment relative to a reference sequence.
+//!
+//! Alleles are the bases `A`, `C`, `G`, `T` and `U`, compared without case
+//! and with `U` read as `T`.
+//! Gaps, `N` and the other IUPAC ambiguity codes are missing data: they
+//! neither make a column polymorphic nor get a genotype. Columns where the
+//! reference itself has no base have no reference allele and are skipped.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::msa_formats::AlignedSequence;
+
+/// Request for [`find_snp_sites`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct SnpSitesRequest {
+    /// Aligned nucleotide sequences, including the reference.
+    pub sequences: Vec<AlignedSequence>,
+    /// Identifier of the reference sequence.
+    pub reference: String,
+    /// Also return the alignment restricted to the variant columns.
+    #[serde(default)]
+    pub snp_alignment: bool,
+}
+
+/// One polymorphic alignment column.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnpSite {
+    /// 1-based alignment column.
+    pub column: usize,
+    /// 1-based position in the ungapped reference sequence.
+    pub position: usize,
+    /// Reference allele.
+    pub reference: String,
+    /// Alternative alleles, most frequent first.
+    pub alternatives: Vec<String>,
+    /// Allele of each sequence in input order: 0 for the reference allele,
+    /// `i` for `alternatives[i - 1]`, or `None` for missing data.
+    pub genotypes: Vec<Option<usize>>,
+}
+
+/// Response of [`find_snp_sites`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnpSitesResponse {
+    /// Variant sites in alignment order.
+    pub sites: Vec<SnpSite>,
+    /// Sequences restricted to the variant columns, when requested.
+    pub alignment: Option<Vec<AlignedSequence>>,
+}
+
+/// Uppercase base, with `U` read as `T` so that DNA and RNA sequences
+/// compare equal; `None` for anything but ACGTU.
+fn allele(byte: u8) -> Option<u8> {
+    match byte.to_ascii_uppercase() {
+        b'U' => Some(b'T'),
+        byte @ (b'A' | b'C' | b'G' | b'T') => Some(byte),
+        _ => None,
+    }
+}
+
+/// Finds the columns where any sequence carries a different base than the
+/// reference, with reference coordinates and per-sequence genotypes.
+pub fn find_snp_sites(request: &SnpSitesRequest) -> Result<SnpSitesResponse, String> {
+    let Some(reference_index) = request
+        .sequences
+        .iter()
+        .position(|record| record.id == request.reference)
+    else {
+        return Err(format!(
+            "reference '{}' is not in the alignment",
+            request.reference
+        ));
+    };
+    if let Some(record) = request.sequences.iter().find(|r| !r.sequence.is_ascii()) {
+        return Err(format!(
+            "sequence '{}' contains non-ASCII characters",
+            record.id
+        ));
+    }
+    let rows: Vec<&[u8]> = request
+        .sequences
+        .iter()
+        .map(|record| record.sequence.as_bytes())
+        .collect();
+    let length = rows[reference_index].len();
+    if let Some(record) = request
+        .sequences
+        .iter()
+        .find(|record| record.sequence.len() != length)
+    {
+        return Err(format!(
+            "sequence '{}' has length {}, expected {length}",
+            record.id,
+            record.sequence.len()
+        ));
+    }
+
+    let mut sites = Vec::new();
+    let mut position = 0;
+    for column in 0..length {
+        let reference_byte = rows[reference_index][column];
+        if !matches!(reference_byte, b'-' | b'.') {
+            position += 1;
+        }
+        let Some(reference) = allele(reference_byte) else {
+            continue;
+        };
+        let alleles: Vec<Option<u8>> = rows.iter().map(|row| allele(row[column])).collect();
+
+        let mut counts: HashMap<u8, usize> = HashMap::new();
+        for &base in alleles.iter().flatten().filter(|&&base| base != reference) {
+            *counts.entry(base).or_default() += 1;
+        }
+        if counts.is_empty() {
+            continue;
+        }
+        let mut alternatives: Vec<(u8, usize)> = counts.into_iter().collect();
+        alternatives.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let genotypes = alleles
+            .iter()
+            .map(|base| {
+                base.map(|base| {
+                    alternatives
+                        .iter()
+                        .position(|&(alternative, _)| alternative == base)
+                        .map_or(0, |index| index + 1)
+                })
+            })
+            .collect();
+        sites.push(SnpSite {
+            column: column + 1,
+            position,
+            reference: char::from(reference).to_string(),
+            alternatives: alternatives
+                .iter()
+                .map(|&(base, _)| char::from(base).to_string())
+                .collect(),
+            genotypes,
+        });
+    }
+
+    let alignment = request.snp_alignment.then(|| {
+        request
+            .sequences
+            .iter()
+            .zip(&rows)
+            .map(|(record, row)| AlignedSequence {
+                id: record.id.clone(),
+                sequence: sites
+                    .iter()
+                    .map(|site| char::from(row[site.column - 1]))
+                    .collect(),
+            })
+            .collect()
+    });
+    Ok(SnpSitesResponse { sites, alignment })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(sequences: &[(&str, &str)], reference: &str) -> SnpSitesRequest {
+        SnpSitesRequest {
+            sequences: sequences
+                .iter()
+                .map(|(id, sequence)| AlignedSequence {
+                    id: id.to_string(),
+                    sequence: sequence.to_string(),
+                })
+                .collect(),
+            reference: reference.to_string(),
+            snp_alignment: true,
+        }
+    }
+
+    #[test]
+    fn test_sites_positions_and_genotypes() {
+        let req = request(
+            &[
+                ("s1", "AC-GTA"),
+                ("ref", "AC-GTA"),
+                ("s2", "ATTGCN"),
+                ("s3", "ATAGgA"),
+            ],
+            "ref",
+        );
+        let response = find_snp_sites(&req).unwrap();
+        let columns: Vec<(usize, usize)> = response
+            .sites
+            .iter()
+            .map(|site| (site.column, site.position))
+            .collect();
+        // Column 3 is a reference gap; column 6 only differs by an N.
+        assert_eq!(columns, [(2, 2), (5, 4)]);
+        assert_eq!(response.sites[0].reference, "C");
+        assert_eq!(response.sites[0].alternatives, ["T"]);
+        assert_eq!(
+            response.sites[0].genotypes,
+            [Some(0), Some(0), Some(1), Some(1)]
+        );
+        // T is the reference at column 5; C and G are one copy each.
+        assert_eq!(response.sites[1].alternatives, ["C", "G"]);
+        assert_eq!(
+            response.sites[1].genotypes,
+            [Some(0), Some(0), Some(1), Some(2)]
+        );
+
+        let alignment = response.alignment.unwrap();
+        assert_eq!(alignment[2].sequence, "TC");
+        assert_eq!(alignment[3].sequence, "Tg");
+    }
+
+    #[test]
+    fn test_gap_only_columns_are_skipped() {
+        let req = request(
+            &[("ref", "A--C.G"), ("s1", "A--T.G"), ("s2", "-----G")],
+            "ref",
+        );
+        let response = find_snp_sites(&req).unwrap();
+        assert_eq!(response.sites.len(), 1);
+        assert_eq!(
+            (response.sites[0].column, response.sites[0].position),
+            (4, 2)
+        );
+        assert_eq!(response.sites[0].genotypes, [Some(0), Some(1), None]);
+        assert_eq!(response.alignment.unwrap()[2].sequence, "-");
+    }
+
+    #[test]
+    fn test_iupac_codes_are_missing_data() {
+        // An ambiguous reference base still counts towards positions.
+        let req = request(&[("ref", "RACG"), ("s1", "AACG"), ("s2", "GYCT")], "ref");
+        let response = find_snp_sites(&req).unwrap();
+        assert_eq!(response.sites.len(), 1);
+        let site = &response.sites[0];
+        assert_eq!((site.column, site.position), (4, 4));
+        assert_eq!(site.alternatives, ["T"]);
+
+        // Y is compatible with the reference C but is not an allele.
+        let req = request(&[("ref", "AC"), ("s1", "AY"), ("s2", "AT")], "ref");
+        let site = &find_snp_sites(&req).unwrap().sites[0];
+        assert_eq!(site.genotypes, [Some(0), None, Some(1)]);
+    }
+
+    #[test]
+    fn test_reference_gaps_before_a_snp_keep_reference_coordinates() {
+        let req = request(&[("s1", "ATTCGA"), ("ref", "A--CGT")], "ref");
+        let response = find_snp_sites(&req).unwrap();
+        let columns: Vec<(usize, usize)> = response
+            .sites
+            .iter()
+            .map(|site| (site.column, site.position))
+            .collect();
+        // The insertion in s1 (columns 2-3) has no reference position.
+        assert_eq!(columns, [(6, 4)]);
+        assert_eq!(response.sites[0].reference, "T");
+        assert_eq!(response.sites[0].genotypes, [Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn test_missing_data_and_errors() {
+        let req = request(&[("ref", "ACG"), ("s1", "-CN"), ("s2", "RCG")], "ref");
+        let response = find_snp_sites(&req).unwrap();
+        assert!(response.sites.is_empty());
+        assert_eq!(response.alignment.unwrap()[0].sequence, "");
+
+        // U and T are the same allele.
+        let req = request(&[("ref", "ACGT"), ("rna", "ACGU"), ("s1", "ACGC")], "ref");
+        let response = find_snp_sites(&req).unwrap();
+        assert_eq!(response.sites.len(), 1);
+        assert_eq!(response.sites[0].alternatives, ["C"]);
+        assert_eq!(response.sites[0].genotypes, [Some(0), Some(0), Some(1)]);
+
+        assert!(find_snp_sites(&request(&[("a", "AC")], "ref")).is_err());
+        assert!(find_snp_sites(&request(&[("ref", "AC"), ("a", "A")], "ref")).is_err());
+    }
+}
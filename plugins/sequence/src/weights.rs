@@ -0,0 +1,207 @@
+//! Sequence weights that down-weight redundant sequences in an alignment.
+//!
+//! Weights are scaled so they sum to the effective number of sequences, so
+//! weighted residue counts are on the same scale as raw counts and equal them
+//! when no weighting is applied.
+
+use serde::{Deserialize, Serialize};
+
+use crate::column_stats::residue_lookup;
+
+/// How sequences are weighted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SequenceWeighting {
+    /// Every sequence has weight 1.
+    #[default]
+    None,
+    /// Position-based weights (Henikoff & Henikoff, 1994).
+    Henikoff,
+    /// Inverse number of sequences at or above an identity threshold, as in
+    /// PSI-BLAST and HHblits.
+    Identity,
+}
+
+/// Default identity threshold for [`SequenceWeighting::Identity`].
+pub const DEFAULT_IDENTITY_THRESHOLD: f64 = 0.8;
+
+/// Per-sequence weights and the effective number of sequences.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SequenceWeights {
+    /// Weight of each sequence, in input order. They sum to
+    /// `effective_sequences`.
+    pub weights: Vec<f64>,
+    /// Effective number of sequences: the sequence count without weighting,
+    /// `(sum w)^2 / sum w^2` of the raw Henikoff weights, or the sum of the
+    /// inverse neighbor counts for identity weighting.
+    pub effective_sequences: f64,
+}
+
+/// Computes weights for aligned sequences over the residues in `alphabet`.
+///
+/// Residues outside the alphabet, including gaps, are ignored. For identity
+/// weighting, identity is the fraction of matching residues among columns
+/// where both sequences have one.
+pub fn compute_sequence_weights(
+    sequences: &[String],
+    alphabet: &[String],
+    weighting: SequenceWeighting,
+    identity_threshold: f64,
+) -> Result<SequenceWeights, String> {
+    let lookup = residue_lookup(alphabet)?;
+    let sequences: Vec<&[u8]> = sequences.iter().map(|s| s.as_bytes()).collect();
+    weights_for(
+        &sequences,
+        &lookup,
+        alphabet.len(),
+        weighting,
+        identity_threshold,
+    )
+}
+
+pub(crate) fn weights_for(
+    sequences: &[&[u8]],
+    lookup: &[Option<usize>; 256],
+    alphabet_size: usize,
+    weighting: SequenceWeighting,
+    identity_threshold: f64,
+) -> Result<SequenceWeights, String> {
+    if !(identity_threshold > 0.0 && identity_threshold <= 1.0) {
+        return Err(format!(
+            "identity_threshold must be in (0, 1], got {identity_threshold}"
+        ));
+    }
+    let n = sequences.len();
+    let uniform = || SequenceWeights {
+        weights: vec![1.0; n],
+        effective_sequences: n as f64,
+    };
+    if n == 0 {
+        return Ok(uniform());
+    }
+
+    // Residue indices per sequence; `None` for gaps and unknown symbols.
+    let encoded: Vec<Vec<Option<usize>>> = sequences
+        .iter()
+        .map(|seq| seq.iter().map(|&b| lookup[b as usize]).collect())
+        .collect();
+
+    match weighting {
+        SequenceWeighting::None => Ok(uniform()),
+        SequenceWeighting::Henikoff => {
+            let raw = henikoff_weights(&encoded, alphabet_size);
+            let sum: f64 = raw.iter().sum();
+            let sum_squares: f64 = raw.iter().map(|w| w * w).sum();
+            if sum == 0.0 {
+                return Ok(uniform());
+            }
+            let effective = sum * sum / sum_squares;
+            Ok(SequenceWeights {
+                weights: raw.iter().map(|w| w * effective / sum).collect(),
+                effective_sequences: effective,
+            })
+        }
+        SequenceWeighting::Identity => {
+            let mut neighbors = vec![1usize; n];
+            for i in 0..n {
+                for j in (i + 1)..n {
+                    if identity(&encoded[i], &encoded[j]) >= identity_threshold {
+                        neighbors[i] += 1;
+                        neighbors[j] += 1;
+                    }
+                }
+            }
+            let weights: Vec<f64> = neighbors.iter().map(|&k| 1.0 / k as f64).collect();
+            let effective = weights.iter().sum();
+            Ok(SequenceWeights {
+                weights,
+                effective_sequences: effective,
+            })
+        }
+    }
+}
+
+/// Sums `1 / (r * n_x)` over columns, where `r` is the number of distinct
+/// residues in the column and `n_x` the number of sequences sharing the
+/// sequence's residue.
+fn henikoff_weights(encoded: &[Vec<Option<usize>>], alphabet_size: usize) -> Vec<f64> {
+    let length = encoded.iter().map(Vec::len).max().unwrap_or(0);
+    let mut weights = vec![0.0; encoded.len()];
+    let mut counts = vec![0usize; alphabet_size];
+    for pos in 0..length {
+        counts.fill(0);
+        for seq in encoded {
+            if let Some(Some(index)) = seq.get(pos) {
+                counts[*index] += 1;
+            }
+        }
+        let distinct = counts.iter().filter(|&&c| c > 0).count();
+        if distinct == 0 {
+            continue;
+        }
+        for (weight, seq) in weights.iter_mut().zip(encoded) {
+            if let Some(Some(index)) = seq.get(pos) {
+                *weight += 1.0 / (distinct * counts[*index]) as f64;
+            }
+        }
+    }
+    weights
+}
+
+/// Fraction of matching residues among columns where both sequences have one.
+pub(crate) fn identity(a: &[Option<usize>], b: &[Option<usize>]) -> f64 {
+    let mut aligned = 0usize;
+    let mut matches = 0usize;
+    for (x, y) in a.iter().zip(b) {
+        if let (Some(x), Some(y)) = (x, y) {
+            aligned += 1;
+            if x == y {
+                matches += 1;
+            }
+        }
+    }
+    if aligned == 0 {
+        0.0
+    } else {
+        matches as f64 / aligned as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weights(sequences: &[&str], weighting: SequenceWeighting) -> SequenceWeights {
+        let sequences: Vec<String> = sequences.iter().map(|s| s.to_string()).collect();
+        let alphabet: Vec<String> = "ACGT".chars().map(String::from).collect();
+        compute_sequence_weights(&sequences, &alphabet, weighting, 0.8).unwrap()
+    }
+
+    #[test]
+    fn test_henikoff_down_weights_duplicates() {
+        let result = weights(&["AAAA", "AAAA", "CCGG"], SequenceWeighting::Henikoff);
+        // Each column: r = 2, duplicates get 1/4 each, the outlier 1/2.
+        let total: f64 = result.weights.iter().sum();
+        assert!((total - result.effective_sequences).abs() < 1e-12);
+        assert!((result.weights[0] - result.weights[1]).abs() < 1e-12);
+        assert!((result.weights[2] - 2.0 * result.weights[0]).abs() < 1e-12);
+        assert!((result.effective_sequences - 16.0 / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_identity_weights_count_neighbors() {
+        let result = weights(
+            &["ACGTACGTAC", "ACGTACGTAA", "TTTTTTTTTT"],
+            SequenceWeighting::Identity,
+        );
+        assert_eq!(result.weights, [0.5, 0.5, 1.0]);
+        assert_eq!(result.effective_sequences, 2.0);
+    }
+
+    #[test]
+    fn test_no_weighting_is_uniform() {
+        let result = weights(&["AC", "AC", "A-"], SequenceWeighting::None);
+        assert_eq!(result.weights, [1.0, 1.0, 1.0]);
+        assert_eq!(result.effective_sequences, 3.0);
+    }
+}
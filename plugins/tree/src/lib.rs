@@ -1,11 +1,21 @@
 //! tree: Phylogenetic tree layout and fitting backend for the Typst package.
+//!
+//! The crate also builds as a native library. The typed functions
+//! [`parse_newick_tree`], [`prepare_tree_layout`] and [`fit_prepared_tree`]
+//! run the same code paths as the `parse_newick`, `prepare_layout` and
+//! `fit_tree` WASM entry points.
+
+#![cfg_attr(not(target_arch = "wasm32"), warn(missing_docs))]
 
 use newick::{NewickTree, one_from_string};
+pub use plugin_io::{WireFormat, decompress, is_gzip};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::cmp::Ordering;
+#[cfg(all(target_arch = "wasm32", feature = "plugin"))]
 use wasm_minimal_protocol::*;
 
+#[cfg(all(target_arch = "wasm32", feature = "plugin"))]
 initiate_protocol!();
 
 const FIT_TOLERANCE_PT: f64 = 0.1;
@@ -14,19 +24,24 @@ const ROTATION_SCORE_TOLERANCE: f64 = 1e-9;
 const DEGENERATE_TOLERANCE: f64 = 1e-12;
 
 /// JSON result returned by the `parse_newick` WASM entry point.
-#[derive(Serialize)]
-struct ParseResult {
-    rooted: bool,
+#[derive(Debug, Clone, Serialize)]
+pub struct ParseResult {
+    /// Whether the Newick input was marked rooted.
+    pub rooted: bool,
+    /// Root node of the parsed tree.
     #[serde(flatten)]
-    tree: SimpleTreeNode,
+    pub tree: SimpleTreeNode,
 }
 
 /// Simplified tree node serialized for Typst-side Newick parsing.
-#[derive(Serialize)]
-struct SimpleTreeNode {
-    name: Option<String>,
-    length: Option<f64>,
-    children: Option<Vec<SimpleTreeNode>>,
+#[derive(Debug, Clone, Serialize)]
+pub struct SimpleTreeNode {
+    /// Node label, if any.
+    pub name: Option<String>,
+    /// Branch length to the parent, if given.
+    pub length: Option<f64>,
+    /// Child nodes, or `none` for a leaf.
+    pub children: Option<Vec<SimpleTreeNode>>,
 }
 
 #[derive(Debug, Clone)]
@@ -38,66 +53,90 @@ struct RawTreeNode {
     rooted: bool,
 }
 
+/// Serialized node of a prepared layout tree.
 #[derive(Debug, Clone, Serialize)]
-struct LayoutNodeWire {
+pub struct LayoutNodeWire {
+    /// Index of the parent node, or `none` for the root.
     #[serde(rename = "parent-id")]
-    parent_id: Option<usize>,
+    pub parent_id: Option<usize>,
+    /// Indices of the child nodes.
     #[serde(rename = "children-ids")]
-    children_ids: Vec<usize>,
+    pub children_ids: Vec<usize>,
+    /// Whether this node is the layout root.
     #[serde(rename = "is-root")]
-    is_root: bool,
+    pub is_root: bool,
+    /// Whether the input tree was rooted.
     #[serde(rename = "input-rooted")]
-    input_rooted: bool,
+    pub input_rooted: bool,
+    /// Whether this node has no children.
     #[serde(rename = "is-leaf")]
-    is_leaf: bool,
+    pub is_leaf: bool,
+    /// Label drawn for the node, if any.
     #[serde(rename = "label-text")]
-    label_text: Option<String>,
+    pub label_text: Option<String>,
+    /// Stable label id used to style individual labels.
     #[serde(rename = "label-id")]
-    label_id: Option<String>,
+    pub label_id: Option<String>,
+    /// Horizontal position in tree units.
     #[serde(rename = "x-unit")]
-    x_unit: f64,
+    pub x_unit: f64,
+    /// Vertical position in tree units.
     #[serde(rename = "y-unit")]
-    y_unit: f64,
+    pub y_unit: f64,
+    /// Direction of the incoming branch, in half turns.
     #[serde(rename = "branch-angle")]
-    branch_angle: f64,
+    pub branch_angle: f64,
 }
 
 /// Typst-facing layout fit modes returned in prepared layout metadata.
 #[derive(Debug, Clone, Copy, Serialize)]
 #[serde(rename_all = "kebab-case")]
-enum LayoutFitModeWire {
+pub enum LayoutFitModeWire {
+    /// Scale the two axes separately, as for rectangular layouts.
     IndependentAxes,
+    /// Scale both axes by the same factor, as for radial layouts.
     Uniform,
 }
 
 /// Typst-facing primitive mode tags used by prepared layout responses.
 #[derive(Debug, Clone, Copy, Serialize)]
 #[serde(rename_all = "kebab-case")]
-enum PrimitiveModeWire {
+pub enum PrimitiveModeWire {
+    /// Horizontal and vertical branch segments.
     Rectangular,
+    /// Straight segments between parent and child.
     EdgeSegments,
 }
 
 /// Serialized layout tree returned by the `prepare_layout` WASM entry point.
 #[derive(Debug, Clone, Serialize)]
-struct LayoutTreeWire {
-    nodes: Vec<LayoutNodeWire>,
+pub struct LayoutTreeWire {
+    /// Layout nodes, indexed by node id.
+    pub nodes: Vec<LayoutNodeWire>,
+    /// Index of the root node.
     #[serde(rename = "root-id")]
-    root_id: usize,
+    pub root_id: usize,
+    /// Number of nodes in the layout.
     #[serde(rename = "node-count")]
-    node_count: usize,
+    pub node_count: usize,
+    /// Whether branch lengths were replaced by unit lengths.
     #[serde(rename = "effective-cladogram")]
-    effective_cladogram: bool,
+    pub effective_cladogram: bool,
+    /// Layout kind that produced the node positions.
     #[serde(rename = "layout-kind")]
-    layout_kind: LayoutKind,
+    pub layout_kind: LayoutKind,
+    /// How the fit step should scale the layout.
     #[serde(rename = "fit-mode")]
-    fit_mode: LayoutFitModeWire,
+    pub fit_mode: LayoutFitModeWire,
+    /// Which primitives the Typst side should draw.
     #[serde(rename = "primitive-mode")]
-    primitive_mode: PrimitiveModeWire,
+    pub primitive_mode: PrimitiveModeWire,
+    /// Horizontal extent of the layout in tree units.
     #[serde(rename = "tree-depth")]
-    tree_depth: f64,
+    pub tree_depth: f64,
+    /// Vertical extent of the layout in tree units.
     #[serde(rename = "tree-height")]
-    tree_height: f64,
+    pub tree_height: f64,
 }
 
 /// JSON request accepted by the `prepare_layout` WASM entry point.
@@ -105,20 +144,25 @@ struct LayoutTreeWire {
 /// `layout-kind` uses the Rust wire vocabulary: `"rectangular"`,
 /// `"equal_angle"`, or `"daylight"`.
 #[derive(Debug, Deserialize)]
-struct PrepareLayoutRequest {
+pub struct PrepareLayoutRequest {
+    /// Tree produced by `parse_newick`.
     #[serde(rename = "tree-data")]
-    tree_data: Value,
+    pub tree_data: Value,
+    /// Ignore branch lengths and use unit lengths.
     #[serde(default)]
-    cladogram: bool,
+    pub cladogram: bool,
+    /// Drop the root of an unrooted input.
     #[serde(rename = "suppress-unrooted")]
     #[serde(default)]
-    suppress_unrooted: bool,
+    pub suppress_unrooted: bool,
+    /// Omit labels on internal nodes.
     #[serde(rename = "hide-internal-labels")]
     #[serde(default)]
-    hide_internal_labels: bool,
+    pub hide_internal_labels: bool,
+    /// Layout algorithm to run.
     #[serde(rename = "layout-kind")]
     #[serde(default)]
-    layout_kind: LayoutKind,
+    pub layout_kind: LayoutKind,
 }
 
 #[derive(Debug, Clone)]
@@ -370,143 +414,226 @@ struct ComponentArcEntry {
     component_index: usize,
 }
 
+/// How `fit_tree` scales the tree to the viewport.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
-enum FitMode {
+pub enum FitMode {
+    /// Scale the two axes separately.
     IndependentAxes,
+    /// Scale both axes by the same factor.
     Uniform,
 }
 
 /// Tree layout kind used on the Rust JSON wire boundary.
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "kebab-case")]
-enum LayoutKind {
+pub enum LayoutKind {
+    /// Rectangular cladogram or phylogram.
     #[default]
     Rectangular,
+    /// Unrooted equal-angle layout.
     EqualAngle,
+    /// Unrooted equal-angle layout refined by the daylight algorithm.
     Daylight,
 }
 
+/// Direction in which the root-to-tip axis runs.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "kebab-case")]
-enum Orientation {
+pub enum Orientation {
+    /// Root on the left, tips on the right.
     #[default]
     Horizontal,
+    /// Root at the top, tips at the bottom.
     Vertical,
 }
 
+/// How the viewport width is chosen.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "kebab-case")]
-enum WidthMode {
+pub enum WidthMode {
+    /// Pick the width from the tree and its labels.
     #[default]
     Auto,
+    /// Use the given viewport width.
     Resolved,
+    /// Use the given viewport width but report it as unresolved.
     Provisional,
 }
 
+/// How the viewport height is chosen.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "kebab-case")]
-enum HeightMode {
+pub enum HeightMode {
+    /// Pick the height from the tree and its labels.
     #[default]
     Auto,
+    /// Use the given viewport height.
     Resolved,
 }
 
+/// Coordinate frame of a label's gap and rotation.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
-enum PlacementFrame {
+pub enum PlacementFrame {
+    /// Gaps and rotation are in page coordinates.
     Screen,
+    /// Gaps and rotation follow the branch direction.
     Local,
 }
 
+/// Kind of node a label belongs to.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
-enum PlacementRole {
+pub enum PlacementRole {
+    /// Label of a leaf.
     TipLabel,
+    /// Label of an internal node.
     InternalLabel,
 }
 
+/// Horizontal alignment of a label relative to its anchor.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
-enum XAlign {
+pub enum XAlign {
+    /// Anchor at the left edge.
     Left,
+    /// Anchor at the right edge.
     Right,
+    /// Anchor at the horizontal center.
     Center,
 }
 
+/// Vertical alignment of a label relative to its anchor.
 #[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
-enum YAlign {
+pub enum YAlign {
+    /// Anchor at the top edge.
     Top,
+    /// Anchor at the bottom edge.
     Bottom,
+    /// Anchor at the vertical center.
     Center,
 }
 
+/// Point in tree units or points, depending on context.
 #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
-struct Point {
-    x: f64,
-    y: f64,
+pub struct Point {
+    /// Horizontal coordinate.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub x: f64,
+    /// Vertical coordinate.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub y: f64,
 }
 
+/// Position made of a tree-unit part and a fixed page offset.
 #[derive(Debug, Clone, Copy, Deserialize)]
-struct Anchor {
-    tree: Point,
-    page: Point,
+pub struct Anchor {
+    /// Part that scales with the tree.
+    pub tree: Point,
+    /// Offset in points that does not scale.
+    pub page: Point,
 }
 
+/// Branch segment measured on the Typst side.
 #[derive(Debug, Clone, Copy, Deserialize)]
-struct PreparedLine {
-    start_anchor: Anchor,
-    end_anchor: Anchor,
-    half_stroke_pt: f64,
+pub struct PreparedLine {
+    /// Start of the segment.
+    pub start_anchor: Anchor,
+    /// End of the segment.
+    pub end_anchor: Anchor,
+    /// Half the stroke width, in points.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub half_stroke_pt: f64,
 }
 
+/// Label measured on the Typst side.
 #[derive(Debug, Clone, Copy, Deserialize)]
-struct PreparedLabel {
-    placement_role: PlacementRole,
-    anchor_tree: Point,
-    anchor_page: Point,
-    x_align: XAlign,
-    y_align: YAlign,
-    x_gap_pt: f64,
-    y_gap_pt: f64,
-    rotation_deg: f64,
-    placement_frame: PlacementFrame,
-    branch_angle_half_turn: Option<f64>,
-    placement_angle_half_turn: Option<f64>,
-    measure_width_pt: f64,
-    measure_height_pt: f64,
+pub struct PreparedLabel {
+    /// Kind of node the label belongs to.
+    pub placement_role: PlacementRole,
+    /// Anchor position in tree units.
+    pub anchor_tree: Point,
+    /// Fixed anchor offset in points.
+    pub anchor_page: Point,
+    /// Horizontal alignment relative to the anchor.
+    pub x_align: XAlign,
+    /// Vertical alignment relative to the anchor.
+    pub y_align: YAlign,
+    /// Horizontal gap between anchor and label, in points.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub x_gap_pt: f64,
+    /// Vertical gap between anchor and label, in points.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub y_gap_pt: f64,
+    /// Label rotation in degrees.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub rotation_deg: f64,
+    /// Frame in which gaps and rotation apply.
+    pub placement_frame: PlacementFrame,
+    /// Direction of the labelled branch, in half turns.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub branch_angle_half_turn: Option<f64>,
+    /// Direction in which a radial label is placed, in half turns.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub placement_angle_half_turn: Option<f64>,
+    /// Measured label width in points.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub measure_width_pt: f64,
+    /// Measured label height in points.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub measure_height_pt: f64,
 }
 
 /// JSON request accepted by the `fit_tree` WASM entry point.
 #[derive(Debug, Deserialize)]
-struct FitRequest {
-    fit_mode: FitMode,
-    layout_kind: LayoutKind,
+pub struct FitRequest {
+    /// How to scale the layout.
+    pub fit_mode: FitMode,
+    /// Layout kind that produced the primitives.
+    pub layout_kind: LayoutKind,
+    /// Direction of the root-to-tip axis.
     #[serde(default)]
-    orientation: Orientation,
-    prepared_lines: Vec<PreparedLine>,
-    prepared_labels: Vec<PreparedLabel>,
-    root_tree_point: Point,
-    tree_depth: f64,
-    tree_height: f64,
+    pub orientation: Orientation,
+    /// Branch segments to place.
+    pub prepared_lines: Vec<PreparedLine>,
+    /// Labels to place.
+    pub prepared_labels: Vec<PreparedLabel>,
+    /// Root position in tree units.
+    pub root_tree_point: Point,
+    /// Horizontal extent of the layout in tree units.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub tree_depth: f64,
+    /// Vertical extent of the layout in tree units.
+    #[serde(deserialize_with = "plugin_io::lenient::float")]
+    pub tree_height: f64,
+    /// How to choose the viewport width.
     #[serde(default)]
-    width_mode: WidthMode,
+    pub width_mode: WidthMode,
+    /// Viewport width in points, when given.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub viewport_width_pt: Option<f64>,
+    /// How to choose the viewport height.
     #[serde(default)]
-    viewport_width_pt: Option<f64>,
+    pub height_mode: HeightMode,
+    /// Viewport height in points, when given.
+    #[serde(default, deserialize_with = "plugin_io::lenient::option_float")]
+    pub viewport_height_pt: Option<f64>,
+    /// Smallest automatic height, in points.
+    #[serde(default, deserialize_with = "plugin_io::lenient::float")]
+    pub auto_height_floor_pt: f64,
+    /// Samples per search band for independent-axis fitting.
     #[serde(default)]
-    height_mode: HeightMode,
+    pub fit_band_samples: Option<usize>,
+    /// Maximum number of search bands.
+    pub fit_max_bands: usize,
+    /// Search for the rotation that fits a uniform layout best.
     #[serde(default)]
-    viewport_height_pt: Option<f64>,
+    pub optimize_uniform_rotation: bool,
+    /// Align tip labels at the right edge of the tree.
     #[serde(default)]
-    auto_height_floor_pt: f64,
-    #[serde(default)]
-    fit_band_samples: Option<usize>,
-    fit_max_bands: usize,
-    #[serde(default)]
-    optimize_uniform_rotation: bool,
-    #[serde(default)]
-    align_tip_labels: bool,
+    pub align_tip_labels: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -711,30 +838,47 @@ struct RotationTransform {
 
 /// JSON response returned by the `fit_tree` WASM entry point.
 #[derive(Serialize)]
-struct FitResponse {
-    width_unresolved: bool,
-    tree_viewport_width_pt: f64,
-    tree_viewport_height_pt: f64,
-    x_scale_pt: f64,
-    tree_translation_pt: Point,
-    root_position_pt: Point,
-    tree_lines: Vec<SerializableLine>,
-    tree_labels: Vec<SerializableLabel>,
-}
-
+pub struct FitResponse {
+    /// Whether the width is provisional and may change.
+    pub width_unresolved: bool,
+    /// Final viewport width in points.
+    pub tree_viewport_width_pt: f64,
+    /// Final viewport height in points.
+    pub tree_viewport_height_pt: f64,
+    /// Points per horizontal tree unit.
+    pub x_scale_pt: f64,
+    /// Offset applied to the whole tree, in points.
+    pub tree_translation_pt: Point,
+    /// Root position in points.
+    pub root_position_pt: Point,
+    /// Placed branch segments.
+    pub tree_lines: Vec<SerializableLine>,
+    /// Placed labels.
+    pub tree_labels: Vec<SerializableLabel>,
+}
+
+/// Branch segment placed by `fit_tree`.
 #[derive(Serialize)]
-struct SerializableLine {
-    line_index: usize,
-    start_pt: Point,
-    end_pt: Point,
+pub struct SerializableLine {
+    /// Index into the request's `prepared_lines`.
+    pub line_index: usize,
+    /// Start of the segment in points.
+    pub start_pt: Point,
+    /// End of the segment in points.
+    pub end_pt: Point,
 }
 
+/// Label placed by `fit_tree`.
 #[derive(Serialize)]
-struct SerializableLabel {
-    label_index: usize,
-    origin_pt: Point,
-    anchor_pt: Point,
-    rotation_deg: f64,
+pub struct SerializableLabel {
+    /// Index into the request's `prepared_labels`.
+    pub label_index: usize,
+    /// Top-left corner of the label box in points.
+    pub origin_pt: Point,
+    /// Anchor position in points.
+    pub anchor_pt: Point,
+    /// Label rotation in degrees.
+    pub rotation_deg: f64,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -3727,17 +3871,10 @@ fn finalize_fitted_tree_plan(
     })
 }
 
-/// WASM entry point for preparing a normalized tree layout.
-///
-/// # Arguments
-/// * `config` - JSON-encoded [`PrepareLayoutRequest`] payload
+/// Normalizes a parsed tree and computes its layout.
 ///
-/// # Returns
-/// JSON bytes of [`LayoutTreeWire`] or an error string.
-#[wasm_func]
-pub fn prepare_layout(config: &[u8]) -> Result<Vec<u8>, String> {
-    let request: PrepareLayoutRequest = serde_json::from_slice(config)
-        .map_err(|e| format!("Invalid prepare-layout config JSON: {e}"))?;
+/// This is the native counterpart of the `prepare_layout` WASM entry point.
+pub fn prepare_tree_layout(request: PrepareLayoutRequest) -> Result<LayoutTreeWire, String> {
     let normalized = normalize_raw_tree(
         request.tree_data,
         request.cladogram,
@@ -3745,25 +3882,27 @@ pub fn prepare_layout(config: &[u8]) -> Result<Vec<u8>, String> {
         request.hide_internal_labels,
     )?;
     let layout = layout_normalized_tree(normalized, request.layout_kind)?;
-    serde_json::to_vec(&layout_tree_to_wire(&layout))
-        .map_err(|e| format!("Serialization failed: {e}"))
+    Ok(layout_tree_to_wire(&layout))
 }
 
-/// WASM entry point for parsing Newick input into a simplified tree structure.
+/// WASM entry point for preparing a normalized tree layout.
 ///
 /// # Arguments
-/// * `input` - Newick source as UTF-8 bytes
+/// * `config` - JSON- or CBOR-encoded [`PrepareLayoutRequest`] payload
 ///
 /// # Returns
-/// JSON bytes of [`ParseResult`] or an error string.
-#[wasm_func]
-pub fn parse_newick(input: &[u8]) -> Result<Vec<u8>, String> {
-    let input_str = std::str::from_utf8(input)
-        .map(|s| s.to_string())
-        .map_err(|e| e.to_string())?;
+/// [`LayoutTreeWire`] encoded like `config`, or an error string.
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+pub fn prepare_layout(config: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(config, "prepare-layout config", prepare_tree_layout)
+}
 
-    let tree = one_from_string(&input_str)
-        .map_err(|_| format!("Failed to parse Newick string: {input_str}"))?;
+/// Parses a Newick string into a simplified tree structure.
+///
+/// This is the native counterpart of the `parse_newick` WASM entry point.
+pub fn parse_newick_tree(input: &str) -> Result<ParseResult, String> {
+    let tree =
+        one_from_string(input).map_err(|_| format!("Failed to parse Newick string: {input}"))?;
 
     let root_id = tree.root();
     let is_rooted = tree
@@ -3772,28 +3911,35 @@ pub fn parse_newick(input: &[u8]) -> Result<Vec<u8>, String> {
         .unwrap_or(false);
     let simple_tree = convert_node_to_simple(&tree, root_id)?;
 
-    let result = ParseResult {
+    Ok(ParseResult {
         rooted: is_rooted,
         tree: simple_tree,
-    };
-
-    serde_json::to_vec(&result).map_err(|e| e.to_string())
+    })
 }
 
-/// WASM entry point for fitting a prepared tree plan into a viewport.
+/// WASM entry point for parsing Newick input into a simplified tree structure.
 ///
 /// # Arguments
-/// * `config` - JSON-encoded [`FitRequest`] payload
+/// * `input` - Newick source as UTF-8 bytes, optionally gzip- or
+///   BGZF-compressed
 ///
 /// # Returns
-/// JSON bytes of [`FitResponse`] or an error string.
-#[wasm_func]
-pub fn fit_tree(config: &[u8]) -> Result<Vec<u8>, String> {
-    let request: FitRequest =
-        serde_json::from_slice(config).map_err(|e| format!("Invalid fit config JSON: {e}"))?;
+/// JSON bytes of [`ParseResult`] or an error string.
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+pub fn parse_newick(input: &[u8]) -> Result<Vec<u8>, String> {
+    let input = decompress(input)?;
+    let input_str = std::str::from_utf8(&input).map_err(|e| e.to_string())?;
+    let result = parse_newick_tree(input_str.trim())?;
+    serde_json::to_vec(&result).map_err(|e| e.to_string())
+}
+
+/// Fits a prepared tree plan into the requested viewport.
+///
+/// This is the native counterpart of the `fit_tree` WASM entry point.
+pub fn fit_prepared_tree(request: &FitRequest) -> Result<FitResponse, String> {
     request.validate()?;
 
-    let fit_inputs = FitInputs::from(&request);
+    let fit_inputs = FitInputs::from(request);
     let auto_height = if request.height_mode == HeightMode::Auto {
         let label_only_bounds = evaluate_tree_bounds_only(
             &fit_inputs.prepared_lines,
@@ -3811,14 +3957,27 @@ pub fn fit_tree(config: &[u8]) -> Result<Vec<u8>, String> {
     };
 
     let fitted_width = match request.fit_mode {
-        FitMode::Uniform => fit_tree_plan_uniform(&request, &fit_inputs, auto_height),
+        FitMode::Uniform => fit_tree_plan_uniform(request, &fit_inputs, auto_height),
         FitMode::IndependentAxes => {
-            fit_tree_plan_independent_axes(&request, &fit_inputs, auto_height)?
+            fit_tree_plan_independent_axes(request, &fit_inputs, auto_height)?
         }
     };
 
-    let response = finalize_fitted_tree_plan(request.fit_mode, fitted_width)?;
-    serde_json::to_vec(&response).map_err(|e| format!("Serialization failed: {e}"))
+    finalize_fitted_tree_plan(request.fit_mode, fitted_width)
+}
+
+/// WASM entry point for fitting a prepared tree plan into a viewport.
+///
+/// # Arguments
+/// * `config` - JSON- or CBOR-encoded [`FitRequest`] payload
+///
+/// # Returns
+/// [`FitResponse`] encoded like `config`, or an error string.
+#[cfg_attr(all(target_arch = "wasm32", feature = "plugin"), wasm_func)]
+pub fn fit_tree(config: &[u8]) -> Result<Vec<u8>, String> {
+    plugin_io::handle(config, "fit config", |request: FitRequest| {
+        fit_prepared_tree(&request)
+    })
 }
 
 #[cfg(test)]
@@ -4350,4 +4509,56 @@ mod tests {
         assert_eq!(wire.nodes[2].label_text.as_deref(), Some("TipB"));
         assert_eq!(wire.nodes[2].label_id, None);
     }
+
+    #[test]
+    fn prepare_layout_accepts_cbor_and_answers_in_cbor() {
+        let request = json!({
+            "tree-data": parsed_tree_data("((A:1,B:2):1,C:3);"),
+            "layout-kind": "rectangular",
+        });
+        let mut cbor_request = Vec::new();
+        ciborium::into_writer(&request, &mut cbor_request).unwrap();
+
+        let from_json: Value = serde_json::from_slice(
+            &prepare_layout(&serde_json::to_vec(&request).unwrap()).unwrap(),
+        )
+        .unwrap();
+        let from_cbor: Value =
+            ciborium::from_reader(prepare_layout(&cbor_request).unwrap().as_slice()).unwrap();
+
+        assert_eq!(from_cbor, from_json);
+    }
+
+    #[test]
+    fn native_prepare_layout_matches_wasm_entry_point() {
+        let parsed = parse_newick_tree("((A:1,B:2):1,C:3);").unwrap();
+        assert!(parsed.rooted);
+
+        let tree_data = serde_json::to_value(&parsed).unwrap();
+        let request = json!({ "tree-data": tree_data, "layout-kind": "rectangular" });
+        let native = prepare_tree_layout(serde_json::from_value(request.clone()).unwrap()).unwrap();
+        let via_wasm: Value = serde_json::from_slice(
+            &prepare_layout(&serde_json::to_vec(&request).unwrap()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(serde_json::to_value(&native).unwrap(), via_wasm);
+    }
+
+    #[test]
+    fn parse_newick_accepts_gzip_input() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let newick = "((A:1,B:2):1,C:3);\n";
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(newick.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(
+            parse_newick(&compressed).unwrap(),
+            parse_newick(newick.as_bytes()).unwrap()
+        );
+    }
 }
@@ -35,18 +35,20 @@
 
 /// Executes pairwise alignment through the WASM backend.
 ///
+/// The request is sent as CBOR, so the DP matrix and traceback paths come
+/// back as CBOR too instead of as a large JSON string.
+///
 /// - seq-1 (str): First cleaned sequence.
 /// - seq-2 (str): Second cleaned sequence.
 /// - config (dictionary): Backend request payload.
 /// -> dictionary
 #let _alignment-align(seq-1, seq-2, config) = {
-  let config-json = json.encode(config)
   let result = _alignment-backend.align(
     bytes(seq-1),
     bytes(seq-2),
-    bytes(config-json),
+    cbor.encode(config),
   )
-  json(result)
+  cbor(result)
 }
 
 /// Retrieves scoring matrix information through the WASM backend.
@@ -60,3 +62,73 @@
   result.insert("scores", result.scores.map(_convert-infinity))
   result
 }
+
+/// Validates a matrix dictionary in the `get-scoring-matrix` shape.
+///
+/// - matrix (dictionary): Matrix with `name`, `alphabet`, and `matrix` keys.
+/// -> none
+#let _validate-matrix-dictionary(matrix) = {
+  for key in ("name", "alphabet", "matrix") {
+    assert(
+      key in matrix,
+      message: "scoring matrix dictionary must have a '" + key + "' key.",
+    )
+  }
+  let n = matrix.alphabet.len()
+  assert(
+    matrix.matrix.len() == n and matrix.matrix.all(row => row.len() == n),
+    message: "scoring matrix must be square and match its alphabet.",
+  )
+}
+
+/// Converts a matrix dictionary to the backend `{name, alphabet, scores}`
+/// shape, encoding infinite scores as i32 sentinels.
+///
+/// - matrix (dictionary): Matrix from `get-scoring-matrix` or
+///   `derive-scoring-matrix`.
+/// -> dictionary
+#let _matrix-to-backend(matrix) = (
+  name: matrix.name,
+  alphabet: matrix.alphabet,
+  scores: matrix.matrix.flatten().map(value => if value == -float.inf {
+    -2147483648
+  } else if value == float.inf { 2147483647 } else { value }),
+)
+
+/// Derives a log-odds substitution matrix through the WASM backend.
+///
+/// Converts backend infinity sentinels before returning the parsed payload.
+///
+/// - config (dictionary): Backend request payload.
+/// -> dictionary
+#let _alignment-derive-matrix(config) = {
+  let result = cbor(_alignment-backend.derive_matrix(cbor.encode(config)))
+  result.insert("scores", result.scores.map(_convert-infinity))
+  result
+}
+
+/// Extrapolates a PAMn log-odds matrix through the WASM backend.
+///
+/// Converts backend infinity sentinels before returning the parsed payload.
+///
+/// - config (dictionary): Backend request payload.
+/// -> dictionary
+#let _alignment-pam-matrix(config) = {
+  let result = cbor(_alignment-backend.pam_matrix(cbor.encode(config)))
+  result.insert("scores", result.scores.map(_convert-infinity))
+  result
+}
+
+/// Computes dot plot segments through the WASM backend.
+///
+/// - seq-1 (str): First sequence.
+/// - seq-2 (str): Second sequence.
+/// - config (dictionary): Backend request payload.
+/// -> dictionary
+#let _alignment-dot-plot(seq-1, seq-2, config) = cbor(
+  _alignment-backend.dot_plot(
+    bytes(seq-1),
+    bytes(seq-2),
+    cbor.encode(config),
+  ),
+)
@@ -0,0 +1,126 @@
+#import "./alignment_backend.typ": _alignment-dot-plot, _resolve-matrix-name
+
+/// Computes a dot plot of two sequences as a list of diagonal segments.
+///
+/// The "kmer" method reports exact matches of `word-size` residues, like
+/// EMBOSS dottup, and uses an index rather than comparing every pair of
+/// positions, so it scales to whole bacterial genomes. The "window" method
+/// scores every window of `window` residue pairs along each diagonal and keeps
+/// those scoring at least `threshold`, like EMBOSS dotmatcher. Without a
+/// scoring matrix or match/mismatch scores, identical residues score 1 and
+/// others 0, so `threshold` is the minimum number of identities per window.
+/// Overlapping hits on the same diagonal are merged into one segment.
+///
+/// Whitespace is removed from both sequences and matching ignores case.
+///
+/// - seq-1 (str): First sequence, on the x axis.
+/// - seq-2 (str): Second sequence, on the y axis.
+/// - method (str): "kmer" or "window" (default: "kmer").
+/// - word-size (int): Word length for the "kmer" method (default: 10).
+/// - window (int): Window length for the "window" method (default: 10).
+/// - threshold (int, none): Minimum window score, required by the "window" method (default: none).
+/// - scoring-matrix (str, none): Built-in scoring matrix name for the "window" method, e.g. "EDNAFULL" or "BLOSUM62". Mutually exclusive with match/mismatch scores (default: none).
+/// - match-score (int, none): Window score for identical residues (default: none).
+/// - mismatch-score (int, none): Window score for different residues (default: none).
+/// - strand (str): "forward", or "both" to also compare against the reverse complement of `seq-2`. Requires nucleotide sequences (default: "forward").
+/// - min-length (int): Shortest segment to report, in residues (default: 0).
+/// - max-cells (int, auto): Largest comparison, in cells summed over strands, that the "window" method may compute, and most word matches the "kmer" method may visit, e.g. for low-complexity sequences. `auto` uses the backend default of 100,000,000 (default: auto).
+/// -> dictionary with keys:
+///   - seq-1-length (int): Length of the cleaned first sequence.
+///   - seq-2-length (int): Length of the cleaned second sequence.
+///   - segments (array): Segments sorted by `x-start`, each a dictionary with:
+///     - strand (str): "forward" or "reverse".
+///     - x-start (int): First position on `seq-1`, 1-based.
+///     - y-start (int): Matching position on `seq-2`, 1-based.
+///     - x-end (int): Last position on `seq-1`.
+///     - y-end (int): Matching position on `seq-2`. Smaller than `y-start`
+///       on the reverse strand.
+///     - length (int): Number of residue pairs.
+#let get-dot-plot(
+  seq-1,
+  seq-2,
+  method: "kmer",
+  word-size: 10,
+  window: 10,
+  threshold: none,
+  scoring-matrix: none,
+  match-score: none,
+  mismatch-score: none,
+  strand: "forward",
+  min-length: 0,
+  max-cells: auto,
+) = {
+  assert(type(seq-1) == str, message: "seq-1 must be a string.")
+  assert(type(seq-2) == str, message: "seq-2 must be a string.")
+  assert(
+    method in ("kmer", "window"),
+    message: "method must be 'kmer' or 'window'.",
+  )
+  assert(
+    type(word-size) == int and word-size > 0,
+    message: "word-size must be a positive integer.",
+  )
+  assert(
+    type(window) == int and window > 0,
+    message: "window must be a positive integer.",
+  )
+  assert(
+    method != "window" or type(threshold) == int,
+    message: "threshold must be an integer for the 'window' method.",
+  )
+  assert(
+    strand in ("forward", "both"),
+    message: "strand must be 'forward' or 'both'.",
+  )
+  assert(
+    type(min-length) == int and min-length >= 0,
+    message: "min-length must be a non-negative integer.",
+  )
+  assert(
+    max-cells == auto or (type(max-cells) == int and max-cells > 0),
+    message: "max-cells must be auto or a positive integer.",
+  )
+
+  let config = (
+    method: method,
+    word_size: word-size,
+    window: window,
+    strand: strand,
+    min_length: min-length,
+  )
+  if threshold != none { config.insert("threshold", threshold) }
+  if max-cells != auto { config.insert("max_cells", max-cells) }
+  if scoring-matrix != none {
+    assert(
+      type(scoring-matrix) == str,
+      message: "scoring-matrix must be a string or none.",
+    )
+    let canonical = _resolve-matrix-name(scoring-matrix)
+    assert(
+      canonical != none,
+      message: "Unknown scoring matrix: '" + scoring-matrix + "'.",
+    )
+    config.insert("matrix", canonical)
+  }
+  if match-score != none { config.insert("match_score", match-score) }
+  if mismatch-score != none { config.insert("mismatch_score", mismatch-score) }
+
+  let result = _alignment-dot-plot(
+    seq-1.replace(regex("\\s"), ""),
+    seq-2.replace(regex("\\s"), ""),
+    config,
+  )
+
+  (
+    seq-1-length: result.seq1_length,
+    seq-2-length: result.seq2_length,
+    segments: result.segments.map(segment => (
+      strand: segment.strand,
+      x-start: segment.x_start,
+      y-start: segment.y_start,
+      x-end: segment.x_end,
+      y-end: segment.y_end,
+      length: segment.length,
+    )),
+  )
+}
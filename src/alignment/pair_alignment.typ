@@ -1,5 +1,8 @@
 #import "../common/fixed_grid.typ": _fixed-width-grid
-#import "./alignment_backend.typ": _alignment-align, _resolve-matrix-name
+#import "./alignment_backend.typ": (
+  _alignment-align, _matrix-to-backend, _resolve-matrix-name,
+  _validate-matrix-dictionary,
+)
 #import "./alignment_coords.typ": _parse-coord, _validate-path
 
 /// Validates and cleans a sequence string.
@@ -21,12 +24,13 @@
   upper(compact)
 }
 
-/// Validates scoring parameters and returns canonical scoring-matrix name.
+/// Validates scoring parameters and returns the resolved scoring matrix.
 ///
-/// - scoring-matrix (str, none): Scoring matrix name.
+/// - scoring-matrix (str, dictionary, none): Scoring matrix name, or a matrix
+///   dictionary from `get-scoring-matrix` or `derive-scoring-matrix`.
 /// - match-score (int, none): Match score.
 /// - mismatch-score (int, none): Mismatch score.
-/// -> str, none (canonical scoring-matrix name when using a matrix)
+/// -> str, dictionary, none (canonical scoring-matrix name or matrix dictionary when using a matrix)
 #let _validate-scoring-params(scoring-matrix, match-score, mismatch-score) = {
   // Mutual exclusivity
   assert(
@@ -42,8 +46,17 @@
     message: "Provide either 'scoring-matrix' or both 'match-score' and 'mismatch-score'.",
   )
 
+  if type(scoring-matrix) == dictionary {
+    _validate-matrix-dictionary(scoring-matrix)
+    return scoring-matrix
+  }
+
   // Matrix name resolution (case-insensitive)
   if scoring-matrix != none {
+    assert(
+      type(scoring-matrix) == str,
+      message: "scoring-matrix must be a string, a dictionary, or none.",
+    )
     let canonical = _resolve-matrix-name(scoring-matrix)
     assert(
       canonical != none,
@@ -55,11 +68,16 @@
 
 /// Builds the Typst configuration dictionary for the alignment backend.
 ///
-/// - canonical-scoring-matrix (str, none): Canonical scoring matrix name.
+/// - canonical-scoring-matrix (str, dictionary, none): Canonical scoring
+///   matrix name or matrix dictionary.
 /// - match-score (int, none): Match score.
 /// - mismatch-score (int, none): Mismatch score.
 /// - gap-penalty (int): Gap penalty (required).
 /// - mode (str): Alignment mode.
+/// - max-dp-cells (int, auto): DP matrix cell limit, or auto for the backend default.
+/// - oversize (str): Behavior when the cell limit is exceeded.
+/// - strand (str): Strands of the second sequence to align.
+/// - strand-report (str): Which strand results to return.
 /// -> dictionary
 #let _build-config(
   canonical-scoring-matrix,
@@ -67,14 +85,27 @@
   mismatch-score,
   gap-penalty,
   mode,
+  max-dp-cells,
+  oversize,
+  strand,
+  strand-report,
 ) = {
   let config = (
     gap_open: gap-penalty,
     gap_extend: gap-penalty,
     mode: mode,
+    oversize: oversize,
+    strand: strand,
+    strand_report: strand-report,
   )
 
-  if canonical-scoring-matrix != none {
+  if max-dp-cells != auto {
+    config.insert("max_cells", max-dp-cells)
+  }
+
+  if type(canonical-scoring-matrix) == dictionary {
+    config.insert("custom_matrix", _matrix-to-backend(canonical-scoring-matrix))
+  } else if canonical-scoring-matrix != none {
     config.insert("matrix", canonical-scoring-matrix)
   } else {
     config.insert("match_score", match-score)
@@ -102,8 +133,10 @@
 ///   - scoring (dictionary): Scoring settings used for the alignment.
 ///   - alignments (array): Alignment dictionaries returned by the backend.
 ///   - traceback-paths (array): Traceback paths in end-to-start order.
-///   - dp-matrix (dictionary): Dense DP matrix payload.
+///   - dp-matrix (dictionary, none): Dense DP matrix payload, or none when omitted.
 ///   - has-alignment (bool): Whether at least one alignment was found.
+///   - strand (str): "forward", or "reverse" when `original-seq-2` is the
+///     reverse complement of the input.
 #let _transform-result(
   wasm-result,
   original-seq-1,
@@ -135,14 +168,17 @@
       mismatch-score: mismatch-score,
       gap-penalty: gap-penalty,
     ),
+    strand: wasm-result.at("strand", default: "forward"),
     alignments: wasm-result.alignments,
     traceback-paths: traceback-paths,
-    dp-matrix: (
-      rows: dp.rows,
-      cols: dp.cols,
-      scores: dp.scores,
-      arrows: dp.arrow_bits,
-    ),
+    dp-matrix: if dp == none { none } else {
+      (
+        rows: dp.rows,
+        cols: dp.cols,
+        scores: dp.scores,
+        arrows: dp.arrow_bits,
+      )
+    },
     has-alignment: has-alignment,
   )
 }
@@ -163,16 +199,23 @@
 ///
 /// - seq-1 (str): First sequence to align.
 /// - seq-2 (str): Second sequence to align.
-/// - scoring-matrix (str, none): Scoring matrix name (e.g., "BLOSUM62"). Mutually exclusive with match/mismatch scores (default: none).
+/// - scoring-matrix (str, dictionary, none): Scoring matrix name (e.g., "BLOSUM62"), or a matrix dictionary from `get-scoring-matrix` or `derive-scoring-matrix`. Mutually exclusive with match/mismatch scores (default: none).
 /// - match-score (int, none): Score for matching characters. Required if scoring-matrix is none (default: none).
 /// - mismatch-score (int, none): Score for mismatching characters. Required if scoring-matrix is none (default: none).
 /// - gap-penalty (int): Gap penalty (required).
 /// - mode (str): Alignment mode: "global" or "local" (default: "global").
+/// - max-dp-cells (int, auto): Largest DP matrix, in cells, that may be computed. `auto` uses the backend default of 4,000,000 cells, about 2000 × 2000 residues (default: auto).
+/// - oversize (str): What to do when the DP matrix would exceed `max-dp-cells`: "error" fails, while "alignment-only" computes the score and a single optimal alignment in linear memory and returns no DP matrix (default: "error").
+/// - strand (str): "forward" aligns `seq-2` as given; "both" also aligns its reverse complement (IUPAC-aware) and returns the higher-scoring strand, forward on ties. Requires nucleotide sequences (default: "forward").
+/// - strand-report (str): With `strand: "both"`, "best" returns only the higher-scoring strand, while "all" also returns the other one as `other-strand` (default: "best").
 /// -> dictionary with keys:
 ///   - seq-1 (str): Cleaned first input sequence.
-///   - seq-2 (str): Cleaned second input sequence.
+///   - seq-2 (str): Cleaned second input sequence, or its reverse complement
+///     when `strand` is "reverse".
 ///   - score (int): Alignment score.
 ///   - mode (str): Alignment mode.
+///   - strand (str): Strand of `seq-2` that was aligned: "forward" or
+///     "reverse".
 ///   - scoring (dictionary): Scoring settings used for the alignment, with:
 ///     - scoring-matrix (str, none): Scoring matrix name, if used.
 ///     - match-score (int, none): Match score, if used.
@@ -181,9 +224,14 @@
 ///   - alignments (array): Alignment dictionaries with keys:
 ///     - seq1 (str): First aligned sequence with gaps.
 ///     - seq2 (str): Second aligned sequence with gaps.
+///     - similarity (str): EMBOSS-style markup with one character per
+///       column: `|` for identical residues, `:` for a positive substitution
+///       score, `.` for a score of zero, and a space for negative scores and
+///       gaps. Pass it to `render-pair-alignment` to show similarity.
 ///   - traceback-paths (array): Traceback paths as arrays of `(row, col)`
 ///     coordinates, in end-to-start order.
-///   - dp-matrix (dictionary): DP matrix data with keys:
+///   - dp-matrix (dictionary, none): DP matrix data, or none when the matrix
+///     exceeded `max-dp-cells` and `oversize` is "alignment-only". Keys:
 ///     - rows (int): Number of DP rows.
 ///     - cols (int): Number of DP columns.
 ///     - scores (array): Flat row-major DP scores.
@@ -192,6 +240,13 @@
 ///       multiple optimal predecessors, so `3` means diagonal+up and `7` means
 ///       diagonal+up+left.
 ///   - has-alignment (bool): Whether at least one alignment was found.
+///   - seq-2-ranges (array): Only with `strand: "both"`. For each alignment,
+///     the 1-based `(first, last)` aligned positions on the input `seq-2`, or
+///     none if no residue was aligned. On the reverse strand `first` is the
+///     larger position.
+///   - other-strand (dictionary, none): Only with `strand: "both"`. The
+///     result for the other strand in the same shape when `strand-report` is
+///     "all", otherwise none.
 #let align-seq-pair(
   seq-1,
   seq-2,
@@ -200,6 +255,10 @@
   mismatch-score: none,
   gap-penalty: none,
   mode: "global",
+  max-dp-cells: auto,
+  oversize: "error",
+  strand: "forward",
+  strand-report: "best",
 ) = {
   let cleaned-seq-1 = _validate-sequence(seq-1, "seq-1")
   let cleaned-seq-2 = _validate-sequence(seq-2, "seq-2")
@@ -214,6 +273,22 @@
     mode in ("global", "local"),
     message: "mode must be 'global' or 'local'.",
   )
+  assert(
+    max-dp-cells == auto or (type(max-dp-cells) == int and max-dp-cells > 0),
+    message: "max-dp-cells must be auto or a positive integer.",
+  )
+  assert(
+    oversize in ("error", "alignment-only"),
+    message: "oversize must be 'error' or 'alignment-only'.",
+  )
+  assert(
+    strand in ("forward", "both"),
+    message: "strand must be 'forward' or 'both'.",
+  )
+  assert(
+    strand-report in ("best", "all"),
+    message: "strand-report must be 'best' or 'all'.",
+  )
 
   // Build config and call WASM
   let config = _build-config(
@@ -222,20 +297,45 @@
     mismatch-score,
     gap-penalty,
     mode,
+    max-dp-cells,
+    oversize,
+    strand,
+    strand-report,
   )
   let wasm-result = _alignment-align(cleaned-seq-1, cleaned-seq-2, config)
 
   // Transform and return result
-  _transform-result(
+  let transform = (wasm-result, aligned-seq-2) => _transform-result(
     wasm-result,
     cleaned-seq-1,
-    cleaned-seq-2,
+    aligned-seq-2,
     mode,
-    canonical-scoring-matrix,
+    if type(canonical-scoring-matrix) == dictionary {
+      canonical-scoring-matrix.name
+    } else { canonical-scoring-matrix },
     match-score,
     mismatch-score,
     gap-penalty,
   )
+
+  if strand == "forward" {
+    return transform(wasm-result, cleaned-seq-2)
+  }
+
+  // Both strands: results come best first, each with its aligned seq-2
+  let results = wasm-result.strands.map(entry => {
+    let result = transform(entry, entry.seq2)
+    result.insert(
+      "seq-2-ranges",
+      entry.seq2_ranges.map(range => if range == none { none } else {
+        (range.at(0), range.at(1))
+      }),
+    )
+    result
+  })
+  let best = results.at(0)
+  best.insert("other-strand", results.at(1, default: none))
+  best
 }
 
 /// Builds the three alignment rows directly from a traceback path.
@@ -246,6 +346,10 @@
 /// - gap-char (str): Character for gaps.
 /// - match-char (str): Character for matches.
 /// - mismatch-char (str): Character for mismatches.
+/// - similarity (array, none): Similarity markup characters, one per path
+///   step, or none to compare characters directly.
+/// - class-chars (dictionary): Display characters for the similarity classes
+///   `|`, `:` and `.`.
 /// - hide-unaligned (bool): Whether to hide unaligned characters entirely.
 /// - build-unaligned-mask (bool): Whether to track unaligned cells for optional coloring.
 /// -> dictionary with keys:
@@ -260,6 +364,8 @@
   gap-char,
   match-char,
   mismatch-char,
+  similarity,
+  class-chars,
   hide-unaligned,
   build-unaligned-mask,
 ) = {
@@ -301,7 +407,9 @@
 
       aligned1.push(char1)
       aligned2.push(char2)
-      match-line.push(if char1 == char2 { match-char } else { mismatch-char })
+      match-line.push(if similarity != none {
+        class-chars.at(similarity.at(i - 1), default: mismatch-char)
+      } else if char1 == char2 { match-char } else { mismatch-char })
       if unaligned-mask != none { unaligned-mask.push(false) }
 
       seq-1-pos += 1
@@ -355,6 +463,8 @@
 ///
 /// Creates a three-line display showing the first aligned sequence (with gaps),
 /// match/mismatch indicators, and the second aligned sequence (with gaps).
+/// Given the `similarity` markup of an alignment from `align-seq-pair`, the
+/// middle line also distinguishes similar from merely aligned residues.
 /// The traceback path from `align-seq-pair` goes from end to start (high indices
 /// to low), so it is automatically reversed before processing.
 ///
@@ -364,6 +474,12 @@
 /// - gap-char (str): Character to display for gaps (default: "–").
 /// - match-char (str): Character to display for matches (default: "│").
 /// - mismatch-char (str): Character to display for mismatches (default: " ").
+/// - similarity (str, none): Similarity markup from the `similarity` key of an
+///   alignment returned by `align-seq-pair`, for the same traceback path. Its
+///   `|`, `:` and `.` columns are shown with `match-char`, `similar-char` and
+///   `weak-char` (default: none, which only marks identical characters).
+/// - similar-char (str): Character to display for positive-scoring substitutions when `similarity` is given (default: ":").
+/// - weak-char (str): Character to display for zero-scoring substitutions when `similarity` is given (default: ".").
 /// - hide-unaligned (bool): Whether to hide unaligned characters entirely (default: false).
 /// - unaligned-color (color, none): Color for visible unaligned characters (default: none, which uses the default text color).
 /// -> content
@@ -374,6 +490,9 @@
   gap-char: "–",
   match-char: "│",
   mismatch-char: " ",
+  similarity: none,
+  similar-char: ":",
+  weak-char: ".",
   hide-unaligned: false,
   unaligned-color: none,
 ) = {
@@ -393,6 +512,18 @@
 
   _validate-path(reversed-path, seq1-chars.len(), seq2-chars.len())
 
+  let similarity-chars = if similarity == none { none } else {
+    assert(
+      type(similarity) == str,
+      message: "similarity must be a string or none.",
+    )
+    similarity.clusters()
+  }
+  assert(
+    similarity-chars == none or similarity-chars.len() == path.len() - 1,
+    message: "similarity must have one character per alignment column.",
+  )
+
   let build-unaligned-mask = not hide-unaligned and unaligned-color != none
 
   let result = _build-alignment-lines(
@@ -402,6 +533,8 @@
     gap-char,
     match-char,
     mismatch-char,
+    similarity-chars,
+    ("|": match-char, ":": similar-char, ".": weak-char),
     hide-unaligned,
     build-unaligned-mask,
   )
@@ -1,6 +1,44 @@
-#import "./alignment_backend.typ": _alignment-matrix-info, _resolve-matrix-name
+#import "./alignment_backend.typ": (
+  _alignment-derive-matrix, _alignment-matrix-info, _alignment-pam-matrix,
+  _resolve-matrix-name,
+)
 #import "../common/colors.typ": diverging-color-map
 
+/// Converts backend matrix statistics to kebab-case keys.
+///
+/// - raw-result (dictionary): Backend matrix payload.
+/// -> dictionary, none
+#let _matrix-statistics(raw-result) = {
+  let stats = raw-result.statistics
+  if stats == none { return none }
+  let n = raw-result.alphabet.len()
+  (
+    lambda: stats.lambda,
+    background-source: stats.background_source,
+    background-frequencies: raw-result
+      .alphabet
+      .zip(stats.background_frequencies)
+      .to-dict(),
+    target-frequencies: stats.target_frequencies.chunks(n),
+    relative-entropy: stats.relative_entropy,
+    expected-score: stats.expected_score,
+  )
+}
+
+/// Builds a `get-scoring-matrix` record from a backend matrix payload.
+///
+/// - raw-result (dictionary): Backend matrix payload.
+/// -> dictionary
+#let _matrix-record(raw-result) = (
+  name: raw-result.name,
+  alphabet: raw-result.alphabet,
+  matrix: raw-result.scores.chunks(raw-result.alphabet.len()),
+  scale-bits: raw-result.scale_bits,
+  reported-entropy: raw-result.reported_entropy,
+  reported-expected-score: raw-result.reported_expected_score,
+  statistics: _matrix-statistics(raw-result),
+)
+
 /// Retrieves a scoring matrix by name from the WASM plugin.
 ///
 /// Available scoring matrices: BLOSUM30, BLOSUM40, BLOSUM45, BLOSUM50,
@@ -10,14 +48,45 @@
 /// Infinite values in the matrix (used for forbidden substitutions) are
 /// represented as `float.inf` or `-float.inf`.
 ///
+/// Header values are taken from the matrix file and are `none` when it does
+/// not state them. `statistics` describes the target frequencies implied by
+/// the scores (Altschul, 1991). The background is the one implied by the
+/// matrix itself when that is a valid distribution (Yu & Altschul, 2005),
+/// otherwise Robinson & Robinson amino acid frequencies or equal A/C/G/T
+/// frequencies; `statistics.background-source` tells which one was used.
+/// Some matrices, such as PAM250, imply no valid background at all.
+///
 /// - name (str): Matrix name (e.g., "BLOSUM62").
+/// - background (auto, str): "implied" to require the background implied by
+///   the matrix and fail if it has none, or auto to fall back to a standard
+///   background (default: auto).
 /// -> dictionary with keys:
 ///   - name (str): Canonical matrix name.
 ///   - alphabet (array): Symbols covered by the matrix.
 ///   - matrix (array): 2D score matrix aligned to `alphabet`.
-#let get-scoring-matrix(name) = {
+///   - scale-bits (float, none): Bits per score unit, e.g. 0.5 for BLOSUM62.
+///   - reported-entropy (float, none): Relative entropy in bits stated in
+///     the header.
+///   - reported-expected-score (float, none): Expected score in matrix units
+///     stated in the header.
+///   - statistics (dictionary, none): `none` if no background applies,
+///     otherwise a dictionary with keys:
+///     - lambda (float): Scale in nats per score unit.
+///     - background-source (str): "implied", "robinson-robinson", or
+///       "uniform-nucleotide".
+///     - background-frequencies (dictionary): Frequency of each residue.
+///     - target-frequencies (array): 2D pair frequencies aligned to
+///       `alphabet`.
+///     - relative-entropy (float): Relative entropy in bits.
+///     - expected-score (float, none): Expected score in matrix units, or
+///       `none` if a possible pair scores `-float.inf`.
+#let get-scoring-matrix(name, background: auto) = {
   let canonical = _resolve-matrix-name(name)
   assert(canonical != none, message: "Unknown scoring matrix: '" + name + "'.")
+  assert(
+    background == auto or background == "implied",
+    message: "background must be auto or \"implied\".",
+  )
 
   let raw-result = _alignment-matrix-info(canonical)
 
@@ -27,14 +96,178 @@
     message: "scoring matrix backend returned an unexpected score count.",
   )
 
-  // Validate backend shape and reshape flat scores into rows.
-  let matrix-2d = raw-result.scores.chunks(n)
+  let record = _matrix-record(raw-result)
+  if background == "implied" {
+    let statistics = record.statistics
+    assert(
+      statistics != none and statistics.background-source == "implied",
+      message: record.name + " does not imply a valid background distribution.",
+    )
+  }
+  record
+}
+
+/// Derives a BLOSUM-style log-odds scoring matrix from alignment data.
+///
+/// Residue pairs are counted in every column of a trusted multiple sequence
+/// alignment, or taken from a pair-count table. Each score is
+/// `scale * log2(q / (p1 * p2))` for observed pair frequency `q` and
+/// background frequencies `p1`, `p2`, rounded to an integer. Pairs that were
+/// never observed score `-float.inf` unless a pseudocount is added.
+///
+/// The result has the same shape as `get-scoring-matrix`, so it can be
+/// rendered with `render-scoring-matrix` or passed to `align-seq-pair` as
+/// `scoring-matrix`.
+///
+/// - sequences (dictionary, array, none): Aligned sequences of equal length,
+///   as an array of strings or a dictionary such as the one returned by
+///   `parse-fasta`. Gaps (`-`, `.`) and residues outside `alphabet` are
+///   skipped (default: none).
+/// - pair-counts (array, none): Square 2D array of residue-pair counts aligned
+///   to `alphabet`. Either a full symmetric table or one holding each pair
+///   once, in either triangle (default: none).
+/// - alphabet (str, array, auto): Residues of the matrix. If auto, uses the
+///   20 standard amino acids in BLOSUM order (default: auto).
+/// - cluster-identity (ratio, none): Sequences sharing at least this identity
+///   are clustered and each cluster counts as a single sequence, e.g. `62%`
+///   for BLOSUM62. Only used with `sequences` (default: none).
+/// - scale (int, float): Multiplier on log2 odds; 2 gives half-bit units
+///   (default: 2).
+/// - rounding (str): Rounding of scaled scores: "nearest", "floor", or "ceil"
+///   (default: "nearest").
+/// - pseudocount (int, float): Count added to every pair before computing
+///   frequencies (default: 0).
+/// - name (str): Name of the resulting matrix (default: "CUSTOM").
+/// -> dictionary with the keys of `get-scoring-matrix`:
+///   - name (str): Matrix name.
+///   - alphabet (array): Symbols covered by the matrix.
+///   - matrix (array): 2D score matrix aligned to `alphabet`.
+///   - scale-bits (float): Bits per score unit, `1 / scale`.
+///   - reported-entropy (none): Always `none`; there is no file header.
+///   - reported-expected-score (none): Always `none`.
+///   - statistics (dictionary, none): Computed from the derived scores, as
+///     for `get-scoring-matrix`.
+#let derive-scoring-matrix(
+  sequences: none,
+  pair-counts: none,
+  alphabet: auto,
+  cluster-identity: none,
+  scale: 2,
+  rounding: "nearest",
+  pseudocount: 0,
+  name: "CUSTOM",
+) = {
+  assert(
+    (sequences == none) != (pair-counts == none),
+    message: "Provide exactly one of 'sequences' or 'pair-counts'.",
+  )
+  assert(
+    cluster-identity == none or type(cluster-identity) == ratio,
+    message: "cluster-identity must be a ratio (e.g., 62%) or none.",
+  )
+  assert(
+    rounding in ("nearest", "floor", "ceil"),
+    message: "rounding must be 'nearest', 'floor', or 'ceil'.",
+  )
+  assert(type(name) == str, message: "name must be a string.")
 
-  (
-    name: raw-result.name,
-    alphabet: raw-result.alphabet,
-    matrix: matrix-2d,
+  let config = (
+    name: name,
+    scale: scale,
+    rounding: rounding,
+    pseudocount: pseudocount,
+  )
+  if alphabet != auto {
+    assert(
+      type(alphabet) in (str, array),
+      message: "alphabet must be a string, an array, or auto.",
+    )
+    config.insert("alphabet", if type(alphabet) == array {
+      alphabet.join()
+    } else { alphabet })
+  }
+  if sequences != none {
+    assert(
+      type(sequences) in (array, dictionary),
+      message: "sequences must be an array or a dictionary.",
+    )
+    config.insert("sequences", if type(sequences) == dictionary {
+      sequences.values()
+    } else { sequences })
+  } else {
+    assert(
+      alphabet != auto,
+      message: "alphabet is required when using pair-counts.",
+    )
+    config.insert("pair_counts", pair-counts.flatten())
+  }
+  if cluster-identity != none {
+    config.insert("cluster_identity", float(cluster-identity))
+  }
+
+  _matrix-record(_alignment-derive-matrix(config))
+}
+
+/// Computes a PAM scoring matrix for any evolutionary distance.
+///
+/// Raises the Dayhoff PAM1 mutation probability matrix to the power
+/// `distance` and converts it to log-odds scores: `scale * log2(p / f)` for
+/// substitution probability `p` and background frequency `f`, averaged over
+/// both directions. Substitutions that remain impossible at short distances
+/// score `-float.inf`. Because the published PAM1 probabilities are rounded,
+/// rare substitutions can differ slightly from the bundled PAM matrices.
+///
+/// The result has the same shape as `get-scoring-matrix`.
+///
+/// - distance (int): Evolutionary distance in PAM units (at least 1).
+/// - scale (auto, int, float): Multiplier on log2 odds; 2 gives half-bit
+///   units and 3 third-bit units. If auto, follows the bundled PAM matrices:
+///   2 below PAM180 and 3 from PAM180 on (default: auto).
+/// - rounding (str): Rounding of scaled scores: "nearest", "floor", or "ceil"
+///   (default: "nearest").
+/// - name (str, auto): Name of the resulting matrix. If auto, uses
+///   `"PAM" + str(distance)` (default: auto).
+/// -> dictionary with the keys of `get-scoring-matrix`:
+///   - name (str): Matrix name.
+///   - alphabet (array): Symbols covered by the matrix.
+///   - matrix (array): 2D score matrix aligned to `alphabet`.
+///   - scale-bits (float): Bits per score unit, `1 / scale`.
+///   - reported-entropy (none): Always `none`; there is no file header.
+///   - reported-expected-score (none): Always `none`.
+///   - statistics (dictionary, none): Computed from the extrapolated scores,
+///     as for `get-scoring-matrix`.
+#let extrapolate-pam-matrix(
+  distance,
+  scale: auto,
+  rounding: "nearest",
+  name: auto,
+) = {
+  assert(
+    type(distance) == int and distance >= 1,
+    message: "distance must be a positive integer.",
+  )
+  assert(
+    scale == auto or (type(scale) in (int, float) and scale > 0),
+    message: "scale must be a positive number or auto.",
   )
+  assert(
+    rounding in ("nearest", "floor", "ceil"),
+    message: "rounding must be 'nearest', 'floor', or 'ceil'.",
+  )
+  assert(
+    name == auto or type(name) == str,
+    message: "name must be a string or auto.",
+  )
+
+  let config = (distance: distance, rounding: rounding)
+  if scale != auto {
+    config.insert("scale", scale)
+  }
+  if name != auto {
+    config.insert("name", name)
+  }
+
+  _matrix-record(_alignment-pam-matrix(config))
 }
 
 /// Retrieves the substitution score for a pair of characters.
@@ -115,7 +115,8 @@
 ///
 /// Returned features can be passed directly to `render-genome-map`.
 ///
-/// - data (str): GFF3-formatted feature data.
+/// - data (str, bytes): GFF3-formatted feature data. Bytes may be gzip- or
+///   BGZF-compressed, e.g. `read("features.gff3.gz", encoding: none)`.
 /// - feature-types (str, array, none): Feature type or feature types to keep. If none, keeps all feature types (default: none).
 /// - range (array, none): Genomic range to keep as `(accession, start, end)`. `start` and `end` may be none (default: none).
 /// - strand (str, int, none): Strand to keep: "positive", "+", 1, "negative", "-", or -1. If none, keeps all strands (default: none).
@@ -143,7 +144,10 @@
   exclude-partial: false,
   label-attribute: "ID",
 ) = {
-  assert(type(data) == str, message: "data must be a string.")
+  assert(
+    type(data) in (str, bytes),
+    message: "data must be a string or bytes.",
+  )
   assert(
     type(exclude-partial) == bool,
     message: "exclude-partial must be a boolean.",
@@ -2,15 +2,15 @@
 
 /// Parses GFF3 feature data through the WASM backend.
 ///
-/// - data (str): Raw GFF3 source.
+/// - data (str, bytes): Raw GFF3 source, optionally gzip- or BGZF-compressed.
 /// - config (dictionary): Parser filter configuration.
 /// -> array
 #let _genome-map-parse-gff(data, config) = {
   let result = _genome-map-backend.parse_gff(
     bytes(data),
-    bytes(json.encode(config, pretty: false)),
+    cbor.encode(config),
   )
-  json(result)
+  cbor(result)
 }
 
 /// Resolves genome-map label layout through the WASM backend.
@@ -22,7 +22,7 @@
 ///   - level-block-height (length): Total height of the label block.
 ///   - labels (array): Positioned backend label records.
 #let _genome-map-layout-labels(payload) = {
-  let result = _genome-map-backend.layout_labels(bytes(json.encode(
+  let result = _genome-map-backend.layout_labels(cbor.encode(
     (
       label_height_pt: payload.label-height / 1pt,
       label_horizontal_gap_pt: payload.label-horizontal-gap / 1pt,
@@ -40,9 +40,8 @@
         gene_center_pt: label.gene-center / 1pt,
       )),
     ),
-    pretty: false,
-  )))
-  let response = json(result)
+  ))
+  let response = cbor(result)
   (
     level-count: response.level_count,
     level-block-height: response.level_block_height_pt * 1pt,
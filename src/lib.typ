@@ -2,22 +2,38 @@
 #import "common/colors.typ": diverging-color-map
 
 // Sequence
-#import "sequence/fasta.typ": parse-fasta, render-fasta
+#import "sequence/fasta.typ": parse-fasta, parse-sequences, render-fasta
+#import "sequence/identity_matrix.typ": get-identity-matrix
+#import "sequence/motif_parsers.typ": parse-motifs
+#import "sequence/motifs.typ": build-pwm, scan-motif
 #import "sequence/msa.typ": render-msa
+#import "sequence/msa_filter.typ": filter-msa, trim-msa
+#import "sequence/msa_parsers.typ": parse-msa
+#import "sequence/popgen.typ": get-popgen-stats
 #import "sequence/residue_palette.typ": residue-palette
 #import "sequence/sequence_logo.typ": render-sequence-logo
+#import "sequence/sequence_weights.typ": get-sequence-weights
+#import "sequence/snp_sites.typ": get-snp-sites
 
 // Alignment
+#import "alignment/dot_plot.typ": get-dot-plot
 #import "alignment/dp_matrix.typ": render-dp-matrix
 #import "alignment/pair_alignment.typ": align-seq-pair, render-pair-alignment
 #import "alignment/scoring_matrix.typ": (
-  get-score-from-matrix, get-scoring-matrix, render-scoring-matrix,
+  derive-scoring-matrix, extrapolate-pam-matrix, get-score-from-matrix,
+  get-scoring-matrix, render-scoring-matrix,
 )
 
 // Genome map
 #import "genome_map/feature_parsers.typ": parse-gff
 #import "genome_map/genome_map.typ": render-genome-map
 
+// Sequence utilities
+#import "seq_utils/composition.typ": get-composition-track
+#import "seq_utils/translation.typ": (
+  find-orfs, translate-sequence, translate-six-frames,
+)
+
 // Tree
 #import "tree/newick_parser.typ": parse-newick
 #import "tree/tree.typ": render-rectangular-tree, render-unrooted-tree
@@ -0,0 +1,55 @@
+#import "../alignment/pair_alignment.typ": _validate-sequence
+#import "./seq_utils_backend.typ": _seq-utils-composition-track
+
+/// Computes nucleotide composition in sliding windows.
+///
+/// Windows use the 1-based, inclusive coordinates of `parse-gff` features,
+/// so the values can be plotted as a track under `render-genome-map`. GC
+/// content counts `G`, `C` and `S` over all unambiguous bases plus `S` and
+/// `W`. The cumulative GC skew adds up the skew of successive windows; its
+/// minimum and maximum hint at the replication origin and terminus of
+/// bacterial chromosomes. Linguistic complexity is the number of distinct
+/// substrings of all lengths in the window over the most possible, so that
+/// low values flag repeats @troyanskaya_sequence_2002.
+///
+/// - sequence (str): Nucleotide sequence. Whitespace is ignored.
+/// - window (int): Window length in bases (default: 100).
+/// - step (int, auto): Distance between window starts. If auto, windows do
+///   not overlap (default: auto).
+/// - k (int): K-mer length for the k-mer entropy (default: 3).
+/// -> array of dictionaries with keys:
+///   - start (int): Window start.
+///   - end (int): Window end.
+///   - gc-content (float, none): Fraction of G and C.
+///   - gc-skew (float, none): `(G - C) / (G + C)`.
+///   - cumulative-gc-skew (float): Sum of the GC skew up to this window.
+///   - linguistic-complexity (float, none): Between 0 and 1.
+///   - kmer-entropy (float, none): Shannon entropy of the k-mers in bits, at
+///     most `2 * k`.
+#let get-composition-track(sequence, window: 100, step: auto, k: 3) = {
+  let sequence = _validate-sequence(sequence, "sequence")
+  assert(
+    type(window) == int and window > 0,
+    message: "window must be a positive integer.",
+  )
+  assert(
+    step == auto or (type(step) == int and step > 0),
+    message: "step must be auto or a positive integer.",
+  )
+  assert(type(k) == int and k > 0, message: "k must be a positive integer.")
+
+  _seq-utils-composition-track((
+    sequence: sequence,
+    window: window,
+    step: if step == auto { window } else { step },
+    k: k,
+  )).map(values => (
+    start: values.start,
+    end: values.end,
+    gc-content: values.gc_content,
+    gc-skew: values.gc_skew,
+    cumulative-gc-skew: values.cumulative_gc_skew,
+    linguistic-complexity: values.linguistic_complexity,
+    kmer-entropy: values.kmer_entropy,
+  ))
+}
@@ -0,0 +1,33 @@
+#let _seq-utils-backend = plugin("seq_utils.wasm")
+
+/// Translates one reading frame through the WASM backend.
+///
+/// - request (dictionary): Translation request.
+/// -> str
+#let _seq-utils-translate(request) = {
+  cbor(_seq-utils-backend.translate_sequence(cbor.encode(request)))
+}
+
+/// Translates all six reading frames through the WASM backend.
+///
+/// - request (dictionary): Translation request with `sequence` and `table`.
+/// -> array
+#let _seq-utils-six-frame-translation(request) = {
+  cbor(_seq-utils-backend.six_frame_translation(cbor.encode(request)))
+}
+
+/// Finds open reading frames through the WASM backend.
+///
+/// - request (dictionary): ORF request.
+/// -> array
+#let _seq-utils-orfs(request) = {
+  cbor(_seq-utils-backend.orfs(cbor.encode(request)))
+}
+
+/// Computes sliding-window composition through the WASM backend.
+///
+/// - request (dictionary): Composition request.
+/// -> array
+#let _seq-utils-composition-track(request) = {
+  cbor(_seq-utils-backend.composition_track(cbor.encode(request)))
+}
@@ -0,0 +1,145 @@
+#import "../alignment/pair_alignment.typ": _validate-sequence
+#import "./seq_utils_backend.typ": (
+  _seq-utils-orfs, _seq-utils-six-frame-translation, _seq-utils-translate,
+)
+
+/// NCBI translation table ids.
+#let _genetic-code-ids = range(1, 7) + range(9, 17) + range(21, 34)
+
+/// Validates an NCBI genetic code id.
+///
+/// - genetic-code (int): Translation table id.
+/// -> int
+#let _validate-genetic-code(genetic-code) = {
+  assert(
+    type(genetic-code) == int and genetic-code in _genetic-code-ids,
+    message: "genetic-code must be an NCBI translation table id: 1-6, 9-16, or 21-33.",
+  )
+}
+
+/// Translates a nucleotide sequence into protein.
+///
+/// Codons are read with the given NCBI genetic code; `U` is read as `T`,
+/// codons with IUPAC ambiguity codes translate to the amino acid all their
+/// expansions share, or `X`. Trailing bases that do not fill a codon are
+/// ignored. Negative frames are read from the reverse complement.
+///
+/// - sequence (str): Nucleotide sequence. Whitespace is ignored.
+/// - genetic-code (int): NCBI translation table id (default: 1).
+/// - frame (int): Reading frame: 1, 2, 3, -1, -2, or -3 (default: 1).
+/// - initiator (bool): Translate a first codon that is a start codon of the
+///   table, such as `TTG` or `GTG`, as `M` (default: false).
+/// - stop (str): Stop codons: "keep" writes `*`, "truncate" ends the protein
+///   before the first stop, "remove" leaves them out (default: "keep").
+/// -> str
+#let translate-sequence(
+  sequence,
+  genetic-code: 1,
+  frame: 1,
+  initiator: false,
+  stop: "keep",
+) = {
+  let sequence = _validate-sequence(sequence, "sequence")
+  _validate-genetic-code(genetic-code)
+  assert(
+    frame in (1, 2, 3, -1, -2, -3),
+    message: "frame must be 1, 2, 3, -1, -2, or -3.",
+  )
+  assert(type(initiator) == bool, message: "initiator must be a boolean.")
+  assert(
+    stop in ("keep", "truncate", "remove"),
+    message: "stop must be \"keep\", \"truncate\", or \"remove\".",
+  )
+  _seq-utils-translate((
+    sequence: sequence,
+    table: genetic-code,
+    frame: frame,
+    initiator: initiator,
+    stop: stop,
+  ))
+}
+
+/// Translates the three forward and three reverse reading frames of a
+/// nucleotide sequence.
+///
+/// Frames 1–3 start at the first, second and third base; frames -1 to -3 at
+/// the first, second and third base of the reverse complement. Stop codons
+/// are written as `*`.
+///
+/// - sequence (str): Nucleotide sequence. Whitespace is ignored.
+/// - genetic-code (int): NCBI translation table id (default: 1).
+/// -> dictionary mapping frame (str: "+1", "+2", "+3", "-1", "-2", "-3") to
+///   protein (str).
+#let translate-six-frames(sequence, genetic-code: 1) = {
+  let sequence = _validate-sequence(sequence, "sequence")
+  _validate-genetic-code(genetic-code)
+  _seq-utils-six-frame-translation((sequence: sequence, table: genetic-code))
+    .map(frame => (
+      (if frame.frame > 0 { "+" } else { "-" }) + str(calc.abs(frame.frame)),
+      frame.protein,
+    ))
+    .to-dict()
+}
+
+/// Finds open reading frames on one or both strands.
+///
+/// An ORF runs from a start codon to the next in-frame stop codon; each stop
+/// codon ends at most one ORF, which starts at the most upstream start codon.
+/// The result uses the feature shape of `parse-gff` and can be passed to
+/// `render-genome-map` directly.
+///
+/// - sequence (str): Nucleotide sequence. Whitespace is ignored.
+/// - genetic-code (int): NCBI translation table id (default: 1).
+/// - start-codons (str): "atg" for `ATG` only, "table" for `ATG` and the
+///   alternative start codons of the genetic code, or "any" for any sense
+///   codon, i.e. stop-to-stop ORFs (default: "atg").
+/// - min-length (int): Shortest ORF in nucleotides, including the stop codon
+///   (default: 75).
+/// - strand (str): Strands to search: "both", "positive", or "negative"
+///   (default: "both").
+/// - partial (bool): Also report ORFs that reach the end of the sequence
+///   without a stop codon (default: false).
+/// - accession (str): Accession written into the features (default: "sequence").
+/// -> array of dictionaries with the keys of `parse-gff` features:
+///   feature-type is "ORF", label and the `ID` attribute are "ORF1", "ORF2",
+///   ... in coordinate order, `partial` marks ORFs without a stop codon, and
+///   the `frame` and `translation` attributes hold the reading frame and
+///   protein.
+#let find-orfs(
+  sequence,
+  genetic-code: 1,
+  start-codons: "atg",
+  min-length: 75,
+  strand: "both",
+  partial: false,
+  accession: "sequence",
+) = {
+  let sequence = _validate-sequence(sequence, "sequence")
+  _validate-genetic-code(genetic-code)
+  assert(
+    start-codons in ("atg", "table", "any"),
+    message: "start-codons must be \"atg\", \"table\", or \"any\".",
+  )
+  assert(
+    type(min-length) == int and min-length >= 3,
+    message: "min-length must be an integer of at least 3.",
+  )
+  assert(
+    strand in ("both", "positive", "negative"),
+    message: "strand must be \"both\", \"positive\", or \"negative\".",
+  )
+  assert(type(partial) == bool, message: "partial must be a boolean.")
+  assert(
+    type(accession) == str and accession.len() > 0,
+    message: "accession must be a non-empty string.",
+  )
+  _seq-utils-orfs((
+    sequence: sequence,
+    accession: accession,
+    table: genetic-code,
+    start_codons: start-codons,
+    min_length: min-length,
+    strand: strand,
+    partial: partial,
+  ))
+}
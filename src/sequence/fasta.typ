@@ -1,60 +1,103 @@
+#import "./sequence_backend.typ": _sequence-parse-sequences
 #import "../common/fixed_grid.typ": _fixed-width-grid
 
-/// Resolves a FASTA record's sequence and validates identifier uniqueness.
+/// Validates a duplicate-identifier policy.
 ///
-/// - sequences (dictionary): Parsed FASTA records.
-/// - seq-id (str): Sequence identifier.
-/// - seq-parts (array): Sequence fragments collected for the record.
-/// -> str
-#let _resolve-fasta-record-sequence(sequences, seq-id, seq-parts) = {
-  let sequence = seq-parts.join()
+/// - duplicates (str): Policy name.
+/// -> none
+#let _validate-duplicate-policy(duplicates) = {
   assert(
-    not (seq-id in sequences),
-    message: "Duplicate FASTA identifier '"
-      + seq-id
-      + "'. FASTA identifiers must be unique.",
+    duplicates in ("error", "rename", "keep-first"),
+    message: "duplicates must be \"error\", \"rename\", or \"keep-first\".",
   )
-  sequence
 }
 
 /// Parses FASTA-formatted sequence data into a dictionary mapping unique
 /// sequence identifiers to sequence strings.
 ///
-/// Duplicate identifiers are rejected.
+/// The full header line after `>` is used as the identifier. Use
+/// `parse-sequences` to split descriptions from identifiers or to read FASTQ.
 ///
-/// - data (str): A string containing the FASTA data.
+/// - data (str, bytes): FASTA data. Bytes may be gzip- or BGZF-compressed,
+///   e.g. `read("sequences.fa.gz", encoding: none)`.
+/// - duplicates (str): Handling of repeated identifiers: "error", "rename"
+///   (appends `_2`, `_3`, ...), or "keep-first" (default: "error").
+/// - strict (bool): Rejects data before the first header and empty headers,
+///   and skips `;` comment lines. When false, data before the first header is
+///   ignored, `>` alone gives the identifier `""`, and `;` lines are read as
+///   sequence (default: false).
 /// -> dictionary with keys:
 ///   - sequence-id (str): Sequence string keyed by each unique FASTA identifier.
-#let parse-fasta(data) = {
-  let sequences = (:)
-  let current-id = none
-  let current-seq = ()
+#let parse-fasta(data, duplicates: "error", strict: false) = {
+  assert(
+    type(data) in (str, bytes),
+    message: "data must be a string or bytes.",
+  )
+  _validate-duplicate-policy(duplicates)
+  assert(type(strict) == bool, message: "strict must be a boolean.")
 
-  for line in data.split("\n") {
-    let line = line.trim()
-    if line.len() == 0 { continue }
-    if line.starts-with(">") {
-      if current-id != none {
-        sequences.insert(
-          current-id,
-          _resolve-fasta-record-sequence(sequences, current-id, current-seq),
-        )
-      }
-      current-id = line.slice(1).trim()
-      current-seq = ()
-    } else {
-      current-seq.push(line)
-    }
-  }
+  let records = _sequence-parse-sequences(data, (
+    format: "fasta",
+    duplicates: duplicates,
+    split_description: false,
+    strict: strict,
+  ))
+  records.map(record => (record.id, record.sequence)).to-dict()
+}
 
-  if current-id != none {
-    sequences.insert(
-      current-id,
-      _resolve-fasta-record-sequence(sequences, current-id, current-seq),
-    )
-  }
+/// Parses FASTA or FASTQ data into an array of sequence records.
+///
+/// The format is detected from the first header unless given. Headers are
+/// split at the first whitespace into an identifier and a description. Lines
+/// may end in LF or CRLF, and FASTQ sequences and qualities may be wrapped.
+/// Parse errors report the line they occur on.
+///
+/// - data (str, bytes): FASTA or FASTQ data. Bytes may be gzip- or
+///   BGZF-compressed.
+/// - format (str, auto): "fasta" or "fastq". If auto, detected from the
+///   first header (default: auto).
+/// - duplicates (str): Handling of repeated identifiers: "error", "rename"
+///   (appends `_2`, `_3`, ...), or "keep-first" (default: "error").
+/// - quality (str): FASTQ quality representation: "string" keeps the quality
+///   line, "phred" converts it to an array of Phred scores (default: "string").
+/// - phred-offset (int): ASCII offset of quality characters (default: 33).
+/// -> array of dictionaries with keys:
+///   - id (str): Sequence identifier.
+///   - description (str, none): Header text after the identifier.
+///   - sequence (str): Sequence string.
+///   - quality (str, array, none): FASTQ quality string or Phred scores, or
+///     none for FASTA records.
+#let parse-sequences(
+  data,
+  format: auto,
+  duplicates: "error",
+  quality: "string",
+  phred-offset: 33,
+) = {
+  assert(
+    type(data) in (str, bytes),
+    message: "data must be a string or bytes.",
+  )
+  assert(
+    format == auto or format in ("fasta", "fastq"),
+    message: "format must be auto, \"fasta\", or \"fastq\".",
+  )
+  _validate-duplicate-policy(duplicates)
+  assert(
+    quality in ("string", "phred"),
+    message: "quality must be \"string\" or \"phred\".",
+  )
+  assert(
+    type(phred-offset) == int and phred-offset >= 0 and phred-offset <= 126,
+    message: "phred-offset must be an integer between 0 and 126.",
+  )
 
-  sequences
+  _sequence-parse-sequences(data, (
+    format: if format == auto { "auto" } else { format },
+    duplicates: duplicates,
+    quality: quality,
+    phred_offset: phred-offset,
+  ))
 }
 
 /// Renders a dictionary of sequences in FASTA format for display.
@@ -0,0 +1,69 @@
+#import "./sequence_alphabet.typ": _resolve-alphabet-config
+#import "./sequence_backend.typ": _sequence-identity-matrix
+#import "./sequence_processing.typ": _validate-alignment
+
+/// Computes pairwise percent identity between the sequences of an alignment.
+///
+/// The result has the same shape as a matrix from `get-scoring-matrix`, with
+/// sequence identifiers as the alphabet, so it can be drawn as a heatmap with
+/// `render-scoring-matrix`. Identifiers are listed in the leaf order of an
+/// agglomerative clustering on `100% - identity` unless `cluster` is none.
+/// As `render-scoring-matrix` centers its color scale on zero, identities
+/// run from the neutral midpoint of `diverging-color-map` at 0% to its blue
+/// end at 100%.
+///
+/// - alignment (dictionary): Dictionary mapping sequence identifiers to aligned sequences.
+/// - definition (str): Denominator of the identity: "aligned" (columns where
+///   both sequences have a residue), "shorter" (residues in the shorter
+///   sequence), or "columns" (alignment length) (default: "aligned").
+/// - cluster (str, none): Linkage for ordering: "average" (UPGMA), "single",
+///   "complete", or none to keep the input order (default: "average").
+/// - alphabet (auto, str): Sequence alphabet: auto, "aa", "dna", or "rna" (default: auto).
+/// -> dictionary with keys:
+///   - name (str): "Percent identity".
+///   - alphabet (array): Sequence identifiers in display order.
+///   - matrix (array): 2D array of rounded percent identities, indexed like
+///     `alphabet`.
+///   - identity (array): 2D array of unrounded identity fractions, indexed
+///     like `alphabet`.
+#let get-identity-matrix(
+  alignment,
+  definition: "aligned",
+  cluster: "average",
+  alphabet: auto,
+) = {
+  assert(
+    type(alignment) == dictionary,
+    message: "alignment must be a dictionary.",
+  )
+  _validate-alignment(alignment)
+  assert(
+    definition in ("aligned", "shorter", "columns"),
+    message: "definition must be \"aligned\", \"shorter\", or \"columns\".",
+  )
+  assert(
+    cluster == none or cluster in ("average", "single", "complete"),
+    message: "cluster must be \"average\", \"single\", \"complete\", or none.",
+  )
+  if alignment.len() == 0 {
+    return (name: "Percent identity", alphabet: (), matrix: (), identity: ())
+  }
+
+  let config = _resolve-alphabet-config(alphabet, alignment.values())
+  let result = _sequence-identity-matrix((
+    sequences: alignment.values(),
+    alphabet: config.chars,
+    definition: definition,
+    linkage: cluster,
+  ))
+  let ids = alignment.keys()
+  let identity = result.order.map(i => result.order.map(j => (
+    result.identity.at(i).at(j)
+  )))
+  (
+    name: "Percent identity",
+    alphabet: result.order.map(i => ids.at(i)),
+    matrix: identity.map(row => row.map(value => int(calc.round(value * 100)))),
+    identity: identity,
+  )
+}
@@ -0,0 +1,51 @@
+#import "./sequence_backend.typ": _sequence-parse-motif-matrices
+
+/// Parses motif matrices in MEME minimal, JASPAR, PFM, or TRANSFAC format.
+///
+/// Each motif is read into per-position residue frequencies, so it can be
+/// drawn with `render-sequence-logo` without the sequences it was built
+/// from. Count matrices (JASPAR, PFM, TRANSFAC) are normalized per position
+/// and keep their counts; MEME letter-probability matrices are renormalized
+/// to absorb rounding. The MEME alphabet comes from the `ALPHABET=` line or
+/// the core letters of an `ALPHABET` block, and defaults to `ACGT`. Bare PFM
+/// rows are read as A, C, G, and T.
+///
+/// - data (str, bytes): Motif data. Bytes may be gzip- or BGZF-compressed.
+/// - format (str, auto): "meme", "jaspar", "pfm", or "transfac". If auto,
+///   detected from the first line (default: auto).
+/// -> array of dictionaries with keys:
+///   - id (str): Motif identifier (MEME `MOTIF`, JASPAR header, or TRANSFAC
+///     `ID`, falling back to `AC`).
+///   - name (str, none): Alternate name, when given.
+///   - alphabet (array): Residues, in the order of the inner arrays.
+///   - frequencies (array): Residue frequencies per motif position.
+///   - counts (array, none): Residue counts per position for count matrices.
+///   - sites (float, none): Number of sites, from MEME `nsites=` or the
+///     largest column total of a count matrix.
+///   - background (dictionary, none): MEME background frequencies keyed by
+///     residue, as accepted by the `background` of `render-sequence-logo`.
+#let parse-motifs(data, format: auto) = {
+  assert(
+    type(data) in (str, bytes),
+    message: "data must be a string or bytes.",
+  )
+  assert(
+    format == auto or format in ("meme", "jaspar", "pfm", "transfac"),
+    message: "format must be auto, \"meme\", \"jaspar\", \"pfm\", or \"transfac\".",
+  )
+
+  let motifs = _sequence-parse-motif-matrices(data, (
+    format: if format == auto { "auto" } else { format },
+  ))
+  motifs.map(motif => (
+    id: motif.id,
+    name: motif.name,
+    alphabet: motif.alphabet,
+    frequencies: motif.frequencies,
+    counts: motif.counts,
+    sites: motif.sites,
+    background: if motif.background == none { none } else {
+      motif.alphabet.zip(motif.background).to-dict()
+    },
+  ))
+}
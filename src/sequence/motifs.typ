@@ -0,0 +1,131 @@
+#import "../alignment/pair_alignment.typ": _validate-sequence
+#import "./sequence_alphabet.typ": _resolve-alphabet-config
+#import "./sequence_backend.typ": (
+  _sequence-motif-pwm, _sequence-scan-motif-hits,
+)
+#import "./sequence_logo.typ": _resolve-logo-background
+#import "./sequence_processing.typ": _validate-alignment
+
+/// Builds a position weight matrix from an ungapped motif alignment.
+///
+/// Column counts are smoothed with a pseudocount shared out by background
+/// frequency, and each score is the log2 odds of the smoothed frequency
+/// against the background. The default total pseudocount of 0.8 follows
+/// @nishida_pseudocounts_2009. Residues outside the alphabet, such as `N`,
+/// are not counted.
+///
+/// - alignment (dictionary): Dictionary mapping identifiers to motif
+///   instances of equal length, as accepted by `render-sequence-logo`.
+/// - background (none, str, dictionary): Background residue frequencies:
+///   none or "uniform", "blosum62" (amino acids), or a dictionary mapping
+///   each residue to its frequency (default: none).
+/// - pseudocount (int, float): Total pseudocount added to each column
+///   (default: 0.8).
+/// - alphabet (auto, str): Sequence alphabet: auto, "aa", "dna", or "rna" (default: auto).
+/// -> dictionary with keys:
+///   - alphabet (array): Residues, in the order of the inner arrays.
+///   - background (array): Background frequency of each residue.
+///   - counts (array): Residue counts per motif position.
+///   - frequencies (array): Smoothed residue frequencies per position.
+///   - scores (array): Log2-odds scores per position.
+#let build-pwm(
+  alignment,
+  background: none,
+  pseudocount: 0.8,
+  alphabet: auto,
+) = {
+  assert(
+    type(alignment) == dictionary and alignment.len() > 0,
+    message: "alignment must be a non-empty dictionary.",
+  )
+  _validate-alignment(alignment)
+  assert(
+    type(pseudocount) in (int, float) and pseudocount > 0,
+    message: "pseudocount must be a positive number.",
+  )
+  let sequences = alignment.values()
+  let config = _resolve-alphabet-config(alphabet, sequences)
+  _sequence-motif-pwm((
+    sequences: sequences,
+    alphabet: config.chars,
+    background: _resolve-logo-background(background, config),
+    pseudocount: float(pseudocount),
+  ))
+}
+
+/// Scans a sequence for occurrences of a motif.
+///
+/// Every window is scored with the PWM on the requested strands; windows
+/// with residues outside the PWM alphabet are skipped. P-values give the
+/// chance that a background sequence scores at least as high, computed
+/// exactly from the score distribution as in FIMO @grant_fimo_2011. Hits are
+/// returned in the feature shape of `parse-gff`, so they can be passed to
+/// `render-genome-map`.
+///
+/// - pwm (dictionary): Matrix from `build-pwm`.
+/// - sequence (str): Sequence to scan. Whitespace is ignored.
+/// - p-value (float, none): Highest p-value reported (default: 1e-4).
+/// - min-score (int, float, none): Lowest score reported, in bits
+///   (default: none).
+/// - strand (str): Strands to scan: "both", "positive", or "negative". Both
+///   and negative need a DNA or RNA motif (default: "both").
+/// - accession (str): Accession written into the features (default: "sequence").
+/// - label (str, none): Label of every hit (default: none).
+/// - feature-type (str): Feature type of every hit (default: "sequence_motif").
+/// -> array of dictionaries with the keys of `parse-gff` features: score is
+///   the PWM score in bits, and the `p-value` and `sequence` attributes hold
+///   the p-value and the matched sequence read on the hit's strand.
+#let scan-motif(
+  pwm,
+  sequence,
+  p-value: 1e-4,
+  min-score: none,
+  strand: "both",
+  accession: "sequence",
+  label: none,
+  feature-type: "sequence_motif",
+) = {
+  assert(
+    type(pwm) == dictionary
+      and ("alphabet", "background", "scores").all(key => key in pwm),
+    message: "pwm must be a matrix from build-pwm.",
+  )
+  let sequence = _validate-sequence(sequence, "sequence")
+  assert(
+    p-value == none or (type(p-value) in (int, float) and p-value > 0),
+    message: "p-value must be none or a positive number.",
+  )
+  assert(
+    min-score == none or type(min-score) in (int, float),
+    message: "min-score must be none or a number.",
+  )
+  assert(
+    p-value != none or min-score != none,
+    message: "p-value and min-score must not both be none.",
+  )
+  assert(
+    strand in ("both", "positive", "negative"),
+    message: "strand must be \"both\", \"positive\", or \"negative\".",
+  )
+  assert(
+    type(accession) == str and accession.len() > 0,
+    message: "accession must be a non-empty string.",
+  )
+  assert(
+    label == none or type(label) == str,
+    message: "label must be none or a string.",
+  )
+  assert(type(feature-type) == str, message: "feature-type must be a string.")
+  _sequence-scan-motif-hits((
+    alphabet: pwm.alphabet,
+    background: pwm.background,
+    scores: pwm.scores,
+    sequence: sequence,
+    accession: accession,
+    strand: strand,
+    min_score: if min-score == none { none } else { float(min-score) },
+    max_p_value: if p-value == none { none } else { float(p-value) },
+    label: label,
+    feature_type: feature-type,
+  ))
+}
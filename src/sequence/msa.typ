@@ -4,7 +4,8 @@
 #import "./sequence_alphabet.typ": _resolve-alphabet-config
 #import "./sequence_processing.typ": (
   _collect-window-column-stats, _compute-consensus-sequence,
-  _lookup-palette-color, _resolve-palette, _validate-alignment,
+  _lookup-palette-color, _resolve-conservation-score, _resolve-palette,
+  _resolve-sequence-weighting, _validate-alignment,
 )
 
 /// Renders a single character in an MSA with optional coloring.
@@ -34,19 +35,22 @@
 
 /// Renders a conservation row for an MSA block.
 ///
-/// Creates a horizontal row of bars where each bar represents information
-/// content (conservation) of a single column in alignment.
+/// Creates a horizontal row of bars where each bar represents the
+/// conservation of a single column in alignment, relative to the highest
+/// value the score can reach. Negative scores draw no bar.
 ///
 /// - column-stats (array): Prepared per-column statistics for the current block.
-/// - max-bits (float): Maximum possible information content (log2 of alphabet size).
 /// - cell-width (length): Width of each character cell.
 /// -> content
-#let _render-msa-conservation-row(column-stats, max-bits, cell-width) = {
+#let _render-msa-conservation-row(column-stats, cell-width) = {
   let bar-height = 1.5em
   let bars = ()
 
   for stats in column-stats {
-    let h = (stats.conservation / max-bits) * bar-height
+    let relative = if stats.max-conservation > 0 {
+      calc.clamp(stats.conservation / stats.max-conservation, 0, 1)
+    } else { 0 }
+    let h = relative * bar-height
     bars.push((
       body: box(
         height: bar-height,
@@ -277,8 +281,22 @@
 /// - show-consensus-sequence (bool): Whether to show a consensus sequence (default: false).
 /// - color-consensus-only (bool): Whether to color only consensus residues (default: false).
 /// - show-conservation (bool): Whether to show conservation bars (default: false).
+/// - conservation-score (str): Column score shown by the conservation bars:
+///   "shannon" (information content), "jensen-shannon" (divergence from the
+///   background), "property-entropy" (entropy over physicochemical classes),
+///   or "sum-of-pairs" (mean substitution score over sequence pairs)
+///   (default: "shannon").
+/// - conservation-matrix (auto, str): Built-in substitution matrix for
+///   "sum-of-pairs"; auto uses BLOSUM62 for amino acids and EDNAFULL for
+///   nucleotides (default: auto).
 /// - minimum-column-occupancy (ratio, float): Hide columns with occupancy below this threshold (default: 0%).
-/// - sampling-correction (bool): Whether to apply small sample correction (default: true).
+/// - sampling-correction (bool): Whether to apply small sample correction to
+///   "shannon" conservation (default: true).
+/// - weighting (str, none): Sequence weighting for conservation and the
+///   consensus: none, "henikoff", or "identity". See `get-sequence-weights`
+///   (default: none).
+/// - identity-threshold (ratio): Neighbor identity for "identity" weighting
+///   (default: 80%).
 /// - alphabet (auto, str): Sequence alphabet: auto, "aa", "dna", or "rna" (default: auto).
 /// - breakable (bool): Whether to allow blocks to break across pages (default: true).
 /// - palette (dictionary, auto): Residue color palette to use (default: auto).
@@ -293,8 +311,12 @@
   show-consensus-sequence: false,
   color-consensus-only: false,
   show-conservation: false,
+  conservation-score: "shannon",
+  conservation-matrix: auto,
   minimum-column-occupancy: 0%,
   sampling-correction: true,
+  weighting: none,
+  identity-threshold: 80%,
   alphabet: auto,
   breakable: true,
   palette: auto,
@@ -305,11 +327,20 @@
   let occupancy-threshold = _resolve-minimum-column-occupancy(
     minimum-column-occupancy,
   )
+  let resolved-weighting = _resolve-sequence-weighting(
+    weighting,
+    identity-threshold,
+  )
   _validate-alignment(alignment)
   let sequences = alignment.values()
   let total-max-len = sequences.first().len()
 
   let config = _resolve-alphabet-config(alphabet, sequences)
+  let resolved-conservation = _resolve-conservation-score(
+    conservation-score,
+    config,
+    matrix: conservation-matrix,
+  )
   let palette-to-use = _resolve-palette(
     palette,
     config,
@@ -327,7 +358,6 @@
   let actual-end = window.actual-end
 
   let filter-columns = occupancy-threshold > 0.0
-  let max-bits = config.max-bits
   let consensus-coloring-enabled = colors and color-consensus-only
   let needs-consensus = show-consensus-sequence or consensus-coloring-enabled
   let needs-column-stats = (
@@ -341,6 +371,8 @@
       config,
       sampling-correction,
       compute-conservation: show-conservation,
+      weighting: resolved-weighting,
+      conservation-score: resolved-conservation,
     )
   } else {
     ()
@@ -388,11 +420,7 @@
             relative-start,
             relative-end,
           )
-          let bars = _render-msa-conservation-row(
-            block-stats,
-            max-bits,
-            box-width,
-          )
+          let bars = _render-msa-conservation-row(block-stats, box-width)
           ([], bars)
         } else {
           ()
@@ -0,0 +1,163 @@
+#import "./sequence_alphabet.typ": _resolve-alphabet-config
+#import "./sequence_backend.typ": (
+  _sequence-filter-msa-sequences, _sequence-trim-msa-columns,
+)
+#import "./sequence_processing.typ": (
+  _resolve-conservation-score, _validate-alignment,
+)
+
+/// Converts an optional ratio argument to a fraction.
+///
+/// - value (ratio, float, int, none): Value to convert.
+/// - name (str): Argument name for error messages.
+/// -> float, none
+#let _resolve-optional-fraction(value, name) = {
+  if value == none { return none }
+  assert(
+    type(value) in (ratio, float, int),
+    message: name + " must be none, a ratio, or a number.",
+  )
+  let fraction = float(value)
+  assert(
+    fraction >= 0.0 and fraction <= 1.0,
+    message: name + " must be between 0% and 100%.",
+  )
+  fraction
+}
+
+/// Converts an alignment dictionary to backend sequence records.
+///
+/// - alignment (dictionary): Dictionary mapping identifiers to sequences.
+/// -> array
+#let _alignment-records(alignment) = {
+  alignment.pairs().map(((id, sequence)) => (id: id, sequence: sequence))
+}
+
+/// Removes alignment columns by gap fraction, conservation, or an automated
+/// gap threshold.
+///
+/// Every criterion that is set must pass for a column to be kept. The
+/// automated mode follows trimAl's `gappyout` idea: it places the gap
+/// threshold where the cumulative distribution of column gap fractions
+/// flattens most sharply. `-` and `.` count as gaps.
+///
+/// - alignment (dictionary): Dictionary mapping sequence identifiers to aligned sequences.
+/// - max-gap-fraction (ratio, none): Remove columns with more gaps than this
+///   (default: none).
+/// - min-conservation (ratio, none): Remove columns whose conservation is
+///   below this fraction of the highest value the score can reach
+///   (default: none).
+/// - automated (bool): Remove gappy columns with an automatically chosen gap
+///   threshold (default: false).
+/// - conservation-score (str): Score for `min-conservation`: "shannon",
+///   "jensen-shannon", "property-entropy", or "sum-of-pairs". See
+///   `render-msa` (default: "shannon").
+/// - conservation-matrix (auto, str): Built-in substitution matrix for
+///   "sum-of-pairs" (default: auto).
+/// - alphabet (auto, str): Sequence alphabet: auto, "aa", "dna", or "rna" (default: auto).
+/// -> dictionary with keys:
+///   - alignment (dictionary): Trimmed alignment.
+///   - kept-columns (array): Original 1-indexed position of each kept column.
+///   - gap-threshold (float, none): Gap threshold chosen in automated mode.
+#let trim-msa(
+  alignment,
+  max-gap-fraction: none,
+  min-conservation: none,
+  automated: false,
+  conservation-score: "shannon",
+  conservation-matrix: auto,
+  alphabet: auto,
+) = {
+  assert(
+    type(alignment) == dictionary,
+    message: "alignment must be a dictionary.",
+  )
+  _validate-alignment(alignment)
+  assert(type(automated) == bool, message: "automated must be a boolean.")
+  let max-gap-fraction = _resolve-optional-fraction(
+    max-gap-fraction,
+    "max-gap-fraction",
+  )
+  let min-conservation = _resolve-optional-fraction(
+    min-conservation,
+    "min-conservation",
+  )
+  if alignment.len() == 0 {
+    return (alignment: (:), kept-columns: (), gap-threshold: none)
+  }
+
+  let config = _resolve-alphabet-config(alphabet, alignment.values())
+  let conservation = _resolve-conservation-score(
+    conservation-score,
+    config,
+    matrix: conservation-matrix,
+  )
+  let result = _sequence-trim-msa-columns((
+    sequences: _alignment-records(alignment),
+    alphabet: config.chars,
+    max_gap_fraction: max-gap-fraction,
+    min_conservation: min-conservation,
+    automated: automated,
+    conservation_score: conservation.score,
+    background: conservation.background,
+    property_groups: conservation.property-groups,
+    matrix: conservation.matrix,
+  ))
+  (
+    alignment: result
+      .sequences
+      .map(record => (record.id, record.sequence))
+      .to-dict(),
+    kept-columns: result.kept_columns.map(column => column + 1),
+    gap-threshold: result.automated_gap_threshold,
+  )
+}
+
+/// Removes redundant or poorly covering sequences from an alignment, like
+/// hhfilter.
+///
+/// The first sequence is the query and is always kept. The others are
+/// considered from the most to the fewest residues; a sequence is dropped if
+/// it covers less than `min-coverage` of the query's residue columns or
+/// shares more than `max-identity` with a sequence already kept. Identity is
+/// measured over columns where both sequences have a residue.
+///
+/// - alignment (dictionary): Dictionary mapping sequence identifiers to aligned sequences.
+/// - max-identity (ratio, none): Highest pairwise identity among kept
+///   sequences (default: 90%).
+/// - min-coverage (ratio, none): Lowest coverage of the query (default: none).
+/// - alphabet (auto, str): Sequence alphabet: auto, "aa", "dna", or "rna" (default: auto).
+/// -> dictionary with keys:
+///   - alignment (dictionary): Kept sequences in input order.
+///   - removed (array): Identifiers of removed sequences.
+#let filter-msa(
+  alignment,
+  max-identity: 90%,
+  min-coverage: none,
+  alphabet: auto,
+) = {
+  assert(
+    type(alignment) == dictionary,
+    message: "alignment must be a dictionary.",
+  )
+  _validate-alignment(alignment)
+  let max-identity = _resolve-optional-fraction(max-identity, "max-identity")
+  let min-coverage = _resolve-optional-fraction(min-coverage, "min-coverage")
+  if alignment.len() == 0 {
+    return (alignment: (:), removed: ())
+  }
+
+  let config = _resolve-alphabet-config(alphabet, alignment.values())
+  let result = _sequence-filter-msa-sequences((
+    sequences: _alignment-records(alignment),
+    alphabet: config.chars,
+    max_identity: max-identity,
+    min_coverage: min-coverage,
+  ))
+  let kept = result.sequences.map(record => (record.id, record.sequence))
+  let kept-ids = kept.map(pair => pair.first())
+  (
+    alignment: kept.to-dict(),
+    removed: alignment.keys().filter(id => id not in kept-ids),
+  )
+}
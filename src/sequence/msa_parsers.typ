@@ -0,0 +1,69 @@
+#import "./fasta.typ": _validate-duplicate-policy
+#import "./sequence_backend.typ": _sequence-parse-msa
+
+/// Parses a multiple sequence alignment in Stockholm, Clustal, PHYLIP,
+/// aligned FASTA, or A2M/A3M format.
+///
+/// The format is detected from the first line unless given; FASTA input that
+/// mixes uppercase and lowercase residues is read as A3M, while all-lowercase
+/// FASTA stays FASTA. Stockholm files contribute their
+/// `#=GC` (e.g. `SS_cons`, `RF`), `#=GR` and `#=GF` annotations; only the
+/// first alignment in the file is read. Clustal conservation lines become the
+/// `conservation` column annotation. PHYLIP may be sequential or interleaved,
+/// with relaxed (whitespace-delimited) or strict 10-character names. In
+/// A2M/A3M, uppercase residues and `-` are match states and lowercase
+/// residues are insertions, which are padded with `.` into insert columns or
+/// removed.
+///
+/// - data (str, bytes): Alignment data. Bytes may be gzip- or BGZF-compressed.
+/// - format (str, auto): "stockholm", "clustal", "phylip", "fasta", "a2m", or
+///   "a3m". If auto, detected from the first line (default: auto).
+/// - duplicates (str): Handling of repeated identifiers in FASTA, A2M/A3M and
+///   PHYLIP input: "error", "rename" (appends `_2`, `_3`, ...), or
+///   "keep-first" (default: "error").
+/// - insertions (str): A2M/A3M insert states: "expand" or "remove"
+///   (default: "expand").
+/// -> dictionary with keys:
+///   - sequences (dictionary): Aligned sequence keyed by identifier, in file
+///     order, as accepted by `render-msa`.
+///   - column-annotations (dictionary): One character per column, keyed by
+///     feature.
+///   - residue-annotations (dictionary): Per-sequence dictionaries of
+///     one-character-per-column tracks, keyed by feature.
+///   - file-annotations (dictionary): Free-text annotations keyed by feature.
+#let parse-msa(
+  data,
+  format: auto,
+  duplicates: "error",
+  insertions: "expand",
+) = {
+  assert(
+    type(data) in (str, bytes),
+    message: "data must be a string or bytes.",
+  )
+  assert(
+    format == auto
+      or format in ("stockholm", "clustal", "phylip", "fasta", "a2m", "a3m"),
+    message: "format must be auto, \"stockholm\", \"clustal\", \"phylip\", \"fasta\", \"a2m\", or \"a3m\".",
+  )
+  _validate-duplicate-policy(duplicates)
+  assert(
+    insertions in ("expand", "remove"),
+    message: "insertions must be \"expand\" or \"remove\".",
+  )
+
+  let result = _sequence-parse-msa(data, (
+    format: if format == auto { "auto" } else { format },
+    duplicates: duplicates,
+    insertions: insertions,
+  ))
+  (
+    sequences: result
+      .sequences
+      .map(record => (record.id, record.sequence))
+      .to-dict(),
+    column-annotations: result.column_annotations,
+    residue-annotations: result.residue_annotations,
+    file-annotations: result.file_annotations,
+  )
+}
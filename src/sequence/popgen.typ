@@ -0,0 +1,96 @@
+#import "./sequence_backend.typ": _sequence-popgen-stats
+#import "./sequence_processing.typ": _validate-alignment
+
+/// Converts backend statistics to kebab-case keys.
+///
+/// - stats (dictionary): Backend statistics for a set of columns.
+/// -> dictionary
+#let _popgen-stats-entry(stats) = (
+  sites: stats.sites,
+  segregating-sites: stats.segregating_sites,
+  mean-pairwise-differences: stats.mean_pairwise_differences,
+  pi: stats.pi,
+  theta-w: stats.theta_w,
+  tajima-d: stats.tajima_d,
+  haplotypes: stats.haplotypes,
+  haplotype-diversity: stats.haplotype_diversity,
+)
+
+/// Computes population-genetics summary statistics for a nucleotide
+/// alignment.
+///
+/// Reports the number of segregating sites, nucleotide diversity π
+/// @nei_mathematical_1979, Watterson's θ @watterson_number_1975, Tajima's D
+/// @tajima_statistical_1989, and the number and diversity of haplotypes,
+/// over the whole alignment and optionally in sliding windows. Only A, C, G
+/// and T (or U) are observed bases; gaps and IUPAC ambiguity codes are
+/// missing data, handled according to `missing`. Haplotypes are compared over
+/// the columns without missing data.
+///
+/// - alignment (dictionary): Dictionary mapping sequence identifiers to
+///   aligned nucleotide sequences.
+/// - missing (str): Handling of missing data: "complete-deletion" drops every
+///   column with a gap or ambiguous base in any sequence; "pairwise-deletion"
+///   uses each column with the sequences that have a base there, dividing
+///   Watterson's θ by the harmonic number for that column's sample size
+///   (default: "complete-deletion").
+/// - window (int, none): Window length in alignment columns, or none for
+///   whole-alignment statistics only (default: none).
+/// - step (int, auto): Distance between window starts; auto uses `window`
+///   (default: auto).
+/// -> dictionary with the statistics of the whole alignment and keys:
+///   - sequences (int): Number of sequences.
+///   - sites (int): Columns used after applying `missing`.
+///   - segregating-sites (int): Used columns with more than one base.
+///   - mean-pairwise-differences (float): Average number of differences
+///     between two sequences.
+///   - pi (float, none): Nucleotide diversity per site.
+///   - theta-w (float, none): Watterson's θ per site.
+///   - tajima-d (float, none): Tajima's D; none without segregating sites or
+///     with fewer than three sequences.
+///   - haplotypes (int): Number of distinct haplotypes.
+///   - haplotype-diversity (float, none): Probability that two sequences
+///     drawn without replacement differ in haplotype.
+///   - windows (array): One dictionary per window with the keys above except
+///     `sequences` and `windows`, plus `start` and `end` (1-indexed,
+///     inclusive alignment columns).
+#let get-popgen-stats(
+  alignment,
+  missing: "complete-deletion",
+  window: none,
+  step: auto,
+) = {
+  assert(
+    type(alignment) == dictionary,
+    message: "alignment must be a dictionary.",
+  )
+  _validate-alignment(alignment)
+  assert(
+    missing in ("complete-deletion", "pairwise-deletion"),
+    message: "missing must be \"complete-deletion\" or \"pairwise-deletion\".",
+  )
+  assert(
+    window == none or (type(window) == int and window > 0),
+    message: "window must be none or a positive integer.",
+  )
+  assert(
+    step == auto or (type(step) == int and step > 0),
+    message: "step must be auto or a positive integer.",
+  )
+
+  let result = _sequence-popgen-stats((
+    sequences: alignment.values(),
+    missing: missing,
+    window: window,
+    step: if step == auto { none } else { step },
+  ))
+  (
+    (sequences: result.sequences)
+      + _popgen-stats-entry(result.overall)
+      + (
+        windows: result.windows.map(w => (
+          (start: w.start, end: w.end) + _popgen-stats-entry(w)
+        )),
+      )
+  )
+}
@@ -0,0 +1,237 @@
+#let _sequence-backend = plugin("sequence.wasm")
+
+/// Parses FASTA or FASTQ data through the WASM backend.
+///
+/// - data (str, bytes): Raw FASTA or FASTQ source, optionally gzip- or
+///   BGZF-compressed.
+/// - config (dictionary): Parser configuration.
+/// -> array
+#let _sequence-parse-sequences(data, config) = {
+  let result = _sequence-backend.parse_sequences(
+    bytes(data),
+    cbor.encode(config),
+  )
+  cbor(result)
+}
+
+/// Computes MSA column statistics for a window through the WASM backend.
+///
+/// - sequences (array): Aligned sequence strings.
+/// - start (int): Window start position (0-indexed, inclusive).
+/// - end (int): Window end position (0-indexed, exclusive).
+/// - alphabet (array): Canonical uppercase residues to count.
+/// - sampling-correction (bool): Apply small sample correction.
+/// - compute-conservation (bool): Compute conservation values.
+/// - weighting (str): Backend sequence weighting: "none", "henikoff", or
+///   "identity".
+/// - identity-threshold (float): Neighbor identity for "identity" weighting.
+/// - conservation (dictionary): Resolved score from
+///   `_resolve-conservation-score`.
+/// -> dictionary
+#let _sequence-column-stats(
+  sequences,
+  start,
+  end,
+  alphabet,
+  sampling-correction,
+  compute-conservation,
+  weighting,
+  identity-threshold,
+  conservation,
+) = {
+  let result = _sequence-backend.column_stats(cbor.encode(
+    (
+      sequences: sequences,
+      alphabet: alphabet,
+      start: start,
+      end: end,
+      sampling_correction: sampling-correction,
+      compute_conservation: compute-conservation,
+      weighting: weighting,
+      identity_threshold: identity-threshold,
+      conservation_score: conservation.score,
+      background: conservation.background,
+      property_groups: conservation.property-groups,
+      matrix: conservation.matrix,
+    ),
+  ))
+  cbor(result)
+}
+
+/// Computes sequence weights through the WASM backend.
+///
+/// - sequences (array): Aligned sequence strings.
+/// - alphabet (array): Canonical uppercase residues.
+/// - weighting (str): "none", "henikoff", or "identity".
+/// - identity-threshold (float): Neighbor identity for "identity" weighting.
+/// -> dictionary
+#let _sequence-weights(sequences, alphabet, weighting, identity-threshold) = {
+  let result = _sequence-backend.sequence_weights(cbor.encode(
+    (
+      sequences: sequences,
+      alphabet: alphabet,
+      weighting: weighting,
+      identity_threshold: identity-threshold,
+    ),
+  ))
+  cbor(result)
+}
+
+/// Computes relative-entropy logo heights for a window through the WASM
+/// backend.
+///
+/// - sequences (array): Aligned sequence strings.
+/// - start (int): Window start position (0-indexed, inclusive).
+/// - end (int): Window end position (0-indexed, exclusive).
+/// - alphabet (array): Canonical uppercase residues.
+/// - weighting (str): "none", "henikoff", or "identity".
+/// - identity-threshold (float): Neighbor identity for "identity" weighting.
+/// - background (str, array): "uniform", "blosum62", or frequencies aligned
+///   to `alphabet`.
+/// - pseudocounts (str): "none", "dirichlet", or "blosum62".
+/// - pseudocount-weight (float, none): Pseudocount weight, or none for the
+///   method default.
+/// - show-depleted (bool): Return signed per-residue heights.
+/// -> dictionary
+#let _sequence-logo-heights(
+  sequences,
+  start,
+  end,
+  alphabet,
+  weighting,
+  identity-threshold,
+  background,
+  pseudocounts,
+  pseudocount-weight,
+  show-depleted,
+) = {
+  let result = _sequence-backend.logo_heights(cbor.encode(
+    (
+      sequences: sequences,
+      alphabet: alphabet,
+      start: start,
+      end: end,
+      weighting: weighting,
+      identity_threshold: identity-threshold,
+      background: background,
+      pseudocounts: pseudocounts,
+      pseudocount_weight: pseudocount-weight,
+      show_depleted: show-depleted,
+    ),
+  ))
+  cbor(result)
+}
+
+/// Computes relative-entropy logo heights from column frequencies through
+/// the WASM backend.
+///
+/// - frequencies (array): Residue frequencies per column, aligned to
+///   `alphabet`.
+/// - alphabet (array): Canonical uppercase residues.
+/// - background (str, array): Backend background name or frequencies.
+/// - show-depleted (bool): Return signed per-residue terms.
+/// -> dictionary
+#let _sequence-frequency-logo-heights(
+  frequencies,
+  alphabet,
+  background,
+  show-depleted,
+) = {
+  let result = _sequence-backend.frequency_logo_heights(cbor.encode(
+    (
+      frequencies: frequencies,
+      alphabet: alphabet,
+      background: background,
+      show_depleted: show-depleted,
+    ),
+  ))
+  cbor(result)
+}
+
+/// Parses a multiple sequence alignment through the WASM backend.
+///
+/// - data (str, bytes): Raw alignment source, optionally gzip- or
+///   BGZF-compressed.
+/// - config (dictionary): Parser configuration.
+/// -> dictionary
+#let _sequence-parse-msa(data, config) = {
+  let result = _sequence-backend.parse_msa(
+    bytes(data),
+    cbor.encode(config),
+  )
+  cbor(result)
+}
+
+/// Trims alignment columns through the WASM backend.
+///
+/// - request (dictionary): Trimming request with `sequences` as an array of
+///   `(id, sequence)` dictionaries.
+/// -> dictionary
+#let _sequence-trim-msa-columns(request) = {
+  cbor(_sequence-backend.trim_msa_columns(cbor.encode(request)))
+}
+
+/// Filters alignment sequences through the WASM backend.
+///
+/// - request (dictionary): Filtering request with `sequences` as an array of
+///   `(id, sequence)` dictionaries.
+/// -> dictionary
+#let _sequence-filter-msa-sequences(request) = {
+  cbor(_sequence-backend.filter_msa_sequences(cbor.encode(request)))
+}
+
+/// Computes a pairwise identity matrix through the WASM backend.
+///
+/// - request (dictionary): Identity request with `sequences` as an array of
+///   aligned sequence strings.
+/// -> dictionary
+#let _sequence-identity-matrix(request) = {
+  cbor(_sequence-backend.identity_matrix(cbor.encode(request)))
+}
+
+/// Builds a position weight matrix through the WASM backend.
+///
+/// - request (dictionary): PWM request with the motif sequences.
+/// -> dictionary
+#let _sequence-motif-pwm(request) = {
+  cbor(_sequence-backend.motif_pwm(cbor.encode(request)))
+}
+
+/// Scans a sequence for motif hits through the WASM backend.
+///
+/// - request (dictionary): Scan request with the PWM scores.
+/// -> array
+#let _sequence-scan-motif-hits(request) = {
+  cbor(_sequence-backend.scan_motif_hits(cbor.encode(request)))
+}
+
+/// Parses motif matrices through the WASM backend.
+///
+/// - data (str, bytes): Raw motif file, optionally gzip- or BGZF-compressed.
+/// - config (dictionary): Parser configuration.
+/// -> array
+#let _sequence-parse-motif-matrices(data, config) = {
+  let result = _sequence-backend.parse_motif_matrices(
+    bytes(data),
+    cbor.encode(config),
+  )
+  cbor(result)
+}
+
+/// Computes population-genetics statistics through the WASM backend.
+///
+/// - request (dictionary): Request with `sequences` as an array of aligned
+///   sequence strings.
+/// -> dictionary
+#let _sequence-popgen-stats(request) = {
+  cbor(_sequence-backend.popgen_stats(cbor.encode(request)))
+}
+
+/// Finds variant sites relative to a reference through the WASM backend.
+///
+/// - request (dictionary): Request with `sequences` as an array of
+///   `(id, sequence)` dictionaries.
+/// -> dictionary
+#let _sequence-snp-sites(request) = {
+  cbor(_sequence-backend.snp_sites(cbor.encode(request)))
+}
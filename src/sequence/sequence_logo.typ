@@ -5,9 +5,12 @@
   _draw-coordinate-axis, _make-axis-scale-label,
 )
 #import "./sequence_alphabet.typ": _resolve-alphabet-config
+#import "./sequence_backend.typ": (
+  _sequence-frequency-logo-heights, _sequence-logo-heights,
+)
 #import "./sequence_processing.typ": (
   _collect-window-column-stats, _lookup-palette-color, _resolve-palette,
-  _validate-alignment,
+  _resolve-sequence-weighting, _validate-alignment,
 )
 
 #let _logo-letter-gap = 0.25pt
@@ -17,13 +20,12 @@
 /// Column stacks are scaled relative to the maximum observed conservation in
 /// the requested window, or to column occupancy when `stack-scale` is
 /// `"occupancy"`. Within each stack, individual glyph heights represent
-/// relative residue frequency. The returned heights exclude the inter-letter gap
-/// added later during stack layout.
+/// relative (weighted) residue frequency. The returned heights exclude the
+/// inter-letter gap added later during stack layout.
 ///
 /// - column-stats (array): Prepared per-column statistics for the requested window.
 /// - logo-height (length): Reference height used to scale the tallest observed stack.
 /// - alphabet-config (dictionary): Canonical alphabet configuration.
-/// - num-sequences (int): Total number of sequences in the alignment.
 /// - stack-scale (str): Whether the letter stack height represents
 ///   "conservation" or "occupancy" (default: "conservation").
 /// -> array: Array of columns, each column being an array of dictionaries
@@ -34,7 +36,6 @@
   column-stats,
   logo-height,
   alphabet-config,
-  num-sequences,
   stack-scale: "conservation",
 ) = {
   if column-stats.len() == 0 { return () }
@@ -54,14 +55,14 @@
   for col in column-stats {
     let column-letters = ()
     let scale = if stack-scale == "occupancy" {
-      col.total-non-gap / num-sequences
+      col.occupancy
     } else {
       col.conservation / divisor
     }
     if scale > 0 {
       for char in alphabet-config.chars {
         if char in col.counts {
-          let f-rel = col.counts.at(char) / col.total-non-gap
+          let f-rel = col.counts.at(char) / col.total-weight
           let symbol-height = f-rel * scale * logo-height
           if symbol-height > 0pt {
             column-letters.push((char: char, height: symbol-height))
@@ -75,6 +76,116 @@
   logo-data
 }
 
+/// Resolves the `background` argument of `render-sequence-logo` for the
+/// backend.
+///
+/// - background (none, str, dictionary): none for uniform, "uniform",
+///   "blosum62", or a dictionary of residue frequencies.
+/// - alphabet-config (dictionary): Canonical alphabet configuration.
+/// -> str | array: Backend background name, or frequencies aligned to the
+///   alphabet characters.
+#let _resolve-logo-background(background, alphabet-config) = {
+  if background == none { return "uniform" }
+  if type(background) == str {
+    assert(
+      background in ("uniform", "blosum62"),
+      message: "background must be none, \"uniform\", \"blosum62\", or a dictionary of residue frequencies.",
+    )
+    return background
+  }
+  assert(
+    type(background) == dictionary,
+    message: "background must be none, \"uniform\", \"blosum62\", or a dictionary of residue frequencies.",
+  )
+  alphabet-config.chars.map(char => {
+    let value = background.at(
+      char,
+      default: background.at(lower(char), default: none),
+    )
+    assert(
+      type(value) in (int, float, ratio) and float(value) > 0,
+      message: "background must give a positive frequency for residue '"
+        + char
+        + "'.",
+    )
+    float(value)
+  })
+}
+
+/// Whether `render-sequence-logo` was given a motif matrix, such as from
+/// `parse-motifs` or `build-pwm`, rather than an alignment.
+///
+/// - alignment (dictionary): The `alignment` argument.
+/// -> bool
+#let _is-motif-matrix(alignment) = (
+  type(alignment) == dictionary
+    and type(alignment.at("frequencies", default: none)) == array
+    and type(alignment.at("alphabet", default: none)) == array
+)
+
+/// Reorders motif frequencies onto the canonical alphabet.
+///
+/// Residues outside the alphabet may only appear with zero frequency.
+///
+/// - motif (dictionary): Motif matrix with `alphabet` and `frequencies`.
+/// - alphabet-config (dictionary): Canonical alphabet configuration.
+/// -> array: Frequencies per position, aligned to `alphabet-config.chars`.
+#let _align-motif-frequencies(motif, alphabet-config) = {
+  let letters = motif.alphabet.map(upper)
+  motif.frequencies.map(column => {
+    assert(
+      type(column) == array and column.len() == letters.len(),
+      message: "every motif position must have one frequency per alphabet residue.",
+    )
+    let by-letter = letters.zip(column).to-dict()
+    for (letter, value) in by-letter {
+      assert(
+        value == 0 or letter in alphabet-config.chars,
+        message: "motif residue '" + letter + "' is not in the logo alphabet.",
+      )
+    }
+    alphabet-config.chars.map(char => float(by-letter.at(char, default: 0)))
+  })
+}
+
+/// Converts backend relative-entropy heights to glyph heights.
+///
+/// Heights are scaled so the tallest enriched stack in the window reaches
+/// `logo-height`. Residues with negative heights (shown with
+/// `show-depleted`) are returned separately, with the most depleted residue
+/// last so it sits furthest from the baseline.
+///
+/// - response (dictionary): Backend `logo_heights` or
+///   `frequency_logo_heights` response.
+/// - logo-height (length): Reference height used to scale the tallest stack.
+/// - alphabet-config (dictionary): Canonical alphabet configuration.
+/// -> dictionary with keys:
+///   - enriched (array): Columns of letters drawn above the baseline.
+///   - depleted (array): Columns of letters drawn below the baseline.
+#let _get-relative-entropy-logo-heights(response, logo-height, alphabet-config) = {
+  let divisor = response.columns.fold(0.0, (max-bits, col) => {
+    calc.max(max-bits, col.heights.filter(h => h > 0).sum(default: 0.0))
+  })
+  if divisor == 0 { divisor = alphabet-config.max-bits }
+
+  let enriched = ()
+  let depleted = ()
+  for col in response.columns {
+    let above = ()
+    let below = ()
+    for (char, bits) in col.residues.zip(col.heights) {
+      let symbol-height = calc.abs(bits) / divisor * logo-height
+      if symbol-height > 0pt {
+        let letter = (char: char, height: symbol-height)
+        if bits > 0 { above.push(letter) } else { below.push(letter) }
+      }
+    }
+    enriched.push(above.sorted(key: it => it.height).rev())
+    depleted.push(below.sorted(key: it => it.height))
+  }
+  (enriched: enriched, depleted: depleted)
+}
+
 /// Builds the unscaled glyph used for sequence-logo letters.
 ///
 /// - char (str): Residue character to render.
@@ -155,14 +266,44 @@
 /// frequency. Set `stack-scale` to "occupancy" to scale stack height by column
 /// occupancy instead. `height` controls the vertical scale of the logo stacks.
 ///
-/// - alignment (dictionary): Dictionary mapping sequence identifiers to aligned sequences.
+/// Setting `background`, `pseudocounts` or `show-depleted` switches to
+/// relative-entropy logos: stack height is the relative entropy of the
+/// column's residue frequencies to the background frequencies, as in
+/// Kullback-Leibler logos. With `show-depleted`, residues rarer than their
+/// background frequency are drawn below the baseline.
+///
+/// A motif matrix from `parse-motifs` or `build-pwm` can be given instead of
+/// an alignment. Its frequencies are drawn directly as a relative-entropy
+/// logo, so with the default uniform background stack heights are the
+/// information content of each position.
+///
+/// - alignment (dictionary): Dictionary mapping sequence identifiers to
+///   aligned sequences, or a motif matrix with `alphabet` and `frequencies`.
 /// - start (int, none): Starting position (1-indexed, inclusive) (default: none).
 /// - end (int, none): Ending position (1-indexed, inclusive) (default: none).
 /// - width (length, auto, ratio, relative): Total width of the logo (default: 100%).
 /// - height (length): Vertical scale used for the logo stacks (default: 60pt).
 /// - stack-scale (str): Whether stack height represents "conservation" or
 ///   "occupancy" (default: "conservation").
-/// - sampling-correction (bool): Whether to apply small sample correction (default: true).
+/// - sampling-correction (bool): Whether to apply small sample correction to
+///   classic logos; ignored for relative-entropy logos (default: true).
+/// - weighting (str, none): Sequence weighting for residue frequencies and
+///   conservation: none, "henikoff", or "identity". See `get-sequence-weights`.
+///   Must be none for motif matrices (default: none).
+/// - identity-threshold (ratio): Neighbor identity for "identity" weighting
+///   (default: 80%).
+/// - background (none, str, dictionary): Background residue frequencies: none
+///   (classic information content), "uniform", "blosum62" (amino acid
+///   frequencies implied by BLOSUM62), or a dictionary mapping each residue
+///   to its frequency (default: none).
+/// - pseudocounts (none, str): Pseudocounts added to column frequencies: none,
+///   "dirichlet" (background-weighted prior), or "blosum62"
+///   (substitution-derived, amino acids only). Must be none for motif
+///   matrices (default: none).
+/// - pseudocount-weight (auto, int, float): Total pseudocount weight (default:
+///   auto, meaning 1 for "dirichlet" and 10 for "blosum62").
+/// - show-depleted (bool): Whether to draw residues below their background
+///   frequency under the baseline (default: false).
 /// - alphabet (auto, str): Sequence alphabet: auto, "aa", "dna", or "rna" (default: auto).
 /// - palette (dictionary, auto): Residue color palette to use (default: auto).
 /// - coordinate-axis (bool): Whether to show the coordinate axis under the logo (default: false).
@@ -180,6 +321,12 @@
   height: 60pt,
   stack-scale: "conservation",
   sampling-correction: true,
+  weighting: none,
+  identity-threshold: 80%,
+  background: none,
+  pseudocounts: none,
+  pseudocount-weight: auto,
+  show-depleted: false,
   alphabet: auto,
   palette: auto,
   coordinate-axis: false,
@@ -189,15 +336,28 @@
   axis-label-gap: 2.5pt,
   axis-logo-gap: 6pt,
 ) = {
-  _validate-alignment(alignment)
+  let from-motif = _is-motif-matrix(alignment)
+  if not from-motif { _validate-alignment(alignment) }
   assert(
     stack-scale in ("conservation", "occupancy"),
     message: "stack-scale must be 'conservation' or 'occupancy'.",
   )
-  let sequences = alignment.values()
+  let resolved-weighting = _resolve-sequence-weighting(
+    weighting,
+    identity-threshold,
+  )
+  // A motif's residues stand in for sequences when detecting the alphabet
+  // and checking palette coverage.
+  let sequences = if from-motif {
+    (alignment.alphabet.map(upper).join(),)
+  } else {
+    alignment.values()
+  }
   let config = _resolve-alphabet-config(alphabet, sequences)
   let palette-to-use = _resolve-palette(palette, config, sequences)
-  let max-len = sequences.map(s => s.len()).fold(0, calc.max)
+  let max-len = if from-motif { alignment.frequencies.len() } else {
+    sequences.map(s => s.len()).fold(0, calc.max)
+  }
   let window = _resolve-1indexed-window(
     start,
     end,
@@ -219,21 +379,75 @@
       message: "axis-logo-gap must be non-negative.",
     )
   }
-  let column-stats = _collect-window-column-stats(
-    sequences,
-    window.actual-start,
-    window.actual-end,
-    config,
-    sampling-correction,
-    compute-conservation: stack-scale == "conservation",
+  assert(
+    pseudocounts in (none, "dirichlet", "blosum62"),
+    message: "pseudocounts must be none, \"dirichlet\", or \"blosum62\".",
   )
-  let logo-data = _get-logo-heights(
-    column-stats,
-    height,
-    config,
-    sequences.len(),
-    stack-scale: stack-scale,
+  assert(
+    pseudocount-weight == auto
+      or (type(pseudocount-weight) in (int, float) and pseudocount-weight >= 0),
+    message: "pseudocount-weight must be auto or a non-negative number.",
   )
+  assert(type(show-depleted) == bool, message: "show-depleted must be a boolean.")
+
+  let relative-entropy = (
+    from-motif or background != none or pseudocounts != none or show-depleted
+  )
+  let depleted-data = ()
+  let logo-data = if relative-entropy {
+    assert(
+      stack-scale == "conservation",
+      message: "motif matrices, background, pseudocounts, and show-depleted require stack-scale: \"conservation\".",
+    )
+    let response = if from-motif {
+      assert(
+        weighting == none and pseudocounts == none,
+        message: "weighting and pseudocounts must be none for motif matrices.",
+      )
+      _sequence-frequency-logo-heights(
+        _align-motif-frequencies(alignment, config).slice(
+          window.actual-start,
+          window.actual-end,
+        ),
+        config.chars,
+        _resolve-logo-background(background, config),
+        show-depleted,
+      )
+    } else {
+      _sequence-logo-heights(
+        sequences,
+        window.actual-start,
+        window.actual-end,
+        config.chars,
+        resolved-weighting.weighting,
+        resolved-weighting.identity-threshold,
+        _resolve-logo-background(background, config),
+        if pseudocounts == none { "none" } else { pseudocounts },
+        if pseudocount-weight == auto { none } else { float(pseudocount-weight) },
+        show-depleted,
+      )
+    }
+    let heights = _get-relative-entropy-logo-heights(response, height, config)
+    depleted-data = heights.depleted
+    heights.enriched
+  } else {
+    let column-stats = _collect-window-column-stats(
+      sequences,
+      window.actual-start,
+      window.actual-end,
+      config,
+      sampling-correction,
+      compute-conservation: stack-scale == "conservation",
+      weighting: resolved-weighting,
+    )
+    _get-logo-heights(
+      column-stats,
+      height,
+      config,
+      stack-scale: stack-scale,
+    )
+  }
+  let has-depleted = depleted-data.any(col => col.len() > 0)
 
   block(width: width)[
     #layout(size => context {
@@ -242,22 +456,24 @@
       let col-width = size.width / n-cols
       let glyph-metrics = _measure-logo-glyphs(config.chars)
 
+      let render-stack(col) = stack(
+        dir: ttb,
+        spacing: _logo-letter-gap,
+        ..col.map(l => _render-logo-letter(
+          l,
+          col-width,
+          palette-to-use,
+          glyph-metrics,
+        )),
+      )
+
+      // Depleted residues hang from the baseline in a second row.
       let logo-grid = grid(
         columns: (col-width,) * n-cols,
-        align: bottom,
+        align: (_, y) => if y == 0 { bottom } else { top },
         row-gutter: 0pt,
-        ..logo-data.map(col => {
-          stack(
-            dir: ttb,
-            spacing: _logo-letter-gap,
-            ..col.map(l => _render-logo-letter(
-              l,
-              col-width,
-              palette-to-use,
-              glyph-metrics,
-            )),
-          )
-        })
+        ..logo-data.map(render-stack),
+        ..if has-depleted { depleted-data.map(render-stack) } else { () },
       )
 
       if not coordinate-axis {
@@ -270,7 +486,9 @@
       let axis-width = if n-cols == 1 { size.width } else {
         size.width - col-width
       }
-      let logo-height = _resolve-logo-height(logo-data)
+      let logo-height = (
+        _resolve-logo-height(logo-data) + _resolve-logo-height(depleted-data)
+      )
       let axis-label-height = measure(_make-axis-scale-label(
         str(last-pos),
         axis-label-size,
@@ -1,3 +1,5 @@
+#import "./sequence_backend.typ": _sequence-column-stats
+
 /// Canonicalizes a residue palette to uppercase keys.
 ///
 /// Duplicate keys that normalize to the same residue are allowed only if they
@@ -39,128 +41,109 @@
   default: none,
 )
 
-/// Computes the sequence conservation of MSA column using the method described
-/// in Schneider, T.D., and Stephens, R.M. "Sequence logos: a new way to display
-/// consensus sequences" (1990).
+/// Computes the consensus sequence from pre-computed column statistics.
 ///
-/// Calculates the information content (measured in bits) for a single column
-/// of a multiple sequence alignment, using Shannon entropy with optional small
-/// sample correction and occupancy scaling.
+/// Each column contributes its most frequent valid residue, with ties
+/// resolved by first-seen residue order, or a gap (`-`) if it has no valid
+/// residues.
 ///
-/// - counts (dictionary): Dictionary mapping characters to their counts in the column.
-/// - total-non-gap (int): Total number of non-gap characters in the column.
-/// - num-sequences (int): Total number of sequences in the alignment.
-/// - sampling-correction (bool): Apply small sample correction.
-/// - max-bits (float): Maximum information content (`log2(alphabet-size)`).
-/// - alphabet-size (int): Size of the alphabet.
-/// -> float
-#let _compute-sequence-conservation(
-  counts,
-  total-non-gap,
-  num-sequences,
-  sampling-correction,
-  max-bits,
-  alphabet-size,
-) = {
-  if total-non-gap == 0 { return 0.0 }
-
-  let entropy = 0.0
-  for count in counts.values() {
-    let p = count / total-non-gap
-    if p > 0 {
-      entropy -= p * calc.log(p, base: 2.0)
-    }
-  }
-
-  // Small sample correction
-  let en = 0.0
-  if sampling-correction {
-    en = (alphabet-size - 1.0) / (2.0 * total-non-gap * calc.ln(2.0))
-  }
-
-  let r = calc.max(0.0, max-bits - (entropy + en))
-
-  // Occupancy scaling
-  let occupancy = total-non-gap / num-sequences
-  occupancy * r
+/// - column-stats (array): Prepared per-column statistics.
+/// -> str
+#let _compute-consensus-sequence(column-stats) = {
+  column-stats.map(stats => stats.consensus).join("", default: "")
 }
 
-/// Computes column statistics for a set of sequences.
-///
-/// Counts occurrences of each valid character at a specific position across all
-/// sequences in the alignment. Matching is case-insensitive.
+/// Validates sequence weighting options and converts them to backend values.
 ///
-/// - sequences (array): Array of sequence strings.
-/// - pos (int): The column position to analyze (0-indexed).
-/// - alphabet-config (dictionary): Canonical alphabet configuration.
+/// - weighting (str, none): none, "henikoff", or "identity".
+/// - identity-threshold (ratio, float): Neighbor identity for "identity".
 /// -> dictionary with keys:
-///   - counts (dictionary): Counts of valid characters at the column.
-///   - total-non-gap (int): Total count of valid non-gap characters.
-///   - residue-order (array): Valid residues in first-seen sequence order.
-#let _get-column-stats(sequences, pos, alphabet-config) = {
-  let counts = (:)
-  let total-non-gap = 0
-  let residue-order = ()
-  for seq in sequences {
-    if pos < seq.len() {
-      let char = upper(seq.at(pos))
-      if char in alphabet-config.char-set {
-        if not (char in counts) {
-          residue-order.push(char)
-        }
-        counts.insert(char, counts.at(char, default: 0) + 1)
-        total-non-gap += 1
-      }
-    }
-  }
+///   - weighting (str): Backend weighting name.
+///   - identity-threshold (float): Threshold as a fraction.
+#let _resolve-sequence-weighting(weighting, identity-threshold) = {
+  assert(
+    weighting == none or weighting in ("henikoff", "identity"),
+    message: "weighting must be none, \"henikoff\", or \"identity\".",
+  )
+  assert(
+    type(identity-threshold) in (ratio, float, int),
+    message: "identity-threshold must be a ratio or a number.",
+  )
+  let threshold = float(identity-threshold)
+  assert(
+    threshold > 0.0 and threshold <= 1.0,
+    message: "identity-threshold must be greater than 0% and at most 100%.",
+  )
   (
-    counts: counts,
-    total-non-gap: total-non-gap,
-    residue-order: residue-order,
+    weighting: if weighting == none { "none" } else { weighting },
+    identity-threshold: threshold,
   )
 }
 
-/// Computes the consensus sequence from pre-computed column statistics.
+/// Resolves a column conservation score and its alphabet-dependent settings.
 ///
-/// For each column, selects the most frequent valid residue. Ties are resolved
-/// by the first-seen residue order captured in the column statistics. Columns
-/// without valid residues are represented as gaps (`-`). The input is assumed
-/// to come from a validated MSA.
+/// Jensen-Shannon divergence uses the BLOSUM62 background for amino acids and
+/// a uniform background for nucleotides. Property entropy groups amino acids
+/// into six physicochemical classes and nucleotides into purines and
+/// pyrimidines. Sum-of-pairs defaults to BLOSUM62 or EDNAFULL.
 ///
-/// - column-stats (array): Prepared per-column statistics.
-/// -> str
-#let _compute-consensus-sequence(column-stats) = {
-  let consensus = ()
-  for stats in column-stats {
-    let best-char = "-"
-    let best-count = 0
-    let residue-order = stats.residue-order
-    for char in residue-order {
-      let count = stats.counts.at(char)
-      if count > best-count {
-        best-char = char
-        best-count = count
-      }
-    }
-    consensus.push(best-char)
-  }
-  consensus.join("", default: "")
+/// - score (str): "shannon", "jensen-shannon", "property-entropy", or
+///   "sum-of-pairs".
+/// - alphabet-config (dictionary): Canonical alphabet configuration.
+/// - matrix (auto, str): Built-in substitution matrix for "sum-of-pairs".
+/// -> dictionary with keys `score`, `background`, `property-groups` and
+///   `matrix`.
+#let _resolve-conservation-score(score, alphabet-config, matrix: auto) = {
+  assert(
+    score in ("shannon", "jensen-shannon", "property-entropy", "sum-of-pairs"),
+    message: "conservation-score must be \"shannon\", \"jensen-shannon\", \"property-entropy\", or \"sum-of-pairs\".",
+  )
+  assert(
+    matrix == auto or type(matrix) == str,
+    message: "conservation-matrix must be auto or a built-in matrix name.",
+  )
+  let is-aa = alphabet-config.size == 20
+  (
+    score: score,
+    background: if is-aa { "blosum62" } else { "uniform" },
+    property-groups: if is-aa { none } else if "U" in alphabet-config.char-set {
+      ("AG", "CU")
+    } else { ("AG", "CT") },
+    matrix: if matrix != auto { matrix } else if is-aa { "BLOSUM62" } else {
+      "EDNAFULL"
+    },
+  )
 }
 
 /// Collects column statistics for a contiguous alignment window.
 ///
+/// Counting and conservation run in the WASM backend. Conservation follows
+/// Schneider, T.D., and Stephens, R.M. "Sequence logos: a new way to display
+/// consensus sequences" (1990): Shannon information content in bits with
+/// optional small sample correction, scaled by column occupancy. With
+/// sequence weighting, counts are sums of sequence weights. Other scores are
+/// selected with `conservation-score`.
+///
 /// - sequences (array): Array of sequence strings.
 /// - start (int): Window start position (0-indexed, inclusive).
 /// - end (int): Window end position (0-indexed, exclusive).
 /// - alphabet-config (dictionary): Canonical alphabet configuration.
 /// - sampling-correction (bool): Apply small sample correction.
 /// - compute-conservation (bool): Compute conservation values.
+/// - weighting (dictionary): Resolved weighting from
+///   `_resolve-sequence-weighting`, or none for unweighted counts.
+/// - conservation-score (dictionary, none): Resolved score from
+///   `_resolve-conservation-score`, or none for Shannon information.
 /// -> array of dictionaries with keys:
-///   - counts (dictionary): Counts of valid characters at each column.
-///   - total-non-gap (int): Total count of valid non-gap characters at each column.
+///   - counts (dictionary): Weighted counts of valid characters at each column.
+///   - total-non-gap (int): Number of valid non-gap characters at each column.
+///   - total-weight (float): Sum of the weighted counts.
 ///   - residue-order (array): Valid residues in first-seen sequence order.
+///   - occupancy (float): Weighted fraction of sequences with a valid residue.
 ///   - conservation (float, none): Occupancy-scaled information content for
 ///     each column, or `none` when `compute-conservation` is `false`.
+///   - max-conservation (float): Highest conservation the score can reach.
+///   - consensus (str): Residue with the highest weighted count, or `-`.
 #let _collect-window-column-stats(
   sequences,
   start,
@@ -168,28 +151,36 @@
   alphabet-config,
   sampling-correction,
   compute-conservation: true,
+  weighting: none,
+  conservation-score: none,
 ) = {
-  let num-sequences = sequences.len()
-  range(start, end).map(pos => {
-    let stats = _get-column-stats(sequences, pos, alphabet-config)
-    (
-      counts: stats.counts,
-      total-non-gap: stats.total-non-gap,
-      residue-order: stats.residue-order,
-      conservation: if compute-conservation {
-        _compute-sequence-conservation(
-          stats.counts,
-          stats.total-non-gap,
-          num-sequences,
-          sampling-correction,
-          alphabet-config.max-bits,
-          alphabet-config.size,
-        )
-      } else {
-        none
-      },
-    )
-  })
+  let weighting = if weighting == none {
+    _resolve-sequence-weighting(none, 80%)
+  } else { weighting }
+  let conservation-score = if conservation-score == none {
+    _resolve-conservation-score("shannon", alphabet-config)
+  } else { conservation-score }
+  let result = _sequence-column-stats(
+    sequences,
+    start,
+    end,
+    alphabet-config.chars,
+    sampling-correction,
+    compute-conservation,
+    weighting.weighting,
+    weighting.identity-threshold,
+    conservation-score,
+  )
+  result.columns.map(column => (
+    counts: column.residue_order.zip(column.counts).to-dict(),
+    total-non-gap: column.total_non_gap,
+    total-weight: column.total_weight,
+    residue-order: column.residue_order,
+    occupancy: column.occupancy,
+    conservation: column.conservation,
+    max-conservation: result.max_conservation,
+    consensus: column.consensus,
+  ))
 }
 
 /// Checks whether a prepared palette covers the observed residues in a sequence
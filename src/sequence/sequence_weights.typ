@@ -0,0 +1,56 @@
+#import "./sequence_alphabet.typ": _resolve-alphabet-config
+#import "./sequence_backend.typ": _sequence-weights
+#import "./sequence_processing.typ": (
+  _resolve-sequence-weighting, _validate-alignment,
+)
+
+/// Computes sequence weights that down-weight redundant sequences in an
+/// alignment.
+///
+/// "henikoff" uses position-based weights (Henikoff & Henikoff, 1994).
+/// "identity" gives each sequence the inverse of the number of sequences,
+/// itself included, that share at least `identity-threshold` identity with
+/// it, as in PSI-BLAST and HHblits. Identity is measured over columns where
+/// both sequences have a residue. Weights sum to the effective number of
+/// sequences; without weighting every sequence has weight 1.
+///
+/// - alignment (dictionary): Dictionary mapping sequence identifiers to
+///   aligned sequences.
+/// - method (str, none): "henikoff", "identity", or none (default: "henikoff").
+/// - identity-threshold (ratio): Neighbor identity for "identity" weighting
+///   (default: 80%).
+/// - alphabet (auto, str): Sequence alphabet: auto, "aa", "dna", or "rna"
+///   (default: auto).
+/// -> dictionary with keys:
+///   - weights (dictionary): Weight keyed by sequence identifier.
+///   - effective-sequences (float): Effective number of sequences: the
+///     inverse-square-sum estimate for "henikoff", the sum of weights for
+///     "identity".
+#let get-sequence-weights(
+  alignment,
+  method: "henikoff",
+  identity-threshold: 80%,
+  alphabet: auto,
+) = {
+  assert(
+    type(alignment) == dictionary,
+    message: "alignment must be a dictionary.",
+  )
+  _validate-alignment(alignment)
+  let weighting = _resolve-sequence-weighting(method, identity-threshold)
+  let sequences = alignment.values()
+  if sequences.len() == 0 {
+    return (weights: (:), effective-sequences: 0.0)
+  }
+  let config = _resolve-alphabet-config(alphabet, sequences)
+  let result = _sequence-weights(
+    sequences,
+    config.chars,
+    weighting.weighting,
+    weighting.identity-threshold,
+  )
+  (
+    weights: alignment.keys().zip(result.weights).to-dict(),
+    effective-sequences: result.effective_sequences,
+  )
+}
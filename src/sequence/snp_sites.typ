@@ -0,0 +1,64 @@
+#import "./msa_filter.typ": _alignment-records
+#import "./sequence_backend.typ": _sequence-snp-sites
+#import "./sequence_processing.typ": _validate-alignment
+
+/// Finds the variant columns of a nucleotide alignment relative to a
+/// reference sequence.
+///
+/// A column is a variant site when any sequence has a different base (A, C,
+/// G, T, or U, ignoring case; U counts as T) than the reference. Gaps, `N`,
+/// and other ambiguity codes are missing data and never make a column variable;
+/// columns where the reference has no base are skipped. Positions count the
+/// reference's non-gap characters, so they match coordinates on the
+/// ungapped reference and on a genome map of it.
+///
+/// - alignment (dictionary): Dictionary mapping sequence identifiers to
+///   aligned sequences.
+/// - reference (str): Identifier of the reference sequence.
+/// - snp-alignment (bool): Whether to also return the alignment restricted to
+///   the variant columns, for `render-msa` (default: false).
+/// -> dictionary with keys:
+///   - sites (array): One dictionary per variant column, with keys:
+///     - column (int): Alignment column (1-indexed).
+///     - position (int): Position on the ungapped reference (1-indexed).
+///     - reference (str): Reference allele.
+///     - alternatives (array): Alternative alleles, most frequent first.
+///     - genotypes (dictionary): Allele index of each sequence keyed by
+///       identifier: 0 for the reference allele, `i` for the `i`-th
+///       alternative, or none for missing data.
+///   - alignment (dictionary, none): Sequences restricted to the variant
+///     columns, keyed by identifier, if `snp-alignment` is true.
+#let get-snp-sites(alignment, reference, snp-alignment: false) = {
+  assert(
+    type(alignment) == dictionary,
+    message: "alignment must be a dictionary.",
+  )
+  _validate-alignment(alignment)
+  assert(
+    type(reference) == str and reference in alignment,
+    message: "reference must be the identifier of a sequence in the alignment.",
+  )
+  assert(
+    type(snp-alignment) == bool,
+    message: "snp-alignment must be a boolean.",
+  )
+
+  let ids = alignment.keys()
+  let result = _sequence-snp-sites((
+    sequences: _alignment-records(alignment),
+    reference: reference,
+    snp_alignment: snp-alignment,
+  ))
+  (
+    sites: result.sites.map(site => (
+      column: site.column,
+      position: site.position,
+      reference: site.reference,
+      alternatives: site.alternatives,
+      genotypes: ids.zip(site.genotypes).to-dict(),
+    )),
+    alignment: if result.alignment == none { none } else {
+      result.alignment.map(record => (record.id, record.sequence)).to-dict()
+    },
+  )
+}
@@ -3,7 +3,8 @@
 /// Parses Newick-formatted phylogenetic tree data into a dictionary structure
 /// suitable for rendering.
 ///
-/// - data (str): Newick-formatted phylogenetic tree data.
+/// - data (str, bytes): Newick-formatted phylogenetic tree data. Bytes may be
+///   gzip- or BGZF-compressed, e.g. `read("tree.nwk.gz", encoding: none)`.
 /// -> dictionary with keys:
 ///   - children (array, none): Child node dictionaries, or none for leaf nodes.
 ///   - name (str, none): Optional node label.
@@ -11,6 +12,10 @@
 ///   - rooted (bool): Root-only rootedness flag.
 /// Child nodes use the same fields except `rooted`.
 #let parse-newick(data) = {
-  let result = _tree-backend.parse_newick(bytes(data.trim()))
+  assert(
+    type(data) in (str, bytes),
+    message: "data must be a string or bytes.",
+  )
+  let result = _tree-backend.parse_newick(bytes(data))
   json(result)
 }
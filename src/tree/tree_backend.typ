@@ -138,14 +138,14 @@
   hide-internal-labels: false,
   layout-kind: "rectangular",
 ) = {
-  let result = _tree-backend.prepare_layout(bytes(json.encode((
+  let result = _tree-backend.prepare_layout(cbor.encode((
     tree-data: tree-data,
     cladogram: cladogram,
     suppress-unrooted: suppress-unrooted,
     hide-internal-labels: hide-internal-labels,
     layout-kind: layout-kind,
-  ))))
-  let decoded = json(result)
+  )))
+  let decoded = cbor(result)
   let nodes = decoded.nodes.map(node => (
     ..node,
     label-id: node.at("label-id", default: none),
@@ -160,8 +160,8 @@
 /// - payload (dictionary): Typst-native fit payload with length-valued fields.
 /// -> dictionary
 #let _tree-fit(payload) = {
-  let result = _tree-backend.fit_tree(bytes(json.encode(
+  let result = _tree-backend.fit_tree(cbor.encode(
     _encode-tree-fit-request(payload),
-  )))
-  _decode-tree-fit-response(json(result))
+  ))
+  _decode-tree-fit-response(cbor(result))
 }